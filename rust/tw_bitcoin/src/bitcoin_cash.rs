@@ -0,0 +1,308 @@
+//! Bitcoin Cash transactions and CashAddr addresses. Bitcoin Cash has no
+//! SegWit or Taproot, but its signature hash follows the BIP-143 algorithm
+//! for all inputs, with the `SIGHASH_FORKID` flag set in the hash type. This
+//! prevents replaying transactions on the Bitcoin chain.
+
+use crate::{Error, Result};
+use bitcoin::bech32::{u5, FromBase32, ToBase32};
+use bitcoin::blockdata::script::{Instruction, PushBytesBuf};
+use bitcoin::hashes::{sha256d, Hash};
+use bitcoin::sighash::{EcdsaSighashType, SighashCache};
+use bitcoin::{
+    Address, Network, PubkeyHash, PublicKey, Script, ScriptBuf, ScriptHash, Transaction, TxOut,
+};
+use std::fmt;
+use std::str::FromStr;
+
+/// The SLIP-44 coin type of Bitcoin Cash.
+pub const BITCOIN_CASH_COIN_TYPE: u32 = 145;
+
+/// The version of the transactions signed by the wallet so far, which keeps
+/// the signatures deterministic across implementations.
+pub const TX_VERSION: i32 = 1;
+
+/// The flag marking a signature as replay-protected.
+pub const SIGHASH_FORKID: u32 = 0x40;
+
+/// The fork ID of Bitcoin Cash, committed to in the upper bits of the hash
+/// type.
+const FORK_ID: u32 = 0;
+
+/// The size of the hash type appended to the signature hash preimage.
+const SIGHASH_TYPE_SIZE: usize = 4;
+
+/// Computes the signature hash of the input at `index`. The preimage is the
+/// one of BIP-143, with the script code being the script of the spent output.
+pub fn signature_hash(
+    tx: &Transaction,
+    prevouts: &[TxOut],
+    index: usize,
+    sighash_type: EcdsaSighashType,
+) -> Result<[u8; 32]> {
+    let prevout = prevouts.get(index).ok_or(Error::Todo)?;
+
+    let mut data = vec![];
+    SighashCache::new(tx)
+        .segwit_encode_signing_data_to(
+            &mut data,
+            index,
+            &prevout.script_pubkey,
+            prevout.value,
+            sighash_type,
+        )
+        .map_err(|_| Error::Todo)?;
+
+    // The preimage ends with the hash type, which carries the fork ID.
+    let type_offset = data.len() - SIGHASH_TYPE_SIZE;
+    data.truncate(type_offset);
+    data.extend(fork_sighash_type(sighash_type).to_le_bytes());
+
+    Ok(sha256d::Hash::hash(&data).to_byte_array())
+}
+
+fn fork_sighash_type(sighash_type: EcdsaSighashType) -> u32 {
+    sighash_type.to_u32() | SIGHASH_FORKID | FORK_ID << 8
+}
+
+/// Sets `SIGHASH_FORKID` on the signature of a P2PKH scriptSig. The signers
+/// only know the hash types of Bitcoin, so the flag is added after signing.
+/// Signatures which already carry the flag are left unchanged.
+pub fn with_fork_id(script_sig: &Script) -> Result<ScriptBuf> {
+    let mut builder = ScriptBuf::builder();
+
+    for (i, instruction) in script_sig.instructions().enumerate() {
+        let Instruction::PushBytes(push) = instruction.map_err(|_| Error::Todo)? else {
+            return Err(Error::Todo);
+        };
+
+        let mut bytes = push.as_bytes().to_vec();
+        if i == 0 {
+            let hash_type = bytes.last_mut().ok_or(Error::Todo)?;
+            if u32::from(*hash_type) & SIGHASH_FORKID == 0 {
+                *hash_type = fork_sighash_type(
+                    EcdsaSighashType::from_standard(u32::from(*hash_type))
+                        .map_err(|_| Error::Todo)?,
+                ) as u8;
+            }
+        }
+
+        let bytes = PushBytesBuf::try_from(bytes).map_err(|_| Error::Todo)?;
+        builder = builder.push_slice(bytes);
+    }
+
+    Ok(builder.into_script())
+}
+
+const CASHADDR_MAINNET: &str = "bitcoincash";
+const CASHADDR_TESTNET: &str = "bchtest";
+const CASHADDR_REGTEST: &str = "bchreg";
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// The version byte, the type is in bits 3-6 and the size of the hash in
+/// bits 0-2, where 0 stands for 160 bits.
+const VERSION_P2PKH: u8 = 0x00;
+const VERSION_P2SH: u8 = 0x08;
+
+/// The number of base32 characters of the checksum.
+const CHECKSUM_LENGTH: usize = 8;
+
+/// A CashAddr address, paying to a public key hash or a script hash, such
+/// as `bitcoincash:qpm2qsznhks23z7629mms6s4cwef74vcwvy22gdx6a`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CashAddress {
+    network: Network,
+    is_script: bool,
+    hash: [u8; 20],
+}
+
+impl CashAddress {
+    /// The `bitcoincash:q...` address of the public key.
+    pub fn p2pkh(pubkey: &PublicKey, network: Network) -> Self {
+        CashAddress {
+            network: normalize(network),
+            is_script: false,
+            hash: pubkey.pubkey_hash().to_byte_array(),
+        }
+    }
+    /// The `bitcoincash:p...` address of the script.
+    pub fn p2sh(script: &Script, network: Network) -> Self {
+        CashAddress {
+            network: normalize(network),
+            is_script: true,
+            hash: script.script_hash().to_byte_array(),
+        }
+    }
+    /// The address of a P2PKH or P2SH scriptPubKey.
+    pub fn from_script(script_pubkey: &Script, network: Network) -> Result<Self> {
+        let bytes = script_pubkey.as_bytes();
+        let (is_script, hash) = if script_pubkey.is_p2pkh() {
+            (false, &bytes[3..23])
+        } else if script_pubkey.is_p2sh() {
+            (true, &bytes[2..22])
+        } else {
+            return Err(Error::Todo);
+        };
+
+        Ok(CashAddress {
+            network: normalize(network),
+            is_script,
+            hash: hash.try_into().expect("hash is 20 bytes"),
+        })
+    }
+    /// Either [`Network::Bitcoin`], [`Network::Testnet`] or
+    /// [`Network::Regtest`].
+    pub fn network(&self) -> Network {
+        self.network
+    }
+    pub fn is_script(&self) -> bool {
+        self.is_script
+    }
+    pub fn script_pubkey(&self) -> ScriptBuf {
+        if self.is_script {
+            ScriptBuf::new_p2sh(&ScriptHash::from_byte_array(self.hash))
+        } else {
+            ScriptBuf::new_p2pkh(&PubkeyHash::from_byte_array(self.hash))
+        }
+    }
+    /// The equivalent base58 address, as used by Bitcoin Cash before
+    /// CashAddr.
+    pub fn to_legacy(&self) -> Address {
+        Address::from_script(&self.script_pubkey(), self.network)
+            .expect("P2PKH and P2SH scripts have an address")
+    }
+    fn prefix(network: Network) -> &'static str {
+        match network {
+            Network::Bitcoin => CASHADDR_MAINNET,
+            Network::Regtest => CASHADDR_REGTEST,
+            _ => CASHADDR_TESTNET,
+        }
+    }
+}
+
+/// Bitcoin Cash has no signet, it shares the testnet prefix.
+fn normalize(network: Network) -> Network {
+    match network {
+        Network::Signet => Network::Testnet,
+        network => network,
+    }
+}
+
+/// The BCH code of CashAddr, which is 40 bits long, unlike the one of
+/// Bech32.
+fn polymod(values: &[u8]) -> u64 {
+    const GENERATORS: [u64; 5] = [
+        0x98_f2bc_8e61,
+        0x79_b76d_99e2,
+        0xf3_3e5f_b3c4,
+        0xae_2eab_e2a8,
+        0x1e_4f43_e470,
+    ];
+
+    let mut checksum = 1;
+    for value in values {
+        let top = checksum >> 35;
+        checksum = (checksum & 0x07_ffff_ffff) << 5 ^ u64::from(*value);
+
+        for (i, generator) in GENERATORS.iter().enumerate() {
+            if top >> i & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+
+    checksum ^ 1
+}
+
+/// The lower five bits of each prefix character, followed by a zero.
+fn expand_prefix(prefix: &str) -> Vec<u8> {
+    let mut values: Vec<u8> = prefix.bytes().map(|c| c & 0x1f).collect();
+    values.push(0);
+    values
+}
+
+impl fmt::Display for CashAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefix = Self::prefix(self.network);
+        let version = if self.is_script {
+            VERSION_P2SH
+        } else {
+            VERSION_P2PKH
+        };
+
+        let mut payload = vec![version];
+        payload.extend(self.hash);
+        let mut data: Vec<u8> = payload.to_base32().into_iter().map(u5::to_u8).collect();
+
+        let mut values = expand_prefix(prefix);
+        values.extend(&data);
+        values.extend([0; CHECKSUM_LENGTH]);
+
+        let checksum = polymod(&values);
+        data.extend((0..CHECKSUM_LENGTH).map(|i| (checksum >> (5 * (7 - i)) & 0x1f) as u8));
+
+        let encoded: String = data
+            .into_iter()
+            .map(|value| CHARSET[value as usize] as char)
+            .collect();
+
+        write!(f, "{}:{}", prefix, encoded)
+    }
+}
+
+impl FromStr for CashAddress {
+    type Err = Error;
+
+    /// Parses an address with or without prefix, the latter is assumed to be
+    /// a mainnet address.
+    fn from_str(string: &str) -> Result<Self> {
+        if string != string.to_lowercase() && string != string.to_uppercase() {
+            return Err(Error::Todo);
+        }
+        let string = string.to_lowercase();
+
+        let (prefix, encoded) = string
+            .split_once(':')
+            .unwrap_or((CASHADDR_MAINNET, string.as_str()));
+
+        let network = [Network::Bitcoin, Network::Testnet, Network::Regtest]
+            .into_iter()
+            .find(|network| Self::prefix(*network) == prefix)
+            .ok_or(Error::Todo)?;
+
+        let data = encoded
+            .bytes()
+            .map(|c| CHARSET.iter().position(|x| *x == c).map(|i| i as u8))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or(Error::Todo)?;
+        if data.len() <= CHECKSUM_LENGTH {
+            return Err(Error::Todo);
+        }
+
+        let mut values = expand_prefix(prefix);
+        values.extend(&data);
+        if polymod(&values) != 0 {
+            return Err(Error::Todo);
+        }
+
+        let data = data[..data.len() - CHECKSUM_LENGTH]
+            .iter()
+            .map(|value| u5::try_from_u8(*value))
+            .collect::<std::result::Result<Vec<u5>, _>>()
+            .map_err(|_| Error::Todo)?;
+        let payload = Vec::<u8>::from_base32(&data).map_err(|_| Error::Todo)?;
+
+        let (version, hash) = payload.split_first().ok_or(Error::Todo)?;
+        let is_script = match *version {
+            VERSION_P2PKH => false,
+            VERSION_P2SH => true,
+            _ => return Err(Error::Todo),
+        };
+
+        Ok(CashAddress {
+            network,
+            is_script,
+            hash: hash.try_into().map_err(|_| Error::Todo)?,
+        })
+    }
+}
@@ -1,4 +1,4 @@
-use crate::ordinals::OrdinalsInscription;
+use crate::ordinals::{InscriptionContent, OrdinalsInscription};
 use crate::{Error, Recipient, Result};
 use bitcoin::PublicKey;
 use serde::{Deserialize, Serialize};
@@ -51,6 +51,39 @@ impl TryFrom<String> for Ticker {
     }
 }
 
+/// A BRC-20 operation, decoded from the content of an inscription.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BRC20Operation {
+    /// Either `deploy`, `mint` or `transfer`.
+    pub operation: String,
+    pub ticker: String,
+    /// The amount minted or transferred, or the maximum supply of a deploy.
+    pub amount: String,
+}
+
+impl BRC20Operation {
+    /// Decodes the operation, if the content is a BRC-20 payload.
+    pub fn from_content(content: &InscriptionContent) -> Option<Self> {
+        let payload: BRC20Payload<serde_json::Value> =
+            serde_json::from_slice(content.data()).ok()?;
+
+        if payload.protocol != BRC20Payload::<serde_json::Value>::PROTOCOL_ID {
+            return None;
+        }
+
+        let amount_field = match payload.operation.as_str() {
+            BRC20DeployPayload::OPERATION => "max",
+            _ => "amt",
+        };
+
+        Some(BRC20Operation {
+            ticker: payload.inner.get("tick")?.as_str()?.to_string(),
+            amount: payload.inner.get(amount_field)?.as_str()?.to_string(),
+            operation: payload.operation,
+        })
+    }
+}
+
 impl BRC20DeployPayload {
     const OPERATION: &str = "deploy";
 
@@ -1,12 +1,15 @@
+use crate::multisig::TapscriptMultisig;
 use crate::{
     Error, Recipient, Result, TaprootScript, TxInputP2PKH, TxInputP2TRKeyPath,
-    TxInputP2TRScriptPath, TxInputP2WPKH,
+    TxInputP2TRScriptPath, TxInputP2WPKH, TxInputP2WSH,
 };
 use bitcoin::key::{KeyPair, PublicKey, TapTweak, TweakedKeyPair, TweakedPublicKey};
-use bitcoin::secp256k1::Secp256k1;
+use bitcoin::opcodes::all::{OP_CHECKSIG, OP_CHECKSIGADD, OP_CHECKSIGVERIFY};
+use bitcoin::script::Instruction;
+use bitcoin::secp256k1::{ecdsa, schnorr, Secp256k1, Signing, XOnlyPublicKey};
 use bitcoin::sighash::{EcdsaSighashType, TapSighashType};
 use bitcoin::taproot::{LeafVersion, Signature};
-use bitcoin::{ScriptBuf, Witness};
+use bitcoin::{Script, ScriptBuf, Witness};
 
 #[derive(Debug, Clone)]
 pub enum ClaimLocation {
@@ -29,6 +32,13 @@ pub trait TransactionSigner {
         sighash: secp256k1::Message,
         sighash_type: EcdsaSighashType,
     ) -> Result<ClaimP2WPKH>;
+    /// Claiming mechanism for SegWit P2WSH outputs.
+    fn claim_p2wsh(
+        &self,
+        input: &TxInputP2WSH,
+        sighash: secp256k1::Message,
+        sighash_type: EcdsaSighashType,
+    ) -> Result<ClaimP2WSH>;
     /// Claiming mechanism for Taproot P2TR key-path outputs.
     fn claim_p2tr_key_path(
         &self,
@@ -53,6 +63,10 @@ pub struct ClaimP2PKH(pub ScriptBuf);
 // the SegWit P2WPKH input.
 pub struct ClaimP2WPKH(pub Witness);
 
+// Contains the Witness that must be included in the transaction when spending
+// the SegWit P2WSH input.
+pub struct ClaimP2WSH(pub Witness);
+
 // Contains the Witness that must be included in the transaction when spending
 // the Taproot P2TR key-path input.
 pub struct ClaimP2TRKeyPath(pub Witness);
@@ -61,14 +75,136 @@ pub struct ClaimP2TRKeyPath(pub Witness);
 // the Taproot P2TR script-path input.
 pub struct ClaimP2TRScriptPath(pub Witness);
 
-impl TransactionSigner for KeyPair {
+/// The auxiliary randomness used when creating Schnorr signatures, as
+/// specified in BIP-340.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AuxRand {
+    /// Fresh randomness is drawn for every signature. This should ALWAYS be
+    /// used in production.
+    #[default]
+    Random,
+    /// The given 32 bytes are used for every signature.
+    Explicit([u8; 32]),
+    /// No auxiliary randomness is used, creating reproducible signatures.
+    Deterministic,
+}
+
+impl AuxRand {
+    fn sign_schnorr<C: Signing>(
+        &self,
+        secp: &Secp256k1<C>,
+        sighash: &secp256k1::Message,
+        keypair: &KeyPair,
+    ) -> schnorr::Signature {
+        match self {
+            AuxRand::Random => secp.sign_schnorr(sighash, keypair),
+            AuxRand::Explicit(aux_rand) => {
+                secp.sign_schnorr_with_aux_rand(sighash, keypair, aux_rand)
+            },
+            AuxRand::Deterministic => secp.sign_schnorr_no_aux_rand(sighash, keypair),
+        }
+    }
+}
+
+/// Signs the inputs with the given keypair, using the configured auxiliary
+/// randomness for Taproot (Schnorr) signatures. Signing with a [`KeyPair`]
+/// directly always uses fresh randomness.
+#[derive(Debug, Clone, Copy)]
+pub struct KeyPairSigner {
+    keypair: KeyPair,
+    aux_rand: AuxRand,
+}
+
+impl KeyPairSigner {
+    pub fn new(keypair: KeyPair) -> Self {
+        KeyPairSigner {
+            keypair,
+            aux_rand: AuxRand::default(),
+        }
+    }
+    pub fn aux_rand(mut self, aux_rand: AuxRand) -> Self {
+        self.aux_rand = aux_rand;
+        self
+    }
+    pub fn keypair(&self) -> &KeyPair {
+        &self.keypair
+    }
+}
+
+impl From<KeyPair> for KeyPairSigner {
+    fn from(keypair: KeyPair) -> Self {
+        KeyPairSigner::new(keypair)
+    }
+}
+
+/// A key that signs the sighashes of the inputs. Every key of this kind is a
+/// [`TransactionSigner`], which checks whether the key can claim the input and
+/// builds the scriptSig or Witness around the signature. The key itself may
+/// be held outside of the process memory, see [`crate::remote_signer`].
+pub trait SigningKey {
+    fn public_key(&self) -> PublicKey;
+    /// Creates the ECDSA signature of the sighash.
+    fn sign_ecdsa(&self, sighash: &secp256k1::Message) -> Result<ecdsa::Signature>;
+    /// Creates the Schnorr signature of the sighash. If `tweak` is set, the
+    /// key is tweaked for a key-path spend without a script tree first, as
+    /// specified in BIP-86.
+    fn sign_schnorr(&self, sighash: &secp256k1::Message, tweak: bool)
+        -> Result<schnorr::Signature>;
+}
+
+impl SigningKey for KeyPair {
+    fn public_key(&self) -> PublicKey {
+        KeyPairSigner::new(*self).public_key()
+    }
+    fn sign_ecdsa(&self, sighash: &secp256k1::Message) -> Result<ecdsa::Signature> {
+        KeyPairSigner::new(*self).sign_ecdsa(sighash)
+    }
+    fn sign_schnorr(
+        &self,
+        sighash: &secp256k1::Message,
+        tweak: bool,
+    ) -> Result<schnorr::Signature> {
+        KeyPairSigner::new(*self).sign_schnorr(sighash, tweak)
+    }
+}
+
+impl SigningKey for KeyPairSigner {
+    fn public_key(&self) -> PublicKey {
+        PublicKey::new(self.keypair.public_key())
+    }
+    fn sign_ecdsa(&self, sighash: &secp256k1::Message) -> Result<ecdsa::Signature> {
+        Ok(self.keypair.secret_key().sign_ecdsa(*sighash))
+    }
+    fn sign_schnorr(
+        &self,
+        sighash: &secp256k1::Message,
+        tweak: bool,
+    ) -> Result<schnorr::Signature> {
+        let secp = Secp256k1::new();
+
+        let keypair = if tweak {
+            // Tweak keypair for P2TR key-path (ie. zeroed Merkle root).
+            let tapped: TweakedKeyPair = self.keypair.tap_tweak(&secp, None);
+            KeyPair::from(tapped)
+        } else {
+            self.keypair
+        };
+
+        Ok(self.aux_rand.sign_schnorr(&secp, sighash, &keypair))
+    }
+}
+
+impl<K: SigningKey> TransactionSigner for K {
     fn claim_p2pkh(
         &self,
         input: &TxInputP2PKH,
         sighash: secp256k1::Message,
         sighash_type: EcdsaSighashType,
     ) -> Result<ClaimP2PKH> {
-        let me = Recipient::<PublicKey>::from_keypair(self);
+        let me = Recipient::<PublicKey>::from(PublicKey {
+            compressed: !input.is_uncompressed(),
+            ..self.public_key()
+        });
 
         // Check whether we can actually claim the input.
         if input.recipient().pubkey_hash() != &me.pubkey_hash() {
@@ -77,7 +213,7 @@ impl TransactionSigner for KeyPair {
 
         // Construct the ECDSA signature.
         let sig = bitcoin::ecdsa::Signature {
-            sig: self.secret_key().sign_ecdsa(sighash),
+            sig: self.sign_ecdsa(&sighash)?,
             hash_ty: sighash_type,
         };
 
@@ -95,7 +231,7 @@ impl TransactionSigner for KeyPair {
         sighash: secp256k1::Message,
         sighash_type: EcdsaSighashType,
     ) -> Result<ClaimP2WPKH> {
-        let me = Recipient::<PublicKey>::from_keypair(self);
+        let me = Recipient::<PublicKey>::from(self.public_key());
 
         if input.recipient().wpubkey_hash() != &me.wpubkey_hash()? {
             return Err(Error::Todo);
@@ -103,7 +239,7 @@ impl TransactionSigner for KeyPair {
 
         // Construct the ECDSA signature.
         let sig = bitcoin::ecdsa::Signature {
-            sig: self.secret_key().sign_ecdsa(sighash),
+            sig: self.sign_ecdsa(&sighash)?,
             hash_ty: sighash_type,
         };
 
@@ -115,36 +251,61 @@ impl TransactionSigner for KeyPair {
 
         Ok(ClaimP2WPKH(witness))
     }
-    fn claim_p2tr_key_path(
+    fn claim_p2wsh(
         &self,
-        input: &TxInputP2TRKeyPath,
+        input: &TxInputP2WSH,
         sighash: secp256k1::Message,
-        sighash_type: TapSighashType,
-    ) -> Result<ClaimP2TRKeyPath> {
-        let me = Recipient::<TweakedPublicKey>::from(self);
+        sighash_type: EcdsaSighashType,
+    ) -> Result<ClaimP2WSH> {
+        let me = Recipient::<PublicKey>::from(self.public_key());
 
         // Check whether we can actually claim the input.
         if input.recipient() != &me {
             return Err(Error::Todo);
         }
 
-        let secp = Secp256k1::new();
+        // Construct the ECDSA signature.
+        let sig = bitcoin::ecdsa::Signature {
+            sig: self.sign_ecdsa(&sighash)?,
+            hash_ty: sighash_type,
+        };
 
-        // Tweak keypair for P2TR key-path (ie. zeroed Merkle root).
-        let tapped: TweakedKeyPair = self.tap_tweak(&secp, None);
-        let tweaked = KeyPair::from(tapped);
+        // Construct the Witness for claiming, where the witness script comes
+        // last.
+        let mut witness = Witness::new();
+        witness.push(sig.serialize());
+        for item in input.witness_items() {
+            witness.push(item);
+        }
+        witness.push(input.witness_script());
 
-        // Construct the Schnorr signature.
-        #[cfg(not(test))]
-        let schnorr = secp.sign_schnorr(&sighash, &tweaked);
-        #[cfg(test)]
-        // For tests, we disable the included randomness in order to create
-        // reproducible signatures. Randomness should ALWAYS be used in
-        // production.
-        let schnorr = secp.sign_schnorr_no_aux_rand(&sighash, &tweaked);
+        Ok(ClaimP2WSH(witness))
+    }
+    fn claim_p2tr_key_path(
+        &self,
+        input: &TxInputP2TRKeyPath,
+        sighash: secp256k1::Message,
+        sighash_type: TapSighashType,
+    ) -> Result<ClaimP2TRKeyPath> {
+        let pubkey = self.public_key();
+        let me = Recipient::<TweakedPublicKey>::from(pubkey);
 
+        // Check whether we can actually claim the input. Silent payment
+        // outputs (see `crate::silent_payments`) pay to the key directly,
+        // without the Taproot tweak.
+        let tweak = if input.recipient() == &me {
+            true
+        } else if input.recipient().tweaked_pubkey().to_inner()
+            == XOnlyPublicKey::from(pubkey.inner)
+        {
+            false
+        } else {
+            return Err(Error::Todo);
+        };
+
+        // Construct the Schnorr signature.
         let sig = bitcoin::taproot::Signature {
-            sig: schnorr,
+            sig: self.sign_schnorr(&sighash, tweak)?,
             hash_ty: sighash_type,
         };
 
@@ -160,11 +321,26 @@ impl TransactionSigner for KeyPair {
         sighash: secp256k1::Message,
         sighash_type: TapSighashType,
     ) -> Result<ClaimP2TRScriptPath> {
+        let pubkey = self.public_key();
+        let x_only = XOnlyPublicKey::from(pubkey.inner);
+
         // Tweak our public key with the Merkle root of the Script to be claimed.
-        let me = Recipient::<TaprootScript>::from_keypair(self, input.recipient().merkle_root());
+        let me = Recipient::<TaprootScript>::from_pubkey_recipient(
+            pubkey.into(),
+            input.recipient().merkle_root(),
+        );
 
-        // Check whether we can actually claim the input.
-        if input.recipient() != &me {
+        // Check whether we can actually claim the input. If the script
+        // checks the signatures of specific keys, ours must be one of them.
+        // Otherwise, we must own the internal key.
+        let signers = required_signers(input.witness());
+        let can_claim = if signers.is_empty() {
+            input.recipient() == &me
+        } else {
+            signers.contains(&x_only)
+        };
+
+        if !can_claim {
             return Err(Error::Todo);
         }
 
@@ -178,17 +354,74 @@ impl TransactionSigner for KeyPair {
         // Construct the Schnorr signature. We leave the keypair untweaked,
         // unlike for key-path.
         let sig = Signature {
-            sig: Secp256k1::new().sign_schnorr(&sighash, self),
+            sig: self.sign_schnorr(&sighash, false)?,
             hash_ty: sighash_type,
         };
 
-        // Construct the Witness for claiming.
+        // A k-of-n multisig fails with more than `k` signatures, so besides
+        // ours only the signatures of the first other signers are used.
+        let required = TapscriptMultisig::from_leaf(input.witness())
+            .map(|multisig| multisig.required())
+            .unwrap_or(signers.len());
+
+        let mut used = vec![x_only];
+        for key in &signers {
+            if used.len() >= required {
+                break;
+            }
+
+            let has_signed = input.signatures().iter().any(|(signer, _)| signer == key);
+            if key != &x_only && has_signed {
+                used.push(*key);
+            }
+        }
+
+        // Construct the Witness for claiming. If the script checks the
+        // signatures of multiple keys, it requires one signature per key (or
+        // an empty push for absent signers), where the signature of the
+        // first key must be on top of the stack.
         let mut witness = Witness::new();
-        // Serialize signature.
-        witness.push(&sig.to_vec());
+        if signers.is_empty() {
+            witness.push(sig.to_vec());
+        } else {
+            for key in signers.iter().rev() {
+                if key == &x_only {
+                    witness.push(sig.to_vec());
+                } else if let Some((_, other)) = input
+                    .signatures()
+                    .iter()
+                    .find(|(signer, _)| signer == key && used.contains(signer))
+                {
+                    witness.push(other.to_vec());
+                } else {
+                    witness.push([]);
+                }
+            }
+        }
+
+        for item in input.witness_items() {
+            witness.push(item);
+        }
         witness.push(input.witness());
         witness.push(control_block.serialize());
 
         Ok(ClaimP2TRScriptPath(witness))
     }
 }
+
+/// Returns the x-only public keys whose signatures are checked by the script,
+/// in the order of the checks, i.e. each `<pubkey>` followed by `OP_CHECKSIG`,
+/// `OP_CHECKSIGVERIFY` or `OP_CHECKSIGADD`.
+pub(crate) fn required_signers(script: &Script) -> Vec<XOnlyPublicKey> {
+    let instructions: Vec<Instruction> = script.instructions().flatten().collect();
+
+    instructions
+        .windows(2)
+        .filter_map(|pair| match pair {
+            [Instruction::PushBytes(push), Instruction::Op(OP_CHECKSIG | OP_CHECKSIGVERIFY | OP_CHECKSIGADD)] => {
+                XOnlyPublicKey::from_slice(push.as_bytes()).ok()
+            },
+            _ => None,
+        })
+        .collect()
+}
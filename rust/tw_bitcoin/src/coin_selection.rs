@@ -0,0 +1,520 @@
+use crate::{estimate_weight, Error, Result, TxInput, TxOutput};
+use bitcoin::{Script, ScriptBuf, VarInt, Weight};
+use std::collections::BTreeMap;
+
+/// The maximum number of branches explored by branch-and-bound before giving
+/// up, the same limit as used by Bitcoin Core.
+const BNB_TOTAL_TRIES: usize = 100_000;
+
+/// The maximum weight of a transaction that is relayed by Bitcoin Core.
+pub const MAX_STANDARD_TX_WEIGHT: Weight = Weight::from_wu(400_000);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CoinSelectionStrategy {
+    /// Searches for a set of inputs that pays for the outputs and the fee
+    /// without creating a change output. Falls back to `LargestFirst` if no
+    /// such set can be found.
+    #[default]
+    BranchAndBound,
+    /// Spends the largest UTXOs first, which minimizes the number of inputs.
+    LargestFirst,
+    /// Spends the UTXOs with the lowest block height first. Unconfirmed UTXOs
+    /// (or those with unknown height) are spent last.
+    OldestFirst,
+    /// Spends all UTXOs sharing the same scriptPubKey together and prefers
+    /// spending a single script, so that addresses are never partially spent
+    /// and unrelated addresses are linked as rarely as possible.
+    Privacy,
+}
+
+/// A spendable output which can be picked by the [`CoinSelector`].
+#[derive(Debug, Clone)]
+pub struct Utxo {
+    input: TxInput,
+    block_height: Option<u32>,
+    locked: bool,
+}
+
+impl Utxo {
+    pub fn new(input: TxInput) -> Self {
+        Utxo {
+            input,
+            block_height: None,
+            locked: false,
+        }
+    }
+    /// The block height at which the UTXO was confirmed.
+    pub fn block_height(mut self, height: u32) -> Self {
+        self.block_height = Some(height);
+        self
+    }
+    /// Locked UTXOs are never selected, for example because they carry an
+    /// inscription.
+    pub fn locked(mut self, locked: bool) -> Self {
+        self.locked = locked;
+        self
+    }
+    pub fn input(&self) -> &TxInput {
+        &self.input
+    }
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+}
+
+impl From<TxInput> for Utxo {
+    fn from(input: TxInput) -> Self {
+        Utxo::new(input)
+    }
+}
+
+/// The result of the coin selection.
+#[derive(Debug, Clone)]
+pub struct CoinSelection {
+    /// The selected inputs, in the order of the given UTXOs.
+    pub inputs: Vec<TxInput>,
+    /// The amount returned to the change script, zero if no change output
+    /// should be created.
+    pub change: u64,
+    /// The fee paid to the miner, including any excess that was too small
+    /// for a change output.
+    pub fee: u64,
+}
+
+/// The result of sweeping UTXOs to a single destination, see
+/// [`CoinSelector::sweep`].
+#[derive(Debug, Clone)]
+pub struct Sweep {
+    /// The transactions, each one staying below [`MAX_STANDARD_TX_WEIGHT`].
+    pub transactions: Vec<SweepTransaction>,
+    /// The marginal cost of each of the given UTXOs, meaning the fee for
+    /// adding it as an input.
+    pub marginal_costs: Vec<u64>,
+    /// The indices of the UTXOs that were not swept, because they are locked,
+    /// below the minimum value, cost more to spend than they are worth or are
+    /// not worth more than dust together after fees.
+    pub skipped: Vec<usize>,
+}
+
+/// A transaction of a [`Sweep`], spending all of its inputs to a single
+/// output.
+#[derive(Debug, Clone)]
+pub struct SweepTransaction {
+    pub inputs: Vec<TxInput>,
+    /// The output paying to the destination, the inputs minus the fee.
+    pub output: TxOutput,
+    pub fee: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct CoinSelector {
+    strategy: CoinSelectionStrategy,
+    sat_vb: u64,
+    use_max_utxo: bool,
+    disable_dust_filter: bool,
+}
+
+impl CoinSelector {
+    pub fn new(strategy: CoinSelectionStrategy) -> Self {
+        CoinSelector {
+            strategy,
+            sat_vb: 1,
+            use_max_utxo: false,
+            disable_dust_filter: false,
+        }
+    }
+    /// The fee rate in satoshis per virtual byte.
+    pub fn sat_vb(mut self, sat_vb: u64) -> Self {
+        self.sat_vb = sat_vb;
+        self
+    }
+    /// Spend all available UTXOs, independent of the strategy.
+    pub fn use_max_utxo(mut self, use_max_utxo: bool) -> Self {
+        self.use_max_utxo = use_max_utxo;
+        self
+    }
+    /// By default, UTXOs which cost more to spend than they are worth are
+    /// ignored.
+    pub fn disable_dust_filter(mut self, disable: bool) -> Self {
+        self.disable_dust_filter = disable;
+        self
+    }
+    /// Selects the inputs for paying the given `outputs`. Any change is meant
+    /// to be sent to `change_script`, which is only added by the caller if
+    /// the returned change is non-zero.
+    pub fn select(
+        &self,
+        utxos: &[Utxo],
+        outputs: &[TxOutput],
+        change_script: &Script,
+    ) -> Result<CoinSelection> {
+        let target: u64 = outputs.iter().map(|output| output.satoshis()).sum();
+
+        // The fee for everything but the inputs. We assume SegWit and a
+        // three-byte input count, so that the final fee can only be lower.
+        let fixed_weight = estimate_weight(std::iter::empty(), outputs)
+            + Weight::from_witness_data_size(2)
+            + Weight::from_non_witness_data_size(2);
+        let needed = target + self.fee(fixed_weight);
+
+        // Prepare the pool of UTXOs that can be selected, together with
+        // their effective value (value minus the fee to spend it).
+        let pool: Vec<Candidate> = utxos
+            .iter()
+            .enumerate()
+            .filter(|(_, utxo)| !utxo.locked)
+            .map(|(index, utxo)| Candidate {
+                index,
+                effective: utxo.input.satoshis() as i64
+                    - self.fee(utxo.input.estimated_weight()) as i64,
+            })
+            .filter(|candidate| self.disable_dust_filter || candidate.effective > 0)
+            .collect();
+
+        let selected = if self.use_max_utxo {
+            let sum: i64 = pool.iter().map(|candidate| candidate.effective).sum();
+            let all = pool.iter().map(|candidate| candidate.index).collect();
+            Some(all).filter(|_| sum >= needed as i64)
+        } else {
+            match self.strategy {
+                CoinSelectionStrategy::BranchAndBound => {
+                    let cost_of_change = self.cost_of_change(change_script);
+                    branch_and_bound(&pool, needed, cost_of_change)
+                        .or_else(|| largest_first(&pool, utxos, needed))
+                },
+                CoinSelectionStrategy::LargestFirst => largest_first(&pool, utxos, needed),
+                CoinSelectionStrategy::OldestFirst => oldest_first(&pool, utxos, needed),
+                CoinSelectionStrategy::Privacy => privacy(&pool, utxos, needed),
+            }
+        };
+
+        let mut selected = selected.ok_or(Error::Todo)?;
+        selected.sort_unstable();
+
+        let inputs: Vec<TxInput> = selected
+            .into_iter()
+            .map(|index| utxos[index].input.clone())
+            .collect();
+
+        self.finalize(inputs, outputs, change_script)
+    }
+    /// Decides whether a change output is worth creating for the selected
+    /// inputs and computes the final fee.
+    fn finalize(
+        &self,
+        inputs: Vec<TxInput>,
+        outputs: &[TxOutput],
+        change_script: &Script,
+    ) -> Result<CoinSelection> {
+        let total: u64 = inputs.iter().map(|input| input.satoshis()).sum();
+        let target: u64 = outputs.iter().map(|output| output.satoshis()).sum();
+
+        let fee = self.fee(estimate_weight(&inputs, outputs));
+        let excess = total.checked_sub(target + fee).ok_or(Error::Todo)?;
+
+        let change_fee = self.fee(change_output_weight(change_script));
+        let change = excess.saturating_sub(change_fee);
+
+        if change >= change_script.dust_value().to_sat() {
+            Ok(CoinSelection {
+                inputs,
+                change,
+                fee: fee + change_fee,
+            })
+        } else {
+            // The excess is too small for a change output, leave it to the
+            // miner.
+            Ok(CoinSelection {
+                inputs,
+                change: 0,
+                fee: fee + excess,
+            })
+        }
+    }
+    /// Spends the given UTXOs to `destination`, with the fee deducted from
+    /// the output. UTXOs below `min_value` are skipped. The UTXOs are split
+    /// into multiple transactions if a single one would be non-standard.
+    pub fn sweep(&self, utxos: &[Utxo], destination: &Script, min_value: u64) -> Result<Sweep> {
+        let marginal_costs: Vec<u64> = utxos
+            .iter()
+            .map(|utxo| self.marginal_cost(&utxo.input))
+            .collect();
+
+        let mut skipped = vec![];
+        let mut swept = vec![];
+        for (index, utxo) in utxos.iter().enumerate() {
+            let satoshis = utxo.input.satoshis();
+            let is_dust = !self.disable_dust_filter && satoshis <= marginal_costs[index];
+
+            if utxo.locked || satoshis < min_value || is_dust {
+                skipped.push(index);
+            } else {
+                swept.push(index);
+            }
+        }
+
+        if swept.is_empty() {
+            return Err(Error::Todo);
+        }
+
+        // Upper bound of the weight of everything but the inputs, the same as
+        // used for the coin selection. Each input may additionally require an
+        // empty witness.
+        let output = TxOutput::from_script(destination.to_owned(), 0)?;
+        let fixed_weight = estimate_weight(std::iter::empty(), &[output])
+            + Weight::from_witness_data_size(2)
+            + Weight::from_non_witness_data_size(2);
+
+        let mut chunks: Vec<Vec<usize>> = vec![vec![]];
+        let mut weight = fixed_weight;
+        for index in swept {
+            let input_weight =
+                utxos[index].input.estimated_weight() + Weight::from_witness_data_size(1);
+
+            if weight + input_weight > MAX_STANDARD_TX_WEIGHT {
+                chunks.push(vec![]);
+                weight = fixed_weight;
+            }
+
+            weight += input_weight;
+            chunks.last_mut().expect("at least one chunk").push(index);
+        }
+
+        // The previous chunks are full, so the UTXOs of a (last) chunk that
+        // is not worth more than dust after fees are skipped instead.
+        let mut transactions = vec![];
+        for chunk in chunks {
+            let inputs = chunk
+                .iter()
+                .map(|index| utxos[*index].input.clone())
+                .collect();
+
+            match self.sweep_transaction(inputs, destination) {
+                Ok(tx) => transactions.push(tx),
+                Err(_) => skipped.extend(chunk),
+            }
+        }
+
+        if transactions.is_empty() {
+            return Err(Error::Todo);
+        }
+
+        skipped.sort_unstable();
+
+        Ok(Sweep {
+            transactions,
+            marginal_costs,
+            skipped,
+        })
+    }
+    /// The fee for spending the input, which is uneconomical if it exceeds
+    /// the value of the input.
+    pub fn marginal_cost(&self, input: &TxInput) -> u64 {
+        self.fee(input.estimated_weight())
+    }
+    fn sweep_transaction(
+        &self,
+        inputs: Vec<TxInput>,
+        destination: &Script,
+    ) -> Result<SweepTransaction> {
+        let total: u64 = inputs.iter().map(|input| input.satoshis()).sum();
+
+        let output = TxOutput::from_script(destination.to_owned(), total)?;
+        let fee = self.fee(estimate_weight(&inputs, &[output]));
+
+        let satoshis = total.checked_sub(fee).ok_or(Error::Todo)?;
+        if !self.disable_dust_filter && satoshis < destination.dust_value().to_sat() {
+            return Err(Error::Todo);
+        }
+
+        Ok(SweepTransaction {
+            inputs,
+            output: TxOutput::from_script(destination.to_owned(), satoshis)?,
+            fee,
+        })
+    }
+    fn fee(&self, weight: Weight) -> u64 {
+        weight.to_vbytes_ceil() * self.sat_vb
+    }
+    /// The cost of creating a change output now and spending it later.
+    fn cost_of_change(&self, change_script: &Script) -> u64 {
+        self.fee(change_output_weight(change_script)) + self.fee(spend_weight(change_script))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Candidate {
+    /// Index into the original list of UTXOs.
+    index: usize,
+    effective: i64,
+}
+
+/// Selects candidates in the given order until their effective value covers
+/// `needed`.
+fn accumulate<'a, I>(candidates: I, needed: u64) -> Option<Vec<usize>>
+where
+    I: IntoIterator<Item = &'a Candidate>,
+{
+    let mut selected = vec![];
+    let mut sum = 0;
+
+    for candidate in candidates {
+        selected.push(candidate.index);
+        sum += candidate.effective;
+
+        if sum >= needed as i64 {
+            return Some(selected);
+        }
+    }
+
+    None
+}
+
+fn largest_first(pool: &[Candidate], utxos: &[Utxo], needed: u64) -> Option<Vec<usize>> {
+    let mut sorted = pool.to_vec();
+    sorted.sort_by_key(|candidate| std::cmp::Reverse(utxos[candidate.index].input.satoshis()));
+
+    accumulate(&sorted, needed)
+}
+
+fn oldest_first(pool: &[Candidate], utxos: &[Utxo], needed: u64) -> Option<Vec<usize>> {
+    let mut sorted = pool.to_vec();
+    sorted.sort_by_key(|candidate| utxos[candidate.index].block_height.unwrap_or(u32::MAX));
+
+    accumulate(&sorted, needed)
+}
+
+fn privacy(pool: &[Candidate], utxos: &[Utxo], needed: u64) -> Option<Vec<usize>> {
+    // Group the candidates by their scriptPubKey.
+    let mut groups: BTreeMap<&ScriptBuf, Vec<Candidate>> = BTreeMap::new();
+    for candidate in pool {
+        let script = &utxos[candidate.index].input.ctx().script_pubkey;
+        groups.entry(script).or_default().push(*candidate);
+    }
+
+    let mut groups: Vec<(i64, Vec<Candidate>)> = groups
+        .into_values()
+        .map(|group| (group.iter().map(|c| c.effective).sum(), group))
+        .collect();
+
+    // Prefer the smallest group that covers the target on its own, which
+    // does not link any addresses.
+    if let Some((_, group)) = groups
+        .iter()
+        .filter(|(sum, _)| *sum >= needed as i64)
+        .min_by_key(|(sum, _)| *sum)
+    {
+        return Some(group.iter().map(|candidate| candidate.index).collect());
+    }
+
+    // Otherwise link as few addresses as possible.
+    groups.sort_by_key(|(sum, _)| std::cmp::Reverse(*sum));
+
+    let mut selected = vec![];
+    let mut sum = 0;
+    for (group_sum, group) in groups {
+        selected.extend(group.iter().map(|candidate| candidate.index));
+        sum += group_sum;
+
+        if sum >= needed as i64 {
+            return Some(selected);
+        }
+    }
+
+    None
+}
+
+/// Depth-first search for a set of candidates whose effective value lies in
+/// the range of `needed` to `needed + cost_of_change`, meaning that the
+/// excess can be left to the miner instead of creating a change output. The
+/// set with the least excess is returned.
+fn branch_and_bound(pool: &[Candidate], needed: u64, cost_of_change: u64) -> Option<Vec<usize>> {
+    let mut sorted: Vec<Candidate> = pool.iter().filter(|c| c.effective > 0).cloned().collect();
+    sorted.sort_by_key(|candidate| std::cmp::Reverse(candidate.effective));
+
+    let needed = needed as i64;
+    let upper_bound = needed + cost_of_change as i64;
+
+    let mut available: i64 = sorted.iter().map(|candidate| candidate.effective).sum();
+    let mut value: i64 = 0;
+    // Whether the candidate at the corresponding depth is included.
+    let mut selection: Vec<bool> = vec![];
+    let mut best: Option<(i64, Vec<bool>)> = None;
+
+    for _ in 0..BNB_TOTAL_TRIES {
+        let backtrack = if value + available < needed || value > upper_bound {
+            // The target cannot be reached anymore or has been exceeded.
+            true
+        } else if value >= needed {
+            let excess = value - needed;
+            let is_better = match &best {
+                Some((best_excess, _)) => excess < *best_excess,
+                None => true,
+            };
+
+            if is_better {
+                best = Some((excess, selection.clone()));
+            }
+
+            // Cannot do better than an exact match.
+            if excess == 0 {
+                break;
+            }
+
+            true
+        } else {
+            false
+        };
+
+        if backtrack {
+            // Walk back to the last included candidate, whose omission branch
+            // still needs to be explored.
+            while let Some(false) = selection.last() {
+                selection.pop();
+                available += sorted[selection.len()].effective;
+            }
+
+            let Some(last) = selection.last_mut() else {
+                // The whole tree has been explored.
+                break;
+            };
+
+            *last = false;
+            value -= sorted[selection.len() - 1].effective;
+        } else {
+            // Continue down the inclusion branch.
+            let candidate = &sorted[selection.len()];
+            available -= candidate.effective;
+            value += candidate.effective;
+            selection.push(true);
+        }
+    }
+
+    best.map(|(_, selection)| {
+        selection
+            .iter()
+            .enumerate()
+            .filter(|(_, included)| **included)
+            .map(|(depth, _)| sorted[depth].index)
+            .collect()
+    })
+}
+
+fn change_output_weight(change_script: &Script) -> Weight {
+    let script_len = change_script.len() as u64;
+    Weight::from_non_witness_data_size(8 + VarInt(script_len).len() as u64 + script_len)
+}
+
+/// The weight of spending an output with the given scriptPubKey, see
+/// [`TxInput::estimated_weight`].
+fn spend_weight(script: &Script) -> Weight {
+    let base = Weight::from_non_witness_data_size(32 + 4 + 4 + 1);
+
+    if script.is_v0_p2wpkh() {
+        base + Weight::from_witness_data_size(1 + 1 + 72 + 1 + 33)
+    } else if script.is_v1_p2tr() {
+        base + Weight::from_witness_data_size(1 + 1 + 64)
+    } else {
+        base + Weight::from_non_witness_data_size(1 + 72 + 1 + 33)
+    }
+}
@@ -0,0 +1,354 @@
+//! Decred transactions and addresses. Decred is derived from Bitcoin, but
+//! splits the transaction into a prefix (inputs, outputs, lock time and
+//! expiry) and a witness (the amounts and signature scripts of the inputs),
+//! and uses BLAKE-256 instead of SHA-256 everywhere.
+//!
+//! The transaction ID only covers the prefix, so it doesn't change when
+//! the inputs get signed. Outpoints additionally carry the tree of the spent
+//! output, which is either the regular or the stake tree.
+
+use crate::{Error, Result};
+use bitcoin::base58;
+use bitcoin::consensus::Encodable;
+use bitcoin::hashes::{ripemd160, Hash};
+use bitcoin::sighash::EcdsaSighashType;
+use bitcoin::{
+    Network, OutPoint, PubkeyHash, PublicKey, Script, ScriptBuf, ScriptHash, Transaction, Txid,
+    VarInt,
+};
+use std::fmt;
+use std::str::FromStr;
+use tw_hash::blake::blake_256;
+
+/// The SLIP-44 coin type of Decred.
+pub const DECRED_COIN_TYPE: u32 = 42;
+
+/// The tree of regular transactions.
+pub const TREE_REGULAR: i8 = 0;
+/// The tree of stake transactions, such as ticket purchases and votes.
+pub const TREE_STAKE: i8 = 1;
+
+/// The transaction version, the upper 16 bits of the version field hold the
+/// serialization type.
+const TX_VERSION: u32 = 1;
+
+const SERIALIZE_FULL: u32 = 0;
+const SERIALIZE_NO_WITNESS: u32 = 1;
+const SERIALIZE_WITNESS_SIGNING: u32 = 3;
+
+/// The script version of all outputs.
+const SCRIPT_VERSION: u16 = 0;
+
+/// The witness of an input doesn't reference a block.
+const NO_BLOCK_HEIGHT: u32 = 0;
+const NO_BLOCK_INDEX: u32 = u32::MAX;
+
+const HASH_SIZE: usize = 32;
+const CHECKSUM_SIZE: usize = 4;
+
+/// The Decred specifics of a transaction, see
+/// [`crate::TransactionBuilder::decred`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DecredParams {
+    expiry: u32,
+    trees: Vec<(OutPoint, i8)>,
+}
+
+impl DecredParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Sets the block height after which the transaction can no longer be
+    /// mined, `0` (the default) disables the expiry.
+    pub fn expiry(mut self, expiry: u32) -> Self {
+        self.expiry = expiry;
+        self
+    }
+    /// Sets the tree of the spent output, which is [`TREE_REGULAR`] unless
+    /// set otherwise.
+    pub fn tree(mut self, outpoint: OutPoint, tree: i8) -> Self {
+        self.trees.retain(|(existing, _)| existing != &outpoint);
+        self.trees.push((outpoint, tree));
+        self
+    }
+    /// The version field of the transaction, without the serialization type.
+    pub fn version_number(&self) -> i32 {
+        TX_VERSION as i32
+    }
+    /// Returns the tree of the spent output.
+    pub fn tree_of(&self, outpoint: &OutPoint) -> i8 {
+        self.trees
+            .iter()
+            .find(|(existing, _)| existing == outpoint)
+            .map(|(_, tree)| *tree)
+            .unwrap_or(TREE_REGULAR)
+    }
+    /// Serializes the full transaction, where `amounts` are the values of the
+    /// spent outputs. The version of the transaction is ignored.
+    pub fn serialize(&self, tx: &Transaction, amounts: &[u64]) -> Result<Vec<u8>> {
+        if amounts.len() != tx.input.len() {
+            return Err(Error::Todo);
+        }
+
+        let mut buffer = vec![];
+        buffer.extend(version(SERIALIZE_FULL).to_le_bytes());
+        self.encode_prefix(tx, &mut buffer);
+
+        encode(&VarInt(tx.input.len() as u64), &mut buffer);
+        for (input, amount) in tx.input.iter().zip(amounts) {
+            buffer.extend(amount.to_le_bytes());
+            buffer.extend(NO_BLOCK_HEIGHT.to_le_bytes());
+            buffer.extend(NO_BLOCK_INDEX.to_le_bytes());
+            encode(&input.script_sig, &mut buffer);
+        }
+
+        Ok(buffer)
+    }
+    /// The transaction ID, the BLAKE-256 hash of the prefix.
+    pub fn txid(&self, tx: &Transaction) -> Txid {
+        let mut data = vec![];
+        data.extend(version(SERIALIZE_NO_WITNESS).to_le_bytes());
+        self.encode_prefix(tx, &mut data);
+
+        Txid::from_byte_array(blake256(&data))
+    }
+    /// Computes the signature hash of the input at `index`, which spends an
+    /// output locked by `script_pubkey`.
+    pub fn signature_hash(
+        &self,
+        tx: &Transaction,
+        index: usize,
+        script_pubkey: &Script,
+        sighash_type: EcdsaSighashType,
+    ) -> Result<[u8; HASH_SIZE]> {
+        use EcdsaSighashType::*;
+
+        if index >= tx.input.len() {
+            return Err(Error::Todo);
+        }
+
+        let anyone_can_pay = matches!(
+            sighash_type,
+            AllPlusAnyoneCanPay | NonePlusAnyoneCanPay | SinglePlusAnyoneCanPay
+        );
+        let is_none = matches!(sighash_type, None | NonePlusAnyoneCanPay);
+        let is_single = matches!(sighash_type, Single | SinglePlusAnyoneCanPay);
+
+        if is_single && index >= tx.output.len() {
+            return Err(Error::Todo);
+        }
+
+        // Only the input being signed is committed to with `ANYONECANPAY`.
+        let (inputs, sign_index) = if anyone_can_pay {
+            (&tx.input[index..=index], 0)
+        } else {
+            (&tx.input[..], index)
+        };
+
+        let outputs = if is_none {
+            &[][..]
+        } else if is_single {
+            &tx.output[..=index]
+        } else {
+            &tx.output[..]
+        };
+
+        // The prefix, where the sequences of the other inputs are zeroed out
+        // for `NONE` and `SINGLE`, as are the outputs before `index` for
+        // `SINGLE`.
+        let mut prefix = vec![];
+        prefix.extend(version(SERIALIZE_NO_WITNESS).to_le_bytes());
+
+        encode(&VarInt(inputs.len() as u64), &mut prefix);
+        for (i, input) in inputs.iter().enumerate() {
+            self.encode_outpoint(&input.previous_output, &mut prefix);
+
+            let mut sequence = input.sequence.to_consensus_u32();
+            if (is_none || is_single) && i != sign_index {
+                sequence = 0;
+            }
+            prefix.extend(sequence.to_le_bytes());
+        }
+
+        encode(&VarInt(outputs.len() as u64), &mut prefix);
+        for (i, output) in outputs.iter().enumerate() {
+            if is_single && i != index {
+                encode_output(u64::MAX, Script::empty(), &mut prefix);
+            } else {
+                encode_output(output.value, &output.script_pubkey, &mut prefix);
+            }
+        }
+
+        prefix.extend(tx.lock_time.to_consensus_u32().to_le_bytes());
+        prefix.extend(self.expiry.to_le_bytes());
+
+        // The witness, which only contains the script of the input being
+        // signed.
+        let mut witness = vec![];
+        witness.extend(version(SERIALIZE_WITNESS_SIGNING).to_le_bytes());
+
+        encode(&VarInt(inputs.len() as u64), &mut witness);
+        for i in 0..inputs.len() {
+            if i == sign_index {
+                encode(script_pubkey, &mut witness);
+            } else {
+                encode(Script::empty(), &mut witness);
+            }
+        }
+
+        let mut data = vec![];
+        data.extend(sighash_type.to_u32().to_le_bytes());
+        data.extend(blake256(&prefix));
+        data.extend(blake256(&witness));
+
+        Ok(blake256(&data))
+    }
+    fn encode_outpoint(&self, outpoint: &OutPoint, buffer: &mut Vec<u8>) {
+        encode(outpoint, buffer);
+        buffer.push(self.tree_of(outpoint) as u8);
+    }
+    fn encode_prefix(&self, tx: &Transaction, buffer: &mut Vec<u8>) {
+        encode(&VarInt(tx.input.len() as u64), buffer);
+        for input in &tx.input {
+            self.encode_outpoint(&input.previous_output, buffer);
+            buffer.extend(input.sequence.to_consensus_u32().to_le_bytes());
+        }
+
+        encode(&VarInt(tx.output.len() as u64), buffer);
+        for output in &tx.output {
+            encode_output(output.value, &output.script_pubkey, buffer);
+        }
+
+        buffer.extend(tx.lock_time.to_consensus_u32().to_le_bytes());
+        buffer.extend(self.expiry.to_le_bytes());
+    }
+}
+
+fn version(serialize_type: u32) -> u32 {
+    TX_VERSION | serialize_type << 16
+}
+
+fn blake256(data: &[u8]) -> [u8; HASH_SIZE] {
+    blake_256(data)
+        .try_into()
+        .expect("BLAKE-256 returns 32 bytes")
+}
+
+fn encode<T: Encodable + ?Sized>(value: &T, buffer: &mut Vec<u8>) {
+    value
+        .consensus_encode(buffer)
+        .expect("encoding to a vector never fails");
+}
+
+fn encode_output(value: u64, script_pubkey: &Script, buffer: &mut Vec<u8>) {
+    buffer.extend(value.to_le_bytes());
+    buffer.extend(SCRIPT_VERSION.to_le_bytes());
+    encode(script_pubkey, buffer);
+}
+
+/// The Decred equivalent of `HASH160`, RIPEMD-160 of BLAKE-256.
+pub fn hash160(data: &[u8]) -> [u8; 20] {
+    ripemd160::Hash::hash(&blake256(data)).to_byte_array()
+}
+
+/// A base58 Decred address, paying to a public key hash or a script hash.
+/// The checksum is the double BLAKE-256 of the payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecredAddress {
+    network: Network,
+    is_script: bool,
+    hash: [u8; 20],
+}
+
+impl DecredAddress {
+    /// The `Ds...` (mainnet) or `Ts...` (testnet) address of the public key.
+    pub fn p2pkh(pubkey: &PublicKey, network: Network) -> Self {
+        DecredAddress {
+            network: normalize(network),
+            is_script: false,
+            hash: hash160(&pubkey.to_bytes()),
+        }
+    }
+    /// The `Dc...` (mainnet) or `Tc...` (testnet) address of the script.
+    pub fn p2sh(script: &Script, network: Network) -> Self {
+        DecredAddress {
+            network: normalize(network),
+            is_script: true,
+            hash: hash160(script.as_bytes()),
+        }
+    }
+    /// Either [`Network::Bitcoin`] for mainnet or [`Network::Testnet`].
+    pub fn network(&self) -> Network {
+        self.network
+    }
+    pub fn is_script(&self) -> bool {
+        self.is_script
+    }
+    pub fn script_pubkey(&self) -> ScriptBuf {
+        if self.is_script {
+            ScriptBuf::new_p2sh(&ScriptHash::from_byte_array(self.hash))
+        } else {
+            ScriptBuf::new_p2pkh(&PubkeyHash::from_byte_array(self.hash))
+        }
+    }
+    fn prefix(network: Network, is_script: bool) -> [u8; 2] {
+        match (network, is_script) {
+            (Network::Bitcoin, false) => [0x07, 0x3f],
+            (Network::Bitcoin, true) => [0x07, 0x1a],
+            (_, false) => [0x0f, 0x21],
+            (_, true) => [0x0e, 0xfc],
+        }
+    }
+}
+
+/// Decred only distinguishes between mainnet and testnet.
+fn normalize(network: Network) -> Network {
+    match network {
+        Network::Bitcoin => Network::Bitcoin,
+        _ => Network::Testnet,
+    }
+}
+
+impl fmt::Display for DecredAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut data = Self::prefix(self.network, self.is_script).to_vec();
+        data.extend(self.hash);
+
+        let checksum = blake256(&blake256(&data));
+        data.extend(&checksum[..CHECKSUM_SIZE]);
+
+        f.write_str(&base58::encode(&data))
+    }
+}
+
+impl FromStr for DecredAddress {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Self> {
+        let data = base58::decode(string).map_err(|_| Error::Todo)?;
+        if data.len() != 2 + 20 + CHECKSUM_SIZE {
+            return Err(Error::Todo);
+        }
+
+        let (payload, checksum) = data.split_at(data.len() - CHECKSUM_SIZE);
+        if blake256(&blake256(payload))[..CHECKSUM_SIZE] != *checksum {
+            return Err(Error::Todo);
+        }
+
+        let (network, is_script) = [
+            (Network::Bitcoin, false),
+            (Network::Bitcoin, true),
+            (Network::Testnet, false),
+            (Network::Testnet, true),
+        ]
+        .into_iter()
+        .find(|(network, is_script)| Self::prefix(*network, *is_script) == payload[..2])
+        .ok_or(Error::Todo)?;
+
+        Ok(DecredAddress {
+            network,
+            is_script,
+            hash: payload[2..].try_into().expect("payload has 22 bytes"),
+        })
+    }
+}
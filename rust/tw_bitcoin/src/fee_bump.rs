@@ -0,0 +1,253 @@
+//! Builders for bumping the fee of a stuck transaction, either by replacing
+//! it (BIP-125 replace-by-fee) or by spending one of its outputs with a child
+//! transaction that pays for both (child-pays-for-parent).
+
+use crate::utils::checked_sum;
+use crate::{estimate_weight, Error, Result, TransactionBuilder, TxInput, TxOutput};
+use bitcoin::{ScriptBuf, Sequence, Transaction};
+use std::cmp::Reverse;
+
+/// The minimum fee rate (in satoshis per vbyte) by which a replacement must
+/// pay for its own relay, as enforced by Bitcoin Core.
+pub const INCREMENTAL_RELAY_FEE_SAT_VB: u64 = 1;
+
+/// Builds a BIP-125 replacement of a signed transaction at a higher fee rate.
+///
+/// The payments of the original transaction are kept as they are, whatever
+/// their scriptPubKey. The fee is taken from the change output (identified by
+/// the change script), which gets dropped if it would become dust. If that's
+/// not sufficient, the additional UTXOs are spent, largest first. Note that those UTXOs must be confirmed,
+/// since a replacement must not introduce new unconfirmed inputs.
+#[derive(Debug, Clone)]
+pub struct RbfBuilder {
+    original: Transaction,
+    prevouts: Vec<TxInput>,
+    utxos: Vec<TxInput>,
+    change_script: Option<ScriptBuf>,
+    sat_vb: u64,
+}
+
+impl RbfBuilder {
+    /// The `prevouts` are the inputs spent by the `original` transaction, in
+    /// the same order.
+    pub fn new(original: Transaction, prevouts: Vec<TxInput>) -> Self {
+        RbfBuilder {
+            original,
+            prevouts,
+            utxos: vec![],
+            change_script: None,
+            sat_vb: 0,
+        }
+    }
+    pub fn sat_vb(mut self, sat_vb: u64) -> Self {
+        self.sat_vb = sat_vb;
+        self
+    }
+    pub fn change_script(mut self, script: ScriptBuf) -> Self {
+        self.change_script = Some(script);
+        self
+    }
+    pub fn add_utxo(mut self, utxo: TxInput) -> Self {
+        self.utxos.push(utxo);
+        self
+    }
+    pub fn build(self) -> Result<TransactionBuilder> {
+        let original = self.original;
+
+        // The original transaction must signal replaceability.
+        if !original.is_explicitly_rbf() || self.prevouts.len() != original.input.len() {
+            return Err(Error::Todo);
+        }
+
+        // Keep the inputs of the original transaction, including sequences.
+        let mut inputs = self.prevouts;
+        for (input, txin) in inputs.iter_mut().zip(&original.input) {
+            if input.ctx().previous_output != txin.previous_output {
+                return Err(Error::Todo);
+            }
+
+            input.set_sequence(txin.sequence);
+        }
+
+        let total_out = checked_sum(original.output.iter().map(|output| output.value))?;
+        let original_fee = total_in(&inputs)?
+            .checked_sub(total_out)
+            .ok_or(Error::Todo)?;
+
+        // The new fee rate must be higher than the original one.
+        if self.sat_vb * original.vsize() as u64 <= original_fee {
+            return Err(Error::Todo);
+        }
+
+        // Separate the payments from the change output, if any.
+        let mut payments = vec![];
+        let mut change = None;
+        for output in &original.output {
+            match &self.change_script {
+                Some(script) if change.is_none() && &output.script_pubkey == script => {
+                    change = Some((payments.len(), script.clone()));
+                },
+                _ => payments.push(TxOutput::from(output.clone())),
+            }
+        }
+
+        let payments_total = checked_sum(payments.iter().map(|output| output.satoshis()))?;
+
+        let mut utxos = self.utxos;
+        utxos.sort_by_key(|utxo| Reverse(utxo.satoshis()));
+        let mut utxos = utxos.into_iter();
+
+        let required_fee = |inputs: &[TxInput], outputs: &[TxOutput]| {
+            let vsize = estimate_weight(inputs, outputs).to_vbytes_ceil();
+            (self.sat_vb * vsize).max(original_fee + INCREMENTAL_RELAY_FEE_SAT_VB * vsize)
+        };
+
+        let (outputs, fee) = loop {
+            let available = total_in(&inputs)?.saturating_sub(payments_total);
+
+            // Try to pay the fee from the change.
+            if let Some((index, script)) = &change {
+                let mut outputs = payments.clone();
+                outputs.insert(*index, TxOutput::from_script(script.clone(), 0)?);
+
+                let fee = required_fee(&inputs, &outputs);
+                if available >= fee + script.dust_value().to_sat() {
+                    outputs[*index] = TxOutput::from_script(script.clone(), available - fee)?;
+                    break (outputs, fee);
+                }
+            }
+
+            // Try without change, any excess goes to the miner.
+            let fee = required_fee(&inputs, &payments);
+            if available >= fee {
+                break (payments, available);
+            }
+
+            // Spend an additional UTXO, which requires a change output.
+            let script = self.change_script.as_ref().ok_or(Error::Todo)?;
+            let mut utxo = utxos.next().ok_or(Error::Todo)?;
+            utxo.set_sequence(Sequence::ENABLE_RBF_NO_LOCKTIME);
+            inputs.push(utxo);
+
+            if change.is_none() {
+                change = Some((payments.len(), script.clone()));
+            }
+        };
+
+        let mut builder = TransactionBuilder::new()
+            .version(original.version)
+            .miner_fee(fee);
+        builder.lock_time = original.lock_time;
+
+        for input in inputs {
+            builder = builder.add_input(input);
+        }
+
+        for output in outputs {
+            builder = builder.add_output(output);
+        }
+
+        Ok(builder)
+    }
+}
+
+/// Builds a child transaction which spends an output of an unconfirmed parent
+/// transaction, paying a fee so that the package (parent and child together)
+/// reaches the target fee rate.
+///
+/// All funds are sent to a single output. If the spent output is not
+/// sufficient to pay for the package, the additional UTXOs are spent, largest
+/// first.
+#[derive(Debug, Clone)]
+pub struct CpfpBuilder {
+    parent: Transaction,
+    parent_fee: u64,
+    input: TxInput,
+    utxos: Vec<TxInput>,
+    output_script: ScriptBuf,
+    sat_vb: u64,
+}
+
+impl CpfpBuilder {
+    /// The `input` must spend an output of the `parent` transaction, which
+    /// pays `parent_fee` to the miner.
+    pub fn new(
+        parent: Transaction,
+        parent_fee: u64,
+        input: TxInput,
+        output_script: ScriptBuf,
+    ) -> Self {
+        CpfpBuilder {
+            parent,
+            parent_fee,
+            input,
+            utxos: vec![],
+            output_script,
+            sat_vb: 0,
+        }
+    }
+    pub fn sat_vb(mut self, sat_vb: u64) -> Self {
+        self.sat_vb = sat_vb;
+        self
+    }
+    pub fn add_utxo(mut self, utxo: TxInput) -> Self {
+        self.utxos.push(utxo);
+        self
+    }
+    pub fn build(self) -> Result<TransactionBuilder> {
+        // Make sure the input actually spends the parent.
+        let ctx = self.input.ctx();
+        let parent_output = self
+            .parent
+            .output
+            .get(ctx.previous_output.vout as usize)
+            .ok_or(Error::Todo)?;
+
+        if ctx.previous_output.txid != self.parent.txid()
+            || ctx.value != parent_output.value
+            || ctx.script_pubkey != parent_output.script_pubkey
+        {
+            return Err(Error::Todo);
+        }
+
+        let parent_vsize = self.parent.vsize() as u64;
+        let dust = self.output_script.dust_value().to_sat();
+
+        let mut utxos = self.utxos;
+        utxos.sort_by_key(|utxo| Reverse(utxo.satoshis()));
+        let mut utxos = utxos.into_iter();
+
+        let mut inputs = vec![self.input];
+
+        let (satoshis, fee) = loop {
+            let output = TxOutput::from_script(self.output_script.clone(), 0)?;
+            let child_vsize = estimate_weight(&inputs, &[output]).to_vbytes_ceil();
+
+            // The child must at least pay for its own relay.
+            let fee = (self.sat_vb * (parent_vsize + child_vsize))
+                .saturating_sub(self.parent_fee)
+                .max(INCREMENTAL_RELAY_FEE_SAT_VB * child_vsize);
+
+            let total = total_in(&inputs)?;
+            if total >= fee + dust {
+                break (total - fee, fee);
+            }
+
+            inputs.push(utxos.next().ok_or(Error::Todo)?);
+        };
+
+        let mut builder = TransactionBuilder::new().miner_fee(fee);
+
+        // Signal replaceability, so the child can be bumped again.
+        for mut input in inputs {
+            input.set_sequence(Sequence::ENABLE_RBF_NO_LOCKTIME);
+            builder = builder.add_input(input);
+        }
+
+        Ok(builder.add_output(TxOutput::from_script(self.output_script, satoshis)?))
+    }
+}
+
+fn total_in(inputs: &[TxInput]) -> Result<u64> {
+    checked_sum(inputs.iter().map(|input| input.satoshis()))
+}
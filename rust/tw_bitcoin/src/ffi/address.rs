@@ -1,7 +1,11 @@
 use super::CTaprootError;
+use crate::bitcoin_cash::CashAddress;
+use crate::decred::DecredAddress;
+use crate::groestlcoin::GroestlcoinAddress;
 use crate::Recipient;
 use bitcoin::PublicKey;
-use std::ffi::CString;
+use std::ffi::{c_char, CStr, CString};
+use std::str::FromStr;
 use tw_memory::ffi::c_byte_array_ref::CByteArrayRef;
 use tw_memory::ffi::c_result::CStrMutResult;
 
@@ -77,6 +81,94 @@ pub unsafe extern "C" fn tw_taproot_address_string(
     CStrMutResult::ok(c_string)
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn tw_decred_address_string(
+    pubkey: *const u8,
+    pubkey_len: usize,
+    network: Network,
+) -> CStrMutResult {
+    // Convert Recipient.
+    let Some(slice) = CByteArrayRef::new(pubkey, pubkey_len).as_slice() else {
+        return CStrMutResult::error(CTaprootError::InvalidSlice);
+    };
+
+    let Ok(recipient) = Recipient::<PublicKey>::from_slice(slice) else {
+        return CStrMutResult::error(CTaprootError::InvalidPubkey);
+    };
+
+    let address = DecredAddress::p2pkh(&recipient.public_key(), network.into()).to_string();
+    let c_string = CString::new(address)
+        .expect("Decred address contains an internal 0 byte")
+        .into_raw();
+
+    CStrMutResult::ok(c_string)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tw_cash_address_string(
+    pubkey: *const u8,
+    pubkey_len: usize,
+    network: Network,
+) -> CStrMutResult {
+    // Convert Recipient.
+    let Some(slice) = CByteArrayRef::new(pubkey, pubkey_len).as_slice() else {
+        return CStrMutResult::error(CTaprootError::InvalidSlice);
+    };
+
+    let Ok(recipient) = Recipient::<PublicKey>::from_slice(slice) else {
+        return CStrMutResult::error(CTaprootError::InvalidPubkey);
+    };
+
+    let address = CashAddress::p2pkh(&recipient.public_key(), network.into()).to_string();
+    let c_string = CString::new(address)
+        .expect("CashAddr address contains an internal 0 byte")
+        .into_raw();
+
+    CStrMutResult::ok(c_string)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tw_groestlcoin_address_string(
+    pubkey: *const u8,
+    pubkey_len: usize,
+    network: Network,
+) -> CStrMutResult {
+    // Convert Recipient.
+    let Some(slice) = CByteArrayRef::new(pubkey, pubkey_len).as_slice() else {
+        return CStrMutResult::error(CTaprootError::InvalidSlice);
+    };
+
+    let Ok(recipient) = Recipient::<PublicKey>::from_slice(slice) else {
+        return CStrMutResult::error(CTaprootError::InvalidPubkey);
+    };
+
+    let address = GroestlcoinAddress::p2pkh(&recipient.public_key(), network.into()).to_string();
+    let c_string = CString::new(address)
+        .expect("Groestlcoin address contains an internal 0 byte")
+        .into_raw();
+
+    CStrMutResult::ok(c_string)
+}
+
+#[no_mangle]
+// Decodes the CashAddr address, with or without prefix, into the equivalent
+// legacy address.
+pub unsafe extern "C" fn tw_cash_address_to_legacy(address: *const c_char) -> CStrMutResult {
+    let Ok(address) = CStr::from_ptr(address).to_str() else {
+        return CStrMutResult::error(CTaprootError::InvalidSlice);
+    };
+
+    let Ok(address) = CashAddress::from_str(address) else {
+        return CStrMutResult::error(CTaprootError::InvalidAddress);
+    };
+
+    let c_string = CString::new(address.to_legacy().to_string())
+        .expect("legacy address contains an internal 0 byte")
+        .into_raw();
+
+    CStrMutResult::ok(c_string)
+}
+
 // A custom reimplementation of of `bitcoin::Network`.
 #[repr(C)]
 pub enum Network {
@@ -0,0 +1,113 @@
+use super::{
+    keypair_from_proto, network_from_proto, proto_utxo_to_input, satoshis_from_proto,
+    script_from_address, sign_and_build_proto,
+};
+use crate::fee_bump::{CpfpBuilder, RbfBuilder};
+use crate::{Error, Recipient, Result};
+use bitcoin::consensus::Decodable;
+use bitcoin::{PublicKey, Transaction};
+use tw_memory::ffi::c_byte_array::CByteArray;
+use tw_memory::ffi::c_byte_array_ref::CByteArrayRef;
+use tw_misc::try_or_else;
+use tw_proto::Bitcoin::Proto::{ChildPaysForParentInput, ReplaceByFeeInput, SigningOutput};
+
+#[no_mangle]
+// Builds and signs a replacement of the given transaction at a higher fee rate.
+pub unsafe extern "C" fn tw_bitcoin_build_rbf_transaction(
+    input: *const u8,
+    input_len: usize,
+) -> CByteArray {
+    let data = CByteArrayRef::new(input, input_len)
+        .to_vec()
+        .unwrap_or_default();
+
+    let proto: ReplaceByFeeInput = try_or_else!(tw_proto::deserialize(&data), CByteArray::null);
+    let signing = try_or_else!(build_rbf_transaction(proto), CByteArray::null);
+
+    let serialized = tw_proto::serialize(&signing).expect("failed to serialize signed transaction");
+
+    CByteArray::from(serialized)
+}
+
+#[no_mangle]
+// Builds and signs a child transaction paying for its unconfirmed parent.
+pub unsafe extern "C" fn tw_bitcoin_build_cpfp_transaction(
+    input: *const u8,
+    input_len: usize,
+) -> CByteArray {
+    let data = CByteArrayRef::new(input, input_len)
+        .to_vec()
+        .unwrap_or_default();
+
+    let proto: ChildPaysForParentInput =
+        try_or_else!(tw_proto::deserialize(&data), CByteArray::null);
+    let signing = try_or_else!(build_cpfp_transaction(proto), CByteArray::null);
+
+    let serialized = tw_proto::serialize(&signing).expect("failed to serialize signed transaction");
+
+    CByteArray::from(serialized)
+}
+
+pub(crate) fn build_rbf_transaction(proto: ReplaceByFeeInput) -> Result<SigningOutput<'static>> {
+    let keypair = keypair_from_proto(&proto.private_key)?;
+    let my_pubkey = Recipient::<PublicKey>::from(keypair);
+
+    let original =
+        Transaction::consensus_decode(&mut proto.transaction.as_ref()).map_err(|_| Error::Todo)?;
+
+    let prevouts = proto
+        .prevouts
+        .iter()
+        .map(|prevout| proto_utxo_to_input(prevout, my_pubkey.clone()))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut rbf = RbfBuilder::new(original, prevouts).sat_vb(satoshis_from_proto(proto.byte_fee)?);
+
+    if !proto.change_address.is_empty() {
+        let network = network_from_proto(proto.network);
+        rbf = rbf.change_script(script_from_address(
+            &proto.change_address,
+            proto.coin_type,
+            network,
+        )?);
+    }
+
+    for utxo in &proto.utxo {
+        rbf = rbf.add_utxo(proto_utxo_to_input(utxo, my_pubkey.clone())?);
+    }
+
+    sign_and_build_proto(rbf.build()?, keypair)
+}
+
+pub(crate) fn build_cpfp_transaction(
+    proto: ChildPaysForParentInput,
+) -> Result<SigningOutput<'static>> {
+    let keypair = keypair_from_proto(&proto.private_key)?;
+    let my_pubkey = Recipient::<PublicKey>::from(keypair);
+
+    let parent = Transaction::consensus_decode(&mut proto.parent_transaction.as_ref())
+        .map_err(|_| Error::Todo)?;
+
+    let utxo = proto.utxo.as_ref().ok_or(Error::Todo)?;
+    let input = proto_utxo_to_input(utxo, my_pubkey.clone())?;
+
+    let output_script = script_from_address(
+        &proto.to_address,
+        proto.coin_type,
+        network_from_proto(proto.network),
+    )?;
+
+    let mut cpfp = CpfpBuilder::new(
+        parent,
+        satoshis_from_proto(proto.parent_fee)?,
+        input,
+        output_script,
+    )
+    .sat_vb(satoshis_from_proto(proto.byte_fee)?);
+
+    for utxo in &proto.extra_utxo {
+        cpfp = cpfp.add_utxo(proto_utxo_to_input(utxo, my_pubkey.clone())?);
+    }
+
+    sign_and_build_proto(cpfp.build()?, keypair)
+}
@@ -0,0 +1,92 @@
+use super::{
+    keypair_from_proto, network_from_proto, output_from_address, proto_utxo_to_utxo,
+    satoshis_from_proto, script_from_address, sign_and_build_proto,
+};
+use crate::claim::KeyPairSigner;
+use crate::coin_selection::Utxo;
+use crate::inscription_batch::{BatchMode, InscriptionBatch, DEFAULT_POSTAGE};
+use crate::{Error, Recipient, Result};
+use bitcoin::{PublicKey, Txid};
+use std::str::FromStr;
+use tw_memory::ffi::c_byte_array::CByteArray;
+use tw_memory::ffi::c_byte_array_ref::CByteArrayRef;
+use tw_misc::try_or_else;
+use tw_proto::Bitcoin::Proto::{
+    InscriptionBatchInput, InscriptionBatchMode, InscriptionBatchOutput,
+};
+
+#[no_mangle]
+// Builds and signs the commit and reveal transaction of an inscription batch.
+pub unsafe extern "C" fn tw_bitcoin_build_inscription_batch(
+    input: *const u8,
+    input_len: usize,
+) -> CByteArray {
+    let data = CByteArrayRef::new(input, input_len)
+        .to_vec()
+        .unwrap_or_default();
+
+    let proto: InscriptionBatchInput = try_or_else!(tw_proto::deserialize(&data), CByteArray::null);
+    let output = try_or_else!(build_inscription_batch(proto), CByteArray::null);
+
+    let serialized = tw_proto::serialize(&output).expect("failed to serialize inscription batch");
+
+    CByteArray::from(serialized)
+}
+
+pub(crate) fn build_inscription_batch(
+    proto: InscriptionBatchInput,
+) -> Result<InscriptionBatchOutput<'static>> {
+    let keypair = keypair_from_proto(&proto.private_key)?;
+    let my_pubkey = Recipient::<PublicKey>::from(keypair);
+
+    let postage = match satoshis_from_proto(proto.postage)? {
+        0 => DEFAULT_POSTAGE,
+        postage => postage,
+    };
+
+    let mode = match proto.mode {
+        InscriptionBatchMode::SHARED_ENVELOPE => BatchMode::SharedEnvelope,
+        InscriptionBatchMode::SEPARATE_INPUTS => BatchMode::SeparateInputs,
+    };
+
+    let network = network_from_proto(proto.network);
+
+    let mut batch = InscriptionBatch::new(my_pubkey.clone())
+        .mode(mode)
+        .postage(postage)
+        .sat_vb(satoshis_from_proto(proto.byte_fee)?)
+        .coin_selection(proto.coin_selection.into());
+
+    for inscription in &proto.inscriptions {
+        let destination =
+            output_from_address(&inscription.to_address, proto.coin_type, network, postage)?;
+        batch = batch.add_inscription(
+            inscription.mime_type.as_bytes(),
+            &inscription.payload,
+            destination.script_pubkey().to_owned(),
+        );
+    }
+
+    let utxos = proto
+        .utxo
+        .iter()
+        .map(|utxo| proto_utxo_to_utxo(utxo, my_pubkey.clone()))
+        .collect::<Result<Vec<Utxo>>>()?;
+
+    let change_script = script_from_address(&proto.change_address, proto.coin_type, network)?;
+
+    let plan = batch.plan(&utxos, change_script)?;
+
+    // The reveal transaction spends the outputs of the signed commit
+    // transaction.
+    let commit = sign_and_build_proto(plan.commit().clone(), KeyPairSigner::new(keypair))?;
+    let commit_txid = Txid::from_str(&commit.transaction_id).map_err(|_| Error::Todo)?;
+    let reveal = sign_and_build_proto(plan.reveal(commit_txid), KeyPairSigner::new(keypair))?;
+
+    Ok(InscriptionBatchOutput {
+        commit: Some(commit),
+        reveal: Some(reveal),
+        commit_fee: plan.commit_fee() as i64,
+        reveal_fee: plan.reveal_fee() as i64,
+    })
+}
@@ -1,28 +1,51 @@
 #![allow(clippy::missing_safety_doc)]
 
+use crate::bitcoin_cash::{self, CashAddress, BITCOIN_CASH_COIN_TYPE};
+use crate::claim::{AuxRand, KeyPairSigner, TransactionSigner};
+use crate::coin_selection::{CoinSelectionStrategy, CoinSelector, Sweep, SweepTransaction, Utxo};
+use crate::decred::{DecredAddress, DecredParams, DECRED_COIN_TYPE};
+use crate::groestlcoin::{self, GroestlcoinAddress, GROESTLCOIN_COIN_TYPE};
+use crate::ordering::TxOrdering;
+use crate::pos;
+use crate::sat_point::SatPoint;
+use crate::utils::same_network;
+use crate::zcash::ZcashParams;
 use crate::{
-    calculate_fee, Error, Result, TXOutputP2TRScriptPath, TaprootScript, TxInputP2TRScriptPath,
+    calculate_fee, Chain, Error, Result, TXOutputP2TRScriptPath, TaprootScript,
+    TxInputP2TRScriptPath,
 };
 use bitcoin::{
     consensus::Decodable,
     taproot::{NodeInfo, TapNodeHash, TaprootSpendInfo},
-    PublicKey, ScriptBuf, Transaction, Txid,
+    Address, PublicKey, ScriptBuf, Transaction, Txid,
 };
+use private_key::private_key_from_signing_input;
 use secp256k1::hashes::Hash;
 use secp256k1::KeyPair;
 use std::borrow::Cow;
+use std::str::FromStr;
 use tw_memory::ffi::c_byte_array::CByteArray;
 use tw_memory::ffi::c_byte_array_ref::CByteArrayRef;
 use tw_memory::ffi::c_result::CUInt64Result;
 use tw_memory::ffi::c_result::ErrorCode;
 use tw_misc::try_or_else;
 use tw_proto::Bitcoin::Proto::{
-    OutPoint, SigningInput, SigningOutput, Transaction as ProtoTransaction, TransactionInput,
-    TransactionOutput, TransactionVariant as TrVariant,
+    CoinSelectionStrategy as ProtoCoinSelectionStrategy, Network as ProtoNetwork, OutPoint,
+    SigningInput, SigningOutput, Transaction as ProtoTransaction, TransactionInput,
+    TransactionOrdering, TransactionOutput, TransactionVariant as TrVariant, UnspentTransaction,
 };
+use tw_proto::Common::Proto::SigningError;
 
 pub mod address;
+pub mod fee_bump;
+pub mod inscription_batch;
+pub mod musig2;
+pub mod private_key;
+pub mod remote_signer;
+pub mod runes;
 pub mod scripts;
+pub mod summary;
+pub mod sweep;
 
 // Re-exports
 pub use address::*;
@@ -30,9 +53,12 @@ pub use scripts::*;
 
 use crate::{
     Recipient, TransactionBuilder, TxInput, TxInputP2PKH, TxInputP2TRKeyPath, TxInputP2WPKH,
-    TxOutput, TxOutputP2PKH, TxOutputP2TRKeyPath, TxOutputP2WPKH,
+    TxOutput, TxOutputOpReturn, TxOutputP2PKH, TxOutputP2TRKeyPath, TxOutputP2WPKH,
 };
 
+/// The SLIP-44 coin type of Bitcoin.
+pub(crate) const BITCOIN_COIN_TYPE: u32 = 0;
+
 #[no_mangle]
 pub unsafe extern "C" fn tw_bitcoin_calculate_transaction_fee(
     input: *const u8,
@@ -83,91 +109,130 @@ pub unsafe extern "C" fn tw_taproot_build_and_sign_transaction(
 /// construct the outputs, which must include the return/change transaction and
 /// how much goes to the miner as fee (<total-satoshi-inputs> minus
 /// <total-satoshi-outputs>).
+///
+/// If the `TransactionPlan` is omitted, the inputs are picked from the UTXOs
+/// with the given `coin_selection` strategy in order to pay `amount` to
+/// `to_address` (plus the `extra_outputs`), and any change is returned to
+/// `change_address`. If `use_max_amount` is set, all UTXOs (except those
+/// below `sweep_min_amount`) are swept to `to_address` instead, with the fee
+/// deducted from the output.
 pub(crate) fn taproot_build_and_sign_transaction(proto: SigningInput) -> Result<SigningOutput> {
-    let privkey = proto.private_key.get(0).ok_or(Error::Todo)?;
+    let privkey = private_key_from_signing_input(&proto)?;
 
-    // Prepare keypair and derive corresponding public key.
-    let keypair = KeyPair::from_seckey_slice(&secp256k1::Secp256k1::new(), privkey.as_ref())
-        .map_err(|_| crate::Error::Todo)?;
+    if !privkey.compressed {
+        // SegWit and Taproot require compressed public keys.
+        if proto
+            .utxo
+            .iter()
+            .any(|utxo| utxo.variant != TrVariant::P2PKH)
+        {
+            return Ok(SigningOutput {
+                error: SigningError::Error_uncompressed_key,
+                error_message: Cow::from("uncompressed keys can only be used with P2PKH inputs"),
+                ..Default::default()
+            });
+        }
+    }
 
-    let my_pubkey = Recipient::<PublicKey>::from(keypair);
+    // Prepare keypair and derive corresponding public key, which is
+    // uncompressed for the P2PKH inputs of old wallets.
+    let secp = secp256k1::Secp256k1::new();
+    let keypair = KeyPair::from_secret_key(&secp, &privkey.inner);
 
-    let mut builder = TransactionBuilder::new();
+    let my_pubkey = Recipient::<PublicKey>::from(privkey.public_key(&secp));
+    let signer = KeyPairSigner::new(keypair).aux_rand(aux_rand_from_proto(&proto)?);
 
-    // Process inputs.
-    for input in proto.utxo {
-        let my_pubkey = my_pubkey.clone();
+    let builder = builder_from_proto(proto, my_pubkey)?;
+    sign_and_build_proto(builder, signer)
+}
 
-        let out_point = input.out_point.ok_or(Error::Todo)?;
-        let txid = Txid::from_slice(&out_point.hash).map_err(|_| crate::Error::Todo)?;
-        let vout = out_point.index;
-        let satoshis = input.amount as u64;
+/// Builds the transaction spending the UTXOs of `my_pubkey`, see
+/// [`taproot_build_and_sign_transaction`]. The private keys are ignored.
+pub(crate) fn builder_from_proto(
+    proto: SigningInput,
+    my_pubkey: Recipient<PublicKey>,
+) -> Result<TransactionBuilder> {
+    let (mut builder, utxos, inscriptions) = prepare_builder_from_proto(&proto, my_pubkey)?;
+
+    if proto.plan.is_none() && proto.use_max_amount {
+        // Sweep all UTXOs to `to_address`, which must fit into a single
+        // transaction. Use `tw_bitcoin_sweep` otherwise.
+        if !proto.extra_outputs.is_empty() {
+            return Err(Error::Todo);
+        }
+
+        let mut sweep = sweep_from_proto(&proto, &utxos)?;
+        if sweep.transactions.len() != 1 {
+            return Err(Error::Todo);
+        }
+
+        return Ok(sweep_builder(builder, sweep.transactions.remove(0)));
+    }
 
-        let script_buf = ScriptBuf::from_bytes(input.script.to_vec());
+    let Some(plan) = proto.plan else {
+        // No plan was provided, so we select the inputs ourselves.
+        let network = network_from_proto(proto.network);
+
+        let mut outputs = vec![output_from_address(
+            &proto.to_address,
+            proto.coin_type,
+            network,
+            satoshis_from_proto(proto.amount)?,
+        )?];
+        for extra in &proto.extra_outputs {
+            outputs.push(output_from_address(
+                &extra.to_address,
+                proto.coin_type,
+                network,
+                satoshis_from_proto(extra.amount)?,
+            )?);
+        }
+
+        let change_script = script_from_address(&proto.change_address, proto.coin_type, network)?;
+
+        let selection = CoinSelector::new(proto.coin_selection.into())
+            .sat_vb(satoshis_from_proto(proto.byte_fee)?)
+            .use_max_utxo(proto.use_max_utxo)
+            .disable_dust_filter(proto.disable_dust_filter)
+            .select(&utxos, &outputs, &change_script)?;
+
+        if selection.change != 0 {
+            outputs.push(TxOutput::from_script(change_script, selection.change)?);
+        }
+
+        for input in selection.inputs {
+            builder = builder.add_input(input);
+        }
+
+        for output in outputs {
+            builder = builder.add_output(output);
+        }
+
+        return Ok(builder);
+    };
 
-        let tx: TxInput = match input.variant {
-            TrVariant::P2PKH => {
-                TxInputP2PKH::new_with_script(txid, vout, my_pubkey.into(), satoshis, script_buf)
-                    .into()
-            },
-            TrVariant::P2WPKH => TxInputP2WPKH::new_with_script(
-                txid,
-                vout,
-                my_pubkey.try_into()?,
-                satoshis,
-                script_buf,
-            )
-            .into(),
-            TrVariant::P2TRKEYPATH => TxInputP2TRKeyPath::new_with_script(
-                txid,
-                vout,
-                my_pubkey.into(),
-                satoshis,
-                script_buf,
-            )
-            .into(),
-            TrVariant::BRC20TRANSFER | TrVariant::NFTINSCRIPTION => {
-                // We construct the merkle root for the given spending script.
-                let spending_script = ScriptBuf::from_bytes(input.spendingScript.to_vec());
-                let merkle_root = TapNodeHash::from_script(
-                    spending_script.as_script(),
-                    bitcoin::taproot::LeafVersion::TapScript,
-                );
-
-                // Convert to tapscript recipient with the given merkle root.
-                let recipient =
-                    Recipient::<TaprootScript>::from_pubkey_recipient(my_pubkey, merkle_root);
-
-                // Derive the spending information for the taproot recipient.
-                let spend_info = TaprootSpendInfo::from_node_info(
-                    &secp256k1::Secp256k1::new(),
-                    recipient.untweaked_pubkey(),
-                    NodeInfo::new_leaf_with_ver(
-                        spending_script.clone(),
-                        bitcoin::taproot::LeafVersion::TapScript,
-                    ),
-                );
-
-                TxInputP2TRScriptPath::new_with_script(
-                    txid,
-                    vout,
-                    recipient,
-                    satoshis,
-                    script_buf,
-                    spending_script,
-                    spend_info,
-                )
-                .into()
-            },
-        };
+    // The consensus branch ID is only set for Zcash.
+    if !plan.branch_id.is_empty() {
+        let branch_id: [u8; 4] = plan
+            .branch_id
+            .as_ref()
+            .try_into()
+            .map_err(|_| Error::Todo)?;
+        builder = builder.zcash(ZcashParams::from_branch_id(u32::from_le_bytes(branch_id))?);
+    }
+
+    for utxo in utxos {
+        builder = builder.add_input(utxo.input().clone());
+    }
 
-        builder = builder.add_input(tx);
+    for sat_point in inscriptions {
+        builder = builder.protect_inscription(sat_point);
     }
 
     // Process outputs.
-    for output in proto.plan.ok_or(Error::Todo)?.utxos {
+    for output in plan.utxos {
         let script_buf = ScriptBuf::from_bytes(output.script.to_vec());
-        let satoshis = output.amount as u64;
+        let satoshis = satoshis_from_proto(output.amount)?;
 
         #[rustfmt::skip]
         let tx: TxOutput = match output.variant {
@@ -183,22 +248,127 @@ pub(crate) fn taproot_build_and_sign_transaction(proto: SigningInput) -> Result<
             // We're keeping those two variants separate for now, we're planning
             // on writing a new interface as part of a larger task anyway.
             TrVariant::BRC20TRANSFER => {
-                TXOutputP2TRScriptPath::new_with_script(satoshis, script_buf).into()
+                TXOutputP2TRScriptPath::new_with_script(satoshis, script_buf)
+                    .inscription_commit(true)
+                    .into()
             },
             TrVariant::NFTINSCRIPTION => {
-                TXOutputP2TRScriptPath::new_with_script(satoshis, script_buf).into()
+                TXOutputP2TRScriptPath::new_with_script(satoshis, script_buf)
+                    .inscription_commit(true)
+                    .into()
+            },
+            TrVariant::OPRETURN => {
+                TxOutputOpReturn::new_with_script(satoshis, script_buf).into()
             }
         };
 
         builder = builder.add_output(tx);
     }
 
-    // Copy those values before `builder` gets consumed.
-    let version = builder.version;
-    let lock_time = builder.lock_time.to_consensus_u32();
+    Ok(builder)
+}
 
+/// Prepares the builder for the chain of the `SigningInput`, without any
+/// inputs or outputs. Returns the UTXOs and the inscriptions they carry.
+pub(crate) fn prepare_builder_from_proto(
+    proto: &SigningInput,
+    my_pubkey: Recipient<PublicKey>,
+) -> Result<(TransactionBuilder, Vec<Utxo>, Vec<SatPoint>)> {
+    let mut builder = TransactionBuilder::new()
+        .disable_dust_filter(proto.disable_dust_filter)
+        .ordering(ordering_from_proto(proto))
+        .pin_first_input_output(proto.is_it_brc_operation);
+
+    if proto.coin_type == BITCOIN_CASH_COIN_TYPE {
+        builder = builder
+            .version(bitcoin_cash::TX_VERSION)
+            .chain(Chain::BitcoinCash);
+    }
+    if proto.coin_type == GROESTLCOIN_COIN_TYPE {
+        builder = builder
+            .version(groestlcoin::TX_VERSION)
+            .chain(Chain::Groestlcoin);
+    }
+
+    // Decred outpoints additionally carry the tree of the spent output.
+    let mut decred = (proto.coin_type == DECRED_COIN_TYPE).then(DecredParams::new);
+
+    // Process inputs.
+    let mut utxos = vec![];
+    let mut inscriptions = vec![];
+    for input in &proto.utxo {
+        let utxo = proto_utxo_to_utxo(input, my_pubkey.clone())?;
+
+        if let Some(params) = decred.take() {
+            let tree = input.out_point.as_ref().ok_or(Error::Todo)?.tree;
+            let tree = i8::try_from(tree).map_err(|_| Error::Todo)?;
+            decred = Some(params.tree(utxo.input().ctx().previous_output, tree));
+        }
+
+        for offset in &input.inscription_offsets {
+            let outpoint = utxo.input().ctx().previous_output;
+            inscriptions.push(SatPoint::new(outpoint, *offset));
+        }
+
+        utxos.push(utxo);
+    }
+
+    if let Some(params) = decred {
+        builder = builder.decred(params);
+    }
+
+    // Proof-of-Stake forks carry the transaction time.
+    if pos::is_pos_coin_type(proto.coin_type) {
+        builder = builder.version(pos::TX_VERSION).timestamp(proto.time);
+    }
+
+    Ok((builder, utxos, inscriptions))
+}
+
+/// Sweeps the UTXOs to `to_address`, see [`CoinSelector::sweep`].
+pub(crate) fn sweep_from_proto(proto: &SigningInput, utxos: &[Utxo]) -> Result<Sweep> {
+    let destination = script_from_address(
+        &proto.to_address,
+        proto.coin_type,
+        network_from_proto(proto.network),
+    )?;
+
+    CoinSelector::new(proto.coin_selection.into())
+        .sat_vb(satoshis_from_proto(proto.byte_fee)?)
+        .disable_dust_filter(proto.disable_dust_filter)
+        .sweep(
+            utxos,
+            &destination,
+            satoshis_from_proto(proto.sweep_min_amount)?,
+        )
+}
+
+/// Adds the inputs and the output of the swept transaction to the prepared
+/// builder.
+pub(crate) fn sweep_builder(
+    mut builder: TransactionBuilder,
+    tx: SweepTransaction,
+) -> TransactionBuilder {
+    builder = builder.miner_fee(tx.fee);
+    for input in tx.inputs {
+        builder = builder.add_input(input);
+    }
+
+    builder.add_output(tx.output)
+}
+
+/// Signs the transaction and creates the corresponding Protobuf structures.
+pub(crate) fn sign_and_build_proto<S>(
+    builder: TransactionBuilder,
+    signer: S,
+) -> Result<SigningOutput<'static>>
+where
+    S: TransactionSigner,
+{
     // Sign transaction and create protobuf structures.
-    let tx = builder.sign_inputs(keypair)?;
+    let tx = builder.sign_inputs(signer)?;
+    let version = tx.inner.version;
+    let lock_time = tx.inner.lock_time.to_consensus_u32();
 
     // Create Protobuf structures of inputs.
     let mut proto_inputs = vec![];
@@ -217,8 +387,7 @@ pub(crate) fn taproot_build_and_sign_transaction(proto: SigningInput) -> Result<
                 hash: Cow::from(txid),
                 index: input.previous_output.vout,
                 sequence: input.sequence.to_consensus_u32(),
-                // Unused.
-                tree: 0,
+                tree: tx.tree(&input.previous_output).into(),
             }),
             sequence: input.sequence.to_consensus_u32(),
             script: {
@@ -230,6 +399,7 @@ pub(crate) fn taproot_build_and_sign_transaction(proto: SigningInput) -> Result<
                     Cow::from(input.script_sig.to_bytes())
                 }
             },
+            witness: input.witness.to_vec().into_iter().map(Cow::from).collect(),
         });
     }
 
@@ -252,9 +422,14 @@ pub(crate) fn taproot_build_and_sign_transaction(proto: SigningInput) -> Result<
             outputs: proto_outputs,
         }),
         encoded: Cow::default(),
-        transaction_id: Cow::from(tx.inner.txid().to_string()),
-        error: tw_proto::Common::Proto::SigningError::OK,
+        transaction_id: Cow::from(tx.txid().to_string()),
+        error: SigningError::OK,
         error_message: Cow::default(),
+        txid: Cow::from(tx.txid().to_byte_array().to_vec()),
+        wtxid: Cow::from(tx.wtxid().to_byte_array().to_vec()),
+        encoded_stripped: Cow::from(tx.serialize_stripped()?),
+        weight: tx.weight()?.to_wu(),
+        vsize: tx.vsize()?,
     };
 
     // Sign transaction and update Protobuf structure.
@@ -264,12 +439,223 @@ pub(crate) fn taproot_build_and_sign_transaction(proto: SigningInput) -> Result<
     Ok(signing)
 }
 
+/// Converts the Protobuf UTXO into a [`TxInput`] claimable by `my_pubkey`.
+pub(crate) fn proto_utxo_to_input(
+    input: &UnspentTransaction,
+    my_pubkey: Recipient<PublicKey>,
+) -> Result<TxInput> {
+    let out_point = input.out_point.as_ref().ok_or(Error::Todo)?;
+    let txid = Txid::from_slice(&out_point.hash).map_err(|_| crate::Error::Todo)?;
+    let vout = out_point.index;
+    let satoshis = satoshis_from_proto(input.amount)?;
+
+    let script_buf = ScriptBuf::from_bytes(input.script.to_vec());
+
+    let tx: TxInput = match input.variant {
+        TrVariant::P2PKH => {
+            // The compressed and uncompressed form of our key have different
+            // hashes, so the output can't be claimed with the other form, see
+            // `uncompressed_public_key`.
+            let pubkey = my_pubkey.public_key();
+            let other_form = PublicKey {
+                compressed: !pubkey.compressed,
+                ..pubkey
+            };
+            if script_buf == ScriptBuf::new_p2pkh(&other_form.pubkey_hash()) {
+                return Err(Error::Todo);
+            }
+
+            let uncompressed = !pubkey.compressed;
+            TxInputP2PKH::new_with_script(txid, vout, my_pubkey.into(), satoshis, script_buf)
+                .uncompressed(uncompressed)
+                .into()
+        },
+        TrVariant::P2WPKH => {
+            TxInputP2WPKH::new_with_script(txid, vout, my_pubkey.try_into()?, satoshis, script_buf)
+                .into()
+        },
+        TrVariant::P2TRKEYPATH => {
+            TxInputP2TRKeyPath::new_with_script(txid, vout, my_pubkey.into(), satoshis, script_buf)
+                .into()
+        },
+        TrVariant::BRC20TRANSFER | TrVariant::NFTINSCRIPTION => {
+            // We construct the merkle root for the given spending script.
+            let spending_script = ScriptBuf::from_bytes(input.spendingScript.to_vec());
+            let merkle_root = TapNodeHash::from_script(
+                spending_script.as_script(),
+                bitcoin::taproot::LeafVersion::TapScript,
+            );
+
+            // The internal key defaults to our own key.
+            let internal_key = if input.internal_pubkey.is_empty() {
+                my_pubkey
+            } else {
+                Recipient::<PublicKey>::from_slice(&input.internal_pubkey)?
+            };
+
+            // Convert to tapscript recipient with the given merkle root.
+            let recipient =
+                Recipient::<TaprootScript>::from_pubkey_recipient(internal_key, merkle_root);
+
+            // Derive the spending information for the taproot recipient.
+            let spend_info = TaprootSpendInfo::from_node_info(
+                &secp256k1::Secp256k1::new(),
+                recipient.untweaked_pubkey(),
+                NodeInfo::new_leaf_with_ver(
+                    spending_script.clone(),
+                    bitcoin::taproot::LeafVersion::TapScript,
+                ),
+            );
+
+            TxInputP2TRScriptPath::new_with_script(
+                txid,
+                vout,
+                recipient,
+                satoshis,
+                script_buf,
+                spending_script,
+                spend_info,
+            )
+            .into()
+        },
+        // `OP_RETURN` outputs are unspendable.
+        TrVariant::OPRETURN => return Err(Error::Todo),
+    };
+
+    Ok(tx)
+}
+
+/// Determines the auxiliary randomness for Schnorr signatures.
+pub(crate) fn aux_rand_from_proto(proto: &SigningInput) -> Result<AuxRand> {
+    if proto.deterministic_schnorr {
+        return Ok(AuxRand::Deterministic);
+    }
+
+    if proto.schnorr_aux_rand.is_empty() {
+        return Ok(AuxRand::Random);
+    }
+
+    let aux_rand = proto
+        .schnorr_aux_rand
+        .as_ref()
+        .try_into()
+        .map_err(|_| Error::Todo)?;
+
+    Ok(AuxRand::Explicit(aux_rand))
+}
+
+fn ordering_from_proto(proto: &SigningInput) -> TxOrdering {
+    match proto.ordering {
+        TransactionOrdering::INSERTION => TxOrdering::Insertion,
+        TransactionOrdering::BIP69 => TxOrdering::Bip69,
+        TransactionOrdering::SHUFFLE => TxOrdering::Shuffle(proto.shuffle_seed),
+    }
+}
+
+/// Converts the Protobuf UTXO into a [`Utxo`] for coin selection. UTXOs
+/// carrying inscriptions are never selected.
+pub(crate) fn proto_utxo_to_utxo(
+    input: &UnspentTransaction,
+    my_pubkey: Recipient<PublicKey>,
+) -> Result<Utxo> {
+    let locked = input.locked || !input.inscription_offsets.is_empty();
+    let mut utxo = Utxo::new(proto_utxo_to_input(input, my_pubkey)?).locked(locked);
+    if input.block_height != 0 {
+        utxo = utxo.block_height(input.block_height);
+    }
+
+    Ok(utxo)
+}
+
+/// Creates the keypair from the first of the given private keys.
+pub(crate) fn keypair_from_proto(private_key: &[Cow<[u8]>]) -> Result<KeyPair> {
+    let privkey = private_key.first().ok_or(Error::Todo)?;
+    KeyPair::from_seckey_slice(&secp256k1::Secp256k1::new(), privkey.as_ref())
+        .map_err(|_| Error::Todo)
+}
+
+/// Converts the Protobuf amount (or fee rate), rejecting negative values.
+pub(crate) fn satoshis_from_proto(amount: i64) -> Result<u64> {
+    u64::try_from(amount).map_err(|_| Error::Todo)
+}
+
+/// Converts the Protobuf network.
+pub(crate) fn network_from_proto(network: ProtoNetwork) -> bitcoin::Network {
+    match network {
+        ProtoNetwork::MAINNET => bitcoin::Network::Bitcoin,
+        ProtoNetwork::TESTNET => bitcoin::Network::Testnet,
+        ProtoNetwork::SIGNET => bitcoin::Network::Signet,
+        ProtoNetwork::REGTEST => bitcoin::Network::Regtest,
+    }
+}
+
+/// Creates the output paying `satoshis` to the given address.
+pub(crate) fn output_from_address(
+    address: &str,
+    coin_type: u32,
+    network: bitcoin::Network,
+    satoshis: u64,
+) -> Result<TxOutput> {
+    TxOutput::from_script(script_from_address(address, coin_type, network)?, satoshis)
+}
+
+/// Returns the scriptPubKey of the given address, which must be a Bitcoin,
+/// CashAddr, Decred or Groestlcoin address as specified by `coin_type`, and
+/// encoded for `network`. Addresses of other chains are rejected, since the
+/// funds would be lost.
+pub(crate) fn script_from_address(
+    address: &str,
+    coin_type: u32,
+    network: bitcoin::Network,
+) -> Result<ScriptBuf> {
+    let (script_pubkey, address_network) = match coin_type {
+        BITCOIN_COIN_TYPE => {
+            let address = Address::from_str(address)
+                .map_err(|_| Error::Todo)?
+                .require_network(network)
+                .map_err(|_| Error::Todo)?;
+            return Ok(address.script_pubkey());
+        },
+        BITCOIN_CASH_COIN_TYPE => {
+            let address = CashAddress::from_str(address)?;
+            (address.script_pubkey(), address.network())
+        },
+        DECRED_COIN_TYPE => {
+            let address = DecredAddress::from_str(address)?;
+            (address.script_pubkey(), address.network())
+        },
+        GROESTLCOIN_COIN_TYPE => {
+            let address = GroestlcoinAddress::from_str(address)?;
+            (address.script_pubkey(), address.network())
+        },
+        _ => return Err(Error::Todo),
+    };
+
+    if !same_network(address_network, network) {
+        return Err(Error::Todo);
+    }
+
+    Ok(script_pubkey)
+}
+
+impl From<ProtoCoinSelectionStrategy> for CoinSelectionStrategy {
+    fn from(strategy: ProtoCoinSelectionStrategy) -> Self {
+        match strategy {
+            ProtoCoinSelectionStrategy::BRANCH_AND_BOUND => CoinSelectionStrategy::BranchAndBound,
+            ProtoCoinSelectionStrategy::LARGEST_FIRST => CoinSelectionStrategy::LargestFirst,
+            ProtoCoinSelectionStrategy::OLDEST_FIRST => CoinSelectionStrategy::OldestFirst,
+            ProtoCoinSelectionStrategy::PRIVACY => CoinSelectionStrategy::Privacy,
+        }
+    }
+}
+
 #[repr(C)]
 pub enum CTaprootError {
     Ok = 0,
     InvalidSlice = 1,
     InvalidPubkey = 2,
     InvalidSegwitPukey = 3,
+    InvalidAddress = 4,
 }
 
 impl From<CTaprootError> for ErrorCode {
@@ -0,0 +1,180 @@
+use crate::musig2::{
+    key_path_witness, KeyAggContext, PartialSignature, PublicNonce, SecretNonce, Session,
+};
+use crate::{Error, Result, TxOutputP2TRKeyPath};
+use bitcoin::consensus::Encodable;
+use bitcoin::secp256k1::{self, KeyPair, Message, PublicKey};
+use std::borrow::Cow;
+use tw_memory::ffi::c_byte_array::CByteArray;
+use tw_memory::ffi::c_byte_array_ref::CByteArrayRef;
+use tw_misc::try_or_else;
+use tw_proto::Bitcoin::Proto::{
+    MuSig2AggregateInput, MuSig2AggregateOutput, MuSig2KeyAggInput, MuSig2KeyAggOutput,
+    MuSig2NonceInput, MuSig2NonceOutput, MuSig2PartialSignInput, MuSig2PartialSignOutput,
+};
+
+#[no_mangle]
+// Aggregates the public keys of the MuSig2 signers into the Taproot output key.
+pub unsafe extern "C" fn tw_bitcoin_musig2_key_agg(
+    input: *const u8,
+    input_len: usize,
+) -> CByteArray {
+    let data = CByteArrayRef::new(input, input_len)
+        .to_vec()
+        .unwrap_or_default();
+
+    let proto: MuSig2KeyAggInput = try_or_else!(tw_proto::deserialize(&data), CByteArray::null);
+    let output = try_or_else!(musig2_key_agg(proto), CByteArray::null);
+
+    let serialized = tw_proto::serialize(&output).expect("failed to serialize aggregated key");
+
+    CByteArray::from(serialized)
+}
+
+#[no_mangle]
+// Generates the nonce of a MuSig2 signer (first round).
+pub unsafe extern "C" fn tw_bitcoin_musig2_nonce(input: *const u8, input_len: usize) -> CByteArray {
+    let data = CByteArrayRef::new(input, input_len)
+        .to_vec()
+        .unwrap_or_default();
+
+    let proto: MuSig2NonceInput = try_or_else!(tw_proto::deserialize(&data), CByteArray::null);
+    let output = try_or_else!(musig2_nonce(proto), CByteArray::null);
+
+    let serialized = tw_proto::serialize(&output).expect("failed to serialize nonce");
+
+    CByteArray::from(serialized)
+}
+
+#[no_mangle]
+// Creates the partial signature of a MuSig2 signer (second round).
+pub unsafe extern "C" fn tw_bitcoin_musig2_partial_sign(
+    input: *const u8,
+    input_len: usize,
+) -> CByteArray {
+    let data = CByteArrayRef::new(input, input_len)
+        .to_vec()
+        .unwrap_or_default();
+
+    let proto: MuSig2PartialSignInput =
+        try_or_else!(tw_proto::deserialize(&data), CByteArray::null);
+    let output = try_or_else!(musig2_partial_sign(proto), CByteArray::null);
+
+    let serialized = tw_proto::serialize(&output).expect("failed to serialize partial signature");
+
+    CByteArray::from(serialized)
+}
+
+#[no_mangle]
+// Aggregates the partial signatures into the final signature and key-path Witness.
+pub unsafe extern "C" fn tw_bitcoin_musig2_aggregate(
+    input: *const u8,
+    input_len: usize,
+) -> CByteArray {
+    let data = CByteArrayRef::new(input, input_len)
+        .to_vec()
+        .unwrap_or_default();
+
+    let proto: MuSig2AggregateInput = try_or_else!(tw_proto::deserialize(&data), CByteArray::null);
+    let output = try_or_else!(musig2_aggregate(proto), CByteArray::null);
+
+    let serialized = tw_proto::serialize(&output).expect("failed to serialize signature");
+
+    CByteArray::from(serialized)
+}
+
+fn key_agg_from_proto(public_keys: &[Cow<[u8]>]) -> Result<KeyAggContext> {
+    let keys = public_keys
+        .iter()
+        .map(|key| PublicKey::from_slice(key).map_err(|_| Error::Todo))
+        .collect::<Result<Vec<_>>>()?;
+
+    KeyAggContext::new(keys)
+}
+
+fn public_nonces_from_proto(public_nonces: &[Cow<[u8]>]) -> Result<Vec<PublicNonce>> {
+    public_nonces
+        .iter()
+        .map(|nonce| PublicNonce::from_slice(nonce))
+        .collect()
+}
+
+fn sighash_from_proto(sighash: &[u8]) -> Result<Message> {
+    if sighash.len() != 32 {
+        return Err(Error::Todo);
+    }
+
+    Message::from_slice(sighash).map_err(|_| Error::Todo)
+}
+
+fn keypair_from_proto(private_key: &[u8]) -> Result<KeyPair> {
+    KeyPair::from_seckey_slice(&secp256k1::Secp256k1::new(), private_key).map_err(|_| Error::Todo)
+}
+
+pub(crate) fn musig2_key_agg(proto: MuSig2KeyAggInput) -> Result<MuSig2KeyAggOutput<'static>> {
+    let key_agg = key_agg_from_proto(&proto.public_keys)?;
+    let recipient = key_agg.recipient();
+    let output = TxOutputP2TRKeyPath::new(0, recipient.clone());
+
+    Ok(MuSig2KeyAggOutput {
+        internal_key: key_agg.internal_key().serialize().to_vec().into(),
+        output_key: recipient.tweaked_pubkey().serialize().to_vec().into(),
+        script_pubkey: output.script_pubkey.to_bytes().into(),
+    })
+}
+
+pub(crate) fn musig2_nonce(proto: MuSig2NonceInput) -> Result<MuSig2NonceOutput<'static>> {
+    let key_agg = key_agg_from_proto(&proto.public_keys)?;
+    let keypair = keypair_from_proto(&proto.private_key)?;
+    let sighash = sighash_from_proto(&proto.sighash)?;
+
+    let (secret, public) = key_agg.generate_nonce(&keypair, &sighash)?;
+
+    Ok(MuSig2NonceOutput {
+        secret_nonce: secret.dangerous_into_bytes().to_vec().into(),
+        public_nonce: public.serialize().to_vec().into(),
+    })
+}
+
+pub(crate) fn musig2_partial_sign(
+    proto: MuSig2PartialSignInput,
+) -> Result<MuSig2PartialSignOutput<'static>> {
+    let key_agg = key_agg_from_proto(&proto.public_keys)?;
+    let keypair = keypair_from_proto(&proto.private_key)?;
+    let sighash = sighash_from_proto(&proto.sighash)?;
+    let secret = SecretNonce::dangerous_from_bytes(&proto.secret_nonce)?;
+    let nonces = public_nonces_from_proto(&proto.public_nonces)?;
+
+    let session = Session::new(&key_agg, &nonces, &sighash)?;
+    let partial = session.partial_sign(secret, &keypair)?;
+
+    Ok(MuSig2PartialSignOutput {
+        partial_signature: partial.serialize().to_vec().into(),
+    })
+}
+
+pub(crate) fn musig2_aggregate(
+    proto: MuSig2AggregateInput,
+) -> Result<MuSig2AggregateOutput<'static>> {
+    let key_agg = key_agg_from_proto(&proto.public_keys)?;
+    let sighash = sighash_from_proto(&proto.sighash)?;
+    let nonces = public_nonces_from_proto(&proto.public_nonces)?;
+    let partials = proto
+        .partial_signatures
+        .iter()
+        .map(|partial| PartialSignature::from_slice(partial))
+        .collect::<Result<Vec<_>>>()?;
+
+    let session = Session::new(&key_agg, &nonces, &sighash)?;
+    let sig = session.aggregate(&partials)?;
+
+    let mut witness = vec![];
+    key_path_witness(sig)
+        .consensus_encode(&mut witness)
+        .map_err(|_| Error::Todo)?;
+
+    Ok(MuSig2AggregateOutput {
+        signature: sig.as_ref().to_vec().into(),
+        witness: witness.into(),
+    })
+}
@@ -0,0 +1,76 @@
+use crate::{private_key_from_wif, private_key_from_xprv, Error, Result};
+use bitcoin::secp256k1::Secp256k1;
+use bitcoin::{Network, PrivateKey};
+use std::borrow::Cow;
+use tw_memory::ffi::c_byte_array::CByteArray;
+use tw_memory::ffi::c_byte_array_ref::CByteArrayRef;
+use tw_misc::try_or_else;
+use tw_proto::Bitcoin::Proto::{DecodedPrivateKey, EncodedPrivateKey, SigningInput};
+
+#[no_mangle]
+// Decodes the WIF or extended private key, validating its network and the
+// coin of a WIF encoded key, and reports whether its public key is compressed.
+pub unsafe extern "C" fn tw_bitcoin_decode_private_key(
+    input: *const u8,
+    input_len: usize,
+) -> CByteArray {
+    let data = CByteArrayRef::new(input, input_len)
+        .to_vec()
+        .unwrap_or_default();
+
+    let proto: EncodedPrivateKey = try_or_else!(tw_proto::deserialize(&data), CByteArray::null);
+    let private_key = try_or_else!(
+        private_key_from_proto(&proto, proto.coin_type),
+        CByteArray::null
+    );
+
+    let decoded = DecodedPrivateKey {
+        private_key: Cow::from(private_key.to_bytes()),
+        public_key: Cow::from(private_key.public_key(&Secp256k1::new()).to_bytes()),
+        compressed: private_key.compressed,
+    };
+
+    let serialized = tw_proto::serialize(&decoded).expect("failed to serialize private key");
+
+    CByteArray::from(serialized)
+}
+
+/// Decodes the encoded private key, a WIF encoded key must be encoded for the
+/// coin `coin_type`.
+pub(crate) fn private_key_from_proto(
+    proto: &EncodedPrivateKey,
+    coin_type: u32,
+) -> Result<PrivateKey> {
+    let network = if proto.testnet {
+        Network::Testnet
+    } else {
+        Network::Bitcoin
+    };
+
+    if !proto.wif.is_empty() {
+        return private_key_from_wif(&proto.wif, coin_type, network);
+    }
+
+    private_key_from_xprv(&proto.xprv, &proto.derivation_path, network)
+}
+
+/// The signing key of the `SigningInput`, either the first raw private key
+/// or the first encoded private key of the coin of the `SigningInput`. Raw
+/// keys are compressed unless `uncompressed_public_key` is set.
+pub(crate) fn private_key_from_signing_input(proto: &SigningInput) -> Result<PrivateKey> {
+    let mut privkey = match proto.private_key.first() {
+        Some(privkey) => {
+            PrivateKey::from_slice(privkey, Network::Bitcoin).map_err(|_| Error::Todo)?
+        },
+        None => {
+            let encoded = proto.encoded_private_key.first().ok_or(Error::Todo)?;
+            private_key_from_proto(encoded, proto.coin_type)?
+        },
+    };
+
+    if proto.uncompressed_public_key {
+        privkey.compressed = false;
+    }
+
+    Ok(privkey)
+}
@@ -0,0 +1,158 @@
+use super::{builder_from_proto, sign_and_build_proto};
+use crate::remote_signer::{RemoteSigner, SignRequest, SignatureKind};
+use crate::{Error, Recipient, Result};
+use bitcoin::PublicKey;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::ffi::c_void;
+use tw_memory::ffi::c_byte_array::CByteArray;
+use tw_memory::ffi::c_byte_array_ref::CByteArrayRef;
+use tw_misc::try_or_else;
+use tw_proto::Bitcoin::Proto::{SigningInput, SigningOutput};
+use tw_proto::Common::Proto::SigningError;
+
+/// The size of the buffer the host writes the signature to, which fits a DER
+/// encoded ECDSA signature.
+pub const SIGNATURE_BUFFER_SIZE: usize = 72;
+
+/// The status returned by a [`CSignCallback`]. The host returns it as a plain
+/// integer, any unknown value is treated as [`CSignStatus::Failed`].
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CSignStatus {
+    Ok = 0,
+    /// The user or the key store refused to sign, such as when the biometric
+    /// prompt was cancelled.
+    Declined = 1,
+    Failed = 2,
+}
+
+impl From<u32> for CSignStatus {
+    fn from(status: u32) -> Self {
+        match status {
+            0 => CSignStatus::Ok,
+            1 => CSignStatus::Declined,
+            _ => CSignStatus::Failed,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CSignatureKind {
+    Ecdsa = 0,
+    SchnorrKeyPath = 1,
+    Schnorr = 2,
+}
+
+impl From<SignatureKind> for CSignatureKind {
+    fn from(kind: SignatureKind) -> Self {
+        match kind {
+            SignatureKind::Ecdsa => CSignatureKind::Ecdsa,
+            SignatureKind::SchnorrKeyPath => CSignatureKind::SchnorrKeyPath,
+            SignatureKind::Schnorr => CSignatureKind::Schnorr,
+        }
+    }
+}
+
+/// Signs the 32-byte `sighash` of the input at `input_index` with the key
+/// identified by `key_id`. The signature is written to `signature`, a buffer
+/// of [`SIGNATURE_BUFFER_SIZE`] bytes, and its length to `signature_len`.
+/// Returns one of the [`CSignStatus`] values.
+pub type CSignCallback = unsafe extern "C" fn(
+    context: *mut c_void,
+    sighash: *const u8,
+    input_index: usize,
+    key_id: *const u8,
+    key_id_len: usize,
+    kind: CSignatureKind,
+    signature: *mut u8,
+    signature_len: *mut usize,
+) -> u32;
+
+#[no_mangle]
+// Builds the transaction of the `SigningInput` and signs each input by calling
+// `callback`, instead of using the private keys of the input. The inputs must
+// belong to `public_key`, whose key is referred to as `key_id` by the host.
+// `context` is passed to the callback as is. If the host declines to sign, the
+// returned output has the `Error_signing` error.
+pub unsafe extern "C" fn tw_bitcoin_sign_with_callback(
+    input: *const u8,
+    input_len: usize,
+    public_key: *const u8,
+    public_key_len: usize,
+    key_id: *const u8,
+    key_id_len: usize,
+    callback: CSignCallback,
+    context: *mut c_void,
+) -> CByteArray {
+    let data = CByteArrayRef::new(input, input_len)
+        .to_vec()
+        .unwrap_or_default();
+    let Some(public_key) = CByteArrayRef::new(public_key, public_key_len).to_vec() else {
+        return CByteArray::null();
+    };
+    let key_id = CByteArrayRef::new(key_id, key_id_len)
+        .to_vec()
+        .unwrap_or_default();
+
+    let proto: SigningInput = try_or_else!(tw_proto::deserialize(&data), CByteArray::null);
+
+    let declined = Cell::new(false);
+    let sign = |request: &SignRequest| {
+        let mut signature = [0; SIGNATURE_BUFFER_SIZE];
+        let mut signature_len = 0;
+
+        let status = CSignStatus::from(callback(
+            context,
+            request.sighash.as_ptr(),
+            request.input_index,
+            request.key_id.as_ptr(),
+            request.key_id.len(),
+            request.kind.into(),
+            signature.as_mut_ptr(),
+            &mut signature_len,
+        ));
+
+        match status {
+            CSignStatus::Ok if signature_len <= SIGNATURE_BUFFER_SIZE => {
+                Ok(signature[..signature_len].to_vec())
+            },
+            CSignStatus::Declined => {
+                declined.set(true);
+                Err(Error::Todo)
+            },
+            _ => Err(Error::Todo),
+        }
+    };
+
+    let signing = match sign_with_callback(proto, &public_key, key_id, sign) {
+        Ok(signing) => signing,
+        Err(_) if declined.get() => SigningOutput {
+            error: SigningError::Error_signing,
+            error_message: Cow::from("declined"),
+            ..Default::default()
+        },
+        Err(_) => return CByteArray::null(),
+    };
+
+    let serialized = tw_proto::serialize(&signing).expect("failed to serialize signed transaction");
+
+    CByteArray::from(serialized)
+}
+
+pub(crate) fn sign_with_callback<F>(
+    proto: SigningInput,
+    public_key: &[u8],
+    key_id: Vec<u8>,
+    sign: F,
+) -> Result<SigningOutput<'static>>
+where
+    F: Fn(&SignRequest) -> Result<Vec<u8>>,
+{
+    let public_key = PublicKey::from_slice(public_key).map_err(|_| Error::Todo)?;
+    let my_pubkey = Recipient::<PublicKey>::from(public_key);
+
+    let builder = builder_from_proto(proto, my_pubkey)?;
+    sign_and_build_proto(builder, RemoteSigner::new(public_key, key_id, sign))
+}
@@ -0,0 +1,236 @@
+use crate::runes::{Artifact, Edict, Etching, Rune, RuneId, Runestone, SpacedRune, Terms};
+use crate::{Error, Result};
+use bitcoin::consensus::Decodable;
+use bitcoin::Transaction;
+use std::borrow::Cow;
+use tw_memory::ffi::c_byte_array::CByteArray;
+use tw_memory::ffi::c_byte_array_ref::CByteArrayRef;
+use tw_misc::try_or_else;
+use tw_proto::Bitcoin::Proto::{
+    RuneEdict, RuneEtching, RuneId as ProtoRuneId, RunePointer, RuneTerms,
+    Runestone as ProtoRunestone, RunestoneArtifact, TransactionOutput,
+};
+
+#[no_mangle]
+// Builds the `OP_RETURN` output of the given runestone.
+pub unsafe extern "C" fn tw_bitcoin_build_runestone(
+    input: *const u8,
+    input_len: usize,
+) -> CByteArray {
+    let data = CByteArrayRef::new(input, input_len)
+        .to_vec()
+        .unwrap_or_default();
+
+    let proto: ProtoRunestone = try_or_else!(tw_proto::deserialize(&data), CByteArray::null);
+    let runestone = try_or_else!(runestone_from_proto(&proto), CByteArray::null);
+
+    // Prepare and serialize protobuf structure.
+    let proto = TransactionOutput {
+        value: 0,
+        script: Cow::from(runestone.encipher().to_bytes()),
+        spendingScript: Cow::default(),
+    };
+
+    let serialized = tw_proto::serialize(&proto).expect("failed to serialize transaction output");
+
+    CByteArray::from(serialized)
+}
+
+#[no_mangle]
+// Deciphers the runestone of the given transaction. Returns null if the
+// transaction does not contain a runestone.
+pub unsafe extern "C" fn tw_bitcoin_decipher_runestone(
+    transaction: *const u8,
+    transaction_len: usize,
+) -> CByteArray {
+    let mut encoded = try_or_else!(
+        CByteArrayRef::new(transaction, transaction_len).as_slice(),
+        CByteArray::null
+    );
+
+    let tx = try_or_else!(
+        Transaction::consensus_decode(&mut encoded).ok(),
+        CByteArray::null
+    );
+    let artifact = try_or_else!(Runestone::decipher(&tx), CByteArray::null);
+
+    let serialized =
+        tw_proto::serialize(&artifact_to_proto(&artifact)).expect("failed to serialize runestone");
+
+    CByteArray::from(serialized)
+}
+
+pub(crate) fn runestone_from_proto(proto: &ProtoRunestone) -> Result<Runestone> {
+    let mut runestone = Runestone::new();
+
+    for edict in &proto.edicts {
+        runestone = runestone.edict(Edict {
+            id: rune_id_from_proto(edict.id.as_ref().ok_or(Error::Todo)?)?,
+            amount: u128_from_proto(&edict.amount)?.unwrap_or_default(),
+            output: edict.output,
+        });
+    }
+
+    if let Some(etching) = &proto.etching {
+        runestone = runestone.etching(etching_from_proto(etching)?);
+    }
+
+    if let Some(mint) = &proto.mint {
+        runestone = runestone.mint(rune_id_from_proto(mint)?);
+    }
+
+    if let Some(pointer) = &proto.pointer {
+        runestone = runestone.pointer(pointer.output);
+    }
+
+    Ok(runestone)
+}
+
+fn etching_from_proto(proto: &RuneEtching) -> Result<Etching> {
+    let (rune, spacers) = if proto.rune.is_empty() {
+        (None, None)
+    } else {
+        let spaced: SpacedRune = proto.rune.parse()?;
+        (
+            Some(spaced.rune),
+            (spaced.spacers != 0).then_some(spaced.spacers),
+        )
+    };
+
+    let mut symbol = proto.symbol.chars();
+    let symbol = match (symbol.next(), symbol.next()) {
+        (symbol, None) => symbol,
+        _ => return Err(Error::Todo),
+    };
+
+    let terms = match &proto.terms {
+        Some(terms) => Some(Terms {
+            amount: u128_from_proto(&terms.amount)?,
+            cap: u128_from_proto(&terms.cap)?,
+            height: (non_zero(terms.height_start), non_zero(terms.height_end)),
+            offset: (non_zero(terms.offset_start), non_zero(terms.offset_end)),
+        }),
+        None => None,
+    };
+
+    Ok(Etching {
+        divisibility: (proto.divisibility != 0)
+            .then(|| u8::try_from(proto.divisibility))
+            .transpose()
+            .map_err(|_| Error::Todo)?,
+        premine: u128_from_proto(&proto.premine)?,
+        rune,
+        spacers,
+        symbol,
+        terms,
+        turbo: proto.turbo,
+    })
+}
+
+fn rune_id_from_proto(proto: &ProtoRuneId) -> Result<RuneId> {
+    RuneId::new(proto.block, proto.tx)
+}
+
+/// Parses the decimal string, an empty string is omitted.
+fn u128_from_proto(value: &str) -> Result<Option<u128>> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    value.parse().map(Some).map_err(|_| Error::Todo)
+}
+
+fn non_zero(value: u64) -> Option<u64> {
+    (value != 0).then_some(value)
+}
+
+fn artifact_to_proto(artifact: &Artifact) -> RunestoneArtifact<'static> {
+    match artifact {
+        Artifact::Runestone(runestone) => RunestoneArtifact {
+            runestone: Some(runestone_to_proto(runestone)),
+            cenotaph: false,
+            flaw: Cow::default(),
+        },
+        Artifact::Cenotaph(cenotaph) => RunestoneArtifact {
+            runestone: Some(ProtoRunestone {
+                edicts: vec![],
+                etching: cenotaph.etching.map(|rune| RuneEtching {
+                    rune: Cow::from(rune.to_string()),
+                    ..RuneEtching::default()
+                }),
+                mint: cenotaph.mint.map(rune_id_to_proto),
+                pointer: None,
+            }),
+            cenotaph: true,
+            flaw: cenotaph
+                .flaw
+                .map(|flaw| Cow::from(flaw.to_string()))
+                .unwrap_or_default(),
+        },
+    }
+}
+
+fn runestone_to_proto(runestone: &Runestone) -> ProtoRunestone<'static> {
+    ProtoRunestone {
+        edicts: runestone
+            .edicts
+            .iter()
+            .map(|edict| RuneEdict {
+                id: Some(rune_id_to_proto(edict.id)),
+                amount: Cow::from(edict.amount.to_string()),
+                output: edict.output,
+            })
+            .collect(),
+        etching: runestone.etching.map(|etching| etching_to_proto(&etching)),
+        mint: runestone.mint.map(rune_id_to_proto),
+        pointer: runestone.pointer.map(|output| RunePointer { output }),
+    }
+}
+
+fn etching_to_proto(etching: &Etching) -> RuneEtching<'static> {
+    let rune = etching
+        .rune
+        .map(|rune: Rune| {
+            SpacedRune {
+                rune,
+                spacers: etching.spacers.unwrap_or_default(),
+            }
+            .to_string()
+        })
+        .unwrap_or_default();
+
+    RuneEtching {
+        rune: Cow::from(rune),
+        divisibility: etching.divisibility.unwrap_or_default().into(),
+        premine: etching
+            .premine
+            .map(|premine| Cow::from(premine.to_string()))
+            .unwrap_or_default(),
+        symbol: etching
+            .symbol
+            .map(|symbol| Cow::from(symbol.to_string()))
+            .unwrap_or_default(),
+        terms: etching.terms.map(|terms| RuneTerms {
+            amount: terms
+                .amount
+                .map(|amount| Cow::from(amount.to_string()))
+                .unwrap_or_default(),
+            cap: terms
+                .cap
+                .map(|cap| Cow::from(cap.to_string()))
+                .unwrap_or_default(),
+            height_start: terms.height.0.unwrap_or_default(),
+            height_end: terms.height.1.unwrap_or_default(),
+            offset_start: terms.offset.0.unwrap_or_default(),
+            offset_end: terms.offset.1.unwrap_or_default(),
+        }),
+        turbo: etching.turbo,
+    }
+}
+
+fn rune_id_to_proto(id: RuneId) -> ProtoRuneId {
+    ProtoRuneId {
+        block: id.block,
+        tx: id.tx,
+    }
+}
@@ -1,15 +1,20 @@
+use super::CTaprootError;
 use crate::brc20::{BRC20TransferInscription, Ticker};
 use crate::nft::OrdinalNftInscription;
+use crate::ordinals::{InscriptionContent, OrdinalsInscription};
+use crate::script::{from_asm, to_asm, ScriptTemplate};
 use crate::{
-    Recipient, TXOutputP2TRScriptPath, TxOutputP2PKH, TxOutputP2TRKeyPath, TxOutputP2WPKH,
+    Error, Recipient, Result, TXOutputP2TRScriptPath, TxOutputP2PKH, TxOutputP2TRKeyPath,
+    TxOutputP2WPKH,
 };
-use bitcoin::{PublicKey, WPubkeyHash};
+use bitcoin::{PublicKey, Script, WPubkeyHash};
 use std::borrow::Cow;
-use std::ffi::{c_char, CStr};
+use std::ffi::{c_char, CStr, CString};
 use tw_memory::ffi::c_byte_array::CByteArray;
 use tw_memory::ffi::c_byte_array_ref::CByteArrayRef;
+use tw_memory::ffi::c_result::CStrMutResult;
 use tw_misc::try_or_else;
-use tw_proto::Bitcoin::Proto::TransactionOutput;
+use tw_proto::Bitcoin::Proto::{OrdinalsInscriptionInput, TransactionOutput};
 
 #[no_mangle]
 // Builds the P2PKH scriptPubkey.
@@ -18,6 +23,9 @@ pub unsafe extern "C" fn tw_build_p2pkh_script(
     pubkey: *const u8,
     pubkey_len: usize,
 ) -> CByteArray {
+    // Reject negative amounts.
+    let satoshis = try_or_else!(u64::try_from(satoshis), CByteArray::null);
+
     // Convert Recipient
     let slice = try_or_else!(
         CByteArrayRef::new(pubkey, pubkey_len).as_slice(),
@@ -25,11 +33,11 @@ pub unsafe extern "C" fn tw_build_p2pkh_script(
     );
     let recipient = try_or_else!(Recipient::<PublicKey>::from_slice(slice), CByteArray::null);
 
-    let tx_out = TxOutputP2PKH::new(satoshis as u64, recipient);
+    let tx_out = TxOutputP2PKH::new(satoshis, recipient);
 
     // Prepare and serialize protobuf structure.
     let proto = TransactionOutput {
-        value: satoshis,
+        value: satoshis as i64,
         script: Cow::from(tx_out.script_pubkey.as_bytes()),
         spendingScript: Cow::default(),
     };
@@ -46,6 +54,9 @@ pub unsafe extern "C" fn tw_build_p2wpkh_script(
     pubkey: *const u8,
     pubkey_len: usize,
 ) -> CByteArray {
+    // Reject negative amounts.
+    let satoshis = try_or_else!(u64::try_from(satoshis), CByteArray::null);
+
     // Convert Recipient
     let slice = try_or_else!(
         CByteArrayRef::new(pubkey, pubkey_len).as_slice(),
@@ -56,11 +67,11 @@ pub unsafe extern "C" fn tw_build_p2wpkh_script(
         CByteArray::null
     );
 
-    let tx_out = TxOutputP2WPKH::new(satoshis as u64, recipient);
+    let tx_out = TxOutputP2WPKH::new(satoshis, recipient);
 
     // Prepare and serialize protobuf structure.
     let proto = TransactionOutput {
-        value: satoshis,
+        value: satoshis as i64,
         script: Cow::from(tx_out.script_pubkey.as_bytes()),
         spendingScript: Cow::default(),
     };
@@ -77,6 +88,9 @@ pub unsafe extern "C" fn tw_build_p2tr_key_path_script(
     pubkey: *const u8,
     pubkey_len: usize,
 ) -> CByteArray {
+    // Reject negative amounts.
+    let satoshis = try_or_else!(u64::try_from(satoshis), CByteArray::null);
+
     // Convert Recipient
     let slice = try_or_else!(
         CByteArrayRef::new(pubkey, pubkey_len).as_slice(),
@@ -84,11 +98,11 @@ pub unsafe extern "C" fn tw_build_p2tr_key_path_script(
     );
     let recipient = try_or_else!(Recipient::<PublicKey>::from_slice(slice), CByteArray::null);
 
-    let tx_out = TxOutputP2TRKeyPath::new(satoshis as u64, recipient.into());
+    let tx_out = TxOutputP2TRKeyPath::new(satoshis, recipient.into());
 
     // Prepare and serialize protobuf structure.
     let proto = TransactionOutput {
-        value: satoshis,
+        value: satoshis as i64,
         script: Cow::from(tx_out.script_pubkey.as_bytes()),
         spendingScript: Cow::default(),
     };
@@ -108,6 +122,9 @@ pub unsafe extern "C" fn tw_build_brc20_transfer_inscription(
     pubkey: *const u8,
     pubkey_len: usize,
 ) -> CByteArray {
+    // Reject negative amounts.
+    let satoshis = try_or_else!(u64::try_from(satoshis), CByteArray::null);
+
     // Convert ticket.
     let ticker = match CStr::from_ptr(ticker).to_str() {
         Ok(input) => input,
@@ -132,12 +149,12 @@ pub unsafe extern "C" fn tw_build_brc20_transfer_inscription(
     let transfer = BRC20TransferInscription::new(recipient, ticker, amount)
         .expect("transfer inscription implemented wrongly");
 
-    let tx_out = TXOutputP2TRScriptPath::new(satoshis as u64, transfer.inscription().recipient());
+    let tx_out = TXOutputP2TRScriptPath::new(satoshis, transfer.inscription().recipient());
     let spending_script = transfer.inscription().taproot_program();
 
     // Prepare and serialize protobuf structure.
     let proto = TransactionOutput {
-        value: satoshis,
+        value: satoshis as i64,
         script: Cow::from(tx_out.script_pubkey.as_bytes()),
         spendingScript: Cow::from(spending_script.as_bytes()),
     };
@@ -157,6 +174,9 @@ pub unsafe extern "C" fn tw_bitcoin_build_nft_inscription(
     pubkey: *const u8,
     pubkey_len: usize,
 ) -> CByteArray {
+    // Reject negative amounts.
+    let satoshis = try_or_else!(u64::try_from(satoshis), CByteArray::null);
+
     // Convert mimeType.
     let mime_type = match CStr::from_ptr(mime_type).to_str() {
         Ok(input) => input,
@@ -181,12 +201,12 @@ pub unsafe extern "C" fn tw_bitcoin_build_nft_inscription(
     let nft = OrdinalNftInscription::new(mime_type.as_bytes(), data, recipient)
         .expect("Ordinal NFT inscription incorrectly constructed");
 
-    let tx_out = TXOutputP2TRScriptPath::new(satoshis as u64, nft.inscription().recipient());
+    let tx_out = TXOutputP2TRScriptPath::new(satoshis, nft.inscription().recipient());
     let spending_script = nft.inscription().taproot_program();
 
     // Prepare and serialize protobuf structure.
     let proto = TransactionOutput {
-        value: satoshis,
+        value: satoshis as i64,
         script: Cow::from(tx_out.script_pubkey.as_bytes()),
         spendingScript: Cow::from(spending_script.as_bytes()),
     };
@@ -195,3 +215,100 @@ pub unsafe extern "C" fn tw_bitcoin_build_nft_inscription(
 
     CByteArray::from(serialized)
 }
+
+#[no_mangle]
+// Builds the commit output of an Ordinals inscription, revealed by a key that
+// may differ from the owner of the commit output.
+pub unsafe extern "C" fn tw_bitcoin_build_ordinals_inscription(
+    input: *const u8,
+    input_len: usize,
+) -> CByteArray {
+    let data = CByteArrayRef::new(input, input_len)
+        .to_vec()
+        .unwrap_or_default();
+
+    let proto: OrdinalsInscriptionInput =
+        try_or_else!(tw_proto::deserialize(&data), CByteArray::null);
+    let output = try_or_else!(build_ordinals_inscription(&proto), CByteArray::null);
+
+    let serialized = tw_proto::serialize(&output).expect("failed to serialized transaction output");
+
+    CByteArray::from(serialized)
+}
+
+pub(crate) fn build_ordinals_inscription(
+    proto: &OrdinalsInscriptionInput,
+) -> Result<TransactionOutput<'static>> {
+    let satoshis = u64::try_from(proto.amount).map_err(|_| Error::Todo)?;
+
+    let content = InscriptionContent::new(proto.mime_type.as_bytes(), &proto.payload);
+    let reveal_key = Recipient::<PublicKey>::from_slice(&proto.reveal_pubkey)?;
+
+    let mut builder = OrdinalsInscription::builder()
+        .content(content)
+        .reveal_key(reveal_key.public_key());
+
+    if !proto.internal_pubkey.is_empty() {
+        let internal_key = Recipient::<PublicKey>::from_slice(&proto.internal_pubkey)?;
+        builder = builder.internal_key(internal_key.public_key());
+    }
+
+    let inscription = builder.build()?;
+    let tx_out = TXOutputP2TRScriptPath::new(satoshis, inscription.recipient());
+
+    Ok(TransactionOutput {
+        value: satoshis as i64,
+        script: Cow::from(tx_out.script_pubkey.to_bytes()),
+        spendingScript: Cow::from(inscription.taproot_program().to_bytes()),
+    })
+}
+
+#[no_mangle]
+// Disassembles the script into its Bitcoin Core-style ASM representation.
+pub unsafe extern "C" fn tw_bitcoin_script_to_asm(
+    script: *const u8,
+    script_len: usize,
+) -> CStrMutResult {
+    let Some(slice) = CByteArrayRef::new(script, script_len).as_slice() else {
+        return CStrMutResult::error(CTaprootError::InvalidSlice);
+    };
+
+    let asm = to_asm(Script::from_bytes(slice));
+    let c_string = CString::new(asm)
+        .expect("script ASM contains an internal 0 byte")
+        .into_raw();
+
+    CStrMutResult::ok(c_string)
+}
+
+#[no_mangle]
+// Assembles the ASM representation into a script.
+pub unsafe extern "C" fn tw_bitcoin_script_from_asm(asm: *const c_char) -> CByteArray {
+    let asm = match CStr::from_ptr(asm).to_str() {
+        Ok(input) => input,
+        Err(_) => return CByteArray::null(),
+    };
+
+    let script = try_or_else!(from_asm(asm), CByteArray::null);
+
+    CByteArray::from(script.into_bytes())
+}
+
+#[no_mangle]
+// Classifies the script as one of the standard templates, named as by Bitcoin
+// Core (e.g. "witness_v0_keyhash" or "nonstandard").
+pub unsafe extern "C" fn tw_bitcoin_classify_script(
+    script: *const u8,
+    script_len: usize,
+) -> CStrMutResult {
+    let Some(slice) = CByteArrayRef::new(script, script_len).as_slice() else {
+        return CStrMutResult::error(CTaprootError::InvalidSlice);
+    };
+
+    let template = ScriptTemplate::classify(Script::from_bytes(slice));
+    let c_string = CString::new(template.to_string())
+        .expect("script template contains an internal 0 byte")
+        .into_raw();
+
+    CStrMutResult::ok(c_string)
+}
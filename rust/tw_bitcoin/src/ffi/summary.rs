@@ -0,0 +1,130 @@
+use super::private_key::private_key_from_signing_input;
+use super::{builder_from_proto, BITCOIN_COIN_TYPE};
+use crate::bitcoin_cash::BITCOIN_CASH_COIN_TYPE;
+use crate::groestlcoin::GROESTLCOIN_COIN_TYPE;
+use crate::sat_point::SatDestination;
+use crate::summary::TransactionSummary;
+use crate::{Error, Recipient, Result};
+use bitcoin::secp256k1::Secp256k1;
+use bitcoin::Network;
+use std::borrow::Cow;
+use tw_memory::ffi::c_byte_array::CByteArray;
+use tw_memory::ffi::c_byte_array_ref::CByteArrayRef;
+use tw_misc::try_or_else;
+use tw_proto::Bitcoin::Proto::{
+    InputSummary, InscriptionSummary, OutputSummary, TransactionSummary as ProtoTransactionSummary,
+    TransactionSummaryInput,
+};
+
+#[no_mangle]
+// Describes the transaction of the `SigningInput` for confirmation by the
+// user, without signing it.
+pub unsafe extern "C" fn tw_bitcoin_transaction_summary(
+    input: *const u8,
+    input_len: usize,
+) -> CByteArray {
+    let data = CByteArrayRef::new(input, input_len)
+        .to_vec()
+        .unwrap_or_default();
+
+    let proto: TransactionSummaryInput =
+        try_or_else!(tw_proto::deserialize(&data), CByteArray::null);
+    let summary = try_or_else!(transaction_summary(proto), CByteArray::null);
+
+    let serialized = tw_proto::serialize(&summary).expect("failed to serialize summary");
+
+    CByteArray::from(serialized)
+}
+
+pub(crate) fn transaction_summary(
+    proto: TransactionSummaryInput,
+) -> Result<ProtoTransactionSummary<'static>> {
+    let signing = proto.signing.ok_or(Error::Todo)?;
+
+    // The addresses and the size of the transactions of other coins are not
+    // described correctly.
+    if ![
+        BITCOIN_COIN_TYPE,
+        BITCOIN_CASH_COIN_TYPE,
+        GROESTLCOIN_COIN_TYPE,
+    ]
+    .contains(&signing.coin_type)
+    {
+        return Err(Error::Todo);
+    }
+
+    let network = if proto.testnet {
+        Network::Testnet
+    } else {
+        Network::Bitcoin
+    };
+
+    let my_pubkey = private_key_from_signing_input(&signing)?.public_key(&Secp256k1::new());
+    let builder = builder_from_proto(signing, Recipient::from(my_pubkey))?;
+
+    let summary = builder.summary(&[my_pubkey], network)?;
+    summary_to_proto(&summary)
+}
+
+fn summary_to_proto(summary: &TransactionSummary) -> Result<ProtoTransactionSummary<'static>> {
+    let amount = |satoshis: u64| i64::try_from(satoshis).map_err(|_| Error::Todo);
+
+    let mut inputs = vec![];
+    for input in &summary.inputs {
+        inputs.push(InputSummary {
+            txid: Cow::from(input.previous_output.txid.to_string()),
+            vout: input.previous_output.vout,
+            amount: amount(input.satoshis)?,
+            input_type: Cow::from(input.input_type.to_string()),
+            sequence: input.sequence.to_consensus_u32(),
+        });
+    }
+
+    let mut outputs = vec![];
+    for output in &summary.outputs {
+        outputs.push(OutputSummary {
+            amount: amount(output.satoshis)?,
+            destination: Cow::from(output.destination.to_string()),
+            script_pubkey: Cow::from(output.script_pubkey.to_bytes()),
+            is_mine: output.is_mine,
+            inscription_commit: output.inscription_commit,
+        });
+    }
+
+    let inscriptions = summary
+        .inscriptions
+        .iter()
+        .map(|inscription| {
+            let brc20 = inscription.brc20.as_ref();
+
+            InscriptionSummary {
+                input: inscription.input as u32,
+                content_type: Cow::from(
+                    String::from_utf8_lossy(inscription.content.mime()).into_owned(),
+                ),
+                content_length: inscription.content.data().len() as u64,
+                output: match inscription.destination {
+                    SatDestination::Output { vout, .. } => vout.into(),
+                    _ => -1,
+                },
+                brc20_operation: Cow::from(
+                    brc20.map(|op| op.operation.clone()).unwrap_or_default(),
+                ),
+                brc20_ticker: Cow::from(brc20.map(|op| op.ticker.clone()).unwrap_or_default()),
+                brc20_amount: Cow::from(brc20.map(|op| op.amount.clone()).unwrap_or_default()),
+            }
+        })
+        .collect();
+
+    Ok(ProtoTransactionSummary {
+        inputs,
+        outputs,
+        fee: amount(summary.fee)?,
+        vsize: summary.vsize,
+        fee_rate: summary.fee_rate(),
+        replaceable: summary.replaceable,
+        lock_time: summary.lock_time.to_consensus_u32(),
+        lock_time_enabled: summary.lock_time_enabled,
+        inscriptions,
+    })
+}
@@ -0,0 +1,92 @@
+use super::private_key::private_key_from_signing_input;
+use super::{
+    aux_rand_from_proto, prepare_builder_from_proto, sign_and_build_proto, sweep_builder,
+    sweep_from_proto,
+};
+use crate::claim::KeyPairSigner;
+use crate::{Error, Recipient, Result};
+use bitcoin::PublicKey;
+use secp256k1::{KeyPair, Secp256k1};
+use std::borrow::Cow;
+use tw_memory::ffi::c_byte_array::CByteArray;
+use tw_memory::ffi::c_byte_array_ref::CByteArrayRef;
+use tw_misc::try_or_else;
+use tw_proto::Bitcoin::Proto::{
+    SigningInput, SweepOutput, SweptTransaction, TransactionVariant as TrVariant,
+};
+use tw_proto::Common::Proto::SigningError;
+
+#[no_mangle]
+// Sweeps the UTXOs of the `SigningInput` to its `to_address` and signs the
+// resulting transactions.
+pub unsafe extern "C" fn tw_bitcoin_sweep(input: *const u8, input_len: usize) -> CByteArray {
+    let data = CByteArrayRef::new(input, input_len)
+        .to_vec()
+        .unwrap_or_default();
+
+    let proto: SigningInput = try_or_else!(tw_proto::deserialize(&data), CByteArray::null);
+    let output = try_or_else!(sweep(proto), CByteArray::null);
+
+    let serialized = tw_proto::serialize(&output).expect("failed to serialize sweep");
+
+    CByteArray::from(serialized)
+}
+
+pub(crate) fn sweep(proto: SigningInput) -> Result<SweepOutput<'static>> {
+    let privkey = private_key_from_signing_input(&proto)?;
+
+    // SegWit and Taproot require compressed public keys.
+    if !privkey.compressed
+        && proto
+            .utxo
+            .iter()
+            .any(|utxo| utxo.variant != TrVariant::P2PKH)
+    {
+        return Ok(SweepOutput {
+            error: SigningError::Error_uncompressed_key,
+            error_message: Cow::from("uncompressed keys can only be used with P2PKH inputs"),
+            ..Default::default()
+        });
+    }
+
+    // Uncompressed keys can only claim the P2PKH inputs of old wallets.
+    let secp = Secp256k1::new();
+    let keypair = KeyPair::from_secret_key(&secp, &privkey.inner);
+    let my_pubkey = Recipient::<PublicKey>::from(privkey.public_key(&secp));
+    let signer = KeyPairSigner::new(keypair).aux_rand(aux_rand_from_proto(&proto)?);
+
+    let (builder, utxos, _) = prepare_builder_from_proto(&proto, my_pubkey)?;
+    let sweep = sweep_from_proto(&proto, &utxos)?;
+
+    let amount = |satoshis: u64| i64::try_from(satoshis).map_err(|_| Error::Todo);
+
+    let mut transactions = vec![];
+    for tx in sweep.transactions {
+        let satoshis = tx.output.satoshis();
+        let fee = tx.fee;
+
+        transactions.push(SweptTransaction {
+            signed: Some(sign_and_build_proto(
+                sweep_builder(builder.clone(), tx),
+                signer,
+            )?),
+            amount: amount(satoshis)?,
+            fee: amount(fee)?,
+        });
+    }
+
+    Ok(SweepOutput {
+        transactions,
+        marginal_costs: sweep
+            .marginal_costs
+            .into_iter()
+            .map(amount)
+            .collect::<Result<_>>()?,
+        skipped: sweep
+            .skipped
+            .into_iter()
+            .map(|index| index as u32)
+            .collect(),
+        ..Default::default()
+    })
+}
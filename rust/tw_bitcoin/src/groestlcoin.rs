@@ -0,0 +1,157 @@
+//! Groestlcoin addresses. Groestlcoin uses Bitcoin's transaction and script
+//! formats, but hashes transactions with single SHA-256 and base58check
+//! addresses with double Groestl-512, see [`ChainHashes::GROESTLCOIN`].
+//! SegWit addresses use the regular Bech32 checksum.
+
+use crate::hasher::ChainHashes;
+use crate::{Error, Result};
+use bitcoin::address::{Payload, WitnessProgram, WitnessVersion};
+use bitcoin::bech32::{self, u5, FromBase32, ToBase32};
+use bitcoin::hashes::Hash;
+use bitcoin::{Network, PubkeyHash, PublicKey, Script, ScriptBuf, ScriptHash};
+use std::fmt;
+use std::str::FromStr;
+
+/// The SLIP-44 coin type of Groestlcoin.
+pub const GROESTLCOIN_COIN_TYPE: u32 = 17;
+
+/// The version of the transactions signed by the wallet so far.
+pub const TX_VERSION: i32 = 1;
+
+/// A Groestlcoin address, such as `Fj62rBJi8LvbmWu2jzkaUX1NFXLEqDLoZM` or
+/// `grs1qw4teyraux2s77nhjdwh9ar8rl9dt7zww8r6lne`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroestlcoinAddress {
+    network: Network,
+    payload: Payload,
+}
+
+impl GroestlcoinAddress {
+    /// The `F...` (mainnet) address of the public key.
+    pub fn p2pkh(pubkey: &PublicKey, network: Network) -> Self {
+        GroestlcoinAddress {
+            network: normalize(network),
+            payload: Payload::p2pkh(pubkey),
+        }
+    }
+    /// The `grs1q...` (mainnet) address of the compressed public key.
+    pub fn p2wpkh(pubkey: &PublicKey, network: Network) -> Result<Self> {
+        Ok(GroestlcoinAddress {
+            network: normalize(network),
+            payload: Payload::p2wpkh(pubkey).map_err(|_| Error::Todo)?,
+        })
+    }
+    /// The address of a standard scriptPubKey.
+    pub fn from_script(script_pubkey: &Script, network: Network) -> Result<Self> {
+        Ok(GroestlcoinAddress {
+            network: normalize(network),
+            payload: Payload::from_script(script_pubkey).map_err(|_| Error::Todo)?,
+        })
+    }
+    /// Either [`Network::Bitcoin`] for mainnet, [`Network::Testnet`] or
+    /// [`Network::Regtest`].
+    pub fn network(&self) -> Network {
+        self.network
+    }
+    pub fn script_pubkey(&self) -> ScriptBuf {
+        self.payload.script_pubkey()
+    }
+    fn base58_prefixes(network: Network) -> (u8, u8) {
+        match network {
+            Network::Bitcoin => (36, 5),
+            _ => (111, 196),
+        }
+    }
+    fn bech32_hrp(network: Network) -> &'static str {
+        match network {
+            Network::Bitcoin => "grs",
+            Network::Regtest => "grsrt",
+            _ => "tgrs",
+        }
+    }
+}
+
+/// Groestlcoin has no signet, it shares the testnet prefixes.
+fn normalize(network: Network) -> Network {
+    match network {
+        Network::Signet => Network::Testnet,
+        network => network,
+    }
+}
+
+impl fmt::Display for GroestlcoinAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (p2pkh, p2sh) = Self::base58_prefixes(self.network);
+
+        let (prefix, hash) = match &self.payload {
+            Payload::PubkeyHash(hash) => (p2pkh, hash.to_byte_array()),
+            Payload::ScriptHash(hash) => (p2sh, hash.to_byte_array()),
+            Payload::WitnessProgram(program) => {
+                let version = u5::try_from_u8(program.version().to_num())
+                    .expect("witness versions are below 32");
+                let mut data = vec![version];
+                data.extend(program.program().as_bytes().to_base32());
+
+                let hrp = Self::bech32_hrp(self.network);
+                let variant = program.version().bech32_variant();
+                let encoded = bech32::encode(hrp, data, variant).map_err(|_| fmt::Error)?;
+                return f.write_str(&encoded);
+            },
+            _ => return Err(fmt::Error),
+        };
+
+        let mut data = vec![prefix];
+        data.extend(hash);
+        f.write_str(&ChainHashes::GROESTLCOIN.base58check_encode(&data))
+    }
+}
+
+impl FromStr for GroestlcoinAddress {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Self> {
+        let networks = [Network::Bitcoin, Network::Testnet, Network::Regtest];
+
+        if let Ok((hrp, data, variant)) = bech32::decode(string) {
+            let network = networks
+                .into_iter()
+                .find(|network| Self::bech32_hrp(*network) == hrp)
+                .ok_or(Error::Todo)?;
+
+            let (version, program) = data.split_first().ok_or(Error::Todo)?;
+            let version = WitnessVersion::try_from(*version).map_err(|_| Error::Todo)?;
+            if version.bech32_variant() != variant {
+                return Err(Error::Todo);
+            }
+
+            let program = Vec::<u8>::from_base32(program).map_err(|_| Error::Todo)?;
+            let program = WitnessProgram::new(version, program).map_err(|_| Error::Todo)?;
+
+            return Ok(GroestlcoinAddress {
+                network,
+                payload: Payload::WitnessProgram(program),
+            });
+        }
+
+        let data = ChainHashes::GROESTLCOIN.base58check_decode(string)?;
+        let (prefix, hash) = data.split_first().ok_or(Error::Todo)?;
+        let hash: [u8; 20] = hash.try_into().map_err(|_| Error::Todo)?;
+
+        // Regtest shares the base58 prefixes of testnet.
+        for network in [Network::Bitcoin, Network::Testnet] {
+            let (p2pkh, p2sh) = Self::base58_prefixes(network);
+
+            let payload = if *prefix == p2pkh {
+                Payload::PubkeyHash(PubkeyHash::from_byte_array(hash))
+            } else if *prefix == p2sh {
+                Payload::ScriptHash(ScriptHash::from_byte_array(hash))
+            } else {
+                continue;
+            };
+
+            return Ok(GroestlcoinAddress { network, payload });
+        }
+
+        Err(Error::Todo)
+    }
+}
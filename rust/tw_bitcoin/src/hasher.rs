@@ -0,0 +1,144 @@
+//! The hash functions of transaction IDs, legacy signature hashes and
+//! base58check checksums. Bitcoin uses double SHA-256 for all of them, but
+//! some of the chains derived from it replaced the hash function while
+//! keeping Bitcoin's transaction format.
+
+use crate::{Error, Result};
+use bitcoin::base58;
+use bitcoin::consensus::Encodable;
+use bitcoin::hashes::{sha256, sha256d, Hash};
+use bitcoin::sighash::{EcdsaSighashType, SighashCache};
+use bitcoin::{Script, Transaction, Txid};
+use tw_hash::blake::blake_256;
+use tw_hash::groestl::groestl_512;
+
+const CHECKSUM_SIZE: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hasher {
+    Sha256,
+    Sha256d,
+    /// Double Groestl-512, truncated to 32 bytes.
+    Groestl512d,
+    /// BLAKE-256, the checksum of Decred's WIF encoded keys.
+    Blake256,
+}
+
+impl Hasher {
+    pub fn hash(&self, data: &[u8]) -> [u8; 32] {
+        match self {
+            Hasher::Sha256 => sha256::Hash::hash(data).to_byte_array(),
+            Hasher::Sha256d => sha256d::Hash::hash(data).to_byte_array(),
+            Hasher::Groestl512d => groestl_512(&groestl_512(data))[..32]
+                .try_into()
+                .expect("Groestl-512 returns 64 bytes"),
+            Hasher::Blake256 => blake_256(data)
+                .try_into()
+                .expect("BLAKE-256 returns 32 bytes"),
+        }
+    }
+    /// Decodes the base58 string and verifies its checksum, the first bytes of
+    /// the hash of the data. The checksum is removed.
+    pub fn base58check_decode(&self, string: &str) -> Result<Vec<u8>> {
+        let mut data = base58::decode(string).map_err(|_| Error::Todo)?;
+        if data.len() < CHECKSUM_SIZE {
+            return Err(Error::Todo);
+        }
+
+        let checksum = data.split_off(data.len() - CHECKSUM_SIZE);
+        if self.hash(&data)[..CHECKSUM_SIZE] != checksum {
+            return Err(Error::Todo);
+        }
+
+        Ok(data)
+    }
+}
+
+/// The hash functions used by a chain, see
+/// [`crate::TransactionBuilder::hashes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainHashes {
+    pub txid: Hasher,
+    pub sighash: Hasher,
+    pub checksum: Hasher,
+}
+
+impl ChainHashes {
+    pub const BITCOIN: ChainHashes = ChainHashes {
+        txid: Hasher::Sha256d,
+        sighash: Hasher::Sha256d,
+        checksum: Hasher::Sha256d,
+    };
+    pub const GROESTLCOIN: ChainHashes = ChainHashes {
+        txid: Hasher::Sha256,
+        sighash: Hasher::Sha256,
+        checksum: Hasher::Groestl512d,
+    };
+
+    /// The transaction ID, the hash of the transaction without witnesses.
+    pub fn txid(&self, tx: &Transaction) -> Txid {
+        let mut stripped = tx.clone();
+        for input in &mut stripped.input {
+            input.witness.clear();
+        }
+
+        let mut data = vec![];
+        stripped
+            .consensus_encode(&mut data)
+            .expect("encoding to a vector never fails");
+
+        Txid::from_byte_array(self.txid.hash(&data))
+    }
+    /// Computes the legacy signature hash of the input at `index`, which
+    /// spends an output locked by `script_pubkey`.
+    pub fn signature_hash(
+        &self,
+        tx: &Transaction,
+        index: usize,
+        script_pubkey: &Script,
+        sighash_type: EcdsaSighashType,
+    ) -> Result<[u8; 32]> {
+        let data = legacy_preimage(tx, index, script_pubkey, sighash_type)?;
+        Ok(self.sighash.hash(&data))
+    }
+    /// Encodes the data followed by its checksum in base58.
+    pub fn base58check_encode(&self, data: &[u8]) -> String {
+        let mut data = data.to_vec();
+        let checksum = self.checksum.hash(&data);
+        data.extend(&checksum[..CHECKSUM_SIZE]);
+
+        base58::encode(&data)
+    }
+    /// Decodes the base58 string and verifies its checksum, which is removed.
+    pub fn base58check_decode(&self, string: &str) -> Result<Vec<u8>> {
+        self.checksum.base58check_decode(string)
+    }
+}
+
+impl Default for ChainHashes {
+    fn default() -> Self {
+        ChainHashes::BITCOIN
+    }
+}
+
+/// The data committed to by the legacy signature hash, before hashing.
+pub fn legacy_preimage(
+    tx: &Transaction,
+    index: usize,
+    script_pubkey: &Script,
+    sighash_type: EcdsaSighashType,
+) -> Result<Vec<u8>> {
+    let mut data = vec![];
+    let single_bug = SighashCache::new(tx)
+        .legacy_encode_signing_data_to(&mut data, index, script_pubkey, sighash_type.to_u32())
+        .is_sighash_single_bug()
+        .map_err(|_| Error::Todo)?;
+
+    // `SIGHASH_SINGLE` without a matching output signs the "one" hash, which
+    // lets anyone spend the input.
+    if single_bug {
+        return Err(Error::Todo);
+    }
+
+    Ok(data)
+}
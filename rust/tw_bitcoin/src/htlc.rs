@@ -0,0 +1,305 @@
+//! Hash-time-locked contracts (HTLC), as used for atomic swaps. The recipient
+//! can claim the funds by revealing the preimage of the payment hash, while
+//! the sender can take back (refund) the funds once the timelock expired.
+//!
+//! The contract can be locked in a P2WSH output, where both paths are
+//! branches of the same witness script, or in a P2TR output, where each path
+//! is a leaf of the script tree.
+
+use crate::ordinals::unspendable_internal_key;
+use crate::{
+    Error, Recipient, Result, TXOutputP2TRScriptPath, TaprootScript, TxInput,
+    TxInputP2TRScriptPath, TxInputP2WSH, TxOutput, TxOutputP2WSH,
+};
+use bitcoin::blockdata::locktime::absolute::LockTime;
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::opcodes::all::{
+    OP_CHECKSIG, OP_CLTV, OP_CSV, OP_DROP, OP_ELSE, OP_ENDIF, OP_EQUALVERIFY, OP_IF, OP_SHA256,
+    OP_SIZE,
+};
+use bitcoin::script::Builder as ScriptBuilder;
+use bitcoin::secp256k1::Secp256k1;
+use bitcoin::taproot::{TapNodeHash, TaprootBuilder, TaprootSpendInfo};
+use bitcoin::{Address, Network, PublicKey, ScriptBuf, Sequence, Txid};
+
+/// The size of the preimage, enforced by the script so that the contract can
+/// be mirrored on chains with other script size limits.
+pub const PREIMAGE_SIZE: usize = 32;
+
+/// The timelock of the refund path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HtlcTimelock {
+    /// The refund is possible once the given block height or time has been
+    /// reached (`OP_CHECKLOCKTIMEVERIFY`).
+    Absolute(LockTime),
+    /// The refund is possible once the funding output has the given age, see
+    /// [`Sequence::from_height`] and [`Sequence::from_512_second_intervals`]
+    /// (`OP_CHECKSEQUENCEVERIFY`).
+    Relative(Sequence),
+}
+
+#[derive(Debug, Clone)]
+pub struct Htlc {
+    payment_hash: sha256::Hash,
+    recipient: Recipient<PublicKey>,
+    refund: Recipient<PublicKey>,
+    timelock: HtlcTimelock,
+    internal_key: PublicKey,
+}
+
+impl Htlc {
+    /// Creates the contract paying to `recipient` in exchange for the
+    /// preimage of `payment_hash`, or back to `refund` once the timelock
+    /// expired.
+    pub fn new(
+        payment_hash: sha256::Hash,
+        recipient: Recipient<PublicKey>,
+        refund: Recipient<PublicKey>,
+        timelock: HtlcTimelock,
+    ) -> Result<Self> {
+        if let HtlcTimelock::Relative(sequence) = timelock {
+            if !sequence.is_relative_lock_time() {
+                return Err(Error::Todo);
+            }
+        }
+
+        Ok(Htlc {
+            payment_hash,
+            recipient,
+            refund,
+            timelock,
+            internal_key: unspendable_internal_key(),
+        })
+    }
+    /// Sets the internal key of the P2TR output, which defaults to the
+    /// unspendable [`crate::ordinals::NUMS_POINT`].
+    pub fn internal_key(mut self, internal_key: PublicKey) -> Self {
+        self.internal_key = internal_key;
+        self
+    }
+    pub fn payment_hash(&self) -> &sha256::Hash {
+        &self.payment_hash
+    }
+    pub fn timelock(&self) -> HtlcTimelock {
+        self.timelock
+    }
+    /// The sequence the refund input must have.
+    pub fn refund_sequence(&self) -> Sequence {
+        match self.timelock {
+            // The lock time is only enforced for non-final inputs.
+            HtlcTimelock::Absolute(_) => Sequence::ENABLE_LOCKTIME_NO_RBF,
+            HtlcTimelock::Relative(sequence) => sequence,
+        }
+    }
+    /// The lock time the refund transaction must have, see
+    /// [`crate::TransactionBuilder::lock_time`].
+    pub fn refund_lock_time(&self) -> LockTime {
+        match self.timelock {
+            HtlcTimelock::Absolute(lock_time) => lock_time,
+            HtlcTimelock::Relative(_) => LockTime::ZERO,
+        }
+    }
+    /// `OP_SIZE 32 OP_EQUALVERIFY OP_SHA256 <payment hash> OP_EQUALVERIFY`
+    fn push_hashlock(&self, builder: ScriptBuilder) -> ScriptBuilder {
+        builder
+            .push_opcode(OP_SIZE)
+            .push_int(PREIMAGE_SIZE as i64)
+            .push_opcode(OP_EQUALVERIFY)
+            .push_opcode(OP_SHA256)
+            .push_slice(self.payment_hash.to_byte_array())
+            .push_opcode(OP_EQUALVERIFY)
+    }
+    /// `<timelock> OP_CHECKLOCKTIMEVERIFY|OP_CHECKSEQUENCEVERIFY OP_DROP`
+    fn push_timelock(&self, builder: ScriptBuilder) -> ScriptBuilder {
+        let builder = match self.timelock {
+            HtlcTimelock::Absolute(lock_time) => {
+                builder.push_lock_time(lock_time).push_opcode(OP_CLTV)
+            },
+            HtlcTimelock::Relative(sequence) => builder.push_sequence(sequence).push_opcode(OP_CSV),
+        };
+
+        builder.push_opcode(OP_DROP)
+    }
+    /// The witness script of the P2WSH output:
+    ///
+    /// ```txt
+    /// OP_IF
+    ///     OP_SIZE 32 OP_EQUALVERIFY OP_SHA256 <payment hash> OP_EQUALVERIFY
+    ///     <recipient>
+    /// OP_ELSE
+    ///     <timelock> OP_CHECKLOCKTIMEVERIFY|OP_CHECKSEQUENCEVERIFY OP_DROP
+    ///     <refund>
+    /// OP_ENDIF
+    /// OP_CHECKSIG
+    /// ```
+    pub fn witness_script(&self) -> ScriptBuf {
+        let builder = self.push_hashlock(ScriptBuilder::new().push_opcode(OP_IF));
+        let builder = builder
+            .push_key(&self.recipient.public_key())
+            .push_opcode(OP_ELSE);
+        let builder = self.push_timelock(builder);
+
+        builder
+            .push_key(&self.refund.public_key())
+            .push_opcode(OP_ENDIF)
+            .push_opcode(OP_CHECKSIG)
+            .into_script()
+    }
+    pub fn p2wsh_output(&self, satoshis: u64) -> TxOutput {
+        TxOutputP2WSH::new(satoshis, &self.witness_script()).into()
+    }
+    pub fn p2wsh_address(&self, network: Network) -> Address {
+        Address::p2wsh(&self.witness_script(), network)
+    }
+    /// The claim leaf of the P2TR output:
+    /// `OP_SIZE 32 OP_EQUALVERIFY OP_SHA256 <payment hash> OP_EQUALVERIFY
+    /// <recipient> OP_CHECKSIG`.
+    pub fn claim_leaf(&self) -> ScriptBuf {
+        self.push_hashlock(ScriptBuilder::new())
+            .push_x_only_key(&self.recipient.untweaked_pubkey())
+            .push_opcode(OP_CHECKSIG)
+            .into_script()
+    }
+    /// The refund leaf of the P2TR output: `<timelock>
+    /// OP_CHECKLOCKTIMEVERIFY|OP_CHECKSEQUENCEVERIFY OP_DROP <refund>
+    /// OP_CHECKSIG`.
+    pub fn refund_leaf(&self) -> ScriptBuf {
+        self.push_timelock(ScriptBuilder::new())
+            .push_x_only_key(&self.refund.untweaked_pubkey())
+            .push_opcode(OP_CHECKSIG)
+            .into_script()
+    }
+    pub fn spend_info(&self) -> TaprootSpendInfo {
+        TaprootBuilder::new()
+            .add_leaf(1, self.claim_leaf())
+            .expect("Two leaves at depth one always form a valid tree")
+            .add_leaf(1, self.refund_leaf())
+            .expect("Two leaves at depth one always form a valid tree")
+            .finalize(
+                &Secp256k1::new(),
+                Recipient::<PublicKey>::from(self.internal_key).untweaked_pubkey(),
+            )
+            .expect("Two leaves at depth one always form a valid tree")
+    }
+    fn merkle_root(&self) -> TapNodeHash {
+        self.spend_info()
+            .merkle_root()
+            .expect("The script tree is never empty")
+    }
+    fn taproot_recipient(&self) -> Recipient<TaprootScript> {
+        Recipient::<TaprootScript>::from_pubkey_recipient(
+            Recipient::<PublicKey>::from(self.internal_key),
+            self.merkle_root(),
+        )
+    }
+    pub fn p2tr_output(&self, satoshis: u64) -> TxOutput {
+        TXOutputP2TRScriptPath::new(satoshis, &self.taproot_recipient()).into()
+    }
+    pub fn p2tr_address(&self, network: Network) -> Address {
+        Address::p2tr(
+            &Secp256k1::new(),
+            self.taproot_recipient().untweaked_pubkey(),
+            Some(self.merkle_root()),
+            network,
+        )
+    }
+    /// Checks that the preimage matches the payment hash.
+    fn check_preimage(&self, preimage: &[u8]) -> Result<()> {
+        if preimage.len() != PREIMAGE_SIZE || sha256::Hash::hash(preimage) != self.payment_hash {
+            return Err(Error::Todo);
+        }
+
+        Ok(())
+    }
+    /// Creates the input claiming the P2WSH output with the preimage, to be
+    /// signed by the recipient.
+    pub fn claim_p2wsh(
+        &self,
+        txid: Txid,
+        vout: u32,
+        satoshis: u64,
+        preimage: &[u8],
+    ) -> Result<TxInput> {
+        self.check_preimage(preimage)?;
+
+        // Witness: <sig> <preimage> 1 <witness script>
+        let input = TxInputP2WSH::new(
+            txid,
+            vout,
+            self.recipient.clone(),
+            satoshis,
+            self.witness_script(),
+        )
+        .add_witness_item(preimage.to_vec())
+        .add_witness_item(vec![1]);
+
+        Ok(input.into())
+    }
+    /// Sets the sequence of the refund input, and requires the transaction to
+    /// have the [`Htlc::refund_lock_time`] for an absolute timelock.
+    fn set_refund_timelock(&self, input: &mut TxInput) {
+        input.set_sequence(self.refund_sequence());
+
+        if let HtlcTimelock::Absolute(lock_time) = self.timelock {
+            input.set_min_lock_time(lock_time);
+        }
+    }
+    /// Creates the input refunding the P2WSH output, to be signed by the
+    /// refund key. The transaction must have the [`Htlc::refund_lock_time`].
+    pub fn refund_p2wsh(&self, txid: Txid, vout: u32, satoshis: u64) -> TxInput {
+        // Witness: <sig> 0 <witness script>
+        let input = TxInputP2WSH::new(
+            txid,
+            vout,
+            self.refund.clone(),
+            satoshis,
+            self.witness_script(),
+        )
+        .add_witness_item(vec![]);
+
+        let mut input = TxInput::from(input);
+        self.set_refund_timelock(&mut input);
+        input
+    }
+    /// Creates the input claiming the P2TR output with the preimage, to be
+    /// signed by the recipient.
+    pub fn claim_p2tr(
+        &self,
+        txid: Txid,
+        vout: u32,
+        satoshis: u64,
+        preimage: &[u8],
+    ) -> Result<TxInput> {
+        self.check_preimage(preimage)?;
+
+        // Witness: <sig> <preimage> <claim leaf> <control block>
+        let input = TxInputP2TRScriptPath::new(
+            txid,
+            vout,
+            self.taproot_recipient(),
+            satoshis,
+            self.claim_leaf(),
+            self.spend_info(),
+        )
+        .add_witness_item(preimage.to_vec());
+
+        Ok(input.into())
+    }
+    /// Creates the input refunding the P2TR output, to be signed by the
+    /// refund key. The transaction must have the [`Htlc::refund_lock_time`].
+    pub fn refund_p2tr(&self, txid: Txid, vout: u32, satoshis: u64) -> TxInput {
+        // Witness: <sig> <refund leaf> <control block>
+        let input = TxInputP2TRScriptPath::new(
+            txid,
+            vout,
+            self.taproot_recipient(),
+            satoshis,
+            self.refund_leaf(),
+            self.spend_info(),
+        );
+
+        let mut input = TxInput::from(input);
+        self.set_refund_timelock(&mut input);
+        input
+    }
+}
@@ -1,25 +1,48 @@
-use bitcoin::{OutPoint, ScriptBuf, Sequence, TxIn, TxOut, Witness};
+use crate::claim::required_signers;
+use crate::multisig::TapscriptMultisig;
+use crate::script::DebugScript;
+use bitcoin::absolute::LockTime;
+use bitcoin::taproot::LeafVersion;
+use bitcoin::{OutPoint, Script, ScriptBuf, Sequence, TxIn, TxOut, VarInt, Weight, Witness};
+use std::fmt;
 
 mod p2pkh;
 mod p2tr_key_path;
 mod p2tr_script_path;
 mod p2wpkh;
+mod p2wsh;
 
 pub use p2pkh::*;
 pub use p2tr_key_path::*;
 pub use p2tr_script_path::*;
 pub use p2wpkh::*;
+pub use p2wsh::*;
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct InputContext {
     pub previous_output: OutPoint,
     pub value: u64,
     // The condition for claiming the output.
     pub script_pubkey: ScriptBuf,
     pub sequence: Sequence,
+    // The lock time the spending transaction must have at least, such as the
+    // timeout of an HTLC refund.
+    pub min_lock_time: Option<LockTime>,
     // Witness data for Segwit/Taproot transactions.
 }
 
+impl fmt::Debug for InputContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InputContext")
+            .field("previous_output", &self.previous_output)
+            .field("value", &self.value)
+            .field("script_pubkey", &DebugScript(&self.script_pubkey))
+            .field("sequence", &self.sequence)
+            .field("min_lock_time", &self.min_lock_time)
+            .finish()
+    }
+}
+
 impl InputContext {
     pub fn new(utxo: TxOut, point: OutPoint) -> Self {
         InputContext {
@@ -28,6 +51,7 @@ impl InputContext {
             script_pubkey: utxo.script_pubkey,
             // Default value of `0xFFFFFFFF = 4294967295`.
             sequence: Sequence::default(),
+            min_lock_time: None,
         }
     }
 }
@@ -36,6 +60,7 @@ impl InputContext {
 pub enum TxInput {
     P2PKH(TxInputP2PKH),
     P2WPKH(TxInputP2WPKH),
+    P2WSH(TxInputP2WSH),
     P2TRKeyPath(TxInputP2TRKeyPath),
     P2TRScriptPath(TxInputP2TRScriptPath),
 }
@@ -52,6 +77,12 @@ impl From<TxInputP2WPKH> for TxInput {
     }
 }
 
+impl From<TxInputP2WSH> for TxInput {
+    fn from(input: TxInputP2WSH) -> Self {
+        TxInput::P2WSH(input)
+    }
+}
+
 impl From<TxInputP2TRKeyPath> for TxInput {
     fn from(input: TxInputP2TRKeyPath) -> Self {
         TxInput::P2TRKeyPath(input)
@@ -82,11 +113,110 @@ impl TxInput {
         match self {
             TxInput::P2PKH(t) => t.ctx(),
             TxInput::P2WPKH(t) => t.ctx(),
+            TxInput::P2WSH(t) => t.ctx(),
             TxInput::P2TRKeyPath(t) => t.ctx(),
             TxInput::P2TRScriptPath(t) => t.ctx(),
         }
     }
+    pub(crate) fn ctx_mut(&mut self) -> &mut InputContext {
+        match self {
+            TxInput::P2PKH(t) => t.ctx_mut(),
+            TxInput::P2WPKH(t) => t.ctx_mut(),
+            TxInput::P2WSH(t) => t.ctx_mut(),
+            TxInput::P2TRKeyPath(t) => t.ctx_mut(),
+            TxInput::P2TRScriptPath(t) => t.ctx_mut(),
+        }
+    }
     pub fn satoshis(&self) -> u64 {
         self.ctx().value
     }
+    pub fn set_sequence(&mut self, sequence: Sequence) {
+        self.ctx_mut().sequence = sequence;
+    }
+    /// Requires the spending transaction to have at least the given lock
+    /// time, otherwise [`crate::TransactionBuilder::validate`] fails.
+    pub fn set_min_lock_time(&mut self, lock_time: LockTime) {
+        self.ctx_mut().min_lock_time = Some(lock_time);
+    }
+    /// Estimates the weight this input adds to the transaction once it has
+    /// been signed. The estimate assumes compressed public keys and the
+    /// default sighash types, with ECDSA signatures at their maximum size.
+    pub fn estimated_weight(&self) -> Weight {
+        // Outpoint, sequence and the length prefix of the scriptSig.
+        const BASE_SIZE: u64 = 32 + 4 + 4 + 1;
+
+        match self {
+            // scriptSig: <sig> <pubkey>
+            TxInput::P2PKH(p) => {
+                let pubkey_size = if p.is_uncompressed() { 65 } else { 33 };
+                Weight::from_non_witness_data_size(BASE_SIZE + 1 + 72 + 1 + pubkey_size)
+            },
+            // Witness: <sig> <pubkey>
+            TxInput::P2WPKH(_) => {
+                Weight::from_non_witness_data_size(BASE_SIZE)
+                    + Weight::from_witness_data_size(1 + 1 + 72 + 1 + 33)
+            },
+            // Witness: <sig> <items...> <script>
+            TxInput::P2WSH(p) => {
+                let script_len = p.witness_script().len() as u64;
+                let witness_size = 1
+                    + (1 + 72)
+                    + witness_items_size(p.witness_items())
+                    + VarInt(script_len).len() as u64
+                    + script_len;
+
+                Weight::from_non_witness_data_size(BASE_SIZE)
+                    + Weight::from_witness_data_size(witness_size)
+            },
+            // Witness: <sig>
+            TxInput::P2TRKeyPath(_) => {
+                Weight::from_non_witness_data_size(BASE_SIZE)
+                    + Weight::from_witness_data_size(1 + 1 + 64)
+            },
+            // Witness: <sig> <items...> <script> <control block>
+            TxInput::P2TRScriptPath(p) => {
+                let script_len = p.witness().len() as u64;
+                let control_block_len = p
+                    .spend_info()
+                    .control_block(&(p.witness().clone(), LeafVersion::TapScript))
+                    .map(|control_block| control_block.serialize().len() as u64)
+                    .unwrap_or(33);
+
+                let witness_size = 1
+                    + script_path_signatures_size(p.witness())
+                    + witness_items_size(p.witness_items())
+                    + VarInt(script_len).len() as u64
+                    + script_len
+                    + VarInt(control_block_len).len() as u64
+                    + control_block_len;
+
+                Weight::from_non_witness_data_size(BASE_SIZE)
+                    + Weight::from_witness_data_size(witness_size)
+            },
+        }
+    }
+    /// Whether the input is claimed via the Witness rather than the scriptSig.
+    pub fn is_segwit(&self) -> bool {
+        !matches!(self, TxInput::P2PKH(_))
+    }
+}
+
+/// The serialized size of the Schnorr signatures required by the script,
+/// where absent signers of a multisig leave an empty push.
+fn script_path_signatures_size(script: &Script) -> u64 {
+    if let Some(multisig) = TapscriptMultisig::from_leaf(script) {
+        let absent = multisig.keys().len() - multisig.required();
+        return multisig.required() as u64 * (1 + 64) + absent as u64;
+    }
+
+    // Otherwise one signature per checked key, or by the internal key.
+    required_signers(script).len().max(1) as u64 * (1 + 64)
+}
+
+/// The serialized size of the Witness items, including their length prefixes.
+fn witness_items_size(items: &[Vec<u8>]) -> u64 {
+    items
+        .iter()
+        .map(|item| VarInt(item.len() as u64).len() as u64 + item.len() as u64)
+        .sum()
 }
@@ -5,6 +5,7 @@ use bitcoin::{OutPoint, PubkeyHash, ScriptBuf, Sequence, Txid};
 pub struct TxInputP2PKH {
     ctx: InputContext,
     recipient: Recipient<PubkeyHash>,
+    uncompressed: bool,
 }
 
 impl TxInputP2PKH {
@@ -25,10 +26,19 @@ impl TxInputP2PKH {
                 value: satoshis,
                 script_pubkey: script,
                 sequence: Sequence::default(),
+                min_lock_time: None,
             },
             recipient,
+            uncompressed: false,
         }
     }
+    /// Whether the output pays to the hash of an uncompressed public key, as
+    /// used by old wallets. The key is then revealed in its uncompressed form
+    /// when spending.
+    pub fn uncompressed(mut self, uncompressed: bool) -> Self {
+        self.uncompressed = uncompressed;
+        self
+    }
     pub fn builder() -> TxInputP2PKHBuilder {
         TxInputP2PKHBuilder::new()
     }
@@ -36,10 +46,16 @@ impl TxInputP2PKH {
     pub fn ctx(&self) -> &InputContext {
         &self.ctx
     }
+    pub(crate) fn ctx_mut(&mut self) -> &mut InputContext {
+        &mut self.ctx
+    }
     /// Read-only exposure to the recipient.
     pub fn recipient(&self) -> &Recipient<PubkeyHash> {
         &self.recipient
     }
+    pub fn is_uncompressed(&self) -> bool {
+        self.uncompressed
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -48,6 +64,7 @@ pub struct TxInputP2PKHBuilder {
     vout: Option<u32>,
     recipient: Option<Recipient<PubkeyHash>>,
     satoshis: Option<u64>,
+    uncompressed: bool,
 }
 
 impl TxInputP2PKHBuilder {
@@ -57,6 +74,7 @@ impl TxInputP2PKHBuilder {
             vout: None,
             recipient: None,
             satoshis: None,
+            uncompressed: false,
         }
     }
     pub fn txid(mut self, txid: Txid) -> TxInputP2PKHBuilder {
@@ -75,12 +93,17 @@ impl TxInputP2PKHBuilder {
         self.satoshis = Some(satoshis);
         self
     }
+    pub fn uncompressed(mut self, uncompressed: bool) -> TxInputP2PKHBuilder {
+        self.uncompressed = uncompressed;
+        self
+    }
     pub fn build(self) -> Result<TxInputP2PKH> {
         Ok(TxInputP2PKH::new(
             self.txid.ok_or(Error::Todo)?,
             self.vout.ok_or(Error::Todo)?,
             self.recipient.ok_or(Error::Todo)?,
             self.satoshis.ok_or(Error::Todo)?,
-        ))
+        )
+        .uncompressed(self.uncompressed))
     }
 }
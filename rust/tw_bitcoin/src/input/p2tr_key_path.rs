@@ -31,6 +31,7 @@ impl TxInputP2TRKeyPath {
                 value: satoshis,
                 script_pubkey: script,
                 sequence: Sequence::default(),
+                min_lock_time: None,
             },
             recipient,
         }
@@ -42,6 +43,9 @@ impl TxInputP2TRKeyPath {
     pub fn ctx(&self) -> &InputContext {
         &self.ctx
     }
+    pub(crate) fn ctx_mut(&mut self) -> &mut InputContext {
+        &mut self.ctx
+    }
     /// Read-only exposure to the recipient.
     pub fn recipient(&self) -> &Recipient<TweakedPublicKey> {
         &self.recipient
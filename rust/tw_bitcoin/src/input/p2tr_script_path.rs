@@ -1,14 +1,32 @@
+use crate::script::DebugScript;
 use crate::{Error, InputContext, Recipient, Result, TaprootScript};
 use bitcoin::script::ScriptBuf;
-use bitcoin::taproot::TaprootSpendInfo;
+use bitcoin::secp256k1::XOnlyPublicKey;
+use bitcoin::taproot::{Signature, TaprootSpendInfo};
 use bitcoin::{OutPoint, Sequence, Txid};
+use std::fmt;
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct TxInputP2TRScriptPath {
     ctx: InputContext,
     recipient: Recipient<TaprootScript>,
     witness: ScriptBuf,
     spend_info: TaprootSpendInfo,
+    witness_items: Vec<Vec<u8>>,
+    signatures: Vec<(XOnlyPublicKey, Signature)>,
+}
+
+impl fmt::Debug for TxInputP2TRScriptPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TxInputP2TRScriptPath")
+            .field("ctx", &self.ctx)
+            .field("recipient", &self.recipient)
+            .field("witness", &DebugScript(&self.witness))
+            .field("spend_info", &self.spend_info)
+            .field("witness_items", &self.witness_items)
+            .field("signatures", &self.signatures)
+            .finish()
+    }
 }
 
 impl TxInputP2TRScriptPath {
@@ -43,18 +61,30 @@ impl TxInputP2TRScriptPath {
                 value: satoshis,
                 script_pubkey: script,
                 sequence: Sequence::default(),
+                min_lock_time: None,
             },
             recipient,
             witness,
             spend_info,
+            witness_items: vec![],
+            signatures: vec![],
         }
     }
+    /// Adds an item to the Witness, pushed after the signature (and any
+    /// previously added items), before the script and the control block.
+    pub fn add_witness_item(mut self, item: Vec<u8>) -> Self {
+        self.witness_items.push(item);
+        self
+    }
     pub fn builder() -> TxInputP2TRScriptPathBuilder {
         TxInputP2TRScriptPathBuilder::new()
     }
     pub fn ctx(&self) -> &InputContext {
         &self.ctx
     }
+    pub(crate) fn ctx_mut(&mut self) -> &mut InputContext {
+        &mut self.ctx
+    }
     pub fn recipient(&self) -> &Recipient<TaprootScript> {
         &self.recipient
     }
@@ -64,6 +94,20 @@ impl TxInputP2TRScriptPath {
     pub fn spend_info(&self) -> &TaprootSpendInfo {
         &self.spend_info
     }
+    pub fn witness_items(&self) -> &[Vec<u8>] {
+        &self.witness_items
+    }
+    /// Adds the signature of another signer of the script, such as a
+    /// co-signer of a [`crate::multisig::TapscriptMultisig`]. It's included in
+    /// the Witness once the input is claimed.
+    pub fn add_signature(mut self, pubkey: XOnlyPublicKey, sig: Signature) -> Self {
+        self.signatures.retain(|(key, _)| key != &pubkey);
+        self.signatures.push((pubkey, sig));
+        self
+    }
+    pub fn signatures(&self) -> &[(XOnlyPublicKey, Signature)] {
+        &self.signatures
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -25,6 +25,7 @@ impl TxInputP2WPKH {
                 value: satoshis,
                 script_pubkey: script,
                 sequence: Sequence::default(),
+                min_lock_time: None,
             },
             recipient,
         }
@@ -36,6 +37,9 @@ impl TxInputP2WPKH {
     pub fn ctx(&self) -> &InputContext {
         &self.ctx
     }
+    pub(crate) fn ctx_mut(&mut self) -> &mut InputContext {
+        &mut self.ctx
+    }
     /// Read-only exposure to the recipient.
     pub fn recipient(&self) -> &Recipient<WPubkeyHash> {
         &self.recipient
@@ -0,0 +1,71 @@
+use crate::script::DebugScript;
+use crate::{InputContext, Recipient};
+use bitcoin::{OutPoint, PublicKey, ScriptBuf, Sequence, Txid};
+use std::fmt;
+
+/// Spends a P2WSH output with a single signature of `recipient`. The Witness
+/// is constructed as `<sig> <witness items...> <witness script>`.
+#[derive(Clone)]
+pub struct TxInputP2WSH {
+    ctx: InputContext,
+    recipient: Recipient<PublicKey>,
+    witness_script: ScriptBuf,
+    witness_items: Vec<Vec<u8>>,
+}
+
+impl fmt::Debug for TxInputP2WSH {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TxInputP2WSH")
+            .field("ctx", &self.ctx)
+            .field("recipient", &self.recipient)
+            .field("witness_script", &DebugScript(&self.witness_script))
+            .field("witness_items", &self.witness_items)
+            .finish()
+    }
+}
+
+impl TxInputP2WSH {
+    pub fn new(
+        txid: Txid,
+        vout: u32,
+        recipient: Recipient<PublicKey>,
+        satoshis: u64,
+        witness_script: ScriptBuf,
+    ) -> Self {
+        let script = ScriptBuf::new_v0_p2wsh(&witness_script.wscript_hash());
+
+        TxInputP2WSH {
+            ctx: InputContext {
+                previous_output: OutPoint { txid, vout },
+                value: satoshis,
+                script_pubkey: script,
+                sequence: Sequence::default(),
+                min_lock_time: None,
+            },
+            recipient,
+            witness_script,
+            witness_items: vec![],
+        }
+    }
+    /// Adds an item to the Witness, pushed after the signature (and any
+    /// previously added items).
+    pub fn add_witness_item(mut self, item: Vec<u8>) -> Self {
+        self.witness_items.push(item);
+        self
+    }
+    pub fn ctx(&self) -> &InputContext {
+        &self.ctx
+    }
+    pub(crate) fn ctx_mut(&mut self) -> &mut InputContext {
+        &mut self.ctx
+    }
+    pub fn recipient(&self) -> &Recipient<PublicKey> {
+        &self.recipient
+    }
+    pub fn witness_script(&self) -> &ScriptBuf {
+        &self.witness_script
+    }
+    pub fn witness_items(&self) -> &[Vec<u8>] {
+        &self.witness_items
+    }
+}
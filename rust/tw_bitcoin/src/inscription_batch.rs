@@ -0,0 +1,257 @@
+//! Inscribing multiple inscriptions at once, using a single commit and a
+//! single reveal transaction.
+
+use crate::coin_selection::{CoinSelectionStrategy, CoinSelector, Utxo};
+use crate::ordinals::{InscriptionContent, OrdinalsInscription};
+use crate::sat_point::{sat_point_at, SatPoint};
+use crate::{
+    estimate_weight, Error, Recipient, Result, TXOutputP2TRScriptPath, TransactionBuilder, TxInput,
+    TxInputP2TRScriptPath, TxOutput,
+};
+use bitcoin::hashes::Hash;
+use bitcoin::{OutPoint, PublicKey, ScriptBuf, Txid};
+
+/// The amount of satoshis each inscription is sent with by default, the same
+/// as used by `ord`.
+pub const DEFAULT_POSTAGE: u64 = 10_000;
+
+/// How the inscriptions of a batch are revealed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BatchMode {
+    /// All inscriptions are placed in a single envelope, revealed by a single
+    /// input. The pointer of each inscription routes it to its own output.
+    #[default]
+    SharedEnvelope,
+    /// Each inscription is placed in its own envelope, revealed by its own
+    /// input. The inscription of the n-th input lands on the n-th output.
+    SeparateInputs,
+}
+
+/// Plans the commit and reveal transaction for a batch of inscriptions.
+///
+/// The reveal transaction sends each inscription with the configured postage
+/// to its destination, while its fee is already paid for by the commit
+/// transaction.
+#[derive(Debug, Clone)]
+pub struct InscriptionBatch {
+    recipient: Recipient<PublicKey>,
+    inscriptions: Vec<(InscriptionContent, ScriptBuf)>,
+    mode: BatchMode,
+    postage: u64,
+    sat_vb: u64,
+    coin_selection: CoinSelectionStrategy,
+}
+
+impl InscriptionBatch {
+    /// The `recipient` is the key that commits to (and later reveals) the
+    /// envelopes.
+    pub fn new(recipient: Recipient<PublicKey>) -> Self {
+        InscriptionBatch {
+            recipient,
+            inscriptions: vec![],
+            mode: BatchMode::default(),
+            postage: DEFAULT_POSTAGE,
+            sat_vb: 0,
+            coin_selection: CoinSelectionStrategy::default(),
+        }
+    }
+    pub fn mode(mut self, mode: BatchMode) -> Self {
+        self.mode = mode;
+        self
+    }
+    pub fn postage(mut self, satoshis: u64) -> Self {
+        self.postage = satoshis;
+        self
+    }
+    pub fn sat_vb(mut self, sat_vb: u64) -> Self {
+        self.sat_vb = sat_vb;
+        self
+    }
+    pub fn coin_selection(mut self, strategy: CoinSelectionStrategy) -> Self {
+        self.coin_selection = strategy;
+        self
+    }
+    /// Adds an inscription, which is sent to `destination` by the reveal
+    /// transaction.
+    pub fn add_inscription(mut self, mime: &[u8], data: &[u8], destination: ScriptBuf) -> Self {
+        self.inscriptions
+            .push((InscriptionContent::new(mime, data), destination));
+        self
+    }
+    /// Plans the commit transaction, funded by the given UTXOs, and the
+    /// corresponding reveal transaction. Any change of the commit transaction
+    /// is sent to `change_script`.
+    pub fn plan(self, utxos: &[Utxo], change_script: ScriptBuf) -> Result<BatchPlan> {
+        if self.inscriptions.is_empty() {
+            return Err(Error::Todo);
+        }
+
+        // Each inscription is sent with the postage to its destination.
+        let mut reveal_outputs = vec![];
+        for (_, destination) in &self.inscriptions {
+            reveal_outputs.push(TxOutput::from_script(destination.clone(), self.postage)?);
+        }
+
+        // The envelopes can only be revealed with a signature of our key, so
+        // that nobody else can spend the commit outputs once the reveal
+        // transaction is broadcast.
+        let pubkey = self.recipient.public_key();
+        let envelope = || {
+            OrdinalsInscription::builder()
+                .reveal_key(pubkey)
+                .internal_key(pubkey)
+        };
+
+        let envelopes = match self.mode {
+            BatchMode::SharedEnvelope => {
+                let batch = self.inscriptions.into_iter().enumerate().fold(
+                    envelope(),
+                    |batch, (index, (content, _))| {
+                        batch.content(content.pointer(index as u64 * self.postage))
+                    },
+                );
+
+                vec![batch.build()?]
+            },
+            BatchMode::SeparateInputs => self
+                .inscriptions
+                .into_iter()
+                .map(|(content, _)| envelope().content(content).build())
+                .collect::<Result<_>>()?,
+        };
+
+        // Each envelope holds the postage of its inscriptions.
+        let per_envelope = reveal_outputs.len() as u64 / envelopes.len() as u64;
+        let mut commit_values = vec![per_envelope * self.postage; envelopes.len()];
+
+        // The reveal fee is paid by the last envelope, so that the satoshis of
+        // the previous envelopes flow exactly into their outputs.
+        let reveal_inputs: Vec<TxInput> = envelopes
+            .iter()
+            .zip(&commit_values)
+            .map(|(envelope, value)| reveal_input(envelope, Txid::all_zeros(), 0, *value))
+            .collect();
+        let reveal_vsize = estimate_weight(&reveal_inputs, &reveal_outputs).to_vbytes_ceil();
+        let reveal_fee = reveal_vsize * self.sat_vb;
+
+        *commit_values.last_mut().expect("at least one envelope") += reveal_fee;
+
+        // Fund the commit outputs.
+        let commit_outputs: Vec<TxOutput> = envelopes
+            .iter()
+            .zip(&commit_values)
+            .map(|(envelope, value)| {
+                TXOutputP2TRScriptPath::new(*value, envelope.recipient())
+                    .inscription_commit(true)
+                    .into()
+            })
+            .collect();
+
+        let selection = CoinSelector::new(self.coin_selection)
+            .sat_vb(self.sat_vb)
+            .select(utxos, &commit_outputs, &change_script)?;
+
+        let mut commit = TransactionBuilder::new().miner_fee(selection.fee);
+        for input in selection.inputs {
+            commit = commit.add_input(input);
+        }
+
+        for output in commit_outputs {
+            commit = commit.add_output(output);
+        }
+
+        if selection.change != 0 {
+            commit = commit.add_output(TxOutput::from_script(change_script, selection.change)?);
+        }
+
+        Ok(BatchPlan {
+            commit,
+            envelopes,
+            commit_values,
+            reveal_outputs,
+            commit_fee: selection.fee,
+            reveal_fee,
+        })
+    }
+}
+
+/// The planned commit and reveal transaction of an [`InscriptionBatch`].
+#[derive(Debug, Clone)]
+pub struct BatchPlan {
+    commit: TransactionBuilder,
+    envelopes: Vec<OrdinalsInscription>,
+    commit_values: Vec<u64>,
+    reveal_outputs: Vec<TxOutput>,
+    commit_fee: u64,
+    reveal_fee: u64,
+}
+
+impl BatchPlan {
+    /// The commit transaction, where the n-th output commits to the n-th
+    /// envelope. Any change output comes last.
+    pub fn commit(&self) -> &TransactionBuilder {
+        &self.commit
+    }
+    pub fn envelopes(&self) -> &[OrdinalsInscription] {
+        &self.envelopes
+    }
+    pub fn commit_fee(&self) -> u64 {
+        self.commit_fee
+    }
+    pub fn reveal_fee(&self) -> u64 {
+        self.reveal_fee
+    }
+    /// Builds the reveal transaction, spending the envelope outputs of the
+    /// signed commit transaction `commit_txid`. Each inscription is protected
+    /// at the satoshi its pointer refers to, or the first satoshi of its input
+    /// without pointer, see [`TransactionBuilder::protect_inscription`].
+    pub fn reveal(&self, commit_txid: Txid) -> TransactionBuilder {
+        let inputs: Vec<TxInput> = self
+            .envelopes
+            .iter()
+            .zip(&self.commit_values)
+            .enumerate()
+            .map(|(vout, (envelope, value))| {
+                reveal_input(envelope, commit_txid, vout as u32, *value)
+            })
+            .collect();
+
+        let mut reveal = TransactionBuilder::new().miner_fee(self.reveal_fee);
+
+        for (vout, envelope) in self.envelopes.iter().enumerate() {
+            let outpoint = OutPoint::new(commit_txid, vout as u32);
+
+            for content in InscriptionContent::parse_envelopes(envelope.taproot_program()) {
+                let sat_point = match content.pointer_offset() {
+                    Some(offset) => sat_point_at(&inputs, offset)
+                        .expect("pointers refer to the satoshis of the envelopes"),
+                    None => SatPoint::new(outpoint, 0),
+                };
+
+                reveal = reveal.protect_inscription(sat_point);
+            }
+        }
+
+        for input in inputs {
+            reveal = reveal.add_input(input);
+        }
+
+        for output in &self.reveal_outputs {
+            reveal = reveal.add_output(output.clone());
+        }
+
+        reveal
+    }
+}
+
+fn reveal_input(envelope: &OrdinalsInscription, txid: Txid, vout: u32, satoshis: u64) -> TxInput {
+    TxInputP2TRScriptPath::new(
+        txid,
+        vout,
+        envelope.recipient().clone(),
+        satoshis,
+        envelope.taproot_program().to_owned(),
+        envelope.spend_info().clone(),
+    )
+    .into()
+}
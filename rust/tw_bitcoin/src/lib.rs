@@ -1,17 +1,36 @@
 extern crate serde;
 
+pub mod bitcoin_cash;
 pub mod brc20;
 pub mod claim;
+pub mod coin_selection;
+pub mod decred;
+pub mod fee_bump;
 pub mod ffi;
+pub mod groestlcoin;
+pub mod hasher;
+pub mod htlc;
 pub mod input;
+pub mod inscription_batch;
+pub mod multisig;
+pub mod musig2;
 pub mod nft;
+pub mod ordering;
 pub mod ordinals;
 pub mod output;
+pub mod pos;
 pub mod recipient;
+pub mod remote_signer;
+pub mod runes;
+pub mod sat_point;
+pub mod script;
+pub mod silent_payments;
+pub mod summary;
 #[cfg(test)]
 mod tests;
 pub mod transaction;
 pub mod utils;
+pub mod zcash;
 
 // Reexports
 pub use input::*;
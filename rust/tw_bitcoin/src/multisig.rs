@@ -0,0 +1,184 @@
+//! Taproot k-of-n multisig, using a single tapscript leaf based on
+//! `OP_CHECKSIGADD` (BIP-342), as `multi_a` of output script descriptors.
+//!
+//! Each signer signs the script-path input on their own. The signatures of
+//! previous signers are passed on via [`TxInputP2TRScriptPath::add_signature`],
+//! so that the last signer produces the complete Witness.
+
+use crate::ordinals::unspendable_internal_key;
+use crate::{
+    Error, Recipient, Result, TXOutputP2TRScriptPath, TaprootScript, TxInputP2TRScriptPath,
+    TxOutput,
+};
+use bitcoin::opcodes::all::{OP_CHECKSIG, OP_CHECKSIGADD, OP_NUMEQUAL};
+use bitcoin::script::{read_scriptint, Builder as ScriptBuilder, Instruction};
+use bitcoin::secp256k1::{Secp256k1, XOnlyPublicKey};
+use bitcoin::taproot::{LeafVersion, Signature, TapNodeHash, TaprootBuilder, TaprootSpendInfo};
+use bitcoin::{Address, Network, PublicKey, Script, ScriptBuf, Txid, Witness};
+
+/// The maximum number of keys, limited by the number of stack elements
+/// allowed when executing the script.
+pub const MAX_KEYS: usize = 999;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TapscriptMultisig {
+    required: usize,
+    keys: Vec<XOnlyPublicKey>,
+    internal_key: PublicKey,
+}
+
+impl TapscriptMultisig {
+    /// Creates the multisig requiring `required` signatures of the given keys.
+    /// The order of the keys is kept, which determines the leaf script.
+    pub fn new(required: usize, keys: Vec<XOnlyPublicKey>) -> Result<Self> {
+        if required == 0 || required > keys.len() || keys.len() > MAX_KEYS {
+            return Err(Error::Todo);
+        }
+
+        // Each key must be unique, otherwise the signatures can't be assigned.
+        let duplicate = keys
+            .iter()
+            .enumerate()
+            .any(|(index, key)| keys[..index].contains(key));
+
+        if duplicate {
+            return Err(Error::Todo);
+        }
+
+        Ok(TapscriptMultisig {
+            required,
+            keys,
+            internal_key: unspendable_internal_key(),
+        })
+    }
+    /// Parses a leaf script as created by [`TapscriptMultisig::leaf_script`].
+    pub fn from_leaf(script: &Script) -> Option<Self> {
+        let instructions: Vec<Instruction> = script
+            .instructions()
+            .collect::<std::result::Result<_, _>>()
+            .ok()?;
+        let (pairs, tail) = instructions.split_at(instructions.len().checked_sub(2)?);
+
+        let required = match tail {
+            [Instruction::Op(op), Instruction::Op(OP_NUMEQUAL)] => {
+                let number = op.to_u8().checked_sub(0x50)?;
+                (1..=16).contains(&number).then_some(number as usize)?
+            },
+            [Instruction::PushBytes(push), Instruction::Op(OP_NUMEQUAL)] => {
+                usize::try_from(read_scriptint(push.as_bytes()).ok()?).ok()?
+            },
+            _ => return None,
+        };
+
+        let keys = pairs
+            .chunks(2)
+            .map(|pair| match pair {
+                [Instruction::PushBytes(push), Instruction::Op(_)] => {
+                    XOnlyPublicKey::from_slice(push.as_bytes()).ok()
+                },
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        // Rebuilding the script rules out any other opcodes.
+        let multisig = TapscriptMultisig::new(required, keys).ok()?;
+        (multisig.leaf_script().as_script() == script).then_some(multisig)
+    }
+    /// Sets the internal key of the P2TR output, which defaults to the
+    /// unspendable [`crate::ordinals::NUMS_POINT`].
+    pub fn internal_key(mut self, internal_key: PublicKey) -> Self {
+        self.internal_key = internal_key;
+        self
+    }
+    pub fn required(&self) -> usize {
+        self.required
+    }
+    pub fn keys(&self) -> &[XOnlyPublicKey] {
+        &self.keys
+    }
+    /// `<key 1> OP_CHECKSIG <key 2> OP_CHECKSIGADD ... <key n> OP_CHECKSIGADD
+    /// <required> OP_NUMEQUAL`
+    pub fn leaf_script(&self) -> ScriptBuf {
+        let mut builder = ScriptBuilder::new();
+        for (index, key) in self.keys.iter().enumerate() {
+            let opcode = if index == 0 {
+                OP_CHECKSIG
+            } else {
+                OP_CHECKSIGADD
+            };
+
+            builder = builder.push_x_only_key(key).push_opcode(opcode);
+        }
+
+        builder
+            .push_int(self.required as i64)
+            .push_opcode(OP_NUMEQUAL)
+            .into_script()
+    }
+    pub fn spend_info(&self) -> TaprootSpendInfo {
+        TaprootBuilder::new()
+            .add_leaf(0, self.leaf_script())
+            .expect("A single leaf always forms a valid tree")
+            .finalize(
+                &Secp256k1::new(),
+                Recipient::<PublicKey>::from(self.internal_key).untweaked_pubkey(),
+            )
+            .expect("A single leaf always forms a valid tree")
+    }
+    fn taproot_recipient(&self) -> Recipient<TaprootScript> {
+        let merkle_root = TapNodeHash::from_script(&self.leaf_script(), LeafVersion::TapScript);
+        Recipient::<TaprootScript>::from_pubkey_recipient(
+            Recipient::<PublicKey>::from(self.internal_key),
+            merkle_root,
+        )
+    }
+    pub fn p2tr_output(&self, satoshis: u64) -> TxOutput {
+        TXOutputP2TRScriptPath::new(satoshis, &self.taproot_recipient()).into()
+    }
+    pub fn p2tr_address(&self, network: Network) -> Address {
+        let recipient = self.taproot_recipient();
+        Address::p2tr(
+            &Secp256k1::new(),
+            recipient.untweaked_pubkey(),
+            Some(recipient.merkle_root()),
+            network,
+        )
+    }
+    /// Creates the input spending the multisig output via the script path.
+    /// Any signatures collected so far must be added to it before signing.
+    pub fn input(&self, txid: Txid, vout: u32, satoshis: u64) -> TxInputP2TRScriptPath {
+        TxInputP2TRScriptPath::new(
+            txid,
+            vout,
+            self.taproot_recipient(),
+            satoshis,
+            self.leaf_script(),
+            self.spend_info(),
+        )
+    }
+    /// Extracts the signatures from the Witness of a (partially) signed
+    /// input, to be passed on to the next signer.
+    pub fn signatures(&self, witness: &Witness) -> Result<Vec<(XOnlyPublicKey, Signature)>> {
+        // <sig n> ... <sig 1> <script> <control block>
+        if witness.len() != self.keys.len() + 2 {
+            return Err(Error::Todo);
+        }
+
+        let mut signatures = vec![];
+        for (key, sig) in self.keys.iter().rev().zip(witness.iter()) {
+            if !sig.is_empty() {
+                let sig = Signature::from_slice(sig).map_err(|_| Error::Todo)?;
+                signatures.push((*key, sig));
+            }
+        }
+
+        Ok(signatures)
+    }
+    /// Whether the Witness contains exactly the required number of
+    /// signatures. Any more signatures make the script fail.
+    pub fn is_complete(&self, witness: &Witness) -> bool {
+        self.signatures(witness)
+            .map(|signatures| signatures.len() == self.required)
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,609 @@
+//! MuSig2 multi-signatures as specified in BIP-327, for spending a Taproot
+//! key-path output owned jointly by multiple parties. On-chain, the output
+//! and its Witness look like those of a single key.
+//!
+//! The protocol has two rounds. First, each signer creates a nonce with
+//! [`KeyAggContext::generate_nonce`] and shares the [`PublicNonce`]. Once all
+//! public nonces are known, each signer creates a [`PartialSignature`] in the
+//! [`Session`], which are then aggregated into the final signature.
+
+use crate::utils::tagged_hash;
+use crate::{Error, Recipient, Result};
+use bitcoin::hashes::Hash;
+use bitcoin::key::{TapTweak, TweakedPublicKey};
+use bitcoin::secp256k1::constants::CURVE_ORDER;
+use bitcoin::secp256k1::rand::{thread_rng, RngCore};
+use bitcoin::secp256k1::{
+    schnorr, KeyPair, Message, Parity, PublicKey, Scalar, Secp256k1, SecretKey, XOnlyPublicKey,
+};
+use bitcoin::sighash::TapSighashType;
+use bitcoin::Witness;
+
+/// An integer modulo the curve order. Zero (which isn't a valid secret key)
+/// is represented as `None`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct ModN(Option<SecretKey>);
+
+impl ModN {
+    const ZERO: ModN = ModN(None);
+
+    fn one() -> Self {
+        let mut bytes = [0; 32];
+        bytes[31] = 1;
+        ModN::from_bytes(bytes).expect("one is below the curve order")
+    }
+    /// Parses the integer, failing if it's not below the curve order.
+    fn from_bytes(bytes: [u8; 32]) -> Option<Self> {
+        if bytes == [0; 32] {
+            return Some(ModN::ZERO);
+        }
+
+        SecretKey::from_slice(&bytes)
+            .ok()
+            .map(|key| ModN(Some(key)))
+    }
+    /// Parses the integer, reducing it modulo the curve order.
+    fn from_bytes_reduced(bytes: [u8; 32]) -> Self {
+        if let Some(number) = ModN::from_bytes(bytes) {
+            return number;
+        }
+
+        // Any 256-bit integer is below twice the curve order.
+        let mut reduced = [0; 32];
+        let mut borrow = 0;
+        for index in (0..32).rev() {
+            let diff = bytes[index] as i16 - CURVE_ORDER[index] as i16 - borrow;
+            reduced[index] = diff.rem_euclid(256) as u8;
+            borrow = (diff < 0) as i16;
+        }
+
+        ModN::from_bytes(reduced).expect("reduced integer is below the curve order")
+    }
+    fn to_bytes(self) -> [u8; 32] {
+        self.0.map(|key| key.secret_bytes()).unwrap_or_default()
+    }
+    fn add(self, other: ModN) -> ModN {
+        match (self.0, other.0) {
+            (Some(a), Some(b)) => ModN(a.add_tweak(&Scalar::from(b)).ok()),
+            (Some(_), None) => self,
+            (None, _) => other,
+        }
+    }
+    fn mul(self, other: ModN) -> ModN {
+        match (self.0, other.0) {
+            (Some(a), Some(b)) => ModN(Some(
+                a.mul_tweak(&Scalar::from(b))
+                    .expect("product of non-zero integers is non-zero"),
+            )),
+            _ => ModN::ZERO,
+        }
+    }
+    fn negate(self) -> ModN {
+        ModN(self.0.map(SecretKey::negate))
+    }
+    /// Multiplies the point by this integer, where `None` is the point at
+    /// infinity.
+    fn mul_point(self, point: &PublicKey) -> Option<PublicKey> {
+        let number = self.0?;
+        let secp = Secp256k1::new();
+
+        Some(
+            point
+                .mul_tweak(&secp, &Scalar::from(number))
+                .expect("non-zero multiple of a point is never infinity"),
+        )
+    }
+    /// Multiplies the generator by this integer.
+    fn mul_generator(self) -> Option<PublicKey> {
+        self.0
+            .map(|number| PublicKey::from_secret_key(&Secp256k1::new(), &number))
+    }
+}
+
+/// Adds the points, where `None` is the point at infinity.
+fn add_points(a: Option<PublicKey>, b: Option<PublicKey>) -> Option<PublicKey> {
+    match (a, b) {
+        (Some(a), Some(b)) => a.combine(&b).ok(),
+        (Some(_), None) => a,
+        (None, _) => b,
+    }
+}
+
+fn has_even_y(point: &PublicKey) -> bool {
+    point.x_only_public_key().1 == Parity::Even
+}
+
+fn x_bytes(point: &PublicKey) -> [u8; 32] {
+    point.x_only_public_key().0.serialize()
+}
+
+/// `1` if the point has an even Y coordinate, `-1` otherwise.
+fn parity_factor(point: &PublicKey) -> ModN {
+    if has_even_y(point) {
+        ModN::one()
+    } else {
+        ModN::one().negate()
+    }
+}
+
+/// The aggregation of the signers' public keys into a single Taproot key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyAggContext {
+    keys: Vec<PublicKey>,
+    internal_key: PublicKey,
+    // The aggregated key with the Taproot tweak applied.
+    output_key: PublicKey,
+    // The accumulated sign and tweak of the Taproot tweak.
+    gacc: [u8; 32],
+    tacc: [u8; 32],
+}
+
+impl KeyAggContext {
+    /// Aggregates the public keys of all signers. The order of the keys
+    /// matters and must be the same for every signer.
+    pub fn new(keys: Vec<PublicKey>) -> Result<Self> {
+        if keys.is_empty() {
+            return Err(Error::Todo);
+        }
+
+        let mut internal_key = None;
+        for key in &keys {
+            let coefficient = key_agg_coefficient(&keys, key);
+            internal_key = add_points(internal_key, coefficient.mul_point(key));
+        }
+
+        let internal_key = internal_key.ok_or(Error::Todo)?;
+
+        // Apply the Taproot tweak without script tree (BIP-86), as an x-only
+        // tweak: Q = g * P + t * G.
+        let (xonly, _) = internal_key.x_only_public_key();
+        let tweak = ModN::from_bytes(
+            bitcoin::taproot::TapTweakHash::from_key_and_tweak(xonly, None).to_byte_array(),
+        )
+        .ok_or(Error::Todo)?;
+
+        let g = parity_factor(&internal_key);
+        let output_key =
+            add_points(g.mul_point(&internal_key), tweak.mul_generator()).ok_or(Error::Todo)?;
+
+        Ok(KeyAggContext {
+            keys,
+            internal_key,
+            output_key,
+            gacc: g.to_bytes(),
+            tacc: tweak.to_bytes(),
+        })
+    }
+    /// Aggregates the public keys without applying the Taproot tweak, as in
+    /// the test vectors of BIP-327.
+    #[cfg(test)]
+    pub(crate) fn new_untweaked(keys: Vec<PublicKey>) -> Result<Self> {
+        let tweaked = KeyAggContext::new(keys)?;
+
+        Ok(KeyAggContext {
+            output_key: tweaked.internal_key,
+            gacc: ModN::one().to_bytes(),
+            tacc: ModN::ZERO.to_bytes(),
+            ..tweaked
+        })
+    }
+    pub fn keys(&self) -> &[PublicKey] {
+        &self.keys
+    }
+    /// The aggregated key, which is the Taproot internal key.
+    pub fn internal_key(&self) -> XOnlyPublicKey {
+        self.internal_key.x_only_public_key().0
+    }
+    /// The tweaked Taproot output key, to be used for
+    /// [`crate::TxOutputP2TRKeyPath`] and [`crate::TxInputP2TRKeyPath`].
+    pub fn recipient(&self) -> Recipient<TweakedPublicKey> {
+        let tweaked =
+            TweakedPublicKey::dangerous_assume_tweaked(self.output_key.x_only_public_key().0);
+        debug_assert_eq!(
+            tweaked,
+            self.internal_key().tap_tweak(&Secp256k1::new(), None).0
+        );
+
+        Recipient::from(tweaked)
+    }
+    fn gacc(&self) -> ModN {
+        ModN::from_bytes(self.gacc).expect("stored integers are below the curve order")
+    }
+    fn tacc(&self) -> ModN {
+        ModN::from_bytes(self.tacc).expect("stored integers are below the curve order")
+    }
+    /// Generates the nonce of the signer for signing `message`. The secret
+    /// nonce must be kept private and can only be used once.
+    pub fn generate_nonce(
+        &self,
+        keypair: &KeyPair,
+        message: &Message,
+    ) -> Result<(SecretNonce, PublicNonce)> {
+        let mut rand = [0; 32];
+        thread_rng().fill_bytes(&mut rand);
+
+        self.generate_nonce_with_rand(keypair, message, rand)
+    }
+    /// Generates the nonce with the given randomness, which MUST be fresh for
+    /// every nonce. Reusing it with a different message reveals the secret
+    /// key. Use [`KeyAggContext::generate_nonce`] unless for testing.
+    pub fn generate_nonce_with_rand(
+        &self,
+        keypair: &KeyPair,
+        message: &Message,
+        rand: [u8; 32],
+    ) -> Result<(SecretNonce, PublicNonce)> {
+        let pubkey = keypair.public_key();
+        if !self.keys.contains(&pubkey) {
+            return Err(Error::Todo);
+        }
+
+        nonce_gen(
+            rand,
+            Some(&keypair.secret_key()),
+            &pubkey,
+            Some(&x_bytes(&self.output_key)),
+            Some(message.as_ref()),
+            &[],
+        )
+    }
+}
+
+/// `NonceGen` of BIP-327, where `rand` is the fresh randomness `rand'`.
+pub(crate) fn nonce_gen(
+    rand: [u8; 32],
+    secret_key: Option<&SecretKey>,
+    pubkey: &PublicKey,
+    aggpk: Option<&[u8; 32]>,
+    message: Option<&[u8]>,
+    extra_in: &[u8],
+) -> Result<(SecretNonce, PublicNonce)> {
+    // Mix the randomness into the secret key, protecting against bad
+    // randomness.
+    let mut rand = rand;
+    if let Some(secret_key) = secret_key {
+        let aux = tagged_hash("MuSig/aux", &[&rand]);
+        rand = secret_key.secret_bytes();
+        for (byte, aux) in rand.iter_mut().zip(aux) {
+            *byte ^= aux;
+        }
+    }
+
+    let pubkey_bytes = pubkey.serialize();
+    let aggpk: &[u8] = aggpk.map_or(&[], |aggpk| aggpk);
+
+    let mut message_prefixed = vec![];
+    match message {
+        Some(message) => {
+            message_prefixed.push(1);
+            message_prefixed.extend((message.len() as u64).to_be_bytes());
+            message_prefixed.extend(message);
+        },
+        None => message_prefixed.push(0),
+    }
+
+    let extra_in_len = (extra_in.len() as u32).to_be_bytes();
+
+    let nonce = |index: u8| {
+        ModN::from_bytes_reduced(tagged_hash(
+            "MuSig/nonce",
+            &[
+                &rand,
+                &[pubkey_bytes.len() as u8],
+                &pubkey_bytes,
+                &[aggpk.len() as u8],
+                aggpk,
+                &message_prefixed,
+                &extra_in_len,
+                extra_in,
+                &[index],
+            ],
+        ))
+    };
+
+    let (k1, k2) = (nonce(0), nonce(1));
+    let public = PublicNonce {
+        r1: k1.mul_generator().ok_or(Error::Todo)?,
+        r2: k2.mul_generator().ok_or(Error::Todo)?,
+    };
+
+    let secret = SecretNonce {
+        k1: k1.to_bytes(),
+        k2: k2.to_bytes(),
+        pubkey: *pubkey,
+    };
+
+    Ok((secret, public))
+}
+
+/// `KeyAggCoeff` of BIP-327.
+fn key_agg_coefficient(keys: &[PublicKey], key: &PublicKey) -> ModN {
+    // The second distinct key has coefficient one.
+    let second = keys.iter().find(|other| *other != &keys[0]);
+    if second == Some(key) {
+        return ModN::one();
+    }
+
+    let serialized: Vec<u8> = keys.iter().flat_map(|key| key.serialize()).collect();
+    let list_hash = tagged_hash("KeyAgg list", &[&serialized]);
+
+    ModN::from_bytes_reduced(tagged_hash(
+        "KeyAgg coefficient",
+        &[&list_hash, &key.serialize()],
+    ))
+}
+
+/// The secret nonce of a signer, which must never be reused. It's consumed
+/// when creating the partial signature.
+pub struct SecretNonce {
+    k1: [u8; 32],
+    k2: [u8; 32],
+    pubkey: PublicKey,
+}
+
+impl SecretNonce {
+    pub const SIZE: usize = 97;
+
+    /// Serializes the nonce as `k1 || k2 || pubkey`, for storing it between
+    /// the two rounds. The nonce is consumed, so that it's either used or
+    /// serialized.
+    ///
+    /// Creating two partial signatures with the same secret nonce reveals the
+    /// secret key. The caller must make sure the serialized nonce is restored
+    /// at most once, such as by deleting it before it's used.
+    pub fn dangerous_into_bytes(self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        bytes[..32].copy_from_slice(&self.k1);
+        bytes[32..64].copy_from_slice(&self.k2);
+        bytes[64..].copy_from_slice(&self.pubkey.serialize());
+        bytes
+    }
+    /// Restores the nonce serialized by [`SecretNonce::dangerous_into_bytes`],
+    /// which must not have been used before.
+    pub fn dangerous_from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != Self::SIZE {
+            return Err(Error::Todo);
+        }
+
+        let mut k1 = [0; 32];
+        let mut k2 = [0; 32];
+        k1.copy_from_slice(&bytes[..32]);
+        k2.copy_from_slice(&bytes[32..64]);
+
+        // Both nonces must be valid non-zero integers.
+        for k in [k1, k2] {
+            if ModN::from_bytes(k).unwrap_or(ModN::ZERO) == ModN::ZERO {
+                return Err(Error::Todo);
+            }
+        }
+
+        Ok(SecretNonce {
+            k1,
+            k2,
+            pubkey: PublicKey::from_slice(&bytes[64..]).map_err(|_| Error::Todo)?,
+        })
+    }
+}
+
+/// The public nonce of a signer, which is shared with the other signers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicNonce {
+    r1: PublicKey,
+    r2: PublicKey,
+}
+
+impl PublicNonce {
+    pub const SIZE: usize = 66;
+
+    pub fn serialize(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        bytes[..33].copy_from_slice(&self.r1.serialize());
+        bytes[33..].copy_from_slice(&self.r2.serialize());
+        bytes
+    }
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != Self::SIZE {
+            return Err(Error::Todo);
+        }
+
+        Ok(PublicNonce {
+            r1: PublicKey::from_slice(&bytes[..33]).map_err(|_| Error::Todo)?,
+            r2: PublicKey::from_slice(&bytes[33..]).map_err(|_| Error::Todo)?,
+        })
+    }
+}
+
+/// The partial signature of a signer, which is shared with the aggregator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartialSignature([u8; 32]);
+
+impl PartialSignature {
+    pub fn serialize(&self) -> [u8; 32] {
+        self.0
+    }
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        let bytes: [u8; 32] = bytes.try_into().map_err(|_| Error::Todo)?;
+        ModN::from_bytes(bytes).ok_or(Error::Todo)?;
+
+        Ok(PartialSignature(bytes))
+    }
+    fn scalar(&self) -> ModN {
+        ModN::from_bytes(self.0).expect("partial signatures are below the curve order")
+    }
+}
+
+/// The signing session of a single message, once the public nonces of all
+/// signers are known.
+#[derive(Debug, Clone)]
+pub struct Session {
+    key_agg: KeyAggContext,
+    nonces: Vec<PublicNonce>,
+    message: Message,
+    b: [u8; 32],
+    r: PublicKey,
+    e: [u8; 32],
+}
+
+impl Session {
+    /// Creates the session, where `nonces` are the public nonces of all
+    /// signers (in any order), exactly one per key of `key_agg`.
+    pub fn new(key_agg: &KeyAggContext, nonces: &[PublicNonce], message: &Message) -> Result<Self> {
+        if nonces.len() != key_agg.keys.len() {
+            return Err(Error::Todo);
+        }
+
+        // Aggregate the nonces, serializing the point at infinity as zeros.
+        let r1 = nonces
+            .iter()
+            .fold(None, |acc, nonce| add_points(acc, Some(nonce.r1)));
+        let r2 = nonces
+            .iter()
+            .fold(None, |acc, nonce| add_points(acc, Some(nonce.r2)));
+
+        let mut aggnonce = [0; 66];
+        if let Some(r1) = r1 {
+            aggnonce[..33].copy_from_slice(&r1.serialize());
+        }
+        if let Some(r2) = r2 {
+            aggnonce[33..].copy_from_slice(&r2.serialize());
+        }
+
+        let aggpk = x_bytes(&key_agg.output_key);
+        let b = ModN::from_bytes_reduced(tagged_hash(
+            "MuSig/noncecoef",
+            &[&aggnonce, &aggpk, message.as_ref()],
+        ));
+
+        // R = R1 + b * R2, or the generator if that's infinity.
+        let r = add_points(r1, r2.and_then(|r2| b.mul_point(&r2)))
+            .unwrap_or_else(|| ModN::one().mul_generator().expect("one is non-zero"));
+
+        let e = ModN::from_bytes_reduced(tagged_hash(
+            "BIP0340/challenge",
+            &[&x_bytes(&r), &aggpk, message.as_ref()],
+        ));
+
+        Ok(Session {
+            key_agg: key_agg.clone(),
+            nonces: nonces.to_vec(),
+            message: *message,
+            b: b.to_bytes(),
+            r,
+            e: e.to_bytes(),
+        })
+    }
+    fn b(&self) -> ModN {
+        ModN::from_bytes(self.b).expect("stored integers are below the curve order")
+    }
+    fn e(&self) -> ModN {
+        ModN::from_bytes(self.e).expect("stored integers are below the curve order")
+    }
+    /// Creates the partial signature of the signer, consuming the secret
+    /// nonce. The partial signature is verified before it's returned.
+    pub fn partial_sign(&self, nonce: SecretNonce, keypair: &KeyPair) -> Result<PartialSignature> {
+        let pubkey = keypair.public_key();
+        if nonce.pubkey != pubkey || !self.key_agg.keys.contains(&pubkey) {
+            return Err(Error::Todo);
+        }
+
+        let k1 = ModN::from_bytes(nonce.k1).ok_or(Error::Todo)?;
+        let k2 = ModN::from_bytes(nonce.k2).ok_or(Error::Todo)?;
+        let public_nonce = PublicNonce {
+            r1: k1.mul_generator().ok_or(Error::Todo)?,
+            r2: k2.mul_generator().ok_or(Error::Todo)?,
+        };
+
+        // The nonce must be part of the session.
+        if !self.nonces.contains(&public_nonce) {
+            return Err(Error::Todo);
+        }
+
+        let (k1, k2) = if has_even_y(&self.r) {
+            (k1, k2)
+        } else {
+            (k1.negate(), k2.negate())
+        };
+
+        let a = key_agg_coefficient(&self.key_agg.keys, &pubkey);
+        let g = parity_factor(&self.key_agg.output_key);
+        let secret = ModN(Some(keypair.secret_key()));
+        let d = g.mul(self.key_agg.gacc()).mul(secret);
+
+        // s = k1 + b * k2 + e * a * d
+        let s = k1.add(self.b().mul(k2)).add(self.e().mul(a).mul(d));
+
+        let partial = PartialSignature(s.to_bytes());
+        if !self.verify_partial_signature(&partial, &public_nonce, &pubkey) {
+            return Err(Error::Todo);
+        }
+
+        Ok(partial)
+    }
+    /// Verifies the partial signature of the signer with the given public
+    /// nonce and key.
+    pub fn verify_partial_signature(
+        &self,
+        partial: &PartialSignature,
+        nonce: &PublicNonce,
+        pubkey: &PublicKey,
+    ) -> bool {
+        if !self.key_agg.keys.contains(pubkey) {
+            return false;
+        }
+
+        let secp = Secp256k1::new();
+
+        // Re = R1 + b * R2, negated if R has an odd Y coordinate.
+        let mut r_e = add_points(Some(nonce.r1), self.b().mul_point(&nonce.r2));
+        if !has_even_y(&self.r) {
+            r_e = r_e.map(|point| point.negate(&secp));
+        }
+
+        let a = key_agg_coefficient(&self.key_agg.keys, pubkey);
+        let g = parity_factor(&self.key_agg.output_key).mul(self.key_agg.gacc());
+
+        // s * G == Re + e * a * g * P
+        let expected = add_points(r_e, self.e().mul(a).mul(g).mul_point(pubkey));
+        partial.scalar().mul_generator() == expected
+    }
+    /// Aggregates the partial signatures of all signers into the final
+    /// Schnorr signature, valid for the Taproot output key.
+    pub fn aggregate(&self, partials: &[PartialSignature]) -> Result<schnorr::Signature> {
+        if partials.len() != self.key_agg.keys.len() {
+            return Err(Error::Todo);
+        }
+
+        // s = sum(s_i) + e * g * tacc
+        let g = parity_factor(&self.key_agg.output_key);
+        let s = partials
+            .iter()
+            .fold(ModN::ZERO, |acc, partial| acc.add(partial.scalar()))
+            .add(self.e().mul(g).mul(self.key_agg.tacc()));
+
+        let mut bytes = [0; 64];
+        bytes[..32].copy_from_slice(&x_bytes(&self.r));
+        bytes[32..].copy_from_slice(&s.to_bytes());
+        let sig = schnorr::Signature::from_slice(&bytes).map_err(|_| Error::Todo)?;
+
+        // Make sure the signature is valid for the output key.
+        let output_key = self.key_agg.output_key.x_only_public_key().0;
+        Secp256k1::new()
+            .verify_schnorr(&sig, &self.message, &output_key)
+            .map_err(|_| Error::Todo)?;
+
+        Ok(sig)
+    }
+}
+
+/// Creates the Witness spending the Taproot key-path input with the
+/// aggregated signature.
+pub fn key_path_witness(sig: schnorr::Signature) -> Witness {
+    let sig = bitcoin::taproot::Signature {
+        sig,
+        hash_ty: TapSighashType::Default,
+    };
+
+    let mut witness = Witness::new();
+    witness.push(sig.to_vec());
+    witness
+}
@@ -0,0 +1,94 @@
+//! Ordering of the transaction inputs and outputs. Keeping the insertion order
+//! usually leaks information, such as which output is the change.
+
+use crate::{TxInput, TxOutput};
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+
+/// Determines the final order of the inputs and outputs of a transaction.
+///
+/// Note that all inputs are signed with `SIGHASH_ALL` (or the Taproot
+/// default), so there are no input/output pairings (as with `SIGHASH_SINGLE`)
+/// that could be broken by reordering.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TxOrdering {
+    /// Keeps the order in which the inputs and outputs were added.
+    #[default]
+    Insertion,
+    /// Sorts the inputs and outputs lexicographically, as specified in BIP-69.
+    Bip69,
+    /// Shuffles the inputs and outputs. The same seed always results in the
+    /// same order.
+    Shuffle(u64),
+}
+
+impl TxOrdering {
+    /// Orders the inputs and outputs, while the first `pinned_inputs` and
+    /// `pinned_outputs` keep their position.
+    pub(crate) fn apply(
+        &self,
+        inputs: &mut [TxInput],
+        outputs: &mut [TxOutput],
+        pinned_inputs: usize,
+        pinned_outputs: usize,
+    ) {
+        let pinned_inputs = pinned_inputs.min(inputs.len());
+        let pinned_outputs = pinned_outputs.min(outputs.len());
+        let inputs = &mut inputs[pinned_inputs..];
+        let outputs = &mut outputs[pinned_outputs..];
+
+        match self {
+            TxOrdering::Insertion => {},
+            TxOrdering::Bip69 => {
+                // The txids are compared in reversed byte order, as displayed.
+                inputs.sort_by_key(|input| {
+                    let point = input.ctx().previous_output;
+                    let mut txid = point.txid.to_byte_array();
+                    txid.reverse();
+
+                    (txid, point.vout)
+                });
+
+                outputs.sort_by(|a, b| {
+                    a.satoshis()
+                        .cmp(&b.satoshis())
+                        .then_with(|| a.script_pubkey().cmp(b.script_pubkey()))
+                });
+            },
+            TxOrdering::Shuffle(seed) => {
+                let mut rng = SeededRng::new(*seed);
+                rng.shuffle(inputs);
+                rng.shuffle(outputs);
+            },
+        }
+    }
+}
+
+/// Deterministic random number generator, hashing the seed together with a
+/// counter. Unlike the generators of the `rand` crate, its output is
+/// guaranteed to never change between versions.
+struct SeededRng {
+    seed: u64,
+    counter: u64,
+}
+
+impl SeededRng {
+    fn new(seed: u64) -> Self {
+        SeededRng { seed, counter: 0 }
+    }
+    fn next_u64(&mut self) -> u64 {
+        let mut engine = sha256::Hash::engine();
+        engine.input(&self.seed.to_le_bytes());
+        engine.input(&self.counter.to_le_bytes());
+        self.counter += 1;
+
+        let hash = sha256::Hash::from_engine(engine).to_byte_array();
+        u64::from_le_bytes(hash[..8].try_into().expect("hash is 32 bytes"))
+    }
+    /// Fisher-Yates shuffle.
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = (self.next_u64() % (i as u64 + 1)) as usize;
+            items.swap(i, j);
+        }
+    }
+}
@@ -1,13 +1,134 @@
-use crate::{Error, Recipient, Result, TaprootProgram, TaprootScript};
-use bitcoin::script::{PushBytesBuf, ScriptBuf};
-use bitcoin::secp256k1::XOnlyPublicKey;
+use crate::{
+    Error, Recipient, Result, TaprootProgram, TaprootScript, TxOutput, TxOutputP2TRKeyPath,
+};
+use bitcoin::script::{Builder as ScriptBuilder, Instruction, PushBytesBuf, ScriptBuf};
+use bitcoin::secp256k1::{Parity, XOnlyPublicKey};
 use bitcoin::taproot::{TaprootBuilder, TaprootSpendInfo};
 use bitcoin::{PublicKey, Script};
 
+/// The x-coordinate of the "nothing up my sleeve" point as suggested by
+/// BIP-341, for which no private key is known. Used as Taproot internal key,
+/// it disables the key-path spend.
+pub const NUMS_POINT: [u8; 32] = [
+    0x50, 0x92, 0x9b, 0x74, 0xc1, 0xa0, 0x49, 0x54, 0xb7, 0x8b, 0x4b, 0x60, 0x35, 0xe9, 0x7a, 0x5e,
+    0x07, 0x8a, 0x5a, 0x0f, 0x28, 0xec, 0x96, 0xd5, 0x47, 0xbf, 0xee, 0x9a, 0xce, 0x80, 0x3a, 0xc0,
+];
+
+/// Returns the [`NUMS_POINT`] as public key.
+pub fn unspendable_internal_key() -> PublicKey {
+    let xonly = XOnlyPublicKey::from_slice(&NUMS_POINT).expect("NUMS point must be valid");
+    PublicKey::new(xonly.public_key(Parity::Even))
+}
+
+/// The content of a single inscription within an envelope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InscriptionContent {
+    mime: Vec<u8>,
+    data: Vec<u8>,
+    pointer: Option<u64>,
+}
+
+impl InscriptionContent {
+    pub fn new(mime: &[u8], data: &[u8]) -> Self {
+        InscriptionContent {
+            mime: mime.to_vec(),
+            data: data.to_vec(),
+            pointer: None,
+        }
+    }
+    /// Routes the inscription to the satoshi at the given offset into the
+    /// outputs of the reveal transaction, rather than the first satoshi of
+    /// the input.
+    pub fn pointer(mut self, offset: u64) -> Self {
+        self.pointer = Some(offset);
+        self
+    }
+    pub fn mime(&self) -> &[u8] {
+        &self.mime
+    }
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+    /// The offset set with [`InscriptionContent::pointer`], if any.
+    pub fn pointer_offset(&self) -> Option<u64> {
+        self.pointer
+    }
+    /// Parses the inscriptions of all envelopes in the script, as created by
+    /// [`OrdinalsInscription`]. Unknown tags are skipped, and a malformed
+    /// envelope ends the parsing.
+    pub fn parse_envelopes(script: &Script) -> Vec<InscriptionContent> {
+        use bitcoin::opcodes::all::*;
+
+        let instructions: Vec<Instruction> = script.instructions().flatten().collect();
+        let mut contents = vec![];
+
+        let mut index = 0;
+        while index + 3 <= instructions.len() {
+            // An envelope starts with `OP_FALSE OP_IF "ord"`.
+            let is_envelope = match &instructions[index..index + 3] {
+                [Instruction::PushBytes(empty), Instruction::Op(OP_IF), Instruction::PushBytes(tag)] => {
+                    empty.is_empty() && tag.as_bytes() == b"ord"
+                },
+                _ => false,
+            };
+
+            index += 1;
+            if !is_envelope {
+                continue;
+            }
+            index += 2;
+
+            let mut content = InscriptionContent::new(&[], &[]);
+            let mut in_body = false;
+            loop {
+                let Some(instruction) = instructions.get(index) else {
+                    return contents;
+                };
+                index += 1;
+
+                let push = match instruction {
+                    Instruction::Op(OP_ENDIF) => break,
+                    Instruction::PushBytes(push) => push.as_bytes(),
+                    Instruction::Op(_) => return contents,
+                };
+
+                // The empty push separates the tags from the body.
+                if in_body {
+                    content.data.extend_from_slice(push);
+                    continue;
+                } else if push.is_empty() {
+                    in_body = true;
+                    continue;
+                }
+
+                let Some(Instruction::PushBytes(value)) = instructions.get(index) else {
+                    return contents;
+                };
+                index += 1;
+
+                match push {
+                    [1] => content.mime = value.as_bytes().to_vec(),
+                    [2] if value.len() <= 8 => {
+                        let mut bytes = [0; 8];
+                        bytes[..value.len()].copy_from_slice(value.as_bytes());
+                        content.pointer = Some(u64::from_le_bytes(bytes));
+                    },
+                    _ => {},
+                }
+            }
+
+            contents.push(content);
+        }
+
+        contents
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct OrdinalsInscription {
     envelope: TaprootProgram,
     recipient: Recipient<TaprootScript>,
+    destination: Option<Recipient<PublicKey>>,
 }
 
 impl OrdinalsInscription {
@@ -17,8 +138,28 @@ impl OrdinalsInscription {
         data: &[u8],
         recipient: Recipient<PublicKey>,
     ) -> Result<OrdinalsInscription> {
-        // Create the envelope, containing the inscription content.
-        let envelope = create_envelope(mime, data, recipient.public_key())?;
+        Self::new_batch(&[InscriptionContent::new(mime, data)], recipient)
+    }
+    /// Creates a single envelope containing multiple inscriptions, which are
+    /// all revealed by the same input. Use [`InscriptionContent::pointer`] to
+    /// send each of them to its own output.
+    pub fn new_batch(
+        contents: &[InscriptionContent],
+        recipient: Recipient<PublicKey>,
+    ) -> Result<OrdinalsInscription> {
+        Self::from_parts(contents, None, recipient, None)
+    }
+    pub fn builder() -> OrdinalsInscriptionBuilder {
+        OrdinalsInscriptionBuilder::new()
+    }
+    fn from_parts(
+        contents: &[InscriptionContent],
+        reveal_key: Option<PublicKey>,
+        internal_key: Recipient<PublicKey>,
+        destination: Option<Recipient<PublicKey>>,
+    ) -> Result<OrdinalsInscription> {
+        // Create the envelope, containing the inscription contents.
+        let envelope = create_envelope(contents, reveal_key, internal_key.public_key())?;
 
         // Compute the merkle root of the inscription.
         let merkle_root = envelope
@@ -28,7 +169,8 @@ impl OrdinalsInscription {
 
         Ok(OrdinalsInscription {
             envelope,
-            recipient: Recipient::<TaprootScript>::from_pubkey_recipient(recipient, merkle_root),
+            recipient: Recipient::<TaprootScript>::from_pubkey_recipient(internal_key, merkle_root),
+            destination,
         })
     }
     pub fn taproot_program(&self) -> &Script {
@@ -40,10 +182,73 @@ impl OrdinalsInscription {
     pub fn recipient(&self) -> &Recipient<TaprootScript> {
         &self.recipient
     }
+    /// The recipient of the inscription once revealed, if set.
+    pub fn destination(&self) -> Option<&Recipient<PublicKey>> {
+        self.destination.as_ref()
+    }
+    /// Creates the P2TR key-path output of the reveal transaction, sending the
+    /// inscription to its destination.
+    pub fn reveal_output(&self, satoshis: u64) -> Result<TxOutput> {
+        let destination = self.destination.clone().ok_or(Error::Todo)?;
+        Ok(TxOutputP2TRKeyPath::new(satoshis, destination.into()).into())
+    }
+}
+
+/// Builds an inscription where the key revealing the envelope, the owner of
+/// the commit output and the recipient of the inscription can all differ.
+/// This allows inscribing on behalf of someone else, without access to their
+/// keys.
+#[derive(Debug, Clone, Default)]
+pub struct OrdinalsInscriptionBuilder {
+    contents: Vec<InscriptionContent>,
+    reveal_key: Option<PublicKey>,
+    internal_key: Option<PublicKey>,
+    destination: Option<Recipient<PublicKey>>,
+}
+
+impl OrdinalsInscriptionBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn content(mut self, content: InscriptionContent) -> Self {
+        self.contents.push(content);
+        self
+    }
+    /// The key that must sign the reveal transaction, which is enforced by
+    /// the envelope script (`<reveal_key> OP_CHECKSIG`).
+    pub fn reveal_key(mut self, pubkey: PublicKey) -> Self {
+        self.reveal_key = Some(pubkey);
+        self
+    }
+    /// The Taproot internal key of the commit output, for example the key of
+    /// the user on whose behalf the inscription is created. Defaults to
+    /// [`unspendable_internal_key`], which rules out any key-path spend.
+    pub fn internal_key(mut self, pubkey: PublicKey) -> Self {
+        self.internal_key = Some(pubkey);
+        self
+    }
+    /// The recipient of the inscription, see
+    /// [`OrdinalsInscription::reveal_output`].
+    pub fn destination(mut self, recipient: Recipient<PublicKey>) -> Self {
+        self.destination = Some(recipient);
+        self
+    }
+    pub fn build(self) -> Result<OrdinalsInscription> {
+        // Without a reveal key, anyone could spend the envelope.
+        let reveal_key = self.reveal_key.ok_or(Error::Todo)?;
+        let internal_key = self.internal_key.unwrap_or_else(unspendable_internal_key);
+
+        OrdinalsInscription::from_parts(
+            &self.contents,
+            Some(reveal_key),
+            Recipient::<PublicKey>::from(internal_key),
+            self.destination,
+        )
+    }
 }
 
-/// Creates an [Ordinals Inscription](https://docs.ordinals.com/inscriptions.html).
-/// This function is used for two purposes:
+/// Creates an [Ordinals Inscription](https://docs.ordinals.com/inscriptions.html),
+/// with one envelope per content. This function is used for two purposes:
 ///
 /// 1. It creates the spending condition for the given `internal_key`. This
 ///    associates the public key of the recipient with the Merkle root of the
@@ -57,16 +262,64 @@ impl OrdinalsInscription {
 /// could also be the same entity. Stage one, the `internal_key` is the
 /// recipient. Stage two, the `internal_key` is the claimer of the transaction
 /// (where the Inscription script is available in the Witness).
-fn create_envelope(mime: &[u8], data: &[u8], internal_key: PublicKey) -> Result<TaprootProgram> {
+///
+/// If a `reveal_key` is given, the script can only be executed with a
+/// signature of that key.
+fn create_envelope(
+    contents: &[InscriptionContent],
+    reveal_key: Option<PublicKey>,
+    internal_key: PublicKey,
+) -> Result<TaprootProgram> {
+    if contents.is_empty() {
+        return Err(Error::Todo);
+    }
+
+    let mut builder = ScriptBuf::builder();
+    if let Some(reveal_key) = reveal_key {
+        builder = builder
+            .push_x_only_key(&XOnlyPublicKey::from(reveal_key.inner))
+            .push_opcode(bitcoin::opcodes::all::OP_CHECKSIG);
+    }
+
+    for content in contents {
+        builder = push_envelope(builder, content)?;
+    }
+
+    // Finalize scripts.
+    let script = builder.into_script();
+
+    // Generate the necessary spending information. As mentioned in the
+    // documentation of this function at the top, this serves two purposes;
+    // setting the spending condition and actually claiming the spending
+    // condition.
+    let spend_info = TaprootBuilder::new()
+        .add_leaf(0, script.clone())
+        .expect("Ordinals Inscription spending info must always build")
+        .finalize(
+            &secp256k1::Secp256k1::new(),
+            XOnlyPublicKey::from(internal_key.inner),
+        )
+        .expect("Ordinals Inscription spending info must always build");
+
+    Ok(TaprootProgram { script, spend_info })
+}
+
+/// Appends the envelope of a single inscription to the script.
+fn push_envelope(
+    mut builder: ScriptBuilder,
+    content: &InscriptionContent,
+) -> Result<ScriptBuilder> {
     use bitcoin::opcodes::all::*;
     use bitcoin::opcodes::*;
 
     // Create MIME buffer.
     let mut mime_buf = PushBytesBuf::new();
-    mime_buf.extend_from_slice(mime).map_err(|_| Error::Todo)?;
+    mime_buf
+        .extend_from_slice(&content.mime)
+        .map_err(|_| Error::Todo)?;
 
     // Create an Ordinals Inscription.
-    let mut builder = ScriptBuf::builder()
+    builder = builder
         .push_opcode(OP_FALSE)
         .push_opcode(OP_IF)
         .push_slice(b"ord")
@@ -81,12 +334,30 @@ fn create_envelope(mime: &[u8], data: &[u8], internal_key: PublicKey) -> Result<
         // inconsistent, it's the current requirement.
         .push_opcode(OP_PUSHBYTES_1)
         // MIME type identifying the data
-        .push_slice(mime_buf.as_push_bytes())
-        // Separator.
-        .push_opcode(OP_PUSHBYTES_0);
+        .push_slice(mime_buf.as_push_bytes());
+
+    // The pointer tag, followed by the offset in little-endian with trailing
+    // zeros removed. A zero pointer is the default and hence omitted.
+    if let Some(pointer) = content.pointer.filter(|&pointer| pointer != 0) {
+        let bytes = pointer.to_le_bytes();
+        let len = bytes
+            .iter()
+            .rposition(|&byte| byte != 0)
+            .map_or(0, |pos| pos + 1);
+
+        let mut pointer_buf = PushBytesBuf::new();
+        pointer_buf
+            .extend_from_slice(&bytes[..len])
+            .map_err(|_| Error::Todo)?;
+
+        builder = builder.push_slice([2]).push_slice(pointer_buf);
+    }
+
+    // Separator.
+    builder = builder.push_opcode(OP_PUSHBYTES_0);
 
     // Push the actual data in chunks.
-    for chunk in data.chunks(520) {
+    for chunk in content.data.chunks(520) {
         // Create data buffer.
         let mut data_buf = PushBytesBuf::new();
         data_buf.extend_from_slice(chunk).map_err(|_| Error::Todo)?;
@@ -95,21 +366,5 @@ fn create_envelope(mime: &[u8], data: &[u8], internal_key: PublicKey) -> Result<
         builder = builder.push_slice(data_buf);
     }
 
-    // Finalize scripts.
-    let script = builder.push_opcode(OP_ENDIF).into_script();
-
-    // Generate the necessary spending information. As mentioned in the
-    // documentation of this function at the top, this serves two purposes;
-    // setting the spending condition and actually claiming the spending
-    // condition.
-    let spend_info = TaprootBuilder::new()
-        .add_leaf(0, script.clone())
-        .expect("Ordinals Inscription spending info must always build")
-        .finalize(
-            &secp256k1::Secp256k1::new(),
-            XOnlyPublicKey::from(internal_key.inner),
-        )
-        .expect("Ordinals Inscription spending info must always build");
-
-    Ok(TaprootProgram { script, spend_info })
+    Ok(builder.push_opcode(OP_ENDIF))
 }
@@ -1,30 +1,87 @@
+use crate::{Error, Result};
+use bitcoin::{Script, ScriptBuf, VarInt, Weight};
+
+mod op_return;
 mod p2pkh;
+mod p2sh;
 mod p2tr_key_path;
 mod p2tr_script_path;
 mod p2wpkh;
+mod p2wsh;
+mod raw;
 
+pub use op_return::*;
 pub use p2pkh::*;
+pub use p2sh::*;
 pub use p2tr_key_path::*;
 pub use p2tr_script_path::*;
 pub use p2wpkh::*;
+pub use p2wsh::*;
+pub use raw::*;
 
 #[derive(Debug, Clone)]
 pub enum TxOutput {
     P2PKH(TxOutputP2PKH),
+    P2SH(TxOutputP2SH),
     P2WPKH(TxOutputP2WPKH),
+    P2WSH(TxOutputP2WSH),
     P2TRKeyPath(TxOutputP2TRKeyPath),
     P2TRScriptPath(TXOutputP2TRScriptPath),
+    OpReturn(TxOutputOpReturn),
+    Raw(TxOutputRaw),
 }
 
 impl TxOutput {
+    /// Creates the output from a standard scriptPubKey. Taproot outputs are
+    /// always treated as key-path, since the two can't be distinguished.
+    pub fn from_script(script_pubkey: ScriptBuf, satoshis: u64) -> Result<Self> {
+        let output = if script_pubkey.is_p2pkh() {
+            TxOutputP2PKH::new_with_script(satoshis, script_pubkey).into()
+        } else if script_pubkey.is_p2sh() {
+            TxOutputP2SH::new_with_script(satoshis, script_pubkey).into()
+        } else if script_pubkey.is_v0_p2wpkh() {
+            TxOutputP2WPKH::new_with_script(satoshis, script_pubkey).into()
+        } else if script_pubkey.is_v0_p2wsh() {
+            TxOutputP2WSH::new_with_script(satoshis, script_pubkey).into()
+        } else if script_pubkey.is_v1_p2tr() {
+            TxOutputP2TRKeyPath::new_with_script(satoshis, script_pubkey).into()
+        } else if script_pubkey.is_op_return() {
+            TxOutputOpReturn::new_with_script(satoshis, script_pubkey).into()
+        } else {
+            return Err(Error::Todo);
+        };
+
+        Ok(output)
+    }
     pub fn satoshis(&self) -> u64 {
         match self {
             TxOutput::P2PKH(p) => p.satoshis,
+            TxOutput::P2SH(p) => p.satoshis,
             TxOutput::P2WPKH(p) => p.satoshis,
+            TxOutput::P2WSH(p) => p.satoshis,
             TxOutput::P2TRKeyPath(p) => p.satoshis,
             TxOutput::P2TRScriptPath(p) => p.satoshis,
+            TxOutput::OpReturn(p) => p.satoshis,
+            TxOutput::Raw(p) => p.satoshis,
         }
     }
+    pub fn script_pubkey(&self) -> &Script {
+        match self {
+            TxOutput::P2PKH(p) => &p.script_pubkey,
+            TxOutput::P2SH(p) => &p.script_pubkey,
+            TxOutput::P2WPKH(p) => &p.script_pubkey,
+            TxOutput::P2WSH(p) => &p.script_pubkey,
+            TxOutput::P2TRKeyPath(p) => &p.script_pubkey,
+            TxOutput::P2TRScriptPath(p) => &p.script_pubkey,
+            TxOutput::OpReturn(p) => &p.script_pubkey,
+            TxOutput::Raw(p) => &p.script_pubkey,
+        }
+    }
+    /// The weight this output adds to the transaction.
+    pub fn weight(&self) -> Weight {
+        let script_len = self.script_pubkey().len() as u64;
+        Weight::from_non_witness_data_size(8 + VarInt(script_len).len() as u64 + script_len)
+    }
 }
 
 impl From<TxOutputP2PKH> for TxOutput {
@@ -33,6 +90,12 @@ impl From<TxOutputP2PKH> for TxOutput {
     }
 }
 
+impl From<TxOutputP2SH> for TxOutput {
+    fn from(output: TxOutputP2SH) -> Self {
+        TxOutput::P2SH(output)
+    }
+}
+
 impl From<TxOutputP2TRKeyPath> for TxOutput {
     fn from(output: TxOutputP2TRKeyPath) -> Self {
         TxOutput::P2TRKeyPath(output)
@@ -45,12 +108,42 @@ impl From<TxOutputP2WPKH> for TxOutput {
     }
 }
 
+impl From<TxOutputP2WSH> for TxOutput {
+    fn from(output: TxOutputP2WSH) -> Self {
+        TxOutput::P2WSH(output)
+    }
+}
+
 impl From<TXOutputP2TRScriptPath> for TxOutput {
     fn from(output: TXOutputP2TRScriptPath) -> Self {
         TxOutput::P2TRScriptPath(output)
     }
 }
 
+impl From<TxOutputOpReturn> for TxOutput {
+    fn from(output: TxOutputOpReturn) -> Self {
+        TxOutput::OpReturn(output)
+    }
+}
+
+impl From<TxOutputRaw> for TxOutput {
+    fn from(output: TxOutputRaw) -> Self {
+        TxOutput::Raw(output)
+    }
+}
+
+/// Keeps the scriptPubKey as it is, only `OP_RETURN` outputs are recognized
+/// as such.
+impl From<bitcoin::TxOut> for TxOutput {
+    fn from(out: bitcoin::TxOut) -> Self {
+        if out.script_pubkey.is_op_return() {
+            TxOutputOpReturn::new_with_script(out.value, out.script_pubkey).into()
+        } else {
+            TxOutputRaw::new_with_script(out.value, out.script_pubkey).into()
+        }
+    }
+}
+
 impl From<TxOutput> for bitcoin::TxOut {
     fn from(out: TxOutput) -> Self {
         match out {
@@ -58,10 +151,18 @@ impl From<TxOutput> for bitcoin::TxOut {
                 value: p.satoshis,
                 script_pubkey: p.script_pubkey,
             },
+            TxOutput::P2SH(p) => Self {
+                value: p.satoshis,
+                script_pubkey: p.script_pubkey,
+            },
             TxOutput::P2WPKH(p) => Self {
                 value: p.satoshis,
                 script_pubkey: p.script_pubkey,
             },
+            TxOutput::P2WSH(p) => Self {
+                value: p.satoshis,
+                script_pubkey: p.script_pubkey,
+            },
             TxOutput::P2TRKeyPath(p) => Self {
                 value: p.satoshis,
                 script_pubkey: p.script_pubkey,
@@ -70,6 +171,14 @@ impl From<TxOutput> for bitcoin::TxOut {
                 value: p.satoshis,
                 script_pubkey: p.script_pubkey,
             },
+            TxOutput::OpReturn(p) => Self {
+                value: p.satoshis,
+                script_pubkey: p.script_pubkey,
+            },
+            TxOutput::Raw(p) => Self {
+                value: p.satoshis,
+                script_pubkey: p.script_pubkey,
+            },
         }
     }
 }
@@ -0,0 +1,39 @@
+use crate::script::DebugScript;
+use crate::{Error, Result};
+use bitcoin::script::PushBytesBuf;
+use bitcoin::ScriptBuf;
+use std::fmt;
+
+/// Provably unspendable output carrying arbitrary data, such as a runestone.
+#[derive(Clone)]
+pub struct TxOutputOpReturn {
+    pub(crate) satoshis: u64,
+    pub(crate) script_pubkey: ScriptBuf,
+}
+
+impl fmt::Debug for TxOutputOpReturn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TxOutputOpReturn")
+            .field("satoshis", &self.satoshis)
+            .field("script_pubkey", &DebugScript(&self.script_pubkey))
+            .finish()
+    }
+}
+
+impl TxOutputOpReturn {
+    /// Creates the `OP_RETURN <data>` output, burning no satoshis.
+    pub fn new(data: &[u8]) -> Result<Self> {
+        let data = PushBytesBuf::try_from(data.to_vec()).map_err(|_| Error::Todo)?;
+
+        Ok(TxOutputOpReturn {
+            satoshis: 0,
+            script_pubkey: ScriptBuf::new_op_return(&data),
+        })
+    }
+    pub fn new_with_script(satoshis: u64, script_pubkey: ScriptBuf) -> Self {
+        TxOutputOpReturn {
+            satoshis,
+            script_pubkey,
+        }
+    }
+}
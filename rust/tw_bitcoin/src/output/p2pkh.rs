@@ -1,12 +1,23 @@
+use crate::script::DebugScript;
 use crate::{Error, Recipient, Result};
 use bitcoin::{PubkeyHash, ScriptBuf};
+use std::fmt;
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct TxOutputP2PKH {
     pub(crate) satoshis: u64,
     pub(crate) script_pubkey: ScriptBuf,
 }
 
+impl fmt::Debug for TxOutputP2PKH {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TxOutputP2PKH")
+            .field("satoshis", &self.satoshis)
+            .field("script_pubkey", &DebugScript(&self.script_pubkey))
+            .finish()
+    }
+}
+
 impl TxOutputP2PKH {
     pub fn new(satoshis: u64, recipient: impl Into<Recipient<PubkeyHash>>) -> Self {
         let recipient: Recipient<PubkeyHash> = recipient.into();
@@ -0,0 +1,65 @@
+use crate::script::DebugScript;
+use crate::{Error, Result};
+use bitcoin::{Script, ScriptBuf};
+use std::fmt;
+
+#[derive(Clone)]
+pub struct TxOutputP2SH {
+    pub(crate) satoshis: u64,
+    pub(crate) script_pubkey: ScriptBuf,
+}
+
+impl fmt::Debug for TxOutputP2SH {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TxOutputP2SH")
+            .field("satoshis", &self.satoshis)
+            .field("script_pubkey", &DebugScript(&self.script_pubkey))
+            .finish()
+    }
+}
+
+impl TxOutputP2SH {
+    /// Creates the output committing to the given redeem script.
+    pub fn new(satoshis: u64, redeem_script: &Script) -> Self {
+        TxOutputP2SH {
+            satoshis,
+            script_pubkey: ScriptBuf::new_p2sh(&redeem_script.script_hash()),
+        }
+    }
+    pub fn new_with_script(satoshis: u64, script_pubkey: ScriptBuf) -> Self {
+        TxOutputP2SH {
+            satoshis,
+            script_pubkey,
+        }
+    }
+    pub fn builder() -> TxOutputP2SHBuilder {
+        TxOutputP2SHBuilder::new()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TxOutputP2SHBuilder {
+    satoshis: Option<u64>,
+    redeem_script: Option<ScriptBuf>,
+}
+
+impl TxOutputP2SHBuilder {
+    pub fn new() -> TxOutputP2SHBuilder {
+        Self::default()
+    }
+    pub fn satoshis(mut self, satoshis: u64) -> TxOutputP2SHBuilder {
+        self.satoshis = Some(satoshis);
+        self
+    }
+    pub fn redeem_script(mut self, redeem_script: ScriptBuf) -> TxOutputP2SHBuilder {
+        self.redeem_script = Some(redeem_script);
+        self
+    }
+    pub fn build(self) -> Result<TxOutputP2SH> {
+        let redeem_script = self.redeem_script.ok_or(Error::Todo)?;
+        Ok(TxOutputP2SH::new(
+            self.satoshis.ok_or(Error::Todo)?,
+            &redeem_script,
+        ))
+    }
+}
@@ -1,13 +1,24 @@
+use crate::script::DebugScript;
 use crate::{Error, Recipient, Result};
 use bitcoin::key::TweakedPublicKey;
 use bitcoin::script::ScriptBuf;
+use std::fmt;
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct TxOutputP2TRKeyPath {
     pub(crate) satoshis: u64,
     pub(crate) script_pubkey: ScriptBuf,
 }
 
+impl fmt::Debug for TxOutputP2TRKeyPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TxOutputP2TRKeyPath")
+            .field("satoshis", &self.satoshis)
+            .field("script_pubkey", &DebugScript(&self.script_pubkey))
+            .finish()
+    }
+}
+
 impl TxOutputP2TRKeyPath {
     pub fn new(satoshis: u64, recipient: Recipient<TweakedPublicKey>) -> Self {
         TxOutputP2TRKeyPath {
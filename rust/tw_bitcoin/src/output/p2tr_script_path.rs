@@ -1,8 +1,10 @@
+use crate::script::DebugScript;
 use crate::{Error, Recipient, Result};
 use bitcoin::key::PublicKey;
 use bitcoin::script::ScriptBuf;
 use bitcoin::secp256k1;
 use bitcoin::taproot::{TapNodeHash, TaprootSpendInfo};
+use std::fmt;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct TaprootScript {
@@ -16,10 +18,21 @@ pub struct TaprootProgram {
     pub spend_info: TaprootSpendInfo,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct TXOutputP2TRScriptPath {
     pub(crate) satoshis: u64,
     pub(crate) script_pubkey: ScriptBuf,
+    pub(crate) inscription_commit: bool,
+}
+
+impl fmt::Debug for TXOutputP2TRScriptPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TXOutputP2TRScriptPath")
+            .field("satoshis", &self.satoshis)
+            .field("script_pubkey", &DebugScript(&self.script_pubkey))
+            .field("inscription_commit", &self.inscription_commit)
+            .finish()
+    }
 }
 
 impl TXOutputP2TRScriptPath {
@@ -33,14 +46,25 @@ impl TXOutputP2TRScriptPath {
         TXOutputP2TRScriptPath {
             satoshis,
             script_pubkey,
+            inscription_commit: false,
         }
     }
     pub fn new_with_script(satoshis: u64, script_pubkey: ScriptBuf) -> Self {
         TXOutputP2TRScriptPath {
             satoshis,
             script_pubkey,
+            inscription_commit: false,
         }
     }
+    /// Marks the output as the commit of an inscription, which is spent by
+    /// the reveal transaction.
+    pub fn inscription_commit(mut self, commit: bool) -> Self {
+        self.inscription_commit = commit;
+        self
+    }
+    pub fn is_inscription_commit(&self) -> bool {
+        self.inscription_commit
+    }
     pub fn builder() -> TxOutputP2TRScriptPathBuilder {
         TxOutputP2TRScriptPathBuilder::new()
     }
@@ -50,6 +74,7 @@ impl TXOutputP2TRScriptPath {
 pub struct TxOutputP2TRScriptPathBuilder {
     satoshis: Option<u64>,
     recipient: Option<Recipient<TaprootScript>>,
+    inscription_commit: bool,
 }
 
 impl TxOutputP2TRScriptPathBuilder {
@@ -67,11 +92,15 @@ impl TxOutputP2TRScriptPathBuilder {
         self.recipient = Some(recipient);
         self
     }
+    pub fn inscription_commit(mut self, commit: bool) -> TxOutputP2TRScriptPathBuilder {
+        self.inscription_commit = commit;
+        self
+    }
     pub fn build(self) -> Result<TXOutputP2TRScriptPath> {
         let recipient = self.recipient.ok_or(Error::Todo)?;
-        Ok(TXOutputP2TRScriptPath::new(
-            self.satoshis.ok_or(Error::Todo)?,
-            &recipient,
-        ))
+        Ok(
+            TXOutputP2TRScriptPath::new(self.satoshis.ok_or(Error::Todo)?, &recipient)
+                .inscription_commit(self.inscription_commit),
+        )
     }
 }
@@ -1,12 +1,23 @@
+use crate::script::DebugScript;
 use crate::{Error, Recipient, Result};
 use bitcoin::{ScriptBuf, WPubkeyHash};
+use std::fmt;
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct TxOutputP2WPKH {
     pub(crate) satoshis: u64,
     pub(crate) script_pubkey: ScriptBuf,
 }
 
+impl fmt::Debug for TxOutputP2WPKH {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TxOutputP2WPKH")
+            .field("satoshis", &self.satoshis)
+            .field("script_pubkey", &DebugScript(&self.script_pubkey))
+            .finish()
+    }
+}
+
 impl TxOutputP2WPKH {
     pub fn new(satoshis: u64, recipient: Recipient<WPubkeyHash>) -> Self {
         TxOutputP2WPKH {
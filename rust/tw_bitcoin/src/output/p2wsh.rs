@@ -0,0 +1,65 @@
+use crate::script::DebugScript;
+use crate::{Error, Result};
+use bitcoin::{Script, ScriptBuf};
+use std::fmt;
+
+#[derive(Clone)]
+pub struct TxOutputP2WSH {
+    pub(crate) satoshis: u64,
+    pub(crate) script_pubkey: ScriptBuf,
+}
+
+impl fmt::Debug for TxOutputP2WSH {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TxOutputP2WSH")
+            .field("satoshis", &self.satoshis)
+            .field("script_pubkey", &DebugScript(&self.script_pubkey))
+            .finish()
+    }
+}
+
+impl TxOutputP2WSH {
+    /// Creates the output committing to the given witness script.
+    pub fn new(satoshis: u64, witness_script: &Script) -> Self {
+        TxOutputP2WSH {
+            satoshis,
+            script_pubkey: ScriptBuf::new_v0_p2wsh(&witness_script.wscript_hash()),
+        }
+    }
+    pub fn new_with_script(satoshis: u64, script_pubkey: ScriptBuf) -> Self {
+        TxOutputP2WSH {
+            satoshis,
+            script_pubkey,
+        }
+    }
+    pub fn builder() -> TxOutputP2WSHBuilder {
+        TxOutputP2WSHBuilder::new()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TxOutputP2WSHBuilder {
+    satoshis: Option<u64>,
+    witness_script: Option<ScriptBuf>,
+}
+
+impl TxOutputP2WSHBuilder {
+    pub fn new() -> TxOutputP2WSHBuilder {
+        Self::default()
+    }
+    pub fn satoshis(mut self, satoshis: u64) -> TxOutputP2WSHBuilder {
+        self.satoshis = Some(satoshis);
+        self
+    }
+    pub fn witness_script(mut self, witness_script: ScriptBuf) -> TxOutputP2WSHBuilder {
+        self.witness_script = Some(witness_script);
+        self
+    }
+    pub fn build(self) -> Result<TxOutputP2WSH> {
+        let witness_script = self.witness_script.ok_or(Error::Todo)?;
+        Ok(TxOutputP2WSH::new(
+            self.satoshis.ok_or(Error::Todo)?,
+            &witness_script,
+        ))
+    }
+}
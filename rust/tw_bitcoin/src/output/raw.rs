@@ -0,0 +1,29 @@
+use crate::script::DebugScript;
+use bitcoin::ScriptBuf;
+use std::fmt;
+
+/// Output with an arbitrary scriptPubKey, such as an output of an existing
+/// transaction which is kept as it is.
+#[derive(Clone)]
+pub struct TxOutputRaw {
+    pub(crate) satoshis: u64,
+    pub(crate) script_pubkey: ScriptBuf,
+}
+
+impl fmt::Debug for TxOutputRaw {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TxOutputRaw")
+            .field("satoshis", &self.satoshis)
+            .field("script_pubkey", &DebugScript(&self.script_pubkey))
+            .finish()
+    }
+}
+
+impl TxOutputRaw {
+    pub fn new_with_script(satoshis: u64, script_pubkey: ScriptBuf) -> Self {
+        TxOutputRaw {
+            satoshis,
+            script_pubkey,
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! The transaction format of Proof-of-Stake forks such as Peercoin, Reddcoin
+//! and Neblio, which carry the transaction time (`nTime`) right after the
+//! version. The time is part of the transaction ID and the legacy signature
+//! hash, but the format is otherwise identical to Bitcoin's.
+
+use crate::hasher::legacy_preimage;
+use crate::Result;
+use bitcoin::consensus::Encodable;
+use bitcoin::hashes::{sha256d, Hash};
+use bitcoin::sighash::EcdsaSighashType;
+use bitcoin::{Script, Transaction, Txid};
+
+/// The version used by the timestamped format.
+pub const TX_VERSION: i32 = 1;
+
+/// The SLIP-44 coin types using the timestamped format.
+pub const REDDCOIN_COIN_TYPE: u32 = 4;
+pub const PEERCOIN_COIN_TYPE: u32 = 6;
+pub const VERGE_COIN_TYPE: u32 = 77;
+pub const NEBLIO_COIN_TYPE: u32 = 146;
+
+/// Returns whether the coin type uses the timestamped format.
+pub fn is_pos_coin_type(coin_type: u32) -> bool {
+    [
+        REDDCOIN_COIN_TYPE,
+        PEERCOIN_COIN_TYPE,
+        VERGE_COIN_TYPE,
+        NEBLIO_COIN_TYPE,
+    ]
+    .contains(&coin_type)
+}
+
+/// The offset of `nTime`, following the 4-byte version.
+const TIME_OFFSET: usize = 4;
+
+/// Serializes the transaction with the given time. Witnesses are not
+/// supported by this format and are ignored.
+pub fn serialize(tx: &Transaction, time: u32) -> Vec<u8> {
+    let mut stripped = tx.clone();
+    for input in &mut stripped.input {
+        input.witness.clear();
+    }
+
+    let mut buffer = vec![];
+    stripped
+        .consensus_encode(&mut buffer)
+        .expect("encoding to a vector never fails");
+
+    insert_time(buffer, time)
+}
+
+/// The transaction ID, which commits to the time.
+pub fn txid(tx: &Transaction, time: u32) -> Txid {
+    Txid::from_raw_hash(sha256d::Hash::hash(&serialize(tx, time)))
+}
+
+/// Computes the legacy signature hash of the input at `index`, which spends
+/// an output locked by `script_pubkey`.
+pub fn signature_hash(
+    tx: &Transaction,
+    time: u32,
+    index: usize,
+    script_pubkey: &Script,
+    sighash_type: EcdsaSighashType,
+) -> Result<[u8; 32]> {
+    let data = legacy_preimage(tx, index, script_pubkey, sighash_type)?;
+    let data = insert_time(data, time);
+    Ok(sha256d::Hash::hash(&data).to_byte_array())
+}
+
+fn insert_time(mut data: Vec<u8>, time: u32) -> Vec<u8> {
+    data.splice(TIME_OFFSET..TIME_OFFSET, time.to_le_bytes());
+    data
+}
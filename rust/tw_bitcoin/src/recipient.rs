@@ -32,6 +32,16 @@ impl Recipient<PublicKey> {
             inner: PublicKey::new(keypair.public_key()),
         }
     }
+    /// The recipient of the uncompressed public key, as used by the P2PKH
+    /// addresses of old wallets.
+    pub fn from_keypair_uncompressed(keypair: &KeyPair) -> Self {
+        Recipient {
+            inner: PublicKey {
+                compressed: false,
+                inner: keypair.public_key(),
+            },
+        }
+    }
     pub fn public_key(&self) -> PublicKey {
         self.inner
     }
@@ -131,6 +141,12 @@ impl From<PublicKey> for Recipient<TweakedPublicKey> {
     }
 }
 
+impl From<TweakedPublicKey> for Recipient<TweakedPublicKey> {
+    fn from(inner: TweakedPublicKey) -> Self {
+        Recipient { inner }
+    }
+}
+
 impl From<Recipient<PublicKey>> for Recipient<TweakedPublicKey> {
     fn from(recipient: Recipient<PublicKey>) -> Self {
         Recipient {
@@ -0,0 +1,119 @@
+//! Signing with keys that never enter the process memory, such as keys held
+//! by the Secure Enclave or StrongBox. The transaction is built as usual, but
+//! each sighash is handed to a callback along with the identifier of the key,
+//! and the returned signature is verified before it's used.
+
+use crate::claim::SigningKey;
+use crate::{Error, Result};
+use bitcoin::key::{PublicKey, TapTweak};
+use bitcoin::secp256k1::{self, ecdsa, schnorr, Secp256k1, XOnlyPublicKey};
+use std::cell::Cell;
+
+/// The signature requested from the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureKind {
+    /// A DER or 64-byte compact ECDSA signature, for legacy and SegWit inputs.
+    Ecdsa,
+    /// A 64-byte BIP-340 Schnorr signature, with the key tweaked for a
+    /// key-path spend as specified in BIP-86.
+    SchnorrKeyPath,
+    /// A 64-byte BIP-340 Schnorr signature with the untweaked key, for
+    /// script-path spends and silent payments.
+    Schnorr,
+}
+
+/// The sighash of an input, to be signed by the host.
+#[derive(Debug, Clone, Copy)]
+pub struct SignRequest<'a> {
+    pub sighash: [u8; 32],
+    pub input_index: usize,
+    pub key_id: &'a [u8],
+    pub kind: SignatureKind,
+}
+
+/// A [`SigningKey`] whose signatures are created by `sign`. The inputs are
+/// signed in order with one signature each, so the index of the input is the
+/// number of previous requests. A new signer must be used per transaction.
+pub struct RemoteSigner<F> {
+    public_key: PublicKey,
+    key_id: Vec<u8>,
+    sign: F,
+    requests: Cell<usize>,
+}
+
+impl<F> RemoteSigner<F>
+where
+    F: Fn(&SignRequest) -> Result<Vec<u8>>,
+{
+    /// Creates the signer of the key with the given public key, which the
+    /// host refers to as `key_id`.
+    pub fn new(public_key: PublicKey, key_id: Vec<u8>, sign: F) -> Self {
+        RemoteSigner {
+            public_key,
+            key_id,
+            sign,
+            requests: Cell::new(0),
+        }
+    }
+    fn request(&self, sighash: &secp256k1::Message, kind: SignatureKind) -> Result<Vec<u8>> {
+        let input_index = self.requests.replace(self.requests.get() + 1);
+
+        (self.sign)(&SignRequest {
+            sighash: *sighash.as_ref(),
+            input_index,
+            key_id: &self.key_id,
+            kind,
+        })
+    }
+}
+
+impl<F> SigningKey for RemoteSigner<F>
+where
+    F: Fn(&SignRequest) -> Result<Vec<u8>>,
+{
+    fn public_key(&self) -> PublicKey {
+        self.public_key
+    }
+    fn sign_ecdsa(&self, sighash: &secp256k1::Message) -> Result<ecdsa::Signature> {
+        let signature = self.request(sighash, SignatureKind::Ecdsa)?;
+
+        let mut sig = ecdsa::Signature::from_der(&signature)
+            .or_else(|_| ecdsa::Signature::from_compact(&signature))
+            .map_err(|_| Error::Todo)?;
+
+        // Signatures with a high S value are non-standard.
+        sig.normalize_s();
+
+        Secp256k1::verification_only()
+            .verify_ecdsa(sighash, &sig, &self.public_key.inner)
+            .map_err(|_| Error::Todo)?;
+
+        Ok(sig)
+    }
+    fn sign_schnorr(
+        &self,
+        sighash: &secp256k1::Message,
+        tweak: bool,
+    ) -> Result<schnorr::Signature> {
+        let secp = Secp256k1::verification_only();
+
+        let (kind, pubkey) = if tweak {
+            let internal = XOnlyPublicKey::from(self.public_key.inner);
+            let (tweaked, _) = internal.tap_tweak(&secp, None);
+            (SignatureKind::SchnorrKeyPath, tweaked.to_inner())
+        } else {
+            (
+                SignatureKind::Schnorr,
+                XOnlyPublicKey::from(self.public_key.inner),
+            )
+        };
+
+        let signature = self.request(sighash, kind)?;
+        let sig = schnorr::Signature::from_slice(&signature).map_err(|_| Error::Todo)?;
+
+        secp.verify_schnorr(&sig, sighash, &pubkey)
+            .map_err(|_| Error::Todo)?;
+
+        Ok(sig)
+    }
+}
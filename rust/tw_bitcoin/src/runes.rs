@@ -0,0 +1,706 @@
+//! Runes protocol: fungible tokens whose etching, minting and transfers are
+//! recorded in a runestone, an `OP_RETURN OP_13` output carrying a sequence of
+//! LEB128 encoded integers.
+
+use crate::{Error, Result, TxOutput, TxOutputOpReturn};
+use bitcoin::blockdata::opcodes::all::{OP_PUSHNUM_13, OP_RETURN};
+use bitcoin::blockdata::script::Instruction;
+use bitcoin::script::{Builder, PushBytesBuf};
+use bitcoin::{Script, ScriptBuf, Transaction};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+
+pub const MAX_DIVISIBILITY: u8 = 38;
+pub const MAX_SPACERS: u32 = 0b0000_0111_1111_1111_1111_1111_1111_1111;
+
+// Maximum size of a single data push.
+const MAX_PUSH_SIZE: usize = 520;
+
+// The fields of a runestone, odd tags can be safely ignored by older parsers.
+mod tag {
+    pub const BODY: u128 = 0;
+    pub const DIVISIBILITY: u128 = 1;
+    pub const FLAGS: u128 = 2;
+    pub const SPACERS: u128 = 3;
+    pub const RUNE: u128 = 4;
+    pub const SYMBOL: u128 = 5;
+    pub const PREMINE: u128 = 6;
+    pub const CAP: u128 = 8;
+    pub const AMOUNT: u128 = 10;
+    pub const HEIGHT_START: u128 = 12;
+    pub const HEIGHT_END: u128 = 14;
+    pub const OFFSET_START: u128 = 16;
+    pub const OFFSET_END: u128 = 18;
+    pub const MINT: u128 = 20;
+    pub const POINTER: u128 = 22;
+}
+
+// Bit positions within the `FLAGS` field.
+mod flag {
+    pub const ETCHING: u32 = 0;
+    pub const TERMS: u32 = 1;
+    pub const TURBO: u32 = 2;
+}
+
+/// Identifies a rune by the block height and the index of its etching
+/// transaction within that block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RuneId {
+    pub block: u64,
+    pub tx: u32,
+}
+
+impl RuneId {
+    pub fn new(block: u64, tx: u32) -> Result<Self> {
+        // The genesis block contains no etchings besides the first rune.
+        if block == 0 && tx > 0 {
+            return Err(Error::Todo);
+        }
+
+        Ok(RuneId { block, tx })
+    }
+    /// The delta encoding of `next` relative to `self`, as used by edicts.
+    fn delta(self, next: RuneId) -> Option<(u128, u128)> {
+        let block = next.block.checked_sub(self.block)?;
+        let tx = if block == 0 {
+            next.tx.checked_sub(self.tx)?
+        } else {
+            next.tx
+        };
+
+        Some((block.into(), tx.into()))
+    }
+    /// Reverses [`RuneId::delta`].
+    fn next(self, block: u128, tx: u128) -> Option<RuneId> {
+        let next_block = self.block.checked_add(block.try_into().ok()?)?;
+        let next_tx = if block == 0 {
+            self.tx.checked_add(tx.try_into().ok()?)?
+        } else {
+            tx.try_into().ok()?
+        };
+
+        RuneId::new(next_block, next_tx).ok()
+    }
+}
+
+impl fmt::Display for RuneId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.block, self.tx)
+    }
+}
+
+impl FromStr for RuneId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (block, tx) = s.split_once(':').ok_or(Error::Todo)?;
+        RuneId::new(
+            block.parse().map_err(|_| Error::Todo)?,
+            tx.parse().map_err(|_| Error::Todo)?,
+        )
+    }
+}
+
+/// The name of a rune, encoded as a modified base-26 integer (`A` is 0, `Z`
+/// is 25, `AA` is 26 and so on).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Rune(pub u128);
+
+impl fmt::Display for Rune {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut n = self.0;
+        if n == u128::MAX {
+            return write!(f, "BCGDENLQRQWDSLRUGSNLBTMFIJAV");
+        }
+
+        n += 1;
+        let mut symbol = String::new();
+        while n > 0 {
+            symbol.push(char::from(b'A' + ((n - 1) % 26) as u8));
+            n = (n - 1) / 26;
+        }
+
+        f.write_str(&symbol.chars().rev().collect::<String>())
+    }
+}
+
+impl FromStr for Rune {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s.is_empty() {
+            return Err(Error::Todo);
+        }
+
+        let mut x = 0u128;
+        for (i, c) in s.chars().enumerate() {
+            if i > 0 {
+                x = x.checked_add(1).ok_or(Error::Todo)?;
+            }
+
+            x = x.checked_mul(26).ok_or(Error::Todo)?;
+            match c {
+                'A'..='Z' => x = x.checked_add(c as u128 - 'A' as u128).ok_or(Error::Todo)?,
+                _ => return Err(Error::Todo),
+            }
+        }
+
+        Ok(Rune(x))
+    }
+}
+
+/// A rune name including its spacers, such as `UNCOMMON•GOODS`. Bit `i` of
+/// `spacers` indicates a spacer after the `i`-th letter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpacedRune {
+    pub rune: Rune,
+    pub spacers: u32,
+}
+
+impl fmt::Display for SpacedRune {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rune = self.rune.to_string();
+
+        for (i, c) in rune.chars().enumerate() {
+            write!(f, "{c}")?;
+
+            if i < rune.len() - 1 && self.spacers & (1 << i) != 0 {
+                write!(f, "•")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for SpacedRune {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut rune = String::new();
+        let mut spacers = 0u32;
+
+        for c in s.chars() {
+            match c {
+                'A'..='Z' => rune.push(c),
+                '.' | '•' => {
+                    // Spacers are only allowed between letters.
+                    let position = rune.len().checked_sub(1).ok_or(Error::Todo)?;
+                    let flag = 1u32.checked_shl(position as u32).ok_or(Error::Todo)?;
+                    if spacers & flag != 0 {
+                        return Err(Error::Todo);
+                    }
+
+                    spacers |= flag;
+                },
+                _ => return Err(Error::Todo),
+            }
+        }
+
+        if 32 - spacers.leading_zeros() >= rune.len() as u32 {
+            return Err(Error::Todo);
+        }
+
+        Ok(SpacedRune {
+            rune: rune.parse()?,
+            spacers,
+        })
+    }
+}
+
+/// The open mint terms of an etched rune.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Terms {
+    /// Amount of runes created by each mint.
+    pub amount: Option<u128>,
+    /// Maximum number of mints.
+    pub cap: Option<u128>,
+    /// Absolute block height range (start and end) in which minting is open.
+    pub height: (Option<u64>, Option<u64>),
+    /// Block height range relative to the etching in which minting is open.
+    pub offset: (Option<u64>, Option<u64>),
+}
+
+/// Creates a new rune.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Etching {
+    pub divisibility: Option<u8>,
+    pub premine: Option<u128>,
+    /// The name of the rune, a reserved name is assigned if omitted.
+    pub rune: Option<Rune>,
+    pub spacers: Option<u32>,
+    pub symbol: Option<char>,
+    pub terms: Option<Terms>,
+    /// Opts into future protocol changes.
+    pub turbo: bool,
+}
+
+impl Etching {
+    /// The maximum supply of the rune, `None` on overflow.
+    pub fn supply(&self) -> Option<u128> {
+        let premine = self.premine.unwrap_or_default();
+        let cap = self.terms.and_then(|terms| terms.cap).unwrap_or_default();
+        let amount = self
+            .terms
+            .and_then(|terms| terms.amount)
+            .unwrap_or_default();
+
+        premine.checked_add(cap.checked_mul(amount)?)
+    }
+}
+
+/// Transfers `amount` of the rune `id` to the output at index `output`. An
+/// amount of zero transfers all remaining runes, an output index equal to the
+/// number of outputs splits the amount across all non-`OP_RETURN` outputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edict {
+    pub id: RuneId,
+    pub amount: u128,
+    pub output: u32,
+}
+
+/// Reason why a runestone is a cenotaph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flaw {
+    EdictOutput,
+    EdictRuneId,
+    InvalidScript,
+    Opcode,
+    SupplyOverflow,
+    TrailingIntegers,
+    TruncatedField,
+    UnrecognizedEvenTag,
+    UnrecognizedFlag,
+    Varint,
+}
+
+impl fmt::Display for Flaw {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            Flaw::EdictOutput => "edict output greater than transaction output count",
+            Flaw::EdictRuneId => "invalid rune ID in edict",
+            Flaw::InvalidScript => "invalid script in OP_RETURN",
+            Flaw::Opcode => "non-pushdata opcode in OP_RETURN",
+            Flaw::SupplyOverflow => "supply overflows u128",
+            Flaw::TrailingIntegers => "trailing integers in body",
+            Flaw::TruncatedField => "field with missing value",
+            Flaw::UnrecognizedEvenTag => "unrecognized even tag",
+            Flaw::UnrecognizedFlag => "unrecognized flag",
+            Flaw::Varint => "invalid varint",
+        };
+
+        f.write_str(message)
+    }
+}
+
+/// A malformed runestone. All runes spent by its transaction are burned,
+/// the etching (if any) creates a rune without supply.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cenotaph {
+    pub etching: Option<Rune>,
+    pub flaw: Option<Flaw>,
+    pub mint: Option<RuneId>,
+}
+
+/// The result of deciphering a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Artifact {
+    Runestone(Box<Runestone>),
+    Cenotaph(Cenotaph),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Runestone {
+    pub edicts: Vec<Edict>,
+    pub etching: Option<Etching>,
+    pub mint: Option<RuneId>,
+    /// Output receiving the runes not transferred by edicts. Defaults to the
+    /// first non-`OP_RETURN` output.
+    pub pointer: Option<u32>,
+}
+
+impl Runestone {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn edict(mut self, edict: Edict) -> Self {
+        self.edicts.push(edict);
+        self
+    }
+    pub fn etching(mut self, etching: Etching) -> Self {
+        self.etching = Some(etching);
+        self
+    }
+    pub fn mint(mut self, id: RuneId) -> Self {
+        self.mint = Some(id);
+        self
+    }
+    pub fn pointer(mut self, output: u32) -> Self {
+        self.pointer = Some(output);
+        self
+    }
+    /// Encodes the runestone as `OP_RETURN OP_13 <payload>` script.
+    pub fn encipher(&self) -> ScriptBuf {
+        let mut payload = vec![];
+
+        if let Some(etching) = &self.etching {
+            let mut flags: u128 = 1 << flag::ETCHING;
+            if etching.terms.is_some() {
+                flags |= 1 << flag::TERMS;
+            }
+            if etching.turbo {
+                flags |= 1 << flag::TURBO;
+            }
+
+            encode_field(&mut payload, tag::FLAGS, Some(flags));
+            encode_field(&mut payload, tag::RUNE, etching.rune.map(|rune| rune.0));
+            encode_field(&mut payload, tag::DIVISIBILITY, etching.divisibility);
+            encode_field(&mut payload, tag::SPACERS, etching.spacers);
+            encode_field(&mut payload, tag::SYMBOL, etching.symbol.map(u32::from));
+            encode_field(&mut payload, tag::PREMINE, etching.premine);
+
+            if let Some(terms) = etching.terms {
+                encode_field(&mut payload, tag::AMOUNT, terms.amount);
+                encode_field(&mut payload, tag::CAP, terms.cap);
+                encode_field(&mut payload, tag::HEIGHT_START, terms.height.0);
+                encode_field(&mut payload, tag::HEIGHT_END, terms.height.1);
+                encode_field(&mut payload, tag::OFFSET_START, terms.offset.0);
+                encode_field(&mut payload, tag::OFFSET_END, terms.offset.1);
+            }
+        }
+
+        if let Some(mint) = self.mint {
+            encode_field(&mut payload, tag::MINT, Some(mint.block));
+            encode_field(&mut payload, tag::MINT, Some(mint.tx));
+        }
+
+        encode_field(&mut payload, tag::POINTER, self.pointer);
+
+        if !self.edicts.is_empty() {
+            encode_varint(tag::BODY, &mut payload);
+
+            let mut edicts = self.edicts.clone();
+            edicts.sort_by_key(|edict| edict.id);
+
+            let mut previous = RuneId::default();
+            for edict in edicts {
+                let (block, tx) = previous
+                    .delta(edict.id)
+                    .expect("edicts are sorted by rune ID");
+
+                encode_varint(block, &mut payload);
+                encode_varint(tx, &mut payload);
+                encode_varint(edict.amount, &mut payload);
+                encode_varint(edict.output.into(), &mut payload);
+
+                previous = edict.id;
+            }
+        }
+
+        let mut builder = Builder::new()
+            .push_opcode(OP_RETURN)
+            .push_opcode(OP_PUSHNUM_13);
+
+        for chunk in payload.chunks(MAX_PUSH_SIZE) {
+            let push = PushBytesBuf::try_from(chunk.to_vec()).expect("chunk is a valid push");
+            builder = builder.push_slice(push);
+        }
+
+        builder.into_script()
+    }
+    /// The `OP_RETURN` output carrying the runestone.
+    pub fn to_output(&self) -> TxOutput {
+        TxOutputOpReturn::new_with_script(0, self.encipher()).into()
+    }
+    /// Decodes the first runestone of the transaction, if any.
+    pub fn decipher(tx: &Transaction) -> Option<Artifact> {
+        let payload = match Self::payload(tx)? {
+            Ok(payload) => payload,
+            Err(flaw) => return Some(Artifact::Cenotaph(cenotaph(flaw))),
+        };
+
+        let Some(integers) = decode_integers(&payload) else {
+            return Some(Artifact::Cenotaph(cenotaph(Flaw::Varint)));
+        };
+
+        let Message {
+            mut flaw,
+            edicts,
+            mut fields,
+        } = Message::from_integers(tx, &integers);
+
+        let mut flags = take(&mut fields, tag::FLAGS, |[flags]| Some(flags)).unwrap_or_default();
+
+        let etching = take_flag(&mut flags, flag::ETCHING).then(|| Etching {
+            divisibility: take(&mut fields, tag::DIVISIBILITY, |[divisibility]| {
+                u8::try_from(divisibility)
+                    .ok()
+                    .filter(|divisibility| *divisibility <= MAX_DIVISIBILITY)
+            }),
+            premine: take(&mut fields, tag::PREMINE, |[premine]| Some(premine)),
+            rune: take(&mut fields, tag::RUNE, |[rune]| Some(Rune(rune))),
+            spacers: take(&mut fields, tag::SPACERS, |[spacers]| {
+                u32::try_from(spacers)
+                    .ok()
+                    .filter(|spacers| *spacers <= MAX_SPACERS)
+            }),
+            symbol: take(&mut fields, tag::SYMBOL, |[symbol]| {
+                u32::try_from(symbol).ok().and_then(char::from_u32)
+            }),
+            terms: take_flag(&mut flags, flag::TERMS).then(|| Terms {
+                amount: take(&mut fields, tag::AMOUNT, |[amount]| Some(amount)),
+                cap: take(&mut fields, tag::CAP, |[cap]| Some(cap)),
+                height: (
+                    take(&mut fields, tag::HEIGHT_START, |[height]| {
+                        height.try_into().ok()
+                    }),
+                    take(&mut fields, tag::HEIGHT_END, |[height]| {
+                        height.try_into().ok()
+                    }),
+                ),
+                offset: (
+                    take(&mut fields, tag::OFFSET_START, |[offset]| {
+                        offset.try_into().ok()
+                    }),
+                    take(&mut fields, tag::OFFSET_END, |[offset]| {
+                        offset.try_into().ok()
+                    }),
+                ),
+            }),
+            turbo: take_flag(&mut flags, flag::TURBO),
+        });
+
+        let mint = take(&mut fields, tag::MINT, |[block, tx]| {
+            RuneId::new(block.try_into().ok()?, tx.try_into().ok()?).ok()
+        });
+
+        let pointer = take(&mut fields, tag::POINTER, |[pointer]| {
+            let pointer = u32::try_from(pointer).ok()?;
+            ((pointer as usize) < tx.output.len()).then_some(pointer)
+        });
+
+        if etching.is_some_and(|etching| etching.supply().is_none()) {
+            flaw.get_or_insert(Flaw::SupplyOverflow);
+        }
+
+        if flags != 0 {
+            flaw.get_or_insert(Flaw::UnrecognizedFlag);
+        }
+
+        if fields.keys().any(|tag| tag % 2 == 0) {
+            flaw.get_or_insert(Flaw::UnrecognizedEvenTag);
+        }
+
+        if flaw.is_some() {
+            return Some(Artifact::Cenotaph(Cenotaph {
+                etching: etching.and_then(|etching| etching.rune),
+                flaw,
+                mint,
+            }));
+        }
+
+        Some(Artifact::Runestone(Box::new(Runestone {
+            edicts,
+            etching,
+            mint,
+            pointer,
+        })))
+    }
+    /// Extracts the concatenated data pushes of the first `OP_RETURN OP_13`
+    /// output.
+    fn payload(tx: &Transaction) -> Option<std::result::Result<Vec<u8>, Flaw>> {
+        for output in &tx.output {
+            if !is_runestone(&output.script_pubkey) {
+                continue;
+            }
+
+            let mut payload = vec![];
+            let instructions = output.script_pubkey.instructions().skip(2);
+            for instruction in instructions {
+                match instruction {
+                    Ok(Instruction::PushBytes(push)) => payload.extend_from_slice(push.as_bytes()),
+                    Ok(Instruction::Op(_)) => return Some(Err(Flaw::Opcode)),
+                    Err(_) => return Some(Err(Flaw::InvalidScript)),
+                }
+            }
+
+            return Some(Ok(payload));
+        }
+
+        None
+    }
+}
+
+/// Whether the script is an `OP_RETURN OP_13` runestone output.
+pub fn is_runestone(script: &Script) -> bool {
+    let mut instructions = script.instructions();
+
+    matches!(instructions.next(), Some(Ok(Instruction::Op(OP_RETURN))))
+        && matches!(
+            instructions.next(),
+            Some(Ok(Instruction::Op(OP_PUSHNUM_13)))
+        )
+}
+
+/// The raw fields and edicts of a runestone.
+struct Message {
+    flaw: Option<Flaw>,
+    edicts: Vec<Edict>,
+    fields: BTreeMap<u128, VecDeque<u128>>,
+}
+
+impl Message {
+    fn from_integers(tx: &Transaction, integers: &[u128]) -> Self {
+        let mut edicts = vec![];
+        let mut fields = BTreeMap::<u128, VecDeque<u128>>::new();
+        let mut flaw = None;
+
+        for i in (0..integers.len()).step_by(2) {
+            let tag = integers[i];
+
+            // All remaining integers are edicts.
+            if tag == tag::BODY {
+                let mut id = RuneId::default();
+                for chunk in integers[i + 1..].chunks(4) {
+                    if chunk.len() != 4 {
+                        flaw.get_or_insert(Flaw::TrailingIntegers);
+                        break;
+                    }
+
+                    let Some(next) = id.next(chunk[0], chunk[1]) else {
+                        flaw.get_or_insert(Flaw::EdictRuneId);
+                        break;
+                    };
+
+                    // The output may be equal to the number of outputs, which
+                    // splits the amount across all outputs.
+                    let output = match u32::try_from(chunk[3]) {
+                        Ok(output) if output as usize <= tx.output.len() => output,
+                        _ => {
+                            flaw.get_or_insert(Flaw::EdictOutput);
+                            break;
+                        },
+                    };
+
+                    edicts.push(Edict {
+                        id: next,
+                        amount: chunk[2],
+                        output,
+                    });
+                    id = next;
+                }
+
+                break;
+            }
+
+            let Some(&value) = integers.get(i + 1) else {
+                flaw.get_or_insert(Flaw::TruncatedField);
+                break;
+            };
+
+            fields.entry(tag).or_default().push_back(value);
+        }
+
+        Message {
+            flaw,
+            edicts,
+            fields,
+        }
+    }
+}
+
+fn cenotaph(flaw: Flaw) -> Cenotaph {
+    Cenotaph {
+        flaw: Some(flaw),
+        ..Cenotaph::default()
+    }
+}
+
+/// Takes the first `N` values of the field if `with` accepts them. Rejected
+/// values remain, so unknown even tags turn the runestone into a cenotaph.
+fn take<const N: usize, T>(
+    fields: &mut BTreeMap<u128, VecDeque<u128>>,
+    tag: u128,
+    with: impl Fn([u128; N]) -> Option<T>,
+) -> Option<T> {
+    let field = fields.get_mut(&tag)?;
+
+    let mut values = [0; N];
+    for (i, value) in values.iter_mut().enumerate() {
+        *value = *field.get(i)?;
+    }
+
+    let value = with(values)?;
+
+    field.drain(0..N);
+    if field.is_empty() {
+        fields.remove(&tag);
+    }
+
+    Some(value)
+}
+
+fn take_flag(flags: &mut u128, position: u32) -> bool {
+    let mask = 1 << position;
+    let set = *flags & mask != 0;
+    *flags &= !mask;
+    set
+}
+
+fn encode_field<T: Into<u128>>(payload: &mut Vec<u8>, tag: u128, value: Option<T>) {
+    if let Some(value) = value {
+        encode_varint(tag, payload);
+        encode_varint(value.into(), payload);
+    }
+}
+
+/// LEB128 encoding.
+fn encode_varint(mut n: u128, payload: &mut Vec<u8>) {
+    while n >> 7 > 0 {
+        payload.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+
+    payload.push(n as u8);
+}
+
+fn decode_varint(buffer: &[u8]) -> Option<(u128, usize)> {
+    let mut n = 0u128;
+
+    for (i, &byte) in buffer.iter().enumerate() {
+        // Overlong encoding.
+        if i > 18 {
+            return None;
+        }
+
+        let value = u128::from(byte) & 0x7f;
+
+        // Overflow of the last byte.
+        if i == 18 && value & 0b0111_1100 != 0 {
+            return None;
+        }
+
+        n |= value << (7 * i);
+
+        if byte & 0x80 == 0 {
+            return Some((n, i + 1));
+        }
+    }
+
+    // Unterminated.
+    None
+}
+
+fn decode_integers(payload: &[u8]) -> Option<Vec<u128>> {
+    let mut integers = vec![];
+    let mut i = 0;
+
+    while i < payload.len() {
+        let (integer, length) = decode_varint(&payload[i..])?;
+        integers.push(integer);
+        i += length;
+    }
+
+    Some(integers)
+}
@@ -0,0 +1,102 @@
+//! Tracking of individual satoshis through a transaction, as defined by
+//! ordinal theory. Satoshis flow first-in-first-out: the satoshis of the
+//! inputs (in order) are assigned to the outputs (in order), and whatever is
+//! left over is paid to the miner as fee.
+
+use crate::{Error, Result, TxInput, TxOutput};
+use bitcoin::OutPoint;
+
+/// The location of a satoshi, given by the output it belongs to and the
+/// offset within that output (in satoshis).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SatPoint {
+    pub outpoint: OutPoint,
+    pub offset: u64,
+}
+
+impl SatPoint {
+    pub fn new(outpoint: OutPoint, offset: u64) -> Self {
+        SatPoint { outpoint, offset }
+    }
+}
+
+/// Where a satoshi ends up after the transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SatDestination {
+    /// The satoshi lands on the output `vout`, at the given offset.
+    Output { vout: u32, offset: u64 },
+    /// The satoshi lands on the `OP_RETURN` output `vout` and is burned.
+    Burned { vout: u32, offset: u64 },
+    /// The satoshi is paid to the miner, at the given offset into the fee.
+    Fee { offset: u64 },
+}
+
+impl SatDestination {
+    /// Whether the satoshi (and hence any inscription on it) is lost.
+    pub fn is_lost(&self) -> bool {
+        !matches!(self, SatDestination::Output { .. })
+    }
+}
+
+/// Determines where the satoshi at `sat_point` ends up when spending `inputs`
+/// to `outputs`. Fails if the satoshi is not part of any of the inputs.
+pub fn track_sat_point(
+    inputs: &[TxInput],
+    outputs: &[TxOutput],
+    sat_point: &SatPoint,
+) -> Result<SatDestination> {
+    // Determine the absolute offset of the satoshi within all inputs.
+    let mut absolute = 0;
+    let mut found = false;
+    for input in inputs {
+        if input.ctx().previous_output == sat_point.outpoint {
+            if sat_point.offset >= input.satoshis() {
+                return Err(Error::Todo);
+            }
+
+            absolute += sat_point.offset;
+            found = true;
+            break;
+        }
+
+        absolute += input.satoshis();
+    }
+
+    if !found {
+        return Err(Error::Todo);
+    }
+
+    // Assign the satoshi to the output covering that offset, if any.
+    let mut start = 0;
+    for (vout, output) in outputs.iter().enumerate() {
+        let end = start + output.satoshis();
+        if absolute < end {
+            let vout = vout as u32;
+            let offset = absolute - start;
+
+            return Ok(match output {
+                TxOutput::OpReturn(_) => SatDestination::Burned { vout, offset },
+                _ => SatDestination::Output { vout, offset },
+            });
+        }
+
+        start = end;
+    }
+
+    Ok(SatDestination::Fee {
+        offset: absolute - start,
+    })
+}
+
+/// The satoshi at the given offset into all inputs.
+pub(crate) fn sat_point_at(inputs: &[TxInput], mut offset: u64) -> Result<SatPoint> {
+    for input in inputs {
+        if offset < input.satoshis() {
+            return Ok(SatPoint::new(input.ctx().previous_output, offset));
+        }
+
+        offset -= input.satoshis();
+    }
+
+    Err(Error::Todo)
+}
@@ -0,0 +1,392 @@
+//! Conversion of scripts to and from their ASM representation, as used by
+//! Bitcoin Core (e.g. `decodescript`), and classification of the standard
+//! script templates.
+//!
+//! Data pushes are shown as hex, except for pushes of up to four bytes, which
+//! are shown as (decimal) numbers. Pushes which would not be reproduced
+//! exactly by the assembler, such as non-minimal encodings, are shown as raw
+//! bytes with a `0x` prefix, so that assembling the ASM always results in the
+//! original script.
+
+use crate::{Error, Result};
+use bitcoin::script::{Builder, PushBytesBuf};
+use bitcoin::{Script, ScriptBuf};
+use std::fmt;
+use tw_encoding::hex;
+
+/// Disassembles the script into its ASM representation. A truncated push is
+/// shown as `[error]`, ending the disassembly.
+pub fn to_asm(script: &Script) -> String {
+    let bytes = script.as_bytes();
+    let mut tokens = vec![];
+
+    let mut index = 0;
+    while index < bytes.len() {
+        let Some((data, end)) = read_instruction(bytes, index) else {
+            tokens.push("[error]".to_string());
+            break;
+        };
+
+        let raw = &bytes[index..end];
+        let token = match data {
+            Some(data) if data.len() <= 4 => decode_number(data).to_string(),
+            Some(data) => hex::encode(data, false),
+            None => opcode_name(raw[0]),
+        };
+
+        // Fall back to the raw bytes if the token would assemble differently.
+        match assemble_token(&token) {
+            Ok(assembled) if assembled == raw => tokens.push(token),
+            _ => tokens.push(format!("0x{}", hex::encode(raw, false))),
+        }
+
+        index = end;
+    }
+
+    tokens.join(" ")
+}
+
+/// Assembles the ASM representation into a script. Besides the output of
+/// [`to_asm`], the following tokens are accepted:
+///
+/// * Opcode names, with or without the `OP_` prefix.
+/// * Decimal numbers, which are pushed in their minimal encoding.
+/// * Hex data, which is pushed with the smallest push opcode.
+/// * Raw bytes prefixed with `0x`, which are inserted as they are.
+/// * Text in single quotes, which is pushed as is.
+pub fn from_asm(asm: &str) -> Result<ScriptBuf> {
+    let mut script = vec![];
+    for token in asm.split_whitespace() {
+        script.extend(assemble_token(token)?);
+    }
+
+    Ok(ScriptBuf::from_bytes(script))
+}
+
+fn assemble_token(token: &str) -> Result<Vec<u8>> {
+    let digits = token.strip_prefix('-').unwrap_or(token);
+
+    // Numbers, limited to the range of Bitcoin Core's parser.
+    if !digits.is_empty() && digits.len() <= 10 && digits.bytes().all(|b| b.is_ascii_digit()) {
+        let number: i64 = token.parse().map_err(|_| Error::Todo)?;
+        if number.unsigned_abs() > 0xffff_ffff {
+            return Err(Error::Todo);
+        }
+
+        return Ok(Builder::new().push_int(number).into_bytes());
+    }
+
+    if let Some(raw) = token.strip_prefix("0x") {
+        return hex::decode(raw).map_err(|_| Error::Todo);
+    }
+
+    if let Some(text) = token.strip_prefix('\'').and_then(|t| t.strip_suffix('\'')) {
+        return push_data(text.as_bytes());
+    }
+
+    if let Some(opcode) = opcode_from_name(token) {
+        return Ok(vec![opcode]);
+    }
+
+    let data = hex::decode(token).map_err(|_| Error::Todo)?;
+    push_data(&data)
+}
+
+fn push_data(data: &[u8]) -> Result<Vec<u8>> {
+    let mut buf = PushBytesBuf::new();
+    buf.extend_from_slice(data).map_err(|_| Error::Todo)?;
+
+    Ok(Builder::new().push_slice(buf).into_bytes())
+}
+
+/// Reads the instruction at `index`, returning the pushed data (if it's a
+/// push) and the index of the next instruction. Returns `None` if the push is
+/// truncated.
+fn read_instruction(bytes: &[u8], index: usize) -> Option<(Option<&[u8]>, usize)> {
+    let opcode = bytes[index];
+
+    // The size of the length prefix, followed by the length itself.
+    let (prefix, len) = match opcode {
+        0x00..=0x4b => (0, opcode as usize),
+        0x4c..=0x4e => {
+            let prefix = 1 << (opcode - 0x4c);
+            let len_bytes = bytes.get(index + 1..index + 1 + prefix)?;
+
+            let mut len = [0; 8];
+            len[..prefix].copy_from_slice(len_bytes);
+            (prefix, usize::try_from(u64::from_le_bytes(len)).ok()?)
+        },
+        _ => return Some((None, index + 1)),
+    };
+
+    let start = index + 1 + prefix;
+    let end = start.checked_add(len)?;
+
+    Some((Some(bytes.get(start..end)?), end))
+}
+
+/// Decodes a number in the encoding of the script interpreter (little-endian,
+/// with the sign as the most significant bit), without enforcing a minimal
+/// encoding.
+fn decode_number(data: &[u8]) -> i64 {
+    let Some((last, _)) = data.split_last() else {
+        return 0;
+    };
+
+    let mut number = 0i64;
+    for (index, byte) in data.iter().enumerate() {
+        number |= (*byte as i64) << (8 * index);
+    }
+
+    if last & 0x80 != 0 {
+        let sign_bit = 0x80i64 << (8 * (data.len() - 1));
+        -(number & !sign_bit)
+    } else {
+        number
+    }
+}
+
+fn opcode_from_name(name: &str) -> Option<u8> {
+    let name = if name.starts_with("OP_") {
+        name.to_string()
+    } else {
+        format!("OP_{name}")
+    };
+
+    // Pushes and numbers are written as such.
+    (0x61..=0xff)
+        .chain([0x50])
+        .find(|opcode| opcode_name(*opcode) == name)
+}
+
+/// Returns the name of the opcode as used by Bitcoin Core. Opcodes which are
+/// undefined in legacy scripts are named after their meaning in tapscript,
+/// `OP_SUCCESSx` as specified in BIP-342.
+fn opcode_name(opcode: u8) -> String {
+    let name = match opcode {
+        0x00 => "0",
+        // Pushes of one to 75 bytes.
+        0x01..=0x4b => return format!("OP_PUSHBYTES_{opcode}"),
+        0x4c => "OP_PUSHDATA1",
+        0x4d => "OP_PUSHDATA2",
+        0x4e => "OP_PUSHDATA4",
+        0x4f => "-1",
+        0x50 => "OP_RESERVED",
+        0x51..=0x60 => return (opcode - 0x50).to_string(),
+        // Control.
+        0x61 => "OP_NOP",
+        0x62 => "OP_VER",
+        0x63 => "OP_IF",
+        0x64 => "OP_NOTIF",
+        0x65 => "OP_VERIF",
+        0x66 => "OP_VERNOTIF",
+        0x67 => "OP_ELSE",
+        0x68 => "OP_ENDIF",
+        0x69 => "OP_VERIFY",
+        0x6a => "OP_RETURN",
+        // Stack.
+        0x6b => "OP_TOALTSTACK",
+        0x6c => "OP_FROMALTSTACK",
+        0x6d => "OP_2DROP",
+        0x6e => "OP_2DUP",
+        0x6f => "OP_3DUP",
+        0x70 => "OP_2OVER",
+        0x71 => "OP_2ROT",
+        0x72 => "OP_2SWAP",
+        0x73 => "OP_IFDUP",
+        0x74 => "OP_DEPTH",
+        0x75 => "OP_DROP",
+        0x76 => "OP_DUP",
+        0x77 => "OP_NIP",
+        0x78 => "OP_OVER",
+        0x79 => "OP_PICK",
+        0x7a => "OP_ROLL",
+        0x7b => "OP_ROT",
+        0x7c => "OP_SWAP",
+        0x7d => "OP_TUCK",
+        // Splice.
+        0x7e => "OP_CAT",
+        0x7f => "OP_SUBSTR",
+        0x80 => "OP_LEFT",
+        0x81 => "OP_RIGHT",
+        0x82 => "OP_SIZE",
+        // Bitwise logic.
+        0x83 => "OP_INVERT",
+        0x84 => "OP_AND",
+        0x85 => "OP_OR",
+        0x86 => "OP_XOR",
+        0x87 => "OP_EQUAL",
+        0x88 => "OP_EQUALVERIFY",
+        0x89 => "OP_RESERVED1",
+        0x8a => "OP_RESERVED2",
+        // Numeric.
+        0x8b => "OP_1ADD",
+        0x8c => "OP_1SUB",
+        0x8d => "OP_2MUL",
+        0x8e => "OP_2DIV",
+        0x8f => "OP_NEGATE",
+        0x90 => "OP_ABS",
+        0x91 => "OP_NOT",
+        0x92 => "OP_0NOTEQUAL",
+        0x93 => "OP_ADD",
+        0x94 => "OP_SUB",
+        0x95 => "OP_MUL",
+        0x96 => "OP_DIV",
+        0x97 => "OP_MOD",
+        0x98 => "OP_LSHIFT",
+        0x99 => "OP_RSHIFT",
+        0x9a => "OP_BOOLAND",
+        0x9b => "OP_BOOLOR",
+        0x9c => "OP_NUMEQUAL",
+        0x9d => "OP_NUMEQUALVERIFY",
+        0x9e => "OP_NUMNOTEQUAL",
+        0x9f => "OP_LESSTHAN",
+        0xa0 => "OP_GREATERTHAN",
+        0xa1 => "OP_LESSTHANOREQUAL",
+        0xa2 => "OP_GREATERTHANOREQUAL",
+        0xa3 => "OP_MIN",
+        0xa4 => "OP_MAX",
+        0xa5 => "OP_WITHIN",
+        // Crypto.
+        0xa6 => "OP_RIPEMD160",
+        0xa7 => "OP_SHA1",
+        0xa8 => "OP_SHA256",
+        0xa9 => "OP_HASH160",
+        0xaa => "OP_HASH256",
+        0xab => "OP_CODESEPARATOR",
+        0xac => "OP_CHECKSIG",
+        0xad => "OP_CHECKSIGVERIFY",
+        0xae => "OP_CHECKMULTISIG",
+        0xaf => "OP_CHECKMULTISIGVERIFY",
+        // Expansion.
+        0xb0 => "OP_NOP1",
+        0xb1 => "OP_CHECKLOCKTIMEVERIFY",
+        0xb2 => "OP_CHECKSEQUENCEVERIFY",
+        0xb3..=0xb9 => return format!("OP_NOP{}", opcode - 0xb3 + 4),
+        // Tapscript only.
+        0xba => "OP_CHECKSIGADD",
+        0xbb..=0xfe => return format!("OP_SUCCESS{opcode}"),
+        0xff => "OP_INVALIDOPCODE",
+    };
+
+    name.to_string()
+}
+
+/// The standard script templates, as classified by Bitcoin Core.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptTemplate {
+    PubKey,
+    PubKeyHash,
+    ScriptHash,
+    /// Bare `m`-of-`n` multisig.
+    Multisig {
+        required: u8,
+        total: u8,
+    },
+    NullData,
+    WitnessV0KeyHash,
+    WitnessV0ScriptHash,
+    WitnessV1Taproot,
+    WitnessUnknown,
+    NonStandard,
+}
+
+impl ScriptTemplate {
+    pub fn classify(script: &Script) -> Self {
+        if script.is_p2pk() {
+            ScriptTemplate::PubKey
+        } else if script.is_p2pkh() {
+            ScriptTemplate::PubKeyHash
+        } else if script.is_p2sh() {
+            ScriptTemplate::ScriptHash
+        } else if script.is_v0_p2wpkh() {
+            ScriptTemplate::WitnessV0KeyHash
+        } else if script.is_v0_p2wsh() {
+            ScriptTemplate::WitnessV0ScriptHash
+        } else if script.is_v1_p2tr() {
+            ScriptTemplate::WitnessV1Taproot
+        } else if script.is_witness_program() {
+            ScriptTemplate::WitnessUnknown
+        } else if is_null_data(script) {
+            ScriptTemplate::NullData
+        } else if let Some((required, total)) = parse_multisig(script) {
+            ScriptTemplate::Multisig { required, total }
+        } else {
+            ScriptTemplate::NonStandard
+        }
+    }
+}
+
+impl fmt::Display for ScriptTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ScriptTemplate::PubKey => "pubkey",
+            ScriptTemplate::PubKeyHash => "pubkeyhash",
+            ScriptTemplate::ScriptHash => "scripthash",
+            ScriptTemplate::Multisig { .. } => "multisig",
+            ScriptTemplate::NullData => "nulldata",
+            ScriptTemplate::WitnessV0KeyHash => "witness_v0_keyhash",
+            ScriptTemplate::WitnessV0ScriptHash => "witness_v0_scripthash",
+            ScriptTemplate::WitnessV1Taproot => "witness_v1_taproot",
+            ScriptTemplate::WitnessUnknown => "witness_unknown",
+            ScriptTemplate::NonStandard => "nonstandard",
+        };
+
+        f.write_str(name)
+    }
+}
+
+/// `OP_RETURN`, followed by data pushes only.
+fn is_null_data(script: &Script) -> bool {
+    let bytes = script.as_bytes();
+    if bytes.first() != Some(&0x6a) {
+        return false;
+    }
+
+    let mut index = 1;
+    while index < bytes.len() {
+        match read_instruction(bytes, index) {
+            Some((Some(_), end)) => index = end,
+            // Like Bitcoin Core, anything up to `OP_16` counts as a push.
+            Some((None, end)) if (0x4f..=0x60).contains(&bytes[index]) => index = end,
+            _ => return false,
+        }
+    }
+
+    true
+}
+
+/// `OP_m <pubkey>... OP_n OP_CHECKMULTISIG`.
+fn parse_multisig(script: &Script) -> Option<(u8, u8)> {
+    let bytes = script.as_bytes();
+    let (&first, rest) = bytes.split_first()?;
+    let (&checkmultisig, rest) = rest.split_last()?;
+    let (&last, mut keys) = rest.split_last()?;
+
+    if checkmultisig != 0xae || !(0x51..=0x60).contains(&first) || !(0x51..=0x60).contains(&last) {
+        return None;
+    }
+
+    let (required, total) = (first - 0x50, last - 0x50);
+
+    let mut count = 0;
+    while let Some((&len, rest)) = keys.split_first() {
+        if (len != 33 && len != 65) || rest.len() < len as usize {
+            return None;
+        }
+
+        keys = &rest[len as usize..];
+        count += 1;
+    }
+
+    (count == total && required <= total).then_some((required, total))
+}
+
+/// Shows the script as ASM in `Debug` output.
+pub(crate) struct DebugScript<'a>(pub &'a Script);
+
+impl fmt::Debug for DebugScript<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Script({})", to_asm(self.0))
+    }
+}
@@ -0,0 +1,515 @@
+//! Silent payments as specified in BIP-352. The receiver publishes a single,
+//! reusable `sp1...` address, while every payment lands on a distinct Taproot
+//! output that can't be linked to the address by outside observers.
+//!
+//! The sender derives the output key from the keys and outpoints of the
+//! transaction inputs, see [`crate::TransactionBuilder::add_silent_payments`].
+//! The receiver finds the outputs with [`SilentPaymentReceiver::scan`], given
+//! the tweak data of the transaction (as provided by an indexer or computed
+//! with [`tweak_data`]).
+
+use crate::ordinals::NUMS_POINT;
+use crate::utils::tagged_hash;
+use crate::{Error, Recipient, Result, TxInput, TxInputP2TRKeyPath, TxOutputP2TRKeyPath};
+use bitcoin::bech32::{self, FromBase32, ToBase32, Variant};
+use bitcoin::consensus::Encodable;
+use bitcoin::key::{KeyPair, TapTweak, TweakedPublicKey};
+use bitcoin::secp256k1::{Parity, PublicKey, Scalar, Secp256k1, SecretKey, XOnlyPublicKey};
+use bitcoin::{Network, OutPoint, PubkeyHash, Transaction, Txid, WPubkeyHash};
+use std::fmt;
+use std::str::FromStr;
+
+/// The version of the addresses created by this module.
+pub const VERSION: u8 = 0;
+
+/// The label of the change address, see
+/// [`SilentPaymentReceiver::labeled_address`].
+pub const CHANGE_LABEL: u32 = 0;
+
+/// The size of the address payload: the scan key followed by the spend key.
+const PAYLOAD_SIZE: usize = 66;
+
+/// A silent payment address, consisting of the scan key and the (possibly
+/// labeled) spend key of the receiver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SilentPaymentAddress {
+    scan_key: PublicKey,
+    spend_key: PublicKey,
+    network: Network,
+}
+
+impl SilentPaymentAddress {
+    pub fn new(scan_key: PublicKey, spend_key: PublicKey, network: Network) -> Self {
+        SilentPaymentAddress {
+            scan_key,
+            spend_key,
+            network,
+        }
+    }
+    pub fn scan_key(&self) -> PublicKey {
+        self.scan_key
+    }
+    pub fn spend_key(&self) -> PublicKey {
+        self.spend_key
+    }
+    /// The network of the address. All test networks share the `tsp` prefix,
+    /// which is parsed as [`Network::Testnet`].
+    pub fn network(&self) -> Network {
+        self.network
+    }
+    fn hrp(network: Network) -> &'static str {
+        match network {
+            Network::Bitcoin => "sp",
+            _ => "tsp",
+        }
+    }
+}
+
+impl fmt::Display for SilentPaymentAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut payload = self.scan_key.serialize().to_vec();
+        payload.extend(self.spend_key.serialize());
+
+        let mut data = vec![bech32::u5::try_from_u8(VERSION).expect("version is a valid u5")];
+        data.extend(payload.to_base32());
+
+        let encoded = bech32::encode(Self::hrp(self.network), data, Variant::Bech32m)
+            .map_err(|_| fmt::Error)?;
+        f.write_str(&encoded)
+    }
+}
+
+impl FromStr for SilentPaymentAddress {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Self> {
+        let (hrp, data, variant) = bech32::decode(string).map_err(|_| Error::Todo)?;
+        if variant != Variant::Bech32m {
+            return Err(Error::Todo);
+        }
+
+        let network = match hrp.as_str() {
+            "sp" => Network::Bitcoin,
+            "tsp" => Network::Testnet,
+            _ => return Err(Error::Todo),
+        };
+
+        let (version, data) = data.split_first().ok_or(Error::Todo)?;
+        let payload = Vec::<u8>::from_base32(data).map_err(|_| Error::Todo)?;
+
+        // Future versions may append data, which is ignored. Version 31 is
+        // reserved for backwards incompatible changes.
+        match version.to_u8() {
+            VERSION if payload.len() == PAYLOAD_SIZE => {},
+            1..=30 if payload.len() >= PAYLOAD_SIZE => {},
+            _ => return Err(Error::Todo),
+        }
+
+        Ok(SilentPaymentAddress {
+            scan_key: PublicKey::from_slice(&payload[..33]).map_err(|_| Error::Todo)?,
+            spend_key: PublicKey::from_slice(&payload[33..PAYLOAD_SIZE])
+                .map_err(|_| Error::Todo)?,
+            network,
+        })
+    }
+}
+
+/// Parses the hash as a secret key, which fails if it's not below the curve
+/// order (with negligible probability).
+fn hash_to_secret(hash: [u8; 32]) -> Result<SecretKey> {
+    SecretKey::from_slice(&hash).map_err(|_| Error::Todo)
+}
+
+fn serialize_outpoint(outpoint: &OutPoint) -> Vec<u8> {
+    let mut buffer = vec![];
+    outpoint
+        .consensus_encode(&mut buffer)
+        .expect("encoding to a vector never fails");
+    buffer
+}
+
+/// `hash_BIP0352/Inputs(outpoint_L || A)`, where `outpoint_L` is the smallest
+/// serialized outpoint of the transaction.
+fn input_hash<'a, I>(outpoints: I, sum: &PublicKey) -> Result<Scalar>
+where
+    I: IntoIterator<Item = &'a OutPoint>,
+{
+    let smallest = outpoints
+        .into_iter()
+        .map(serialize_outpoint)
+        .min()
+        .ok_or(Error::Todo)?;
+
+    let hash = tagged_hash("BIP0352/Inputs", &[&smallest, &sum.serialize()]);
+    Ok(Scalar::from(hash_to_secret(hash)?))
+}
+
+/// `hash_BIP0352/SharedSecret(ecdh_shared_secret || k)`
+fn shared_secret_tweak(shared_secret: &PublicKey, k: u32) -> Result<SecretKey> {
+    hash_to_secret(tagged_hash(
+        "BIP0352/SharedSecret",
+        &[&shared_secret.serialize(), &k.to_be_bytes()],
+    ))
+}
+
+/// `hash_BIP0352/Label(b_scan || m)`
+fn label_tweak(scan_key: &SecretKey, label: u32) -> Result<SecretKey> {
+    hash_to_secret(tagged_hash(
+        "BIP0352/Label",
+        &[&scan_key.secret_bytes(), &label.to_be_bytes()],
+    ))
+}
+
+/// The output key `B_spend + t * G`.
+fn output_key(spend_key: &PublicKey, tweak: &SecretKey) -> Result<XOnlyPublicKey> {
+    let secp = Secp256k1::new();
+    let key = spend_key
+        .add_exp_tweak(&secp, &Scalar::from(*tweak))
+        .map_err(|_| Error::Todo)?;
+
+    Ok(key.x_only_public_key().0)
+}
+
+/// Negates the secret key if its public key has an odd Y coordinate, as
+/// required for Taproot inputs.
+fn even_y_secret(keypair: &KeyPair) -> SecretKey {
+    match keypair.x_only_public_key().1 {
+        Parity::Even => keypair.secret_key(),
+        Parity::Odd => keypair.secret_key().negate(),
+    }
+}
+
+/// Returns the secret key of the input, or `None` if the input is not
+/// eligible for silent payments. Fails if the input is eligible but none of
+/// the keys can spend it.
+fn input_secret(input: &TxInput, keys: &[KeyPair]) -> Result<Option<SecretKey>> {
+    let secp = Secp256k1::new();
+
+    let secret = match input {
+        TxInput::P2PKH(p) => {
+            // Inputs of uncompressed keys are not eligible.
+            let uncompressed = p.is_uncompressed()
+                || keys.iter().any(|keypair| {
+                    let pubkey = bitcoin::PublicKey::new_uncompressed(keypair.public_key());
+                    &pubkey.pubkey_hash() == p.recipient().pubkey_hash()
+                });
+
+            if uncompressed {
+                return Ok(None);
+            }
+
+            keys.iter()
+                .find(|keypair| {
+                    let hash: PubkeyHash = *Recipient::<PubkeyHash>::from(*keypair).pubkey_hash();
+                    &hash == p.recipient().pubkey_hash()
+                })
+                .map(KeyPair::secret_key)
+        },
+        TxInput::P2WPKH(p) => keys
+            .iter()
+            .find(|keypair| {
+                let hash: Option<WPubkeyHash> = Recipient::<WPubkeyHash>::try_from(*keypair)
+                    .ok()
+                    .map(|recipient| *recipient.wpubkey_hash());
+                hash.as_ref() == Some(p.recipient().wpubkey_hash())
+            })
+            .map(KeyPair::secret_key),
+        // Scripts are not eligible.
+        TxInput::P2WSH(_) => return Ok(None),
+        TxInput::P2TRKeyPath(p) => {
+            let output_key = p.recipient().tweaked_pubkey().to_inner();
+            keys.iter().find_map(|keypair| {
+                let tweaked = KeyPair::from(keypair.tap_tweak(&secp, None));
+                if tweaked.x_only_public_key().0 == output_key {
+                    Some(even_y_secret(&tweaked))
+                } else if keypair.x_only_public_key().0 == output_key {
+                    // A previously received silent payment.
+                    Some(even_y_secret(keypair))
+                } else {
+                    None
+                }
+            })
+        },
+        TxInput::P2TRScriptPath(p) => {
+            let internal_key = p.recipient().untweaked_pubkey();
+
+            // Outputs without a key path are not eligible.
+            if internal_key.serialize() == NUMS_POINT {
+                return Ok(None);
+            }
+
+            // The output key of the script path spend is used, which
+            // requires the internal key.
+            let merkle_root = p.recipient().merkle_root();
+            keys.iter()
+                .find(|keypair| keypair.x_only_public_key().0 == internal_key)
+                .map(|keypair| {
+                    let tweaked = KeyPair::from(keypair.tap_tweak(&secp, Some(merkle_root)));
+                    even_y_secret(&tweaked)
+                })
+        },
+    };
+
+    secret.map(Some).ok_or(Error::Todo)
+}
+
+/// Derives the output keys paying to the silent payment addresses, in the
+/// same order. `keys` must contain the private keys of all eligible inputs.
+pub fn output_keys(
+    inputs: &[TxInput],
+    keys: &[KeyPair],
+    recipients: &[SilentPaymentAddress],
+) -> Result<Vec<TweakedPublicKey>> {
+    let secp = Secp256k1::new();
+
+    // Sum up the private keys of the eligible inputs.
+    let mut sum: Option<SecretKey> = None;
+    for input in inputs {
+        if let Some(secret) = input_secret(input, keys)? {
+            sum = Some(match sum {
+                Some(sum) => sum
+                    .add_tweak(&Scalar::from(secret))
+                    .map_err(|_| Error::Todo)?,
+                None => secret,
+            });
+        }
+    }
+
+    let sum = sum.ok_or(Error::Todo)?;
+    let input_hash = input_hash(
+        inputs.iter().map(|input| &input.ctx().previous_output),
+        &sum.public_key(&secp),
+    )?;
+    let secret = sum.mul_tweak(&input_hash).map_err(|_| Error::Todo)?;
+
+    let mut output_keys = vec![];
+    for (index, recipient) in recipients.iter().enumerate() {
+        // Outputs to the same scan key are numbered in order.
+        let k = recipients[..index]
+            .iter()
+            .filter(|other| other.scan_key == recipient.scan_key)
+            .count() as u32;
+
+        let shared_secret = recipient
+            .scan_key
+            .mul_tweak(&secp, &Scalar::from(secret))
+            .map_err(|_| Error::Todo)?;
+
+        let tweak = shared_secret_tweak(&shared_secret, k)?;
+        let key = output_key(&recipient.spend_key, &tweak)?;
+        output_keys.push(TweakedPublicKey::dangerous_assume_tweaked(key));
+    }
+
+    Ok(output_keys)
+}
+
+/// Creates the outputs paying `satoshis` to the silent payment addresses, see
+/// [`output_keys`].
+pub fn outputs(
+    inputs: &[TxInput],
+    keys: &[KeyPair],
+    payments: &[(SilentPaymentAddress, u64)],
+) -> Result<Vec<TxOutputP2TRKeyPath>> {
+    let recipients: Vec<_> = payments.iter().map(|(address, _)| *address).collect();
+    let output_keys = output_keys(inputs, keys, &recipients)?;
+
+    Ok(output_keys
+        .into_iter()
+        .zip(payments)
+        .map(|(key, (_, satoshis))| TxOutputP2TRKeyPath::new(*satoshis, Recipient::from(key)))
+        .collect())
+}
+
+/// Computes the tweak data `input_hash * A` of a transaction, from the
+/// outpoints of all inputs and the public keys of the eligible inputs. For
+/// Taproot inputs, this is the output key with an even Y coordinate.
+pub fn tweak_data(outpoints: &[OutPoint], input_keys: &[PublicKey]) -> Result<PublicKey> {
+    let keys: Vec<&PublicKey> = input_keys.iter().collect();
+    let sum = PublicKey::combine_keys(&keys).map_err(|_| Error::Todo)?;
+
+    let input_hash = input_hash(outpoints, &sum)?;
+    sum.mul_tweak(&Secp256k1::new(), &input_hash)
+        .map_err(|_| Error::Todo)
+}
+
+/// The receiver of silent payments, holding the private scan key.
+#[derive(Debug, Clone)]
+pub struct SilentPaymentReceiver {
+    scan_key: SecretKey,
+    spend_key: PublicKey,
+    labels: Vec<u32>,
+}
+
+impl SilentPaymentReceiver {
+    pub fn new(scan_key: SecretKey, spend_key: PublicKey) -> Self {
+        SilentPaymentReceiver {
+            scan_key,
+            spend_key,
+            labels: vec![],
+        }
+    }
+    /// Scans for payments to the address with the given label as well. Label
+    /// `0` is reserved for change and always scanned.
+    pub fn add_label(mut self, label: u32) -> Self {
+        if !self.labels.contains(&label) {
+            self.labels.push(label);
+        }
+
+        self
+    }
+    pub fn address(&self, network: Network) -> SilentPaymentAddress {
+        SilentPaymentAddress::new(
+            self.scan_key.public_key(&Secp256k1::new()),
+            self.spend_key,
+            network,
+        )
+    }
+    /// The address with the given label, whose spend key is `B_spend +
+    /// hash(b_scan || m) * G`. Payments to it can be told apart by the
+    /// receiver, while looking unrelated to the unlabeled address for anyone
+    /// else.
+    pub fn labeled_address(&self, label: u32, network: Network) -> Result<SilentPaymentAddress> {
+        let tweak = label_tweak(&self.scan_key, label)?;
+        let spend_key = self
+            .spend_key
+            .add_exp_tweak(&Secp256k1::new(), &Scalar::from(tweak))
+            .map_err(|_| Error::Todo)?;
+
+        Ok(SilentPaymentAddress::new(
+            self.scan_key.public_key(&Secp256k1::new()),
+            spend_key,
+            network,
+        ))
+    }
+    /// Finds the outputs of the transaction paying to the receiver, given
+    /// the tweak data of the transaction.
+    pub fn scan(
+        &self,
+        tweak_data: &PublicKey,
+        tx: &Transaction,
+    ) -> Result<Vec<SilentPaymentOutput>> {
+        let secp = Secp256k1::new();
+        let shared_secret = tweak_data
+            .mul_tweak(&secp, &Scalar::from(self.scan_key))
+            .map_err(|_| Error::Todo)?;
+
+        let mut candidates: Vec<(u32, XOnlyPublicKey, u64)> = tx
+            .output
+            .iter()
+            .enumerate()
+            .filter(|(_, output)| output.script_pubkey.is_v1_p2tr())
+            .filter_map(|(vout, output)| {
+                let key = XOnlyPublicKey::from_slice(&output.script_pubkey.as_bytes()[2..]).ok()?;
+                Some((vout as u32, key, output.value))
+            })
+            .collect();
+
+        // The change label is scanned even if it was not added.
+        let labels = std::iter::once(&CHANGE_LABEL)
+            .chain(self.labels.iter().filter(|label| **label != CHANGE_LABEL))
+            .map(|label| Ok((*label, label_tweak(&self.scan_key, *label)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut found = vec![];
+        for k in 0.. {
+            let tweak = shared_secret_tweak(&shared_secret, k)?;
+
+            // Check for the unlabeled output key first, then for each label.
+            let mut tweaks = vec![(None, tweak)];
+            for (label, label_tweak) in &labels {
+                let combined = tweak
+                    .add_tweak(&Scalar::from(*label_tweak))
+                    .map_err(|_| Error::Todo)?;
+                tweaks.push((Some(*label), combined));
+            }
+
+            let mut matched = None;
+            'search: for (label, tweak) in tweaks {
+                let key = output_key(&self.spend_key, &tweak)?;
+                for (index, (_, candidate, _)) in candidates.iter().enumerate() {
+                    if candidate == &key {
+                        matched = Some((index, label, tweak));
+                        break 'search;
+                    }
+                }
+            }
+
+            let Some((index, label, tweak)) = matched else {
+                break;
+            };
+
+            let (vout, key, satoshis) = candidates.remove(index);
+            found.push(SilentPaymentOutput {
+                txid: tx.txid(),
+                vout,
+                satoshis,
+                output_key: TweakedPublicKey::dangerous_assume_tweaked(key),
+                tweak,
+                label,
+            });
+        }
+
+        Ok(found)
+    }
+}
+
+/// An output paying to the receiver, as found by
+/// [`SilentPaymentReceiver::scan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SilentPaymentOutput {
+    txid: Txid,
+    vout: u32,
+    satoshis: u64,
+    output_key: TweakedPublicKey,
+    tweak: SecretKey,
+    label: Option<u32>,
+}
+
+impl SilentPaymentOutput {
+    pub fn txid(&self) -> Txid {
+        self.txid
+    }
+    pub fn vout(&self) -> u32 {
+        self.vout
+    }
+    pub fn satoshis(&self) -> u64 {
+        self.satoshis
+    }
+    pub fn output_key(&self) -> TweakedPublicKey {
+        self.output_key
+    }
+    /// The label of the address that was paid, if any.
+    pub fn label(&self) -> Option<u32> {
+        self.label
+    }
+    /// The tweak to be added to the private spend key, which can be stored
+    /// instead of the private key itself.
+    pub fn tweak(&self) -> [u8; 32] {
+        self.tweak.secret_bytes()
+    }
+    /// Derives the private key spending the output, `b_spend + tweak`. It
+    /// signs the key-path input without the Taproot tweak.
+    pub fn spending_key(&self, spend_key: &SecretKey) -> Result<KeyPair> {
+        let secret = spend_key
+            .add_tweak(&Scalar::from(self.tweak))
+            .map_err(|_| Error::Todo)?;
+        let keypair = KeyPair::from_secret_key(&Secp256k1::new(), &secret);
+
+        // Make sure the spend key belongs to the receiver.
+        if keypair.x_only_public_key().0 != self.output_key.to_inner() {
+            return Err(Error::Todo);
+        }
+
+        Ok(keypair)
+    }
+    /// Creates the input spending the output.
+    pub fn input(&self) -> TxInput {
+        TxInputP2TRKeyPath::new(
+            self.txid,
+            self.vout,
+            Recipient::from(self.output_key),
+            self.satoshis,
+        )
+        .into()
+    }
+}
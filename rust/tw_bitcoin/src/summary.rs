@@ -0,0 +1,252 @@
+//! A description of what a transaction does, shown to the user for
+//! confirmation before it gets signed, see [`TransactionBuilder::summary`].
+
+use crate::bitcoin_cash::CashAddress;
+use crate::brc20::BRC20Operation;
+use crate::groestlcoin::GroestlcoinAddress;
+use crate::ordinals::InscriptionContent;
+use crate::sat_point::{sat_point_at, track_sat_point, SatDestination, SatPoint};
+use crate::utils::checked_sum;
+use crate::{estimate_weight, Chain, Error, Result, TxInput, TxOutput};
+use bitcoin::absolute::LockTime;
+use bitcoin::secp256k1::{Secp256k1, XOnlyPublicKey};
+use bitcoin::{Address, Network, OutPoint, PublicKey, ScriptBuf, Sequence};
+use std::fmt;
+
+#[cfg(doc)]
+use crate::TransactionBuilder;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputType {
+    P2PKH,
+    P2WPKH,
+    P2WSH,
+    P2TRKeyPath,
+    P2TRScriptPath,
+}
+
+impl fmt::Display for InputType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            InputType::P2PKH => "P2PKH",
+            InputType::P2WPKH => "P2WPKH",
+            InputType::P2WSH => "P2WSH",
+            InputType::P2TRKeyPath => "P2TR key-path",
+            InputType::P2TRScriptPath => "P2TR script-path",
+        })
+    }
+}
+
+/// Where an output pays to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Destination {
+    Address(Address),
+    CashAddress(CashAddress),
+    GroestlcoinAddress(GroestlcoinAddress),
+    OpReturn,
+    /// A non-standard script.
+    Unknown,
+}
+
+impl fmt::Display for Destination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Destination::Address(address) => address.fmt(f),
+            Destination::CashAddress(address) => address.fmt(f),
+            Destination::GroestlcoinAddress(address) => address.fmt(f),
+            Destination::OpReturn => f.write_str("OP_RETURN"),
+            Destination::Unknown => f.write_str("unknown"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InputSummary {
+    pub previous_output: OutPoint,
+    pub satoshis: u64,
+    pub input_type: InputType,
+    pub sequence: Sequence,
+}
+
+#[derive(Debug, Clone)]
+pub struct OutputSummary {
+    pub satoshis: u64,
+    pub script_pubkey: ScriptBuf,
+    pub destination: Destination,
+    /// Whether the output pays back to one of our keys, such as the change.
+    pub is_mine: bool,
+    /// Whether the output is the commit of an inscription, which is spent by
+    /// the reveal transaction.
+    pub inscription_commit: bool,
+}
+
+/// An inscription revealed by the transaction.
+#[derive(Debug, Clone)]
+pub struct InscriptionSummary {
+    /// The index of the input revealing the inscription.
+    pub input: usize,
+    pub content: InscriptionContent,
+    /// Where the inscribed satoshi ends up.
+    pub destination: SatDestination,
+    pub brc20: Option<BRC20Operation>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TransactionSummary {
+    pub inputs: Vec<InputSummary>,
+    pub outputs: Vec<OutputSummary>,
+    /// The inputs minus the outputs, paid to the miner.
+    pub fee: u64,
+    /// The estimated virtual size of the signed transaction.
+    pub vsize: u64,
+    /// Whether an input signals replaceability as specified in BIP-125.
+    pub replaceable: bool,
+    pub lock_time: LockTime,
+    /// Whether the lock time is enforced, which requires an input with a
+    /// non-final sequence.
+    pub lock_time_enabled: bool,
+    pub inscriptions: Vec<InscriptionSummary>,
+}
+
+impl TransactionSummary {
+    /// Describes the transaction spending `inputs` to `outputs`. The outputs
+    /// paying to the P2PKH, P2WPKH or P2TR key-path address of `my_keys` are
+    /// marked as ours, and addresses are encoded for `network` of `chain`.
+    pub fn new(
+        inputs: &[TxInput],
+        outputs: &[TxOutput],
+        lock_time: LockTime,
+        my_keys: &[PublicKey],
+        network: Network,
+        chain: Chain,
+    ) -> Result<Self> {
+        let total_in = checked_sum(inputs.iter().map(|input| input.satoshis()))?;
+        let total_out = checked_sum(outputs.iter().map(|output| output.satoshis()))?;
+        let fee = total_in.checked_sub(total_out).ok_or(Error::Todo)?;
+
+        let my_scripts: Vec<ScriptBuf> = my_keys.iter().flat_map(key_scripts).collect();
+
+        let input_summaries: Vec<InputSummary> = inputs
+            .iter()
+            .map(|input| InputSummary {
+                previous_output: input.ctx().previous_output,
+                satoshis: input.satoshis(),
+                input_type: input_type(input),
+                sequence: input.ctx().sequence,
+            })
+            .collect();
+
+        let output_summaries = outputs
+            .iter()
+            .map(|output| OutputSummary {
+                satoshis: output.satoshis(),
+                script_pubkey: output.script_pubkey().to_owned(),
+                destination: destination(output, network, chain),
+                is_mine: my_scripts
+                    .iter()
+                    .any(|script| script.as_script() == output.script_pubkey()),
+                inscription_commit: matches!(
+                    output,
+                    TxOutput::P2TRScriptPath(output) if output.is_inscription_commit()
+                ),
+            })
+            .collect();
+
+        let replaceable = input_summaries.iter().any(|input| input.sequence.is_rbf());
+        let lock_time_enabled = lock_time.to_consensus_u32() != 0
+            && input_summaries
+                .iter()
+                .any(|input| input.sequence.enables_absolute_lock_time());
+
+        Ok(TransactionSummary {
+            inputs: input_summaries,
+            outputs: output_summaries,
+            fee,
+            vsize: estimate_weight(inputs, outputs).to_vbytes_ceil(),
+            replaceable,
+            lock_time,
+            lock_time_enabled,
+            inscriptions: inscriptions(inputs, outputs)?,
+        })
+    }
+    /// The fee rate in satoshis per vbyte, based on the estimated size.
+    pub fn fee_rate(&self) -> f64 {
+        self.fee as f64 / self.vsize as f64
+    }
+}
+
+fn input_type(input: &TxInput) -> InputType {
+    match input {
+        TxInput::P2PKH(_) => InputType::P2PKH,
+        TxInput::P2WPKH(_) => InputType::P2WPKH,
+        TxInput::P2WSH(_) => InputType::P2WSH,
+        TxInput::P2TRKeyPath(_) => InputType::P2TRKeyPath,
+        TxInput::P2TRScriptPath(_) => InputType::P2TRScriptPath,
+    }
+}
+
+fn destination(output: &TxOutput, network: Network, chain: Chain) -> Destination {
+    let script_pubkey = output.script_pubkey();
+
+    let destination = match (output, chain) {
+        (TxOutput::OpReturn(_), _) => return Destination::OpReturn,
+        (_, Chain::Bitcoin) => Address::from_script(script_pubkey, network)
+            .map(Destination::Address)
+            .ok(),
+        (_, Chain::BitcoinCash) => CashAddress::from_script(script_pubkey, network)
+            .map(Destination::CashAddress)
+            .ok(),
+        (_, Chain::Groestlcoin) => GroestlcoinAddress::from_script(script_pubkey, network)
+            .map(Destination::GroestlcoinAddress)
+            .ok(),
+    };
+
+    destination.unwrap_or(Destination::Unknown)
+}
+
+/// The scriptPubKeys of the single-key addresses of the public key.
+fn key_scripts(pubkey: &PublicKey) -> Vec<ScriptBuf> {
+    let mut scripts = vec![ScriptBuf::new_p2pkh(&pubkey.pubkey_hash())];
+
+    if let Some(wpubkey_hash) = pubkey.wpubkey_hash() {
+        scripts.push(ScriptBuf::new_v0_p2wpkh(&wpubkey_hash));
+    }
+
+    let xonly = XOnlyPublicKey::from(pubkey.inner);
+    scripts.push(ScriptBuf::new_v1_p2tr(&Secp256k1::new(), xonly, None));
+
+    scripts
+}
+
+/// Finds the inscriptions revealed by the script-path inputs. Each one is
+/// inscribed on the first satoshi of its input, unless its pointer refers to
+/// a satoshi of the outputs.
+fn inscriptions(inputs: &[TxInput], outputs: &[TxOutput]) -> Result<Vec<InscriptionSummary>> {
+    let total_out = checked_sum(outputs.iter().map(|output| output.satoshis()))?;
+
+    let mut inscriptions = vec![];
+    for (index, input) in inputs.iter().enumerate() {
+        let TxInput::P2TRScriptPath(script_path) = input else {
+            continue;
+        };
+
+        for content in InscriptionContent::parse_envelopes(script_path.witness()) {
+            let sat_point = match content
+                .pointer_offset()
+                .filter(|&offset| offset < total_out)
+            {
+                Some(offset) => sat_point_at(inputs, offset)?,
+                None => SatPoint::new(input.ctx().previous_output, 0),
+            };
+
+            inscriptions.push(InscriptionSummary {
+                input: index,
+                destination: track_sat_point(inputs, outputs, &sat_point)?,
+                brc20: BRC20Operation::from_content(&content),
+                content,
+            });
+        }
+    }
+
+    Ok(inscriptions)
+}
@@ -0,0 +1,183 @@
+use crate::bitcoin_cash::*;
+use crate::{Chain, TransactionBuilder, TxInput, TxInputP2PKH, TxOutputP2PKH};
+use bitcoin::key::KeyPair;
+use bitcoin::secp256k1::Secp256k1;
+use bitcoin::{Network, PublicKey, ScriptBuf, Sequence, Txid};
+use std::str::FromStr;
+use tw_encoding::hex;
+
+// Signed with Trust Wallet Core, on mainnet.
+pub const PRIVATE_KEY: &str = "7fdafb9db5bc501f2096e7d13d331dc7a75d9594af3d251313ba8b6200f4e384";
+pub const TXID: &str = "050d00e2e18ef13969606f1ceee290d3f49bd940684ce39898159352952b8ce2";
+pub const MY_SCRIPT: &str = "76a914aff1e0789e5fe316b729577665aa0a04d5b0f8c788ac";
+pub const TO_SCRIPT: &str = "76a914769bdff96a02f9135a1d19b749db6a78fe07dc9088ac";
+pub const CHANGE_SCRIPT: &str = "76a9149e089b6889e032d46e3b915a3392edfd616fb1c488ac";
+pub const SIGNED_TX: &str = "0100000001e28c2b955293159898e34c6840d99bf4d390e2ee1c6f606939f18ee1e2000d05020000006b483045022100b70d158b43cbcded60e6977e93f9a84966bc0cec6f2dfd1463d1223a90563f0d02207548d081069de570a494d0967ba388ff02641d91cadb060587ead95a98d4e3534121038eab72ec78e639d02758e7860cdec018b49498c307791f785aa3019622f4ea5bffffffff0258020000000000001976a914769bdff96a02f9135a1d19b749db6a78fe07dc9088ace5100000000000001976a9149e089b6889e032d46e3b915a3392edfd616fb1c488ac00000000";
+
+pub fn keypair() -> KeyPair {
+    KeyPair::from_seckey_slice(&Secp256k1::new(), &hex::decode(PRIVATE_KEY).unwrap()).unwrap()
+}
+
+pub fn script(hex: &str) -> ScriptBuf {
+    ScriptBuf::from_bytes(hex::decode(hex).unwrap())
+}
+
+fn builder() -> TransactionBuilder {
+    let mut input = TxInput::from(TxInputP2PKH::new_with_script(
+        Txid::from_str(TXID).unwrap(),
+        2,
+        keypair().into(),
+        5151,
+        script(MY_SCRIPT),
+    ));
+    input.set_sequence(Sequence::MAX);
+
+    TransactionBuilder::new()
+        .version(TX_VERSION)
+        .add_input(input)
+        .add_output(TxOutputP2PKH::new_with_script(600, script(TO_SCRIPT)).into())
+        .add_output(TxOutputP2PKH::new_with_script(4325, script(CHANGE_SCRIPT)).into())
+}
+
+#[test]
+fn cash_address() {
+    let key = KeyPair::from_seckey_slice(
+        &Secp256k1::new(),
+        &hex::decode("28071bf4e2b0340db41b807ed8a5514139e5d6427ff9d58dbd22b7ed187103a4").unwrap(),
+    )
+    .unwrap();
+    let pubkey = PublicKey::new(key.public_key());
+
+    let address = CashAddress::p2pkh(&pubkey, Network::Bitcoin);
+    assert_eq!(
+        address.to_string(),
+        "bitcoincash:qruxj7zq6yzpdx8dld0e9hfvt7u47zrw9gfr5hy0vh"
+    );
+    assert_eq!(
+        address.to_legacy().to_string(),
+        "1PeUvjuxyf31aJKX6kCXuaqxhmG78ZUdL1"
+    );
+
+    // The prefix is optional, and the case is ignored.
+    let address = CashAddress::from_str("qpk05r5kcd8uuzwqunn8rlx5xvuvzjqju5rch3tc0u").unwrap();
+    assert_eq!(address.network(), Network::Bitcoin);
+    assert_eq!(
+        address.script_pubkey(),
+        script("76a9146cfa0e96c34fce09c0e4e671fcd43338c14812e588ac")
+    );
+    assert_eq!(
+        address.to_legacy().to_string(),
+        "1AwDXywmyhASpCCFWkqhySgZf8KiswFoGh"
+    );
+    assert_eq!(
+        CashAddress::from_str("BITCOINCASH:QPK05R5KCD8UUZWQUNN8RLX5XVUVZJQJU5RCH3TC0U").unwrap(),
+        address
+    );
+
+    let p2sh = CashAddress::from_str("pzukqjmcyzrkh3gsqzdcy3e3d39cqxhl3g0f405k5l").unwrap();
+    assert!(p2sh.is_script());
+    assert_eq!(
+        p2sh.script_pubkey(),
+        script("a914b9604b7820876bc510009b8247316c4b801aff8a87")
+    );
+    assert_eq!(
+        p2sh.to_string(),
+        "bitcoincash:pzukqjmcyzrkh3gsqzdcy3e3d39cqxhl3g0f405k5l"
+    );
+
+    let testnet = CashAddress::p2pkh(&pubkey, Network::Signet);
+    assert!(testnet.to_string().starts_with("bchtest:q"));
+    assert_eq!(testnet.network(), Network::Testnet);
+    assert_eq!(
+        CashAddress::from_str(&testnet.to_string()).unwrap(),
+        testnet
+    );
+
+    // Wrong checksum.
+    assert!(CashAddress::from_str("pqx578nanz2h2estzmkr53zqdg6qt8xyqvffffffff").is_err());
+    // eCash and unknown prefixes.
+    assert!(CashAddress::from_str("ecash:pqx578nanz2h2estzmkr53zqdg6qt8xyqvh683mrz0").is_err());
+    assert!(CashAddress::from_str("bcash:pqx578nanz2h2estzmkr53zqdg6qt8xyqvwhn6qeyc").is_err());
+    // Characters outside of the charset, and mixed case.
+    assert!(CashAddress::from_str("poi578nanz2h2estzmkr53zqdg6qt8xyqvwhn6qeyc").is_err());
+    assert!(CashAddress::from_str("Pqx578nanz2h2estzmkr53zqdg6qt8xyqvwhn6qeyc").is_err());
+    assert!(CashAddress::from_str("pqx578nanz2h2estzmkr53zqdg6qt8xyqvwhn6qeyc").is_ok());
+    // Legacy addresses.
+    assert!(CashAddress::from_str("1AwDXywmyhASpCCFWkqhySgZf8KiswFoGh").is_err());
+}
+
+#[test]
+fn bitcoin_cash_sign_p2pkh() {
+    let signed = builder()
+        .chain(Chain::BitcoinCash)
+        .sign_inputs(keypair())
+        .unwrap();
+
+    assert_eq!(hex::encode(&signed.serialize().unwrap(), false), SIGNED_TX);
+    assert_eq!(signed.txid(), signed.inner.txid());
+
+    // The signature is marked with `SIGHASH_ALL | SIGHASH_FORKID`.
+    let script_sig = &signed.inner.input[0].script_sig;
+    let push = script_sig.instructions().next().unwrap().unwrap();
+    assert_eq!(push.push_bytes().unwrap().as_bytes().last(), Some(&0x41));
+}
+
+#[test]
+fn bitcoin_cash_signature_hash() {
+    let hashes = builder()
+        .chain(Chain::BitcoinCash)
+        .signature_hashes()
+        .unwrap();
+    let bitcoin = builder().signature_hashes().unwrap();
+    assert_ne!(hashes, bitcoin);
+
+    // The amount of the spent output is signed.
+    let mut input = TxInput::from(TxInputP2PKH::new_with_script(
+        Txid::from_str(TXID).unwrap(),
+        2,
+        keypair().into(),
+        5152,
+        script(MY_SCRIPT),
+    ));
+    input.set_sequence(Sequence::MAX);
+    let other = TransactionBuilder::new()
+        .chain(Chain::BitcoinCash)
+        .add_input(input)
+        .add_output(TxOutputP2PKH::new_with_script(600, script(TO_SCRIPT)).into())
+        .add_output(TxOutputP2PKH::new_with_script(4325, script(CHANGE_SCRIPT)).into())
+        .signature_hashes()
+        .unwrap();
+    assert_ne!(hashes, other);
+}
+
+#[test]
+fn bitcoin_cash_no_segwit() {
+    let alice = keypair();
+
+    // SegWit inputs...
+    let p2wpkh = crate::TxInputP2WPKH::new(
+        Txid::from_str(TXID).unwrap(),
+        3,
+        alice.try_into().unwrap(),
+        5151,
+    );
+    let result = builder()
+        .chain(Chain::BitcoinCash)
+        .add_input(p2wpkh.into())
+        .sign_inputs(alice);
+    assert!(result.is_err());
+
+    // ...and outputs are rejected.
+    let p2wpkh = crate::TxOutputP2WPKH::new(100, alice.try_into().unwrap());
+    let result = builder()
+        .chain(Chain::BitcoinCash)
+        .add_output(p2wpkh.into())
+        .sign_inputs(alice);
+    assert!(result.is_err());
+    let p2tr = crate::TxOutputP2TRKeyPath::new(100, alice.into());
+    let result = builder()
+        .chain(Chain::BitcoinCash)
+        .add_output(p2tr.into())
+        .sign_inputs(alice);
+    assert!(result.is_err());
+}
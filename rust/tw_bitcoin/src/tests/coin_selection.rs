@@ -0,0 +1,297 @@
+use crate::coin_selection::{CoinSelectionStrategy, CoinSelector, Utxo, MAX_STANDARD_TX_WEIGHT};
+use crate::tests::p2pkh::{ALICE_WIF, BOB_WIF};
+use crate::{
+    estimate_weight, keypair_from_wif, Recipient, TxInput, TxInputP2WPKH, TxOutput, TxOutputP2WPKH,
+};
+use bitcoin::{PublicKey, ScriptBuf, Txid, WPubkeyHash};
+use secp256k1::hashes::Hash;
+
+const SAT_VB: u64 = 10;
+
+fn alice() -> Recipient<PublicKey> {
+    Recipient::<PublicKey>::from(keypair_from_wif(ALICE_WIF).unwrap())
+}
+
+fn bob() -> Recipient<PublicKey> {
+    Recipient::<PublicKey>::from(keypair_from_wif(BOB_WIF).unwrap())
+}
+
+/// Creates a P2WPKH UTXO owned by `recipient`, with a distinct (dummy) txid.
+fn utxo(recipient: &Recipient<PublicKey>, vout: u32, satoshis: u64) -> Utxo {
+    let input = TxInputP2WPKH::new(
+        Txid::all_zeros(),
+        vout,
+        recipient.clone().try_into().unwrap(),
+        satoshis,
+    );
+
+    Utxo::new(input.into())
+}
+
+fn pay_bob(satoshis: u64) -> Vec<TxOutput> {
+    let bob: Recipient<WPubkeyHash> = bob().try_into().unwrap();
+    vec![TxOutputP2WPKH::new(satoshis, bob).into()]
+}
+
+fn change_script() -> ScriptBuf {
+    ScriptBuf::new_v0_p2wpkh(alice().wpubkey_hash().as_ref().unwrap())
+}
+
+fn vouts(inputs: &[TxInput]) -> Vec<u32> {
+    inputs
+        .iter()
+        .map(|input| input.ctx().previous_output.vout)
+        .collect()
+}
+
+#[test]
+fn coin_selection_largest_first() {
+    let alice = alice();
+    let utxos = vec![
+        utxo(&alice, 0, 10_000),
+        utxo(&alice, 1, 50_000),
+        utxo(&alice, 2, 30_000),
+    ];
+
+    let selection = CoinSelector::new(CoinSelectionStrategy::LargestFirst)
+        .sat_vb(SAT_VB)
+        .select(&utxos, &pay_bob(60_000), &change_script())
+        .unwrap();
+
+    assert_eq!(vouts(&selection.inputs), vec![1, 2]);
+    assert_eq!(selection.change + selection.fee + 60_000, 80_000);
+    assert!(selection.change > 0);
+}
+
+#[test]
+fn coin_selection_oldest_first() {
+    let alice = alice();
+    let utxos = vec![
+        // Unconfirmed, spent last.
+        utxo(&alice, 0, 50_000),
+        utxo(&alice, 1, 50_000).block_height(800_000),
+        utxo(&alice, 2, 50_000).block_height(700_000),
+    ];
+
+    let selection = CoinSelector::new(CoinSelectionStrategy::OldestFirst)
+        .sat_vb(SAT_VB)
+        .select(&utxos, &pay_bob(60_000), &change_script())
+        .unwrap();
+
+    assert_eq!(vouts(&selection.inputs), vec![1, 2]);
+}
+
+#[test]
+fn coin_selection_branch_and_bound_changeless() {
+    let alice = alice();
+    let outputs = pay_bob(50_000);
+
+    // Effective value of a P2WPKH input at 10 sat/vB: `value - 68 * 10`.
+    // Fixed part of the transaction (including the SegWit marker and a
+    // three-byte input count): `ceil(174 / 4) * 10`.
+    let fixed_fee = 440;
+    let exact = 50_000 + fixed_fee + 680;
+
+    let utxos = vec![
+        utxo(&alice, 0, 100_000),
+        utxo(&alice, 1, exact),
+        utxo(&alice, 2, 70_000),
+    ];
+
+    let selection = CoinSelector::new(CoinSelectionStrategy::BranchAndBound)
+        .sat_vb(SAT_VB)
+        .select(&utxos, &outputs, &change_script())
+        .unwrap();
+
+    assert_eq!(vouts(&selection.inputs), vec![1]);
+    assert_eq!(selection.change, 0);
+    assert_eq!(selection.fee, exact - 50_000);
+}
+
+#[test]
+fn coin_selection_branch_and_bound_fallback() {
+    let alice = alice();
+    let utxos = vec![utxo(&alice, 0, 100_000), utxo(&alice, 1, 300_000)];
+
+    // No changeless solution exists, fall back to largest-first.
+    let selection = CoinSelector::new(CoinSelectionStrategy::BranchAndBound)
+        .sat_vb(SAT_VB)
+        .select(&utxos, &pay_bob(50_000), &change_script())
+        .unwrap();
+
+    assert_eq!(vouts(&selection.inputs), vec![1]);
+    assert!(selection.change > 0);
+}
+
+#[test]
+fn coin_selection_privacy() {
+    let alice = alice();
+    let bob = bob();
+    let utxos = vec![
+        utxo(&alice, 0, 40_000),
+        utxo(&bob, 1, 100_000),
+        utxo(&alice, 2, 40_000),
+    ];
+
+    // Both of Alice's UTXOs are spent together, without linking Bob's.
+    let selection = CoinSelector::new(CoinSelectionStrategy::Privacy)
+        .sat_vb(SAT_VB)
+        .select(&utxos, &pay_bob(60_000), &change_script())
+        .unwrap();
+
+    assert_eq!(vouts(&selection.inputs), vec![0, 2]);
+}
+
+#[test]
+fn coin_selection_locked_utxos() {
+    let alice = alice();
+    let utxos = vec![
+        utxo(&alice, 0, 100_000).locked(true),
+        utxo(&alice, 1, 60_000),
+    ];
+
+    let selection = CoinSelector::new(CoinSelectionStrategy::LargestFirst)
+        .sat_vb(SAT_VB)
+        .select(&utxos, &pay_bob(50_000), &change_script())
+        .unwrap();
+
+    assert_eq!(vouts(&selection.inputs), vec![1]);
+
+    // Not enough unlocked funds.
+    let err = CoinSelector::new(CoinSelectionStrategy::LargestFirst)
+        .sat_vb(SAT_VB)
+        .select(&utxos, &pay_bob(70_000), &change_script());
+
+    assert!(err.is_err());
+}
+
+#[test]
+fn coin_selection_use_max_utxo_and_dust_filter() {
+    let alice = alice();
+    let utxos = vec![
+        utxo(&alice, 0, 100_000),
+        // Costs more to spend (680 sats) than it's worth.
+        utxo(&alice, 1, 600),
+        utxo(&alice, 2, 60_000),
+    ];
+
+    let selection = CoinSelector::new(CoinSelectionStrategy::LargestFirst)
+        .sat_vb(SAT_VB)
+        .use_max_utxo(true)
+        .select(&utxos, &pay_bob(50_000), &change_script())
+        .unwrap();
+
+    assert_eq!(vouts(&selection.inputs), vec![0, 2]);
+
+    let selection = CoinSelector::new(CoinSelectionStrategy::LargestFirst)
+        .sat_vb(SAT_VB)
+        .use_max_utxo(true)
+        .disable_dust_filter(true)
+        .select(&utxos, &pay_bob(50_000), &change_script())
+        .unwrap();
+
+    assert_eq!(vouts(&selection.inputs), vec![0, 1, 2]);
+}
+
+#[test]
+fn coin_selection_sweep() {
+    let alice = alice();
+    let bob_script = pay_bob(0)[0].script_pubkey().to_owned();
+    let utxos = vec![
+        utxo(&alice, 0, 100_000),
+        // Costs more to spend (680 sats) than it's worth.
+        utxo(&alice, 1, 600),
+        utxo(&alice, 2, 5_000),
+        utxo(&alice, 3, 60_000).locked(true),
+        utxo(&alice, 4, 60_000),
+    ];
+
+    let sweep = CoinSelector::new(CoinSelectionStrategy::default())
+        .sat_vb(SAT_VB)
+        .sweep(&utxos, &bob_script, 0)
+        .unwrap();
+
+    assert_eq!(sweep.marginal_costs, vec![680; 5]);
+    assert_eq!(sweep.skipped, vec![1, 3]);
+    assert_eq!(sweep.transactions.len(), 1);
+
+    let tx = &sweep.transactions[0];
+    assert_eq!(vouts(&tx.inputs), vec![0, 2, 4]);
+    assert_eq!(tx.output.script_pubkey(), bob_script.as_script());
+    assert_eq!(tx.output.satoshis() + tx.fee, 165_000);
+
+    // The fee covers the estimated size of the transaction.
+    let vsize = estimate_weight(&tx.inputs, std::slice::from_ref(&tx.output)).to_vbytes_ceil();
+    assert_eq!(tx.fee, vsize * SAT_VB);
+
+    // Skip UTXOs below the threshold.
+    let sweep = CoinSelector::new(CoinSelectionStrategy::default())
+        .sat_vb(SAT_VB)
+        .sweep(&utxos, &bob_script, 10_000)
+        .unwrap();
+
+    assert_eq!(sweep.skipped, vec![1, 2, 3]);
+    assert_eq!(vouts(&sweep.transactions[0].inputs), vec![0, 4]);
+
+    // Nothing left to sweep.
+    let result = CoinSelector::new(CoinSelectionStrategy::default())
+        .sat_vb(SAT_VB)
+        .sweep(&utxos, &bob_script, 1_000_000);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn coin_selection_sweep_split() {
+    let alice = alice();
+    let bob_script = pay_bob(0)[0].script_pubkey().to_owned();
+    let utxos: Vec<Utxo> = (0..3_000).map(|vout| utxo(&alice, vout, 10_000)).collect();
+
+    let sweep = CoinSelector::new(CoinSelectionStrategy::default())
+        .sat_vb(SAT_VB)
+        .sweep(&utxos, &bob_script, 0)
+        .unwrap();
+
+    assert!(sweep.skipped.is_empty());
+    assert_eq!(sweep.transactions.len(), 3);
+
+    let mut swept = vec![];
+    for tx in &sweep.transactions {
+        let weight = estimate_weight(&tx.inputs, std::slice::from_ref(&tx.output));
+        assert!(weight <= MAX_STANDARD_TX_WEIGHT);
+        assert_eq!(tx.fee, weight.to_vbytes_ceil() * SAT_VB);
+
+        swept.extend(vouts(&tx.inputs));
+    }
+
+    // Every UTXO is spent exactly once, in the given order.
+    assert_eq!(swept, (0..3_000).collect::<Vec<u32>>());
+}
+
+#[test]
+fn coin_selection_sweep_dust_chunk() {
+    let alice = alice();
+    let bob_script = pay_bob(0)[0].script_pubkey().to_owned();
+    let selector = CoinSelector::new(CoinSelectionStrategy::default()).sat_vb(SAT_VB);
+
+    // The number of UTXOs filling a single transaction.
+    let utxos: Vec<Utxo> = (0..3_000).map(|vout| utxo(&alice, vout, 10_000)).collect();
+    let full = selector.sweep(&utxos, &bob_script, 0).unwrap().transactions[0]
+        .inputs
+        .len();
+
+    // The last UTXO is worth more than its marginal cost, but its own
+    // transaction would not pay more than dust.
+    let mut utxos: Vec<Utxo> = (0..full as u32)
+        .map(|vout| utxo(&alice, vout, 10_000))
+        .collect();
+    utxos.push(utxo(&alice, full as u32, 1_000));
+
+    let sweep = selector.sweep(&utxos, &bob_script, 0).unwrap();
+    assert_eq!(sweep.transactions.len(), 1);
+    assert_eq!(sweep.transactions[0].inputs.len(), full);
+    assert_eq!(sweep.skipped, vec![full]);
+
+    // Without any transaction left, sweeping fails.
+    assert!(selector.sweep(&utxos[full..], &bob_script, 0).is_err());
+}
@@ -0,0 +1,196 @@
+use crate::decred::*;
+use crate::{TransactionBuilder, TxInputP2PKH, TxOutputP2PKH};
+use bitcoin::absolute::LockTime;
+use bitcoin::hashes::Hash;
+use bitcoin::key::KeyPair;
+use bitcoin::secp256k1::Secp256k1;
+use bitcoin::{
+    Network, OutPoint, PubkeyHash, PublicKey, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid,
+    Witness,
+};
+use std::str::FromStr;
+use tw_encoding::hex;
+
+// The vectors of the C++ implementation.
+pub const PRIVATE_KEY: &str = "22a47fa09a223f2aa079edf85a7c2d4f8720ee63e502ee2869afab7de234b80c";
+pub const ADDRESS: &str = "DsoPDLh462ULTy1QMSvBGLqGKQENerrdZDH";
+pub const ORIGIN_TXID: &str = "0ff6ff7c6774a56ccc51598b11724c9c441cadc52978ddb5f08f3511a0cc777a";
+pub const ONE_DCR: u64 = 100_000_000;
+
+const SIGNED_TX: &str = concat!(
+    // Version and serialization type.
+    "01000000",
+    // Inputs: hash, index, tree and sequence.
+    "01",
+    "0ff6ff7c6774a56ccc51598b11724c9c441cadc52978ddb5f08f3511a0cc777a",
+    "00000000",
+    "00",
+    "ffffffff",
+    // Outputs: value, script version and script.
+    "01",
+    "0000000000000000",
+    "0000",
+    "00",
+    // Lock time and expiry.
+    "00000000",
+    "00000000",
+    // Witness: value, block height, block index and signature script.
+    "01",
+    "00e1f50500000000",
+    "00000000",
+    "ffffffff",
+    "6a47304402201ac7bdf56a9d12f3bc09cf7b47cdfafc1348628f659e37b455d497cb6e7a748802202b3630eedee1bbc9248424e4a1b8671e14631a069f36ac8860dee0bb9ea1541f012102a673638cb9587cb68ea08dbef685c6f2d2a751a8b3c6f2a7e9a4999e6e4bfaf5",
+);
+
+pub fn keypair() -> KeyPair {
+    KeyPair::from_seckey_slice(&Secp256k1::new(), &hex::decode(PRIVATE_KEY).unwrap()).unwrap()
+}
+
+pub fn p2pkh_script(keypair: &KeyPair) -> ScriptBuf {
+    let hash = hash160(&keypair.public_key().serialize());
+    ScriptBuf::new_p2pkh(&PubkeyHash::from_byte_array(hash))
+}
+
+#[test]
+fn decred_address() {
+    let pubkey =
+        PublicKey::from_str("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+            .unwrap();
+
+    let address = DecredAddress::p2pkh(&pubkey, Network::Bitcoin);
+    assert_eq!(address.to_string(), "DsmcYVbP1Nmag2H4AS17UTvmWXmGeA7nLDx");
+    assert_eq!(
+        DecredAddress::from_str("DsmcYVbP1Nmag2H4AS17UTvmWXmGeA7nLDx").unwrap(),
+        address
+    );
+
+    let pubkey = PublicKey::new(keypair().public_key());
+    let address = DecredAddress::p2pkh(&pubkey, Network::Bitcoin);
+    assert_eq!(address.to_string(), ADDRESS);
+    assert_eq!(address.script_pubkey(), p2pkh_script(&keypair()));
+
+    let p2sh = DecredAddress::from_str("Dcur2mcGjmENx4DhNqDctW5wJCVyT3Qeqkx").unwrap();
+    assert!(p2sh.is_script());
+    assert!(p2sh.script_pubkey().is_p2sh());
+    assert_eq!(p2sh.to_string(), "Dcur2mcGjmENx4DhNqDctW5wJCVyT3Qeqkx");
+
+    let testnet = DecredAddress::p2pkh(&pubkey, Network::Regtest);
+    assert!(testnet.to_string().starts_with("Ts"));
+    assert_eq!(testnet.network(), Network::Testnet);
+
+    // Bitcoin addresses and other chains are rejected.
+    assert!(DecredAddress::from_str("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2").is_err());
+    assert!(DecredAddress::from_str("rnBFvgZphmN39GWzUJeUitaP22Fr9be75H").is_err());
+    assert!(DecredAddress::from_str("t3gQDEavk5VzAAHK8TrQu2BWDLxEiF1unBm").is_err());
+    assert!(DecredAddress::from_str("DsmcYVbP1Nmag2H4AS17UTvmWXmGeA7nLDy").is_err());
+}
+
+#[test]
+fn decred_txid() {
+    // The fake transaction funding the address.
+    let origin = Transaction {
+        version: 1,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::new(Txid::all_zeros(), u32::MAX),
+            script_sig: ScriptBuf::from_bytes(vec![0, 0]),
+            sequence: Sequence::MAX,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: ONE_DCR,
+            script_pubkey: p2pkh_script(&keypair()),
+        }],
+    };
+
+    let txid = DecredParams::new().txid(&origin);
+    assert_eq!(hex::encode(txid.as_byte_array(), false), ORIGIN_TXID);
+
+    // The witness is not part of the transaction ID, but the tree and
+    // expiry are.
+    let mut signed = origin.clone();
+    signed.input[0].script_sig = ScriptBuf::new();
+    assert_eq!(DecredParams::new().txid(&signed), txid);
+
+    let outpoint = origin.input[0].previous_output;
+    let stake = DecredParams::new().tree(outpoint, TREE_STAKE);
+    assert_eq!(stake.tree_of(&outpoint), TREE_STAKE);
+    assert_ne!(stake.txid(&origin), txid);
+    assert_ne!(DecredParams::new().expiry(1).txid(&origin), txid);
+}
+
+#[test]
+fn decred_sign_p2pkh() {
+    let alice = keypair();
+    let txid = Txid::from_slice(&hex::decode(ORIGIN_TXID).unwrap()).unwrap();
+
+    let input = TxInputP2PKH::new_with_script(txid, 0, alice.into(), ONE_DCR, p2pkh_script(&alice));
+    // The output is a placeholder, the full amount is paid as fee.
+    let output = TxOutputP2PKH::new_with_script(0, ScriptBuf::new());
+
+    let signed = TransactionBuilder::new()
+        .decred(DecredParams::new())
+        .disable_dust_filter(true)
+        .max_fee_rate(u64::MAX)
+        .add_input(input.into())
+        .add_output(output.into())
+        .sign_inputs(alice)
+        .unwrap();
+
+    assert_eq!(signed.inner.version, 1);
+    assert_eq!(hex::encode(&signed.serialize().unwrap(), false), SIGNED_TX);
+    assert_eq!(signed.txid(), DecredParams::new().txid(&signed.inner));
+}
+
+#[test]
+fn decred_signature_hash() {
+    let alice = keypair();
+    let txid = Txid::from_slice(&hex::decode(ORIGIN_TXID).unwrap()).unwrap();
+    let script = p2pkh_script(&alice);
+
+    let tx = Transaction {
+        version: 1,
+        lock_time: LockTime::ZERO,
+        input: vec![
+            TxIn {
+                previous_output: OutPoint::new(txid, 0),
+                ..Default::default()
+            },
+            TxIn {
+                previous_output: OutPoint::new(txid, 1),
+                ..Default::default()
+            },
+        ],
+        output: vec![TxOut {
+            value: ONE_DCR,
+            script_pubkey: script.clone(),
+        }],
+    };
+
+    use bitcoin::sighash::EcdsaSighashType::*;
+    let params = DecredParams::new();
+    let all = params.signature_hash(&tx, 0, &script, All).unwrap();
+
+    // `ANYONECANPAY` doesn't commit to the other inputs.
+    let acp = params
+        .signature_hash(&tx, 0, &script, AllPlusAnyoneCanPay)
+        .unwrap();
+    let mut other = tx.clone();
+    other.input[1].previous_output.vout = 2;
+    assert_ne!(params.signature_hash(&other, 0, &script, All).unwrap(), all);
+    assert_eq!(
+        params
+            .signature_hash(&other, 0, &script, AllPlusAnyoneCanPay)
+            .unwrap(),
+        acp
+    );
+
+    // `SINGLE` requires a matching output.
+    assert!(params.signature_hash(&tx, 0, &script, Single).is_ok());
+    assert!(params.signature_hash(&tx, 1, &script, Single).is_err());
+    assert!(params.signature_hash(&tx, 2, &script, All).is_err());
+
+    // The tree of the outpoint is committed to.
+    let stake = DecredParams::new().tree(OutPoint::new(txid, 0), TREE_STAKE);
+    assert_ne!(stake.signature_hash(&tx, 0, &script, All).unwrap(), all);
+}
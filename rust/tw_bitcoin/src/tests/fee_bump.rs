@@ -0,0 +1,219 @@
+use crate::fee_bump::{CpfpBuilder, RbfBuilder};
+use crate::tests::p2pkh::{ALICE_WIF, BOB_WIF};
+use crate::{
+    keypair_from_wif, Recipient, TransactionBuilder, TxInput, TxInputP2WPKH, TxOutput,
+    TxOutputP2WPKH, TxOutputRaw,
+};
+use bitcoin::{PublicKey, ScriptBuf, Sequence, Transaction, Txid, WPubkeyHash};
+use secp256k1::hashes::Hash;
+use secp256k1::KeyPair;
+
+fn alice() -> KeyPair {
+    keypair_from_wif(ALICE_WIF).unwrap()
+}
+
+fn alice_input(txid: Txid, vout: u32, satoshis: u64) -> TxInput {
+    let recipient = Recipient::<PublicKey>::from(alice());
+    TxInputP2WPKH::new(txid, vout, recipient.try_into().unwrap(), satoshis).into()
+}
+
+fn change_script() -> ScriptBuf {
+    let alice = Recipient::<PublicKey>::from(alice());
+    ScriptBuf::new_v0_p2wpkh(alice.wpubkey_hash().as_ref().unwrap())
+}
+
+fn pay_bob(satoshis: u64) -> TxOutput {
+    let bob = Recipient::<PublicKey>::from(keypair_from_wif(BOB_WIF).unwrap());
+    let bob: Recipient<WPubkeyHash> = bob.try_into().unwrap();
+    TxOutputP2WPKH::new(satoshis, bob).into()
+}
+
+/// Alice pays 50_000 sats to Bob and returns `change` to herself, signalling
+/// replaceability.
+fn replaceable_tx(satoshis: u64, change: u64) -> (Transaction, TxInput) {
+    let mut input = alice_input(Txid::all_zeros(), 0, satoshis);
+    input.set_sequence(Sequence::ENABLE_RBF_NO_LOCKTIME);
+
+    let tx = TransactionBuilder::new()
+        .add_input(input.clone())
+        .add_output(pay_bob(50_000))
+        .add_output(TxOutput::from_script(change_script(), change).unwrap())
+        .sign_inputs(alice())
+        .unwrap()
+        .inner;
+
+    (tx, input)
+}
+
+fn fee(tx: &Transaction, inputs: &[u64]) -> u64 {
+    inputs.iter().sum::<u64>() - tx.output.iter().map(|output| output.value).sum::<u64>()
+}
+
+#[test]
+fn rbf_reduce_change() {
+    let (original, prevout) = replaceable_tx(100_000, 49_000);
+
+    let replacement = RbfBuilder::new(original.clone(), vec![prevout])
+        .sat_vb(20)
+        .change_script(change_script())
+        .build()
+        .unwrap()
+        .sign_inputs(alice())
+        .unwrap()
+        .inner;
+
+    assert_eq!(replacement.input.len(), 1);
+    assert_eq!(
+        replacement.input[0].previous_output,
+        original.input[0].previous_output
+    );
+    assert!(replacement.is_explicitly_rbf());
+
+    // The payment is untouched, the change pays for the fee.
+    assert_eq!(replacement.output.len(), 2);
+    assert_eq!(replacement.output[0], original.output[0]);
+    assert!(replacement.output[1].value < 49_000);
+
+    let fee = fee(&replacement, &[100_000]);
+    assert!(fee >= replacement.vsize() as u64 * 20);
+    assert!(fee >= 1_000 + replacement.vsize() as u64);
+}
+
+#[test]
+fn rbf_keeps_any_payment() {
+    let mut input = alice_input(Txid::all_zeros(), 0, 100_000);
+    input.set_sequence(Sequence::ENABLE_RBF_NO_LOCKTIME);
+
+    // Payments to P2SH and to a bare script are kept as they are.
+    let p2sh = ScriptBuf::new_p2sh(&ScriptBuf::from_bytes(vec![0x51]).script_hash());
+    let bare = ScriptBuf::from_bytes(vec![0x51]);
+
+    let original = TransactionBuilder::new()
+        .add_input(input.clone())
+        .add_output(TxOutputRaw::new_with_script(20_000, p2sh).into())
+        .add_output(TxOutputRaw::new_with_script(20_000, bare).into())
+        .add_output(TxOutput::from_script(change_script(), 59_000).unwrap())
+        .sign_inputs(alice())
+        .unwrap()
+        .inner;
+
+    let replacement = RbfBuilder::new(original.clone(), vec![input])
+        .sat_vb(20)
+        .change_script(change_script())
+        .build()
+        .unwrap()
+        .sign_inputs(alice())
+        .unwrap()
+        .inner;
+
+    assert_eq!(replacement.output.len(), 3);
+    assert_eq!(replacement.output[..2], original.output[..2]);
+    assert!(replacement.output[2].value < 59_000);
+}
+
+#[test]
+fn rbf_add_input() {
+    let (original, prevout) = replaceable_tx(51_000, 500);
+    let utxo = alice_input(Txid::from_byte_array([1; 32]), 0, 20_000);
+
+    let replacement = RbfBuilder::new(original, vec![prevout])
+        .sat_vb(50)
+        .change_script(change_script())
+        .add_utxo(utxo)
+        .build()
+        .unwrap()
+        .sign_inputs(alice())
+        .unwrap()
+        .inner;
+
+    assert_eq!(replacement.input.len(), 2);
+    assert_eq!(
+        replacement.input[1].sequence,
+        Sequence::ENABLE_RBF_NO_LOCKTIME
+    );
+
+    let fee = fee(&replacement, &[51_000, 20_000]);
+    assert!(fee >= replacement.vsize() as u64 * 50);
+}
+
+#[test]
+fn rbf_invalid() {
+    let (original, prevout) = replaceable_tx(100_000, 49_000);
+
+    // Fee rate is not higher than the original one.
+    let res = RbfBuilder::new(original, vec![prevout.clone()])
+        .sat_vb(1)
+        .change_script(change_script())
+        .build();
+    assert!(res.is_err());
+
+    // Original transaction does not signal replaceability.
+    let (mut original, _) = replaceable_tx(100_000, 49_000);
+    original.input[0].sequence = Sequence::MAX;
+
+    let res = RbfBuilder::new(original, vec![prevout])
+        .sat_vb(20)
+        .change_script(change_script())
+        .build();
+    assert!(res.is_err());
+
+    // The spent amount exceeds the maximum amount of bitcoin.
+    let (original, _) = replaceable_tx(100_000, 49_000);
+    let prevout = alice_input(Txid::all_zeros(), 0, u64::MAX);
+
+    let res = RbfBuilder::new(original, vec![prevout])
+        .sat_vb(20)
+        .change_script(change_script())
+        .build();
+    assert!(res.is_err());
+}
+
+#[test]
+fn rbf_insufficient_funds() {
+    let (original, prevout) = replaceable_tx(51_000, 500);
+
+    // Additional UTXOs are required, but none are provided.
+    let res = RbfBuilder::new(original, vec![prevout])
+        .sat_vb(50)
+        .change_script(change_script())
+        .build();
+    assert!(res.is_err());
+}
+
+#[test]
+fn cpfp_spend_change() {
+    let (parent, _) = replaceable_tx(100_000, 49_500);
+    let parent_fee = fee(&parent, &[100_000]);
+
+    // Spend the change output of the parent.
+    let input = alice_input(parent.txid(), 1, 49_500);
+
+    let child = CpfpBuilder::new(parent.clone(), parent_fee, input, change_script())
+        .sat_vb(20)
+        .build()
+        .unwrap()
+        .sign_inputs(alice())
+        .unwrap()
+        .inner;
+
+    assert_eq!(child.input.len(), 1);
+    assert_eq!(child.output.len(), 1);
+
+    // The package reaches the target fee rate.
+    let child_fee = fee(&child, &[49_500]);
+    let package_vsize = (parent.vsize() + child.vsize()) as u64;
+    assert!(parent_fee + child_fee >= package_vsize * 20);
+}
+
+#[test]
+fn cpfp_invalid_input() {
+    let (parent, _) = replaceable_tx(100_000, 49_500);
+
+    // Wrong amount of the parent output.
+    let input = alice_input(parent.txid(), 1, 60_000);
+
+    let res = CpfpBuilder::new(parent, 500, input, change_script())
+        .sat_vb(20)
+        .build();
+    assert!(res.is_err());
+}
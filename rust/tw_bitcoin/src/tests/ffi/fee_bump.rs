@@ -0,0 +1,150 @@
+use crate::decred::DECRED_COIN_TYPE;
+use crate::ffi::fee_bump::{build_cpfp_transaction, build_rbf_transaction};
+use crate::tests::ffi::utils::{call_ffi_build_p2wpkh_script, ProtoTransactionBuilder};
+use crate::tests::p2pkh::{ALICE_WIF, BOB_WIF};
+use crate::{keypair_from_wif, Recipient, TransactionBuilder, TxInput, TxInputP2WPKH, TxOutput};
+use bitcoin::consensus::Decodable;
+use bitcoin::{Network, PublicKey, Sequence, Transaction, Txid};
+use secp256k1::hashes::Hash;
+use std::borrow::Cow;
+use tw_proto::Bitcoin::Proto::{
+    ChildPaysForParentInput, Network as ProtoNetwork, ReplaceByFeeInput, TransactionVariant,
+};
+
+const FULL_SATOSHIS: u64 = 100_000;
+const SEND_SATOSHIS: u64 = 50_000;
+const CHANGE_SATOSHIS: u64 = 49_000;
+
+/// Alice pays Bob and returns the change to herself, signalling
+/// replaceability.
+fn replaceable_tx(alice: &Recipient<PublicKey>, bob: &Recipient<PublicKey>) -> Transaction {
+    let keypair = keypair_from_wif(ALICE_WIF).unwrap();
+
+    let mut input: TxInput = TxInputP2WPKH::new(
+        Txid::all_zeros(),
+        0,
+        alice.clone().try_into().unwrap(),
+        FULL_SATOSHIS,
+    )
+    .into();
+    input.set_sequence(Sequence::ENABLE_RBF_NO_LOCKTIME);
+
+    let bob_script = call_ffi_build_p2wpkh_script(0, bob).script.into_owned();
+    let alice_script = call_ffi_build_p2wpkh_script(0, alice).script.into_owned();
+
+    TransactionBuilder::new()
+        .add_input(input)
+        .add_output(TxOutput::from_script(bob_script.into(), SEND_SATOSHIS).unwrap())
+        .add_output(TxOutput::from_script(alice_script.into(), CHANGE_SATOSHIS).unwrap())
+        .sign_inputs(keypair)
+        .unwrap()
+        .inner
+}
+
+#[test]
+fn proto_build_rbf_transaction() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let alice_privkey = alice.secret_bytes();
+    let alice_recipient = Recipient::<PublicKey>::from_keypair(&alice);
+    let bob_recipient = Recipient::<PublicKey>::from_keypair(&keypair_from_wif(BOB_WIF).unwrap());
+
+    let original = replaceable_tx(&alice_recipient, &bob_recipient);
+    let encoded = bitcoin::consensus::serialize(&original);
+
+    let txid = Txid::all_zeros();
+    let script = call_ffi_build_p2wpkh_script(0, &alice_recipient).script;
+
+    let prevout = ProtoTransactionBuilder::new()
+        .txid(txid.as_byte_array())
+        .vout(0)
+        .script_pubkey(&script)
+        .satoshis(FULL_SATOSHIS)
+        .variant(TransactionVariant::P2WPKH)
+        .build();
+
+    let input = ReplaceByFeeInput {
+        transaction: Cow::from(encoded.as_slice()),
+        prevouts: vec![prevout],
+        utxo: vec![],
+        change_address: alice_recipient
+            .segwit_address_string(Network::Regtest)
+            .unwrap()
+            .into(),
+        byte_fee: 20,
+        private_key: vec![Cow::from(alice_privkey.as_slice())],
+        network: ProtoNetwork::REGTEST,
+        coin_type: 0,
+    };
+
+    // The change address must be encoded for the given network.
+    let mut mainnet = input.clone();
+    mainnet.network = ProtoNetwork::MAINNET;
+    assert!(build_rbf_transaction(mainnet).is_err());
+
+    let signed = build_rbf_transaction(input).unwrap();
+    let replacement = Transaction::consensus_decode(&mut signed.encoded.as_ref()).unwrap();
+
+    assert_eq!(
+        replacement.input[0].previous_output,
+        original.input[0].previous_output
+    );
+    assert_eq!(replacement.output[0], original.output[0]);
+    assert!(replacement.output[1].value < CHANGE_SATOSHIS);
+
+    let fee = FULL_SATOSHIS - SEND_SATOSHIS - replacement.output[1].value;
+    assert!(fee >= replacement.vsize() as u64 * 20);
+}
+
+#[test]
+fn proto_build_cpfp_transaction() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let alice_privkey = alice.secret_bytes();
+    let alice_recipient = Recipient::<PublicKey>::from_keypair(&alice);
+    let bob_recipient = Recipient::<PublicKey>::from_keypair(&keypair_from_wif(BOB_WIF).unwrap());
+
+    let parent = replaceable_tx(&alice_recipient, &bob_recipient);
+    let parent_fee = FULL_SATOSHIS - SEND_SATOSHIS - CHANGE_SATOSHIS;
+    let encoded = bitcoin::consensus::serialize(&parent);
+
+    let txid = parent.txid();
+    let script = call_ffi_build_p2wpkh_script(0, &alice_recipient).script;
+
+    // Spend the change output of the parent.
+    let utxo = ProtoTransactionBuilder::new()
+        .txid(txid.as_byte_array())
+        .vout(1)
+        .script_pubkey(&script)
+        .satoshis(CHANGE_SATOSHIS)
+        .variant(TransactionVariant::P2WPKH)
+        .build();
+
+    let input = ChildPaysForParentInput {
+        parent_transaction: Cow::from(encoded.as_slice()),
+        parent_fee: parent_fee as i64,
+        utxo: Some(utxo),
+        extra_utxo: vec![],
+        to_address: alice_recipient
+            .segwit_address_string(Network::Regtest)
+            .unwrap()
+            .into(),
+        byte_fee: 20,
+        private_key: vec![Cow::from(alice_privkey.as_slice())],
+        network: ProtoNetwork::REGTEST,
+        coin_type: 0,
+    };
+
+    // Addresses of other chains are rejected.
+    let mut decred = input.clone();
+    decred.coin_type = DECRED_COIN_TYPE;
+    assert!(build_cpfp_transaction(decred).is_err());
+
+    let signed = build_cpfp_transaction(input).unwrap();
+    let child = Transaction::consensus_decode(&mut signed.encoded.as_ref()).unwrap();
+
+    assert_eq!(child.input[0].previous_output.txid, txid);
+    assert_eq!(child.output.len(), 1);
+
+    let child_fee = CHANGE_SATOSHIS - child.output[0].value;
+    let package_vsize = (parent.vsize() + child.vsize()) as u64;
+    assert!(parent_fee + child_fee >= package_vsize * 20);
+}
@@ -0,0 +1,85 @@
+use crate::ffi::inscription_batch::build_inscription_batch;
+use crate::tests::ffi::utils::{call_ffi_build_p2wpkh_script, ProtoTransactionBuilder};
+use crate::tests::p2pkh::{ALICE_WIF, BOB_WIF};
+use crate::{keypair_from_wif, Recipient};
+use bitcoin::consensus::Decodable;
+use bitcoin::{Network, PublicKey, Transaction, Txid};
+use secp256k1::hashes::Hash;
+use std::borrow::Cow;
+use tw_proto::Bitcoin::Proto::{
+    BatchInscription, InscriptionBatchInput, InscriptionBatchMode, Network as ProtoNetwork,
+    TransactionVariant,
+};
+
+const FULL_SATOSHIS: u64 = 100_000;
+
+#[test]
+fn proto_build_inscription_batch() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let alice_privkey = alice.secret_bytes();
+    let alice_recipient = Recipient::<PublicKey>::from_keypair(&alice);
+    let bob_recipient = Recipient::<PublicKey>::from_keypair(&keypair_from_wif(BOB_WIF).unwrap());
+
+    let txid = Txid::all_zeros();
+    let script = call_ffi_build_p2wpkh_script(0, &alice_recipient).script;
+
+    let utxo = ProtoTransactionBuilder::new()
+        .txid(txid.as_byte_array())
+        .vout(0)
+        .script_pubkey(&script)
+        .satoshis(FULL_SATOSHIS)
+        .variant(TransactionVariant::P2WPKH)
+        .build();
+
+    let bob_address = bob_recipient
+        .segwit_address_string(Network::Regtest)
+        .unwrap();
+    let inscription = |payload: &'static [u8]| BatchInscription {
+        mime_type: Cow::from("text/plain"),
+        payload: Cow::from(payload),
+        to_address: Cow::from(bob_address.clone()),
+    };
+
+    let input = InscriptionBatchInput {
+        private_key: vec![Cow::from(alice_privkey.as_slice())],
+        inscriptions: vec![inscription(b"first"), inscription(b"second")],
+        mode: InscriptionBatchMode::SEPARATE_INPUTS,
+        postage: 0,
+        byte_fee: 2,
+        utxo: vec![utxo],
+        change_address: Cow::from(
+            alice_recipient
+                .segwit_address_string(Network::Regtest)
+                .unwrap(),
+        ),
+        coin_selection: Default::default(),
+        network: ProtoNetwork::REGTEST,
+        coin_type: 0,
+    };
+
+    // The change address must be encoded for the same network.
+    let mut testnet_change = input.clone();
+    testnet_change.change_address = alice_recipient
+        .segwit_address_string(Network::Testnet)
+        .unwrap()
+        .into();
+    assert!(build_inscription_batch(testnet_change).is_err());
+
+    let output = build_inscription_batch(input).unwrap();
+    let commit = output.commit.unwrap();
+    let reveal = output.reveal.unwrap();
+
+    let commit_tx = Transaction::consensus_decode(&mut commit.encoded.as_ref()).unwrap();
+    let reveal_tx = Transaction::consensus_decode(&mut reveal.encoded.as_ref()).unwrap();
+
+    // The reveal spends both envelopes of the commit transaction.
+    assert_eq!(reveal_tx.input.len(), 2);
+    for (vout, input) in reveal_tx.input.iter().enumerate() {
+        assert_eq!(input.previous_output.txid, commit_tx.txid());
+        assert_eq!(input.previous_output.vout, vout as u32);
+    }
+
+    assert_eq!(reveal_tx.output.len(), 2);
+    assert!(reveal_tx.output.iter().all(|output| output.value == 10_000));
+    assert_eq!(commit_tx.output[1].value, 10_000 + output.reveal_fee as u64);
+}
@@ -1,6 +1,14 @@
 mod brc20_transfer;
+mod fee_bump;
 mod fees;
+mod inscription_batch;
+mod musig2;
 mod nft;
+mod private_key;
+mod remote_signer;
+mod runes;
 mod scripts;
+mod summary;
+mod sweep;
 mod transaction;
 mod utils;
@@ -0,0 +1,94 @@
+use crate::ffi::musig2::{musig2_aggregate, musig2_key_agg, musig2_nonce, musig2_partial_sign};
+use crate::keypair_from_wif;
+use crate::tests::p2pkh::{ALICE_WIF, BOB_WIF};
+use bitcoin::consensus::Decodable;
+use bitcoin::secp256k1::{schnorr, Message, Secp256k1, XOnlyPublicKey};
+use bitcoin::{ScriptBuf, Witness};
+use std::borrow::Cow;
+use tw_proto::Bitcoin::Proto::{
+    MuSig2AggregateInput, MuSig2KeyAggInput, MuSig2NonceInput, MuSig2PartialSignInput,
+};
+
+const SIGHASH: [u8; 32] = [7; 32];
+
+#[test]
+fn proto_musig2_sign() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+
+    let public_keys: Vec<Cow<[u8]>> = [alice, bob]
+        .iter()
+        .map(|keypair| keypair.public_key().serialize().to_vec().into())
+        .collect();
+
+    let key_agg = musig2_key_agg(MuSig2KeyAggInput {
+        public_keys: public_keys.clone(),
+    })
+    .unwrap();
+
+    let output_key = XOnlyPublicKey::from_slice(&key_agg.output_key).unwrap();
+    let script_pubkey = ScriptBuf::from_bytes(key_agg.script_pubkey.to_vec());
+    assert!(script_pubkey.is_v1_p2tr());
+    assert_eq!(&script_pubkey.as_bytes()[2..], key_agg.output_key.as_ref());
+
+    // First round: each signer generates a nonce.
+    let nonces = [alice, bob].map(|keypair| {
+        musig2_nonce(MuSig2NonceInput {
+            public_keys: public_keys.clone(),
+            private_key: keypair.secret_bytes().to_vec().into(),
+            sighash: SIGHASH.to_vec().into(),
+        })
+        .unwrap()
+    });
+
+    let public_nonces: Vec<Cow<[u8]>> = nonces
+        .iter()
+        .map(|nonce| nonce.public_nonce.to_vec().into())
+        .collect();
+
+    // Second round: each signer creates the partial signature.
+    let partial_signatures: Vec<Cow<[u8]>> = [alice, bob]
+        .iter()
+        .zip(&nonces)
+        .map(|(keypair, nonce)| {
+            musig2_partial_sign(MuSig2PartialSignInput {
+                public_keys: public_keys.clone(),
+                private_key: keypair.secret_bytes().to_vec().into(),
+                sighash: SIGHASH.to_vec().into(),
+                secret_nonce: nonce.secret_nonce.to_vec().into(),
+                public_nonces: public_nonces.clone(),
+            })
+            .unwrap()
+            .partial_signature
+            .to_vec()
+            .into()
+        })
+        .collect();
+
+    let aggregated = musig2_aggregate(MuSig2AggregateInput {
+        public_keys: public_keys.clone(),
+        sighash: SIGHASH.to_vec().into(),
+        public_nonces: public_nonces.clone(),
+        partial_signatures: partial_signatures.clone(),
+    })
+    .unwrap();
+
+    let sig = schnorr::Signature::from_slice(&aggregated.signature).unwrap();
+    let message = Message::from_slice(&SIGHASH).unwrap();
+    Secp256k1::new()
+        .verify_schnorr(&sig, &message, &output_key)
+        .unwrap();
+
+    let witness = Witness::consensus_decode(&mut aggregated.witness.as_ref()).unwrap();
+    assert_eq!(witness.len(), 1);
+    assert_eq!(&witness[0], aggregated.signature.as_ref());
+
+    // A missing partial signature fails the aggregation.
+    let missing = musig2_aggregate(MuSig2AggregateInput {
+        public_keys,
+        sighash: SIGHASH.to_vec().into(),
+        public_nonces,
+        partial_signatures: partial_signatures[..1].to_vec(),
+    });
+    assert!(missing.is_err());
+}
@@ -144,3 +144,72 @@ fn proto_sign_nft_inscription_reveal() {
     assert_ne!(hex[164..292], REVEAL_RAW_TX[164..292]);
     assert_eq!(hex[292..], REVEAL_RAW_TX[292..]);
 }
+
+#[test]
+fn proto_reveal_inscription_on_behalf_of_user() {
+    use crate::ffi::scripts::build_ordinals_inscription;
+    use crate::ordinals::{unspendable_internal_key, NUMS_POINT};
+    use crate::tests::nft::COMMIT_TXID;
+    use crate::tests::p2pkh::BOB_WIF;
+    use bitcoin::consensus::Decodable;
+    use bitcoin::Transaction;
+    use tw_proto::Bitcoin::Proto::OrdinalsInscriptionInput;
+
+    const COMMIT_SATOSHIS: u64 = 10_000;
+    const POSTAGE: u64 = 546;
+
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let alice_recipient = Recipient::<PublicKey>::from(alice);
+    let service = keypair_from_wif(BOB_WIF).unwrap();
+    let service_privkey = service.secret_bytes();
+    let service_pubkey = Recipient::<PublicKey>::from(service)
+        .public_key()
+        .to_bytes();
+
+    // The service commits to the inscription, without an internal key.
+    let commit = build_ordinals_inscription(&OrdinalsInscriptionInput {
+        mime_type: "text/plain".into(),
+        payload: b"inscribed on behalf of Alice".as_slice().into(),
+        amount: COMMIT_SATOSHIS as i64,
+        reveal_pubkey: service_pubkey.as_slice().into(),
+        internal_pubkey: Default::default(),
+    })
+    .unwrap();
+
+    // The service reveals, sending the inscription to Alice.
+    let txid = reverse_txid(COMMIT_TXID);
+    let nums = unspendable_internal_key().to_bytes();
+    let output = call_ffi_build_p2wpkh_script(POSTAGE, &alice_recipient);
+
+    let signing = ProtoSigningInputBuilder::new()
+        .private_key(&service_privkey)
+        .input(
+            ProtoTransactionBuilder::new()
+                .txid(&txid)
+                .vout(0)
+                .script_pubkey(&commit.script)
+                .spending_script(&commit.spendingScript)
+                .internal_pubkey(&nums)
+                .satoshis(COMMIT_SATOSHIS)
+                .variant(TransactionVariant::NFTINSCRIPTION)
+                .build(),
+        )
+        .output(
+            ProtoTransactionBuilder::new()
+                .script_pubkey(&output.script)
+                .satoshis(POSTAGE)
+                .variant(TransactionVariant::P2WPKH)
+                .build(),
+        )
+        .build();
+
+    let signed = taproot_build_and_sign_transaction(signing).unwrap();
+    let tx = Transaction::consensus_decode(&mut signed.encoded.as_ref()).unwrap();
+
+    // The witness contains the service signature, the envelope and the
+    // control block, which commits to the NUMS internal key.
+    let witness = tx.input[0].witness.to_vec();
+    assert_eq!(witness.len(), 3);
+    assert_eq!(witness[1], commit.spendingScript.as_ref());
+    assert_eq!(&witness[2][1..33], NUMS_POINT.as_slice());
+}
@@ -0,0 +1,286 @@
+use crate::decred::DECRED_COIN_TYPE;
+use crate::ffi::private_key::tw_bitcoin_decode_private_key;
+use crate::ffi::taproot_build_and_sign_transaction;
+use crate::groestlcoin::GROESTLCOIN_COIN_TYPE;
+use crate::hasher::ChainHashes;
+use crate::tests::ffi::utils::{
+    call_ffi_build_p2tr_key_path_script, reverse_txid, ProtoSigningInputBuilder,
+    ProtoTransactionBuilder,
+};
+use crate::tests::p2tr_key_path::*;
+use crate::{
+    keypair_from_wif, private_key_from_wif, Recipient, DOGECOIN_COIN_TYPE, LITECOIN_COIN_TYPE,
+};
+use bitcoin::{Network, PublicKey, ScriptBuf};
+use std::borrow::Cow;
+use tw_encoding::hex;
+use tw_proto::Bitcoin::Proto::{DecodedPrivateKey, EncodedPrivateKey, TransactionVariant};
+use tw_proto::Common::Proto::SigningError;
+
+// BIP-32 test vector 1.
+const XPRV: &str = "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi";
+const XPRV_PATH: &str = "m/0'/1";
+const XPRV_DERIVED: &str = "3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368";
+
+// Bob's key, WIF encoded for Litecoin and Dogecoin mainnet.
+const LITECOIN_WIF: &str = "T4MKWCb3GwbAQvFc3GLGPApw1UYo4G3ohvzyjzSgXR7MCF9HELiW";
+const DOGECOIN_WIF: &str = "QPuyDJ6r8B6gsc1n5uEC438AXew42jAimyndfpCygoYY8HZCEjPq";
+
+// The WIF test vectors of `dcrutil`.
+const DECRED_WIF: &str = "PmQdMn8xafwaQouk8ngs1CccRCB1ZmsqQxBaxNR4vhQi5a5QB5716";
+const DECRED_WIF_KEY: &str = "0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d";
+const DECRED_TESTNET_WIF: &str = "PtWVDUidYaiiNT5e2Sfb1Ah4evbaSopZJkkpFBuzkJYcYteugvdFg";
+const DECRED_TESTNET_WIF_KEY: &str =
+    "dda35a1488fb97b6eb3fe6e9ef2a25814e396fb5dc295fe994b96789b21a0398";
+
+fn call_ffi_decode_private_key(encoded: &EncodedPrivateKey) -> Option<DecodedPrivateKey<'static>> {
+    let serialized = tw_proto::serialize(encoded).unwrap();
+
+    let raw = unsafe { tw_bitcoin_decode_private_key(serialized.as_ptr(), serialized.len()) };
+    if raw.data().is_null() {
+        return None;
+    }
+
+    let raw = unsafe { raw.into_vec() };
+    let decoded: DecodedPrivateKey = tw_proto::deserialize(&raw).unwrap();
+
+    Some(DecodedPrivateKey {
+        private_key: decoded.private_key.into_owned().into(),
+        public_key: decoded.public_key.into_owned().into(),
+        compressed: decoded.compressed,
+    })
+}
+
+/// Bob's key, WIF encoded with an uncompressed public key.
+fn uncompressed_wif() -> String {
+    let mut private_key = private_key_from_wif(BOB_WIF, 0, Network::Testnet).unwrap();
+    private_key.compressed = false;
+    private_key.to_wif()
+}
+
+#[test]
+fn ffi_decode_private_key_wif() {
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+
+    let mut encoded = EncodedPrivateKey {
+        wif: BOB_WIF.into(),
+        testnet: true,
+        ..Default::default()
+    };
+
+    let decoded = call_ffi_decode_private_key(&encoded).unwrap();
+    assert_eq!(decoded.private_key, bob.secret_bytes().as_slice());
+    assert_eq!(decoded.public_key, bob.public_key().serialize().as_slice());
+    assert!(decoded.compressed);
+
+    // The key is encoded for testnet.
+    encoded.testnet = false;
+    assert!(call_ffi_decode_private_key(&encoded).is_none());
+
+    let encoded = EncodedPrivateKey {
+        wif: uncompressed_wif().into(),
+        testnet: true,
+        ..Default::default()
+    };
+
+    let decoded = call_ffi_decode_private_key(&encoded).unwrap();
+    assert_eq!(decoded.private_key, bob.secret_bytes().as_slice());
+    assert_eq!(
+        decoded.public_key,
+        bob.public_key().serialize_uncompressed().as_slice()
+    );
+    assert!(!decoded.compressed);
+}
+
+#[test]
+fn ffi_decode_private_key_xprv() {
+    let mut encoded = EncodedPrivateKey {
+        xprv: XPRV.into(),
+        derivation_path: XPRV_PATH.into(),
+        ..Default::default()
+    };
+
+    let decoded = call_ffi_decode_private_key(&encoded).unwrap();
+    assert_eq!(hex::encode(&decoded.private_key, false), XPRV_DERIVED);
+    assert!(decoded.compressed);
+
+    // The key is encoded for mainnet.
+    encoded.testnet = true;
+    assert!(call_ffi_decode_private_key(&encoded).is_none());
+
+    encoded.testnet = false;
+    encoded.derivation_path = "m/0'/x".into();
+    assert!(call_ffi_decode_private_key(&encoded).is_none());
+}
+
+#[test]
+fn proto_sign_with_encoded_private_key() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let alice_recipient = Recipient::<PublicKey>::from_keypair(&alice);
+
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+    let bob_recipient = Recipient::<PublicKey>::from_keypair(&bob);
+
+    let txid = reverse_txid(SECOND_TXID);
+
+    let input = call_ffi_build_p2tr_key_path_script(SEND_SATOSHIS_TO_BOB, &bob_recipient);
+    let output = call_ffi_build_p2tr_key_path_script(SEND_SATOSHIS_TO_ALICE, &alice_recipient);
+
+    let mut signing = ProtoSigningInputBuilder::new()
+        .input(
+            ProtoTransactionBuilder::new()
+                .txid(&txid)
+                .vout(0)
+                .script_pubkey(&input.script)
+                .satoshis(SEND_SATOSHIS_TO_BOB)
+                .variant(TransactionVariant::P2TRKEYPATH)
+                .build(),
+        )
+        .output(
+            ProtoTransactionBuilder::new()
+                .script_pubkey(&output.script)
+                .satoshis(SEND_SATOSHIS_TO_ALICE)
+                .variant(TransactionVariant::P2TRKEYPATH)
+                .build(),
+        )
+        .build();
+
+    signing.deterministic_schnorr = true;
+    signing.encoded_private_key = vec![EncodedPrivateKey {
+        wif: BOB_WIF.into(),
+        testnet: true,
+        ..Default::default()
+    }];
+
+    let signed = taproot_build_and_sign_transaction(signing.clone()).unwrap();
+    assert_eq!(signed.error, SigningError::OK);
+    assert_eq!(hex::encode(&signed.encoded, false), SECOND_TX_RAW);
+
+    // Uncompressed keys are rejected for Taproot inputs.
+    let uncompressed = uncompressed_wif();
+    signing.encoded_private_key[0].wif = Cow::from(uncompressed.as_str());
+
+    let signed = taproot_build_and_sign_transaction(signing.clone()).unwrap();
+    assert_eq!(signed.error, SigningError::Error_uncompressed_key);
+    assert!(signed.encoded.is_empty());
+
+    // Keys of another network are rejected.
+    signing.encoded_private_key[0].wif = BOB_WIF.into();
+    signing.encoded_private_key[0].testnet = false;
+    assert!(taproot_build_and_sign_transaction(signing).is_err());
+}
+
+#[test]
+fn proto_sign_uncompressed_p2pkh() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let alice_recipient = Recipient::<PublicKey>::from_keypair(&alice);
+
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+    let bob_privkey = bob.secret_bytes();
+    let bob_uncompressed = Recipient::<PublicKey>::from_keypair_uncompressed(&bob);
+
+    let txid = reverse_txid(SECOND_TXID);
+
+    // Bob's coins are held at the address of his uncompressed public key.
+    let input = ScriptBuf::new_p2pkh(&bob_uncompressed.pubkey_hash());
+    let output = call_ffi_build_p2tr_key_path_script(SEND_SATOSHIS_TO_ALICE, &alice_recipient);
+
+    let mut signing = ProtoSigningInputBuilder::new()
+        .private_key(&bob_privkey)
+        .input(
+            ProtoTransactionBuilder::new()
+                .txid(&txid)
+                .vout(0)
+                .script_pubkey(input.as_bytes())
+                .satoshis(SEND_SATOSHIS_TO_BOB)
+                .variant(TransactionVariant::P2PKH)
+                .build(),
+        )
+        .output(
+            ProtoTransactionBuilder::new()
+                .script_pubkey(&output.script)
+                .satoshis(SEND_SATOSHIS_TO_ALICE)
+                .variant(TransactionVariant::P2TRKEYPATH)
+                .build(),
+        )
+        .build();
+
+    // The compressed key can't claim the input.
+    assert!(taproot_build_and_sign_transaction(signing.clone()).is_err());
+
+    signing.uncompressed_public_key = true;
+    let signed = taproot_build_and_sign_transaction(signing.clone()).unwrap();
+    assert_eq!(signed.error, SigningError::OK);
+
+    // The scriptSig ends with the uncompressed public key.
+    let script_sig = &signed.transaction.as_ref().unwrap().inputs[0].script;
+    assert!(script_sig.ends_with(&bob_uncompressed.public_key().to_bytes()));
+
+    // The uncompressed WIF results in the same transaction.
+    let uncompressed = uncompressed_wif();
+    signing.private_key = vec![];
+    signing.uncompressed_public_key = false;
+    signing.encoded_private_key = vec![EncodedPrivateKey {
+        wif: Cow::from(uncompressed.as_str()),
+        testnet: true,
+        ..Default::default()
+    }];
+
+    let signed_wif = taproot_build_and_sign_transaction(signing).unwrap();
+    assert_eq!(signed_wif.encoded, signed.encoded);
+}
+
+#[test]
+fn ffi_decode_private_key_wif_coins() {
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+
+    let encoded = |wif: &str, coin_type: u32| EncodedPrivateKey {
+        wif: wif.to_string().into(),
+        coin_type,
+        ..Default::default()
+    };
+
+    for (wif, coin_type) in [
+        (LITECOIN_WIF, LITECOIN_COIN_TYPE),
+        (DOGECOIN_WIF, DOGECOIN_COIN_TYPE),
+    ] {
+        let decoded = call_ffi_decode_private_key(&encoded(wif, coin_type)).unwrap();
+        assert_eq!(decoded.private_key, bob.secret_bytes().as_slice());
+        assert!(decoded.compressed);
+
+        // The version prefix is the one of another coin.
+        assert!(call_ffi_decode_private_key(&encoded(wif, 0)).is_none());
+    }
+
+    // Groestlcoin uses Bitcoin's version prefix, but another checksum.
+    let mut payload = vec![0x80];
+    payload.extend(bob.secret_bytes());
+    payload.push(0x01);
+
+    let groestlcoin_wif = ChainHashes::GROESTLCOIN.base58check_encode(&payload);
+    let bitcoin_wif = ChainHashes::BITCOIN.base58check_encode(&payload);
+
+    let decoded =
+        call_ffi_decode_private_key(&encoded(&groestlcoin_wif, GROESTLCOIN_COIN_TYPE)).unwrap();
+    assert_eq!(decoded.private_key, bob.secret_bytes().as_slice());
+    assert!(call_ffi_decode_private_key(&encoded(&bitcoin_wif, GROESTLCOIN_COIN_TYPE)).is_none());
+    assert!(call_ffi_decode_private_key(&encoded(&groestlcoin_wif, 0)).is_none());
+
+    // Decred keys are always compressed.
+    let decoded = call_ffi_decode_private_key(&encoded(DECRED_WIF, DECRED_COIN_TYPE)).unwrap();
+    assert_eq!(hex::encode(&decoded.private_key, false), DECRED_WIF_KEY);
+    assert!(decoded.compressed);
+
+    let mut testnet = encoded(DECRED_TESTNET_WIF, DECRED_COIN_TYPE);
+    assert!(call_ffi_decode_private_key(&testnet).is_none());
+
+    testnet.testnet = true;
+    let decoded = call_ffi_decode_private_key(&testnet).unwrap();
+    assert_eq!(
+        hex::encode(&decoded.private_key, false),
+        DECRED_TESTNET_WIF_KEY
+    );
+
+    // Unknown coins are rejected.
+    assert!(call_ffi_decode_private_key(&encoded(BOB_WIF, 1)).is_none());
+}
@@ -0,0 +1,161 @@
+use crate::claim::{AuxRand, KeyPairSigner, SigningKey};
+use crate::ffi::remote_signer::{
+    tw_bitcoin_sign_with_callback, CSignCallback, CSignStatus, CSignatureKind,
+};
+use crate::tests::ffi::utils::{
+    call_ffi_build_p2tr_key_path_script, reverse_txid, ProtoSigningInputBuilder,
+    ProtoTransactionBuilder,
+};
+use crate::tests::p2tr_key_path::*;
+use crate::{keypair_from_wif, Recipient};
+use bitcoin::secp256k1::{KeyPair, Message};
+use bitcoin::PublicKey;
+use std::ffi::c_void;
+use tw_encoding::hex;
+use tw_memory::ffi::c_byte_array::CByteArray;
+use tw_proto::Bitcoin::Proto::{SigningOutput, TransactionVariant};
+use tw_proto::Common::Proto::SigningError;
+
+const KEY_ID: &[u8] = b"strongbox/bob";
+
+unsafe extern "C" fn sign_with_keypair(
+    context: *mut c_void,
+    sighash: *const u8,
+    _input_index: usize,
+    key_id: *const u8,
+    key_id_len: usize,
+    kind: CSignatureKind,
+    signature: *mut u8,
+    signature_len: *mut usize,
+) -> u32 {
+    assert_eq!(std::slice::from_raw_parts(key_id, key_id_len), KEY_ID);
+    assert_eq!(kind, CSignatureKind::SchnorrKeyPath);
+
+    let keypair = *(context as *const KeyPair);
+    let sighash = Message::from_slice(std::slice::from_raw_parts(sighash, 32)).unwrap();
+
+    let sig = KeyPairSigner::new(keypair)
+        .aux_rand(AuxRand::Deterministic)
+        .sign_schnorr(&sighash, true)
+        .unwrap();
+
+    std::ptr::copy_nonoverlapping(sig.as_ref().as_ptr(), signature, 64);
+    *signature_len = 64;
+
+    CSignStatus::Ok as u32
+}
+
+unsafe extern "C" fn decline(
+    _context: *mut c_void,
+    _sighash: *const u8,
+    _input_index: usize,
+    _key_id: *const u8,
+    _key_id_len: usize,
+    _kind: CSignatureKind,
+    _signature: *mut u8,
+    _signature_len: *mut usize,
+) -> u32 {
+    CSignStatus::Declined as u32
+}
+
+unsafe extern "C" fn unknown_status(
+    _context: *mut c_void,
+    _sighash: *const u8,
+    _input_index: usize,
+    _key_id: *const u8,
+    _key_id_len: usize,
+    _kind: CSignatureKind,
+    _signature: *mut u8,
+    _signature_len: *mut usize,
+) -> u32 {
+    42
+}
+
+/// The serialized `SigningInput` of Bob's P2TR key-path spend.
+fn signing_input() -> Vec<u8> {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let alice_recipient = Recipient::<PublicKey>::from_keypair(&alice);
+
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+    let bob_recipient = Recipient::<PublicKey>::from_keypair(&bob);
+
+    let txid = reverse_txid(SECOND_TXID);
+
+    let input = call_ffi_build_p2tr_key_path_script(SEND_SATOSHIS_TO_BOB, &bob_recipient);
+    let output = call_ffi_build_p2tr_key_path_script(SEND_SATOSHIS_TO_ALICE, &alice_recipient);
+
+    // No private key is provided.
+    let signing = ProtoSigningInputBuilder::new()
+        .input(
+            ProtoTransactionBuilder::new()
+                .txid(&txid)
+                .vout(0)
+                .script_pubkey(&input.script)
+                .satoshis(SEND_SATOSHIS_TO_BOB)
+                .variant(TransactionVariant::P2TRKEYPATH)
+                .build(),
+        )
+        .output(
+            ProtoTransactionBuilder::new()
+                .script_pubkey(&output.script)
+                .satoshis(SEND_SATOSHIS_TO_ALICE)
+                .variant(TransactionVariant::P2TRKEYPATH)
+                .build(),
+        )
+        .build();
+
+    tw_proto::serialize(&signing).unwrap()
+}
+
+/// Signs Bob's spend, the callback signs with the `host_key`.
+fn call_ffi_sign_with_callback(callback: CSignCallback, host_key: &KeyPair) -> CByteArray {
+    let serialized = signing_input();
+    let pubkey = keypair_from_wif(BOB_WIF).unwrap().public_key().serialize();
+
+    unsafe {
+        tw_bitcoin_sign_with_callback(
+            serialized.as_ptr(),
+            serialized.len(),
+            pubkey.as_ptr(),
+            pubkey.len(),
+            KEY_ID.as_ptr(),
+            KEY_ID.len(),
+            callback,
+            host_key as *const KeyPair as *mut c_void,
+        )
+    }
+}
+
+#[test]
+fn ffi_sign_with_callback() {
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+
+    let raw = unsafe { call_ffi_sign_with_callback(sign_with_keypair, &bob).into_vec() };
+    let signed: SigningOutput = tw_proto::deserialize(&raw).unwrap();
+    assert_eq!(signed.error, SigningError::OK);
+    assert_eq!(hex::encode(&signed.encoded, false), SECOND_TX_RAW);
+}
+
+#[test]
+fn ffi_sign_with_callback_declined() {
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+
+    let raw = unsafe { call_ffi_sign_with_callback(decline, &bob).into_vec() };
+    let declined: SigningOutput = tw_proto::deserialize(&raw).unwrap();
+    assert_eq!(declined.error, SigningError::Error_signing);
+    assert!(declined.encoded.is_empty());
+
+    // The signature was created with another key.
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let raw = call_ffi_sign_with_callback(sign_with_keypair, &alice);
+    assert!(raw.data().is_null());
+}
+
+#[test]
+fn ffi_sign_with_callback_unknown_status() {
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+
+    // An unknown status is treated as a failure.
+    let raw = call_ffi_sign_with_callback(unknown_status, &bob);
+    assert!(raw.data().is_null());
+}
@@ -0,0 +1,150 @@
+use crate::ffi::runes::{tw_bitcoin_build_runestone, tw_bitcoin_decipher_runestone};
+use crate::ffi::taproot_build_and_sign_transaction;
+use crate::runes::{Edict, Etching, RuneId, Runestone, SpacedRune, Terms};
+use crate::tests::ffi::utils::{
+    call_ffi_build_p2wpkh_script, reverse_txid, ProtoSigningInputBuilder, ProtoTransactionBuilder,
+};
+use crate::tests::p2wpkh::{ALICE_WIF, BOB_WIF, TXID};
+use crate::{keypair_from_wif, Recipient};
+use bitcoin::PublicKey;
+use std::borrow::Cow;
+use tw_proto::Bitcoin::Proto::{
+    RuneEdict, RuneEtching, RuneId as ProtoRuneId, RuneTerms, Runestone as ProtoRunestone,
+    RunestoneArtifact, TransactionOutput, TransactionVariant,
+};
+
+fn call_ffi_build_runestone(runestone: &ProtoRunestone) -> TransactionOutput<'static> {
+    let serialized = tw_proto::serialize(runestone).unwrap();
+    let raw =
+        unsafe { tw_bitcoin_build_runestone(serialized.as_ptr(), serialized.len()).into_vec() };
+    let output: TransactionOutput = tw_proto::deserialize(&raw).unwrap();
+
+    TransactionOutput {
+        value: output.value,
+        script: Cow::from(output.script.to_vec()),
+        spendingScript: Cow::default(),
+    }
+}
+
+#[test]
+fn proto_build_runestone() {
+    let proto = ProtoRunestone {
+        edicts: vec![RuneEdict {
+            id: Some(ProtoRuneId { block: 1, tx: 0 }),
+            amount: "1000".into(),
+            output: 1,
+        }],
+        etching: Some(RuneEtching {
+            rune: "UNCOMMON•GOODS".into(),
+            symbol: "⧉".into(),
+            terms: Some(RuneTerms {
+                amount: "1".into(),
+                cap: "340282366920938463463374607431768211455".into(),
+                height_start: 840_000,
+                height_end: 1_050_000,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        mint: None,
+        pointer: None,
+    };
+
+    let output = call_ffi_build_runestone(&proto);
+
+    // Compare with native call.
+    let spaced: SpacedRune = "UNCOMMON•GOODS".parse().unwrap();
+    let runestone = Runestone::new()
+        .etching(Etching {
+            rune: Some(spaced.rune),
+            spacers: Some(spaced.spacers),
+            symbol: Some('⧉'),
+            terms: Some(Terms {
+                amount: Some(1),
+                cap: Some(u128::MAX),
+                height: (Some(840_000), Some(1_050_000)),
+                offset: (None, None),
+            }),
+            ..Default::default()
+        })
+        .edict(Edict {
+            id: RuneId::new(1, 0).unwrap(),
+            amount: 1_000,
+            output: 1,
+        });
+
+    assert_eq!(output.value, 0);
+    assert_eq!(output.script, runestone.encipher().to_bytes());
+
+    // Invalid rune names are rejected.
+    let mut invalid = proto;
+    invalid.etching.as_mut().unwrap().rune = "uncommon".into();
+    let serialized = tw_proto::serialize(&invalid).unwrap();
+    let raw = unsafe { tw_bitcoin_build_runestone(serialized.as_ptr(), serialized.len()) };
+    assert!(raw.data().is_null());
+}
+
+#[test]
+fn proto_sign_rune_transfer() {
+    let alice: secp256k1::KeyPair = keypair_from_wif(ALICE_WIF).unwrap();
+    let alice_privkey = alice.secret_bytes();
+    let alice_recipient = Recipient::<PublicKey>::from_keypair(&alice);
+    let bob_recipient = Recipient::<PublicKey>::from_keypair(&keypair_from_wif(BOB_WIF).unwrap());
+
+    let txid = reverse_txid(TXID);
+
+    // Transfer 500 runes to Bob (second output).
+    let runestone = ProtoRunestone {
+        edicts: vec![RuneEdict {
+            id: Some(ProtoRuneId {
+                block: 840_000,
+                tx: 1,
+            }),
+            amount: "500".into(),
+            output: 1,
+        }],
+        ..Default::default()
+    };
+
+    let op_return = call_ffi_build_runestone(&runestone);
+    let input = call_ffi_build_p2wpkh_script(10_000, &alice_recipient);
+    let output = call_ffi_build_p2wpkh_script(546, &bob_recipient);
+
+    let signing = ProtoSigningInputBuilder::new()
+        .private_key(&alice_privkey)
+        .input(
+            ProtoTransactionBuilder::new()
+                .txid(&txid)
+                .vout(0)
+                .script_pubkey(&input.script)
+                .satoshis(10_000)
+                .variant(TransactionVariant::P2WPKH)
+                .build(),
+        )
+        .output(
+            ProtoTransactionBuilder::new()
+                .script_pubkey(&op_return.script)
+                .satoshis(0)
+                .variant(TransactionVariant::OPRETURN)
+                .build(),
+        )
+        .output(
+            ProtoTransactionBuilder::new()
+                .script_pubkey(&output.script)
+                .satoshis(546)
+                .variant(TransactionVariant::P2WPKH)
+                .build(),
+        )
+        .build();
+
+    let signed = taproot_build_and_sign_transaction(signing).unwrap();
+
+    // Decipher the runestone of the signed transaction.
+    let raw = unsafe {
+        tw_bitcoin_decipher_runestone(signed.encoded.as_ptr(), signed.encoded.len()).into_vec()
+    };
+    let artifact: RunestoneArtifact = tw_proto::deserialize(&raw).unwrap();
+
+    assert!(!artifact.cenotaph);
+    assert_eq!(artifact.runestone.unwrap(), runestone);
+}
@@ -1,3 +1,6 @@
+use crate::ffi::scripts::{
+    tw_bitcoin_classify_script, tw_bitcoin_script_from_asm, tw_bitcoin_script_to_asm,
+};
 use crate::tests::ffi::utils::{
     call_ffi_build_p2pkh_script, call_ffi_build_p2tr_key_path_script, call_ffi_build_p2wpkh_script,
 };
@@ -5,6 +8,8 @@ use crate::tests::p2pkh::ALICE_WIF;
 use crate::{keypair_from_wif, Recipient, TxOutputP2PKH, TxOutputP2TRKeyPath, TxOutputP2WPKH};
 use bitcoin::PublicKey;
 use std::borrow::Cow;
+use std::ffi::CString;
+use tw_encoding::hex;
 use tw_proto::Bitcoin::Proto::TransactionOutput;
 
 #[test]
@@ -75,3 +80,29 @@ fn proto_build_p2tr_key_path_script() {
 
     assert_eq!(ffi_out, proto);
 }
+
+#[test]
+fn ffi_script_asm() {
+    let script = hex::decode("0014aabbccddeeff00112233445566778899aabbccdd").unwrap();
+
+    // Disassemble.
+    let res = unsafe { tw_bitcoin_script_to_asm(script.as_ptr(), script.len()) };
+    let asm = unsafe { CString::from_raw(res.unwrap()) };
+    assert_eq!(
+        asm.to_str().unwrap(),
+        "0 aabbccddeeff00112233445566778899aabbccdd"
+    );
+
+    // Classify.
+    let res = unsafe { tw_bitcoin_classify_script(script.as_ptr(), script.len()) };
+    let template = unsafe { CString::from_raw(res.unwrap()) };
+    assert_eq!(template.to_str().unwrap(), "witness_v0_keyhash");
+
+    // Assemble.
+    let assembled = unsafe { tw_bitcoin_script_from_asm(asm.as_ptr()).into_vec() };
+    assert_eq!(assembled, script);
+
+    let invalid = CString::new("OP_DUP OP_UNKNOWN").unwrap();
+    let assembled = unsafe { tw_bitcoin_script_from_asm(invalid.as_ptr()) };
+    assert!(assembled.data().is_null());
+}
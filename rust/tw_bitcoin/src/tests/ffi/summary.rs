@@ -0,0 +1,149 @@
+use crate::ffi::summary::tw_bitcoin_transaction_summary;
+use crate::tests::ffi::utils::{
+    call_ffi_build_p2pkh_script, call_ffi_build_p2wpkh_script, reverse_txid,
+    ProtoSigningInputBuilder, ProtoTransactionBuilder,
+};
+use crate::tests::p2wpkh::*;
+use crate::{keypair_from_wif, Recipient};
+use bitcoin::PublicKey;
+use tw_proto::Bitcoin::Proto::{TransactionSummary, TransactionSummaryInput, TransactionVariant};
+
+const CHANGE_SATOSHIS: u64 = 10_000;
+
+#[test]
+fn ffi_transaction_summary() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let alice_privkey = alice.secret_bytes();
+    let alice_recipient = Recipient::<PublicKey>::from_keypair(&alice);
+
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+    let bob_recipient = Recipient::<PublicKey>::from_keypair(&bob);
+
+    let txid = reverse_txid(TXID);
+
+    let input = call_ffi_build_p2pkh_script(FULL_SATOSHIS, &alice_recipient);
+    let output = call_ffi_build_p2wpkh_script(SEND_SATOSHIS - CHANGE_SATOSHIS, &bob_recipient);
+    let change = call_ffi_build_p2wpkh_script(CHANGE_SATOSHIS, &alice_recipient);
+
+    let signing = ProtoSigningInputBuilder::new()
+        .private_key(&alice_privkey)
+        .input(
+            ProtoTransactionBuilder::new()
+                .txid(&txid)
+                .vout(0)
+                .script_pubkey(&input.script)
+                .satoshis(FULL_SATOSHIS)
+                .variant(TransactionVariant::P2PKH)
+                .build(),
+        )
+        .output(
+            ProtoTransactionBuilder::new()
+                .script_pubkey(&output.script)
+                .satoshis(SEND_SATOSHIS - CHANGE_SATOSHIS)
+                .variant(TransactionVariant::P2WPKH)
+                .build(),
+        )
+        .output(
+            ProtoTransactionBuilder::new()
+                .script_pubkey(&change.script)
+                .satoshis(CHANGE_SATOSHIS)
+                .variant(TransactionVariant::P2WPKH)
+                .build(),
+        )
+        .build();
+
+    let serialized = tw_proto::serialize(&TransactionSummaryInput {
+        signing: Some(signing),
+        testnet: true,
+    })
+    .unwrap();
+
+    let raw =
+        unsafe { tw_bitcoin_transaction_summary(serialized.as_ptr(), serialized.len()).into_vec() };
+    let summary: TransactionSummary = tw_proto::deserialize(&raw).unwrap();
+
+    assert_eq!(summary.inputs.len(), 1);
+    assert_eq!(summary.inputs[0].txid, TXID);
+    assert_eq!(summary.inputs[0].input_type, "P2PKH");
+    assert_eq!(summary.inputs[0].amount, FULL_SATOSHIS as i64);
+
+    assert_eq!(summary.outputs.len(), 2);
+    assert!(summary.outputs[0].destination.starts_with("tb1q"));
+    assert!(!summary.outputs[0].is_mine);
+    assert!(summary.outputs[1].is_mine);
+
+    assert_eq!(summary.fee, MINER_FEE as i64);
+    assert_eq!(summary.fee_rate, MINER_FEE as f64 / summary.vsize as f64);
+    assert!(!summary.replaceable);
+    assert!(summary.inscriptions.is_empty());
+}
+
+#[test]
+fn ffi_transaction_summary_coin_types() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let alice_privkey = alice.secret_bytes();
+    let alice_recipient = Recipient::<PublicKey>::from_keypair(&alice);
+
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+    let bob_recipient = Recipient::<PublicKey>::from_keypair(&bob);
+
+    let txid = reverse_txid(TXID);
+
+    let input = call_ffi_build_p2pkh_script(FULL_SATOSHIS, &alice_recipient);
+    let output = call_ffi_build_p2pkh_script(SEND_SATOSHIS, &bob_recipient);
+
+    let signing = ProtoSigningInputBuilder::new()
+        .private_key(&alice_privkey)
+        .input(
+            ProtoTransactionBuilder::new()
+                .txid(&txid)
+                .vout(0)
+                .script_pubkey(&input.script)
+                .satoshis(FULL_SATOSHIS)
+                .variant(TransactionVariant::P2PKH)
+                .build(),
+        )
+        .output(
+            ProtoTransactionBuilder::new()
+                .script_pubkey(&output.script)
+                .satoshis(SEND_SATOSHIS)
+                .variant(TransactionVariant::P2PKH)
+                .build(),
+        )
+        .build();
+
+    let call_ffi_summary = |coin_type: u32| {
+        let mut signing = signing.clone();
+        signing.coin_type = coin_type;
+
+        let serialized = tw_proto::serialize(&TransactionSummaryInput {
+            signing: Some(signing),
+            testnet: false,
+        })
+        .unwrap();
+
+        let raw = unsafe { tw_bitcoin_transaction_summary(serialized.as_ptr(), serialized.len()) };
+        if raw.data().is_null() {
+            return vec![];
+        }
+
+        unsafe { raw.into_vec() }
+    };
+
+    // The addresses are encoded for the chain of the coin type.
+    let raw = call_ffi_summary(0);
+    let summary: TransactionSummary = tw_proto::deserialize(&raw).unwrap();
+    assert!(summary.outputs[0].destination.starts_with('1'));
+
+    let raw = call_ffi_summary(145);
+    let summary: TransactionSummary = tw_proto::deserialize(&raw).unwrap();
+    assert!(summary.outputs[0].destination.starts_with("bitcoincash:q"));
+
+    let raw = call_ffi_summary(17);
+    let summary: TransactionSummary = tw_proto::deserialize(&raw).unwrap();
+    assert!(summary.outputs[0].destination.starts_with('F'));
+
+    // Decred and Litecoin are not supported.
+    assert!(call_ffi_summary(42).is_empty());
+    assert!(call_ffi_summary(2).is_empty());
+}
@@ -0,0 +1,110 @@
+use crate::ffi::sweep::tw_bitcoin_sweep;
+use crate::ffi::taproot_build_and_sign_transaction;
+use crate::tests::ffi::utils::{
+    call_ffi_build_p2wpkh_script, reverse_txid, ProtoTransactionBuilder,
+};
+use crate::tests::p2wpkh::*;
+use crate::{keypair_from_wif, Recipient};
+use bitcoin::{Network, PublicKey};
+use std::borrow::Cow;
+use tw_proto::Bitcoin::Proto::{
+    Network as ProtoNetwork, SigningInput, SweepOutput, TransactionVariant,
+};
+use tw_proto::Common::Proto::SigningError;
+
+const BYTE_FEE: i64 = 10;
+
+/// Sweeps three UTXOs of Alice to Bob, skipping those below 1000 satoshis.
+fn sweep_input(alice_privkey: &[u8], alice_recipient: &Recipient<PublicKey>) -> Vec<u8> {
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+    let bob_recipient = Recipient::<PublicKey>::from_keypair(&bob);
+
+    let txid = reverse_txid(TXID);
+    let input = call_ffi_build_p2wpkh_script(0, alice_recipient);
+
+    let utxo = [FULL_SATOSHIS, 900, SEND_SATOSHIS]
+        .into_iter()
+        .enumerate()
+        .map(|(vout, satoshis)| {
+            ProtoTransactionBuilder::new()
+                .txid(&txid)
+                .vout(vout as u32)
+                .script_pubkey(&input.script)
+                .satoshis(satoshis)
+                .variant(TransactionVariant::P2WPKH)
+                .build()
+        })
+        .collect();
+
+    let signing = SigningInput {
+        private_key: vec![Cow::from(alice_privkey)],
+        utxo,
+        to_address: bob_recipient
+            .segwit_address_string(Network::Regtest)
+            .unwrap()
+            .into(),
+        byte_fee: BYTE_FEE,
+        use_max_amount: true,
+        network: ProtoNetwork::REGTEST,
+        sweep_min_amount: 1_000,
+        ..Default::default()
+    };
+
+    tw_proto::serialize(&signing).unwrap()
+}
+
+#[test]
+fn ffi_sweep() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let alice_privkey = alice.secret_bytes();
+    let alice_recipient = Recipient::<PublicKey>::from_keypair(&alice);
+
+    let serialized = sweep_input(&alice_privkey, &alice_recipient);
+
+    let raw = unsafe { tw_bitcoin_sweep(serialized.as_ptr(), serialized.len()).into_vec() };
+    let output: SweepOutput = tw_proto::deserialize(&raw).unwrap();
+
+    // The UTXO of 900 satoshis is below the threshold.
+    assert_eq!(output.marginal_costs, vec![680; 3]);
+    assert_eq!(output.skipped, vec![1]);
+    assert_eq!(output.transactions.len(), 1);
+
+    let swept = &output.transactions[0];
+    let signed = swept.signed.as_ref().unwrap();
+    assert_eq!(signed.error, SigningError::OK);
+
+    let tx = signed.transaction.as_ref().unwrap();
+    assert_eq!(tx.inputs.len(), 2);
+    assert_eq!(tx.outputs.len(), 1);
+    assert_eq!(tx.outputs[0].value, swept.amount);
+    assert_eq!(
+        swept.amount + swept.fee,
+        (FULL_SATOSHIS + SEND_SATOSHIS) as i64
+    );
+
+    // The same transaction is created with `use_max_amount`.
+    let signing: SigningInput = tw_proto::deserialize(&serialized).unwrap();
+    let signed = taproot_build_and_sign_transaction(signing).unwrap();
+    assert_eq!(signed.transaction.unwrap().outputs[0].value, swept.amount);
+}
+
+#[test]
+fn ffi_sweep_uncompressed_key() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let alice_privkey = alice.secret_bytes();
+    let alice_recipient = Recipient::<PublicKey>::from_keypair(&alice);
+
+    let serialized = sweep_input(&alice_privkey, &alice_recipient);
+    let mut signing: SigningInput = tw_proto::deserialize(&serialized).unwrap();
+    signing.uncompressed_public_key = true;
+    let serialized = tw_proto::serialize(&signing).unwrap();
+
+    let raw = unsafe { tw_bitcoin_sweep(serialized.as_ptr(), serialized.len()) };
+    assert!(!raw.data().is_null());
+    let raw = unsafe { raw.into_vec() };
+    let output: SweepOutput = tw_proto::deserialize(&raw).unwrap();
+
+    // SegWit inputs can't be swept with an uncompressed key.
+    assert_eq!(output.error, SigningError::Error_uncompressed_key);
+    assert!(output.transactions.is_empty());
+}
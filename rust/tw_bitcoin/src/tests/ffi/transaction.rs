@@ -5,6 +5,7 @@ use crate::tests::ffi::utils::{
 };
 use crate::{keypair_from_wif, Recipient};
 use bitcoin::PublicKey;
+use std::borrow::Cow;
 use tw_encoding::hex;
 use tw_proto::Bitcoin::Proto::TransactionVariant;
 
@@ -136,3 +137,627 @@ pub fn proto_sign_input_p2pkh_output_p2tr_key_path() {
     let signed = taproot_build_and_sign_transaction(signing).unwrap();
     assert_eq!(hex::encode(&signed.encoded, false), FIRST_TX_RAW);
 }
+
+#[test]
+pub fn proto_sign_input_p2tr_key_path_deterministic() {
+    use crate::tests::p2tr_key_path::*;
+
+    // Prepare keys.
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let alice_recipient = Recipient::<PublicKey>::from_keypair(&alice);
+
+    let bob: secp256k1::KeyPair = keypair_from_wif(BOB_WIF).unwrap();
+    let bob_privkey = bob.secret_bytes();
+    let bob_recipient = Recipient::<PublicKey>::from_keypair(&bob);
+
+    let txid = reverse_txid(SECOND_TXID);
+
+    // Prepare the scripts.
+    let input = call_ffi_build_p2tr_key_path_script(SEND_SATOSHIS_TO_BOB, &bob_recipient);
+    let output = call_ffi_build_p2tr_key_path_script(SEND_SATOSHIS_TO_ALICE, &alice_recipient);
+
+    // Construct Protobuf payload.
+    let mut signing = ProtoSigningInputBuilder::new()
+        .private_key(&bob_privkey)
+        .input(
+            ProtoTransactionBuilder::new()
+                .txid(&txid)
+                .vout(0)
+                .script_pubkey(&input.script)
+                .satoshis(SEND_SATOSHIS_TO_BOB)
+                .variant(TransactionVariant::P2TRKEYPATH)
+                .build(),
+        )
+        .output(
+            ProtoTransactionBuilder::new()
+                .script_pubkey(&output.script)
+                .satoshis(SEND_SATOSHIS_TO_ALICE)
+                .variant(TransactionVariant::P2TRKEYPATH)
+                .build(),
+        )
+        .build();
+
+    // Explicit auxiliary randomness must be exactly 32 bytes.
+    signing.schnorr_aux_rand = vec![1; 31].into();
+    assert!(taproot_build_and_sign_transaction(signing.clone()).is_err());
+
+    signing.deterministic_schnorr = true;
+    let signed = taproot_build_and_sign_transaction(signing).unwrap();
+    assert_eq!(hex::encode(&signed.encoded, false), SECOND_TX_RAW);
+}
+
+#[test]
+pub fn proto_sign_witness_and_wtxid() {
+    use crate::tests::p2tr_key_path::*;
+    use bitcoin::consensus::deserialize;
+    use bitcoin::hashes::{sha256d, Hash};
+    use bitcoin::Transaction;
+
+    // Prepare keys.
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let alice_recipient = Recipient::<PublicKey>::from_keypair(&alice);
+
+    let bob: secp256k1::KeyPair = keypair_from_wif(BOB_WIF).unwrap();
+    let bob_privkey = bob.secret_bytes();
+    let bob_recipient = Recipient::<PublicKey>::from_keypair(&bob);
+
+    let txid = reverse_txid(SECOND_TXID);
+
+    // Prepare the scripts.
+    let input = call_ffi_build_p2tr_key_path_script(SEND_SATOSHIS_TO_BOB, &bob_recipient);
+    let output = call_ffi_build_p2tr_key_path_script(SEND_SATOSHIS_TO_ALICE, &alice_recipient);
+
+    // Construct Protobuf payload.
+    let mut signing = ProtoSigningInputBuilder::new()
+        .private_key(&bob_privkey)
+        .input(
+            ProtoTransactionBuilder::new()
+                .txid(&txid)
+                .vout(0)
+                .script_pubkey(&input.script)
+                .satoshis(SEND_SATOSHIS_TO_BOB)
+                .variant(TransactionVariant::P2TRKEYPATH)
+                .build(),
+        )
+        .output(
+            ProtoTransactionBuilder::new()
+                .script_pubkey(&output.script)
+                .satoshis(SEND_SATOSHIS_TO_ALICE)
+                .variant(TransactionVariant::P2TRKEYPATH)
+                .build(),
+        )
+        .build();
+
+    signing.deterministic_schnorr = true;
+    let signed = taproot_build_and_sign_transaction(signing).unwrap();
+    assert_eq!(hex::encode(&signed.encoded, false), SECOND_TX_RAW);
+
+    let tx: Transaction = deserialize(&signed.encoded).unwrap();
+
+    // The witness stack holds the single Schnorr signature.
+    let proto_input = &signed.transaction.as_ref().unwrap().inputs[0];
+    assert_eq!(proto_input.witness.len(), 1);
+    assert_eq!(proto_input.witness[0].len(), 64);
+    assert_eq!(proto_input.witness[0], tx.input[0].witness.to_vec()[0]);
+
+    // The transaction ID is the hash of the stripped serialization.
+    let stripped: Transaction = deserialize(&signed.encoded_stripped).unwrap();
+    assert!(stripped.input[0].witness.is_empty());
+    assert!(signed.encoded_stripped.len() < signed.encoded.len());
+
+    assert_eq!(
+        signed.txid.as_ref(),
+        sha256d::Hash::hash(&signed.encoded_stripped).as_byte_array()
+    );
+    assert_eq!(
+        signed.wtxid.as_ref(),
+        sha256d::Hash::hash(&signed.encoded).as_byte_array()
+    );
+    assert_ne!(signed.txid, signed.wtxid);
+
+    let mut reversed = signed.txid.to_vec();
+    reversed.reverse();
+    assert_eq!(hex::encode(&reversed, false), signed.transaction_id);
+
+    assert_eq!(signed.weight, tx.weight().to_wu());
+    assert_eq!(signed.vsize, tx.vsize() as u64);
+}
+
+#[test]
+pub fn proto_sign_with_coin_selection() {
+    use crate::tests::p2pkh::*;
+    use crate::tests::ONE_BTC;
+    use bitcoin::Network;
+    use std::borrow::Cow;
+    use tw_proto::Bitcoin::Proto::{CoinSelectionStrategy, Network as ProtoNetwork, SigningInput};
+
+    // Prepare keys.
+    let alice: secp256k1::KeyPair = keypair_from_wif(ALICE_WIF).unwrap();
+    let alice_privkey = alice.secret_bytes();
+    let alice_recipient = Recipient::<PublicKey>::from_keypair(&alice);
+
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+    let bob_recipient = Recipient::<PublicKey>::from_keypair(&bob);
+
+    let txid = reverse_txid(TXID);
+
+    let input = call_ffi_build_p2wpkh_script(FULL_SATOSHIS, &alice_recipient);
+
+    // The first UTXO is locked and must not be spent, even though it is the
+    // largest.
+    let mut locked = ProtoTransactionBuilder::new()
+        .txid(&txid)
+        .vout(0)
+        .script_pubkey(&input.script)
+        .satoshis(FULL_SATOSHIS * 2)
+        .variant(TransactionVariant::P2WPKH)
+        .build();
+    locked.locked = true;
+
+    let unlocked = ProtoTransactionBuilder::new()
+        .txid(&txid)
+        .vout(1)
+        .script_pubkey(&input.script)
+        .satoshis(FULL_SATOSHIS)
+        .variant(TransactionVariant::P2WPKH)
+        .build();
+
+    // Construct Protobuf payload without a transaction plan.
+    let signing = SigningInput {
+        private_key: vec![Cow::from(alice_privkey.as_slice())],
+        utxo: vec![locked, unlocked],
+        to_address: bob_recipient
+            .segwit_address_string(Network::Regtest)
+            .unwrap()
+            .into(),
+        change_address: alice_recipient
+            .segwit_address_string(Network::Regtest)
+            .unwrap()
+            .into(),
+        amount: (ONE_BTC * 10) as i64,
+        byte_fee: 10,
+        coin_selection: CoinSelectionStrategy::LARGEST_FIRST,
+        plan: None,
+        network: ProtoNetwork::REGTEST,
+        ..Default::default()
+    };
+
+    // The addresses are rejected for another network.
+    let mut mainnet = signing.clone();
+    mainnet.network = ProtoNetwork::MAINNET;
+    assert!(taproot_build_and_sign_transaction(mainnet).is_err());
+
+    let signed = taproot_build_and_sign_transaction(signing).unwrap();
+    let tx = signed.transaction.unwrap();
+
+    assert_eq!(tx.inputs.len(), 1);
+    assert_eq!(tx.inputs[0].previousOutput.as_ref().unwrap().index, 1);
+
+    // Payment to Bob and change back to Alice.
+    assert_eq!(tx.outputs.len(), 2);
+    assert_eq!(tx.outputs[0].value, (ONE_BTC * 10) as i64);
+    assert_eq!(tx.outputs[0].script, output_script(&bob_recipient));
+    assert_eq!(tx.outputs[1].script, output_script(&alice_recipient));
+
+    // 141 vbytes at 10 sat/vB.
+    let fee = FULL_SATOSHIS as i64 - tx.outputs[0].value - tx.outputs[1].value;
+    assert_eq!(fee, 1_410);
+}
+
+#[test]
+pub fn proto_sign_to_p2sh_address() {
+    use crate::tests::p2pkh::*;
+    use crate::tests::ONE_BTC;
+    use bitcoin::{Address, Network, ScriptBuf};
+    use std::borrow::Cow;
+    use tw_proto::Bitcoin::Proto::{Network as ProtoNetwork, SigningInput};
+
+    let alice: secp256k1::KeyPair = keypair_from_wif(ALICE_WIF).unwrap();
+    let alice_privkey = alice.secret_bytes();
+    let alice_recipient = Recipient::<PublicKey>::from_keypair(&alice);
+
+    let txid = reverse_txid(TXID);
+    let input = call_ffi_build_p2wpkh_script(FULL_SATOSHIS, &alice_recipient);
+    let utxo = ProtoTransactionBuilder::new()
+        .txid(&txid)
+        .vout(0)
+        .script_pubkey(&input.script)
+        .satoshis(FULL_SATOSHIS)
+        .variant(TransactionVariant::P2WPKH)
+        .build();
+
+    // Pays to a `2...` address, such as a wrapped SegWit or multisig wallet.
+    let redeem_script = ScriptBuf::from_bytes(vec![0x51]);
+    let to_address = Address::p2sh(&redeem_script, Network::Regtest).unwrap();
+
+    let signing = SigningInput {
+        private_key: vec![Cow::from(alice_privkey.as_slice())],
+        utxo: vec![utxo],
+        to_address: to_address.to_string().into(),
+        change_address: alice_recipient
+            .segwit_address_string(Network::Regtest)
+            .unwrap()
+            .into(),
+        amount: ONE_BTC as i64,
+        byte_fee: 10,
+        network: ProtoNetwork::REGTEST,
+        ..Default::default()
+    };
+
+    let signed = taproot_build_and_sign_transaction(signing).unwrap();
+    let tx = signed.transaction.unwrap();
+
+    assert_eq!(tx.outputs[0].value, ONE_BTC as i64);
+    assert_eq!(tx.outputs[0].script, to_address.script_pubkey().as_bytes());
+}
+
+#[test]
+pub fn proto_address_coin_types() {
+    use crate::bitcoin_cash::BITCOIN_CASH_COIN_TYPE;
+    use crate::decred::DECRED_COIN_TYPE;
+    use crate::ffi::script_from_address;
+    use crate::groestlcoin::GROESTLCOIN_COIN_TYPE;
+    use crate::tests::p2pkh::*;
+    use bitcoin::{Address, Network};
+
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let pubkey = Recipient::<PublicKey>::from(alice).public_key();
+    let bitcoin = Address::p2pkh(&pubkey, Network::Bitcoin).to_string();
+    let cash = "bitcoincash:qpk05r5kcd8uuzwqunn8rlx5xvuvzjqju5rch3tc0u";
+    let decred = crate::tests::decred::ADDRESS;
+    let groestl = crate::tests::groestlcoin::ADDRESS;
+
+    let addresses = [
+        (0, bitcoin.as_str()),
+        (BITCOIN_CASH_COIN_TYPE, cash),
+        (DECRED_COIN_TYPE, decred),
+        (GROESTLCOIN_COIN_TYPE, groestl),
+    ];
+
+    // Each address is only accepted for its own chain.
+    for (coin_type, _) in addresses {
+        for (address_coin_type, address) in addresses {
+            let script = script_from_address(address, coin_type, Network::Bitcoin);
+            assert_eq!(script.is_ok(), coin_type == address_coin_type);
+        }
+    }
+
+    // Addresses of unsupported chains can't be parsed.
+    assert!(script_from_address(&bitcoin, 2, Network::Bitcoin).is_err());
+
+    // Addresses of another network are rejected.
+    assert!(script_from_address(cash, BITCOIN_CASH_COIN_TYPE, Network::Testnet).is_err());
+    assert!(script_from_address(decred, DECRED_COIN_TYPE, Network::Testnet).is_err());
+    assert!(script_from_address(groestl, GROESTLCOIN_COIN_TYPE, Network::Testnet).is_err());
+}
+
+#[test]
+pub fn proto_sign_negative_amount() {
+    use crate::tests::p2pkh::*;
+
+    // Prepare keys.
+    let alice: secp256k1::KeyPair = keypair_from_wif(ALICE_WIF).unwrap();
+    let alice_privkey = alice.secret_bytes();
+    let alice_recipient = Recipient::<PublicKey>::from_keypair(&alice);
+
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+    let bob_recipient = Recipient::<PublicKey>::from_keypair(&bob);
+
+    let txid = reverse_txid(TXID);
+
+    // Prepare the scripts.
+    let input = call_ffi_build_p2pkh_script(FULL_SATOSHIS, &alice_recipient);
+    let output = call_ffi_build_p2pkh_script(SEND_SATOSHIS, &bob_recipient);
+
+    // Construct Protobuf payload.
+    let mut signing = ProtoSigningInputBuilder::new()
+        .private_key(&alice_privkey)
+        .input(
+            ProtoTransactionBuilder::new()
+                .txid(&txid)
+                .vout(0)
+                .script_pubkey(&input.script)
+                .satoshis(FULL_SATOSHIS)
+                .variant(TransactionVariant::P2PKH)
+                .build(),
+        )
+        .output(
+            ProtoTransactionBuilder::new()
+                .script_pubkey(&output.script)
+                .satoshis(SEND_SATOSHIS)
+                .variant(TransactionVariant::P2PKH)
+                .build(),
+        )
+        .build();
+
+    // Negative amounts are rejected rather than wrapped around.
+    signing.plan.as_mut().unwrap().utxos[0].amount = -1;
+    assert!(taproot_build_and_sign_transaction(signing).is_err());
+}
+
+fn output_script(recipient: &Recipient<PublicKey>) -> Vec<u8> {
+    call_ffi_build_p2wpkh_script(0, recipient)
+        .script
+        .into_owned()
+}
+
+#[test]
+pub fn proto_sign_protects_inscriptions() {
+    use crate::tests::p2wpkh::*;
+
+    // Prepare keys.
+    let alice: secp256k1::KeyPair = keypair_from_wif(ALICE_WIF).unwrap();
+    let alice_privkey = alice.secret_bytes();
+    let alice_recipient = Recipient::<PublicKey>::from_keypair(&alice);
+
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+    let bob_recipient = Recipient::<PublicKey>::from_keypair(&bob);
+
+    let txid = reverse_txid(TXID);
+
+    // Prepare the scripts.
+    let input = call_ffi_build_p2pkh_script(FULL_SATOSHIS, &alice_recipient);
+    let output = call_ffi_build_p2wpkh_script(SEND_SATOSHIS, &bob_recipient);
+
+    let signing_with_inscription = |offset: u64| {
+        let mut utxo = ProtoTransactionBuilder::new()
+            .txid(&txid)
+            .vout(0)
+            .script_pubkey(&input.script)
+            .satoshis(FULL_SATOSHIS)
+            .variant(TransactionVariant::P2PKH)
+            .build();
+        utxo.inscription_offsets = vec![offset];
+
+        ProtoSigningInputBuilder::new()
+            .private_key(&alice_privkey)
+            .input(utxo)
+            .output(
+                ProtoTransactionBuilder::new()
+                    .script_pubkey(&output.script)
+                    .satoshis(SEND_SATOSHIS)
+                    .variant(TransactionVariant::P2WPKH)
+                    .build(),
+            )
+            .build()
+    };
+
+    // The inscription is transferred to Bob.
+    let signed = taproot_build_and_sign_transaction(signing_with_inscription(0)).unwrap();
+    assert_eq!(hex::encode(&signed.encoded, false), TX_RAW);
+
+    // The inscription would be paid as fee.
+    let signing = signing_with_inscription(FULL_SATOSHIS - 1);
+    assert!(taproot_build_and_sign_transaction(signing).is_err());
+}
+
+#[test]
+pub fn proto_sign_zcash_blossom() {
+    use crate::tests::zcash::*;
+    use crate::zcash::BLOSSOM_BRANCH_ID;
+
+    let alice = keypair(&"46".repeat(32));
+    let alice_privkey = alice.secret_bytes();
+
+    let txid: Vec<u8> = hex::decode(BLOSSOM_TXID)
+        .unwrap()
+        .into_iter()
+        .rev()
+        .collect();
+    let input = bitcoin::ScriptBuf::new_p2pkh(&PublicKey::new(alice.public_key()).pubkey_hash());
+    let output = hex::decode(BLOSSOM_OUTPUT).unwrap();
+
+    let mut signing = ProtoSigningInputBuilder::new()
+        .private_key(&alice_privkey)
+        .input(
+            ProtoTransactionBuilder::new()
+                .txid(&txid)
+                .vout(0)
+                .script_pubkey(input.as_bytes())
+                .satoshis(27_615)
+                .variant(TransactionVariant::P2PKH)
+                .build(),
+        )
+        .output(
+            ProtoTransactionBuilder::new()
+                .script_pubkey(&output)
+                .satoshis(17_615)
+                .variant(TransactionVariant::P2PKH)
+                .build(),
+        )
+        .build();
+
+    signing.plan.as_mut().unwrap().branch_id = Cow::from(BLOSSOM_BRANCH_ID.to_le_bytes().to_vec());
+
+    let signed = taproot_build_and_sign_transaction(signing).unwrap();
+    assert_eq!(hex::encode(&signed.encoded, false), BLOSSOM_TX);
+    assert_eq!(signed.transaction.unwrap().version, 4);
+}
+
+#[test]
+pub fn proto_sign_decred_stake_tree() {
+    use crate::decred::{DecredAddress, DECRED_COIN_TYPE, TREE_STAKE};
+    use crate::tests::decred::*;
+    use std::str::FromStr;
+
+    let alice = keypair();
+    let alice_privkey = alice.secret_bytes();
+
+    let txid: Vec<u8> = hex::decode(ORIGIN_TXID).unwrap();
+    let input = p2pkh_script(&alice);
+    let output = DecredAddress::from_str(ADDRESS).unwrap().script_pubkey();
+
+    let mut signing = ProtoSigningInputBuilder::new()
+        .private_key(&alice_privkey)
+        .input(
+            ProtoTransactionBuilder::new()
+                .txid(&txid)
+                .vout(0)
+                .script_pubkey(input.as_bytes())
+                .satoshis(ONE_DCR)
+                .variant(TransactionVariant::P2PKH)
+                .build(),
+        )
+        .output(
+            ProtoTransactionBuilder::new()
+                .script_pubkey(output.as_bytes())
+                .satoshis(ONE_DCR - 10_000)
+                .variant(TransactionVariant::P2PKH)
+                .build(),
+        )
+        .build();
+
+    signing.coin_type = DECRED_COIN_TYPE;
+    signing.utxo[0].out_point.as_mut().unwrap().tree = TREE_STAKE.into();
+
+    let signed = taproot_build_and_sign_transaction(signing).unwrap();
+    let transaction = signed.transaction.unwrap();
+    assert_eq!(transaction.version, 1);
+
+    let out_point = transaction.inputs[0].previousOutput.as_ref().unwrap();
+    assert_eq!(out_point.tree, i32::from(TREE_STAKE));
+
+    // Version, input count, the outpoint and the tree.
+    let expected = format!("0100000001{}0000000001", ORIGIN_TXID);
+    assert!(hex::encode(&signed.encoded, false).starts_with(&expected));
+}
+
+#[test]
+pub fn proto_sign_pos_timestamp() {
+    use crate::pos::PEERCOIN_COIN_TYPE;
+    use crate::tests::pos::*;
+
+    let alice = keypair();
+    let alice_privkey = alice.secret_bytes();
+
+    let txid = reverse_txid(TXID);
+    let input = script(MY_SCRIPT);
+    let output = script(TO_SCRIPT);
+
+    let mut signing = ProtoSigningInputBuilder::new()
+        .private_key(&alice_privkey)
+        .input(
+            ProtoTransactionBuilder::new()
+                .txid(&txid)
+                .vout(0)
+                .script_pubkey(input.as_bytes())
+                .satoshis(100_000)
+                .variant(TransactionVariant::P2PKH)
+                .build(),
+        )
+        .output(
+            ProtoTransactionBuilder::new()
+                .script_pubkey(output.as_bytes())
+                .satoshis(90_000)
+                .variant(TransactionVariant::P2PKH)
+                .build(),
+        )
+        .build();
+
+    signing.time = TIME;
+
+    // The time is ignored for coins without the timestamped format.
+    let signed = taproot_build_and_sign_transaction(signing.clone()).unwrap();
+    assert_eq!(signed.transaction.unwrap().version, 2);
+
+    signing.coin_type = PEERCOIN_COIN_TYPE;
+
+    let signed = taproot_build_and_sign_transaction(signing).unwrap();
+    assert_eq!(signed.transaction.unwrap().version, 1);
+
+    // Version and time.
+    let expected = format!("01000000{}01", hex::encode(&TIME.to_le_bytes(), false));
+    assert!(hex::encode(&signed.encoded, false).starts_with(&expected));
+}
+
+#[test]
+pub fn proto_sign_bitcoin_cash() {
+    use crate::bitcoin_cash::BITCOIN_CASH_COIN_TYPE;
+    use crate::tests::bitcoin_cash::*;
+
+    let alice = keypair();
+    let alice_privkey = alice.secret_bytes();
+
+    let txid = reverse_txid(TXID);
+    let input = script(MY_SCRIPT);
+    let output = script(TO_SCRIPT);
+    let change = script(CHANGE_SCRIPT);
+
+    let mut signing = ProtoSigningInputBuilder::new()
+        .private_key(&alice_privkey)
+        .input(
+            ProtoTransactionBuilder::new()
+                .txid(&txid)
+                .vout(2)
+                .script_pubkey(input.as_bytes())
+                .satoshis(5151)
+                .variant(TransactionVariant::P2PKH)
+                .build(),
+        )
+        .output(
+            ProtoTransactionBuilder::new()
+                .script_pubkey(output.as_bytes())
+                .satoshis(600)
+                .variant(TransactionVariant::P2PKH)
+                .build(),
+        )
+        .output(
+            ProtoTransactionBuilder::new()
+                .script_pubkey(change.as_bytes())
+                .satoshis(4325)
+                .variant(TransactionVariant::P2PKH)
+                .build(),
+        )
+        .build();
+
+    signing.coin_type = BITCOIN_CASH_COIN_TYPE;
+
+    let signed = taproot_build_and_sign_transaction(signing).unwrap();
+    assert_eq!(hex::encode(&signed.encoded, false), SIGNED_TX);
+}
+
+#[test]
+pub fn proto_sign_groestlcoin() {
+    use crate::groestlcoin::GROESTLCOIN_COIN_TYPE;
+    use crate::tests::groestlcoin::*;
+
+    let alice = keypair();
+    let alice_privkey = alice.secret_bytes();
+
+    let txid = hex::decode(ORIGIN_TXID).unwrap();
+    let input = script(MY_SCRIPT);
+    let output = script(TO_SCRIPT);
+    let change = script(CHANGE_SCRIPT);
+
+    let mut signing = ProtoSigningInputBuilder::new()
+        .private_key(&alice_privkey)
+        .input(
+            ProtoTransactionBuilder::new()
+                .txid(&txid)
+                .vout(0)
+                .script_pubkey(input.as_bytes())
+                .satoshis(5000)
+                .variant(TransactionVariant::P2PKH)
+                .build(),
+        )
+        .output(
+            ProtoTransactionBuilder::new()
+                .script_pubkey(output.as_bytes())
+                .satoshis(2500)
+                .variant(TransactionVariant::P2WPKH)
+                .build(),
+        )
+        .output(
+            ProtoTransactionBuilder::new()
+                .script_pubkey(change.as_bytes())
+                .satoshis(2274)
+                .variant(TransactionVariant::P2PKH)
+                .build(),
+        )
+        .build();
+
+    signing.coin_type = GROESTLCOIN_COIN_TYPE;
+
+    let signed = taproot_build_and_sign_transaction(signing).unwrap();
+    assert_eq!(hex::encode(&signed.encoded, false), SIGNED_TX);
+}
@@ -230,6 +230,10 @@ impl<'a> ProtoTransactionBuilder<'a> {
         self.inner.spendingScript = script.into();
         self
     }
+    pub fn internal_pubkey(mut self, pubkey: &'a [u8]) -> Self {
+        self.inner.internal_pubkey = pubkey.into();
+        self
+    }
     pub fn build(self) -> UnspentTransaction<'a> {
         self.inner
     }
@@ -0,0 +1,136 @@
+use crate::groestlcoin::*;
+use crate::hasher::{ChainHashes, Hasher};
+use crate::{Chain, TransactionBuilder, TxInput, TxInputP2PKH, TxOutputP2PKH, TxOutputP2WPKH};
+use bitcoin::hashes::Hash;
+use bitcoin::key::KeyPair;
+use bitcoin::secp256k1::Secp256k1;
+use bitcoin::{Network, PublicKey, ScriptBuf, Sequence, Txid};
+use std::str::FromStr;
+use tw_encoding::hex;
+
+// Signed with Trust Wallet Core, on mainnet.
+pub const PRIVATE_KEY: &str = "3c3385ddc6fd95ba7282051aeb440bc75820b8c10db5c83c052d7586e3e98e84";
+pub const ADDRESS: &str = "Fj62rBJi8LvbmWu2jzkaUX1NFXLEqDLoZM";
+pub const ORIGIN_TXID: &str = "9568b09e6c6d940302ec555a877c9e5f799de8ee473e18d3a19ae14478cc4e8f";
+pub const MY_SCRIPT: &str = "76a91498af0aaca388a7e1024f505c033626d908e3b54a88ac";
+pub const TO_SCRIPT: &str = "00147557920fbc32a1ef4ef26bae5e8ce3f95abf09ce";
+pub const CHANGE_SCRIPT: &str = "a9140055b0c94df477ee6b9f75185dfc9aa8ce2e52e487";
+pub const SIGNED_TX: &str = "01000000019568b09e6c6d940302ec555a877c9e5f799de8ee473e18d3a19ae14478cc4e8f000000006a47304402202163ab98b028aa13563f0de00b785d6df81df5eac0b7c91d23f5be7ea674aa3702202bf6cd7055c6f8f697ce045b1a4f9b997cf6e5761a661d27696ac34064479d19012103b85cc59b67c35851eb5060cfc3a759a482254553c5857075c9e247d74d412c91ffffffff02c4090000000000001600147557920fbc32a1ef4ef26bae5e8ce3f95abf09cee20800000000000017a9140055b0c94df477ee6b9f75185dfc9aa8ce2e52e48700000000";
+const SIGNED_TXID: &str = "74a0dd12bc178cfcc1e0982a2a5b2c01a50e41abbb63beb031bcd21b3e28eac0";
+
+pub fn keypair() -> KeyPair {
+    KeyPair::from_seckey_slice(&Secp256k1::new(), &hex::decode(PRIVATE_KEY).unwrap()).unwrap()
+}
+
+pub fn script(hex: &str) -> ScriptBuf {
+    ScriptBuf::from_bytes(hex::decode(hex).unwrap())
+}
+
+fn builder() -> TransactionBuilder {
+    let txid = Txid::from_slice(&hex::decode(ORIGIN_TXID).unwrap()).unwrap();
+
+    let mut input = TxInput::from(TxInputP2PKH::new_with_script(
+        txid,
+        0,
+        keypair().into(),
+        5000,
+        script(MY_SCRIPT),
+    ));
+    input.set_sequence(Sequence::MAX);
+
+    // There is no P2SH output type, the change script is carried as is.
+    TransactionBuilder::new()
+        .version(TX_VERSION)
+        .add_input(input)
+        .add_output(TxOutputP2WPKH::new_with_script(2500, script(TO_SCRIPT)).into())
+        .add_output(TxOutputP2PKH::new_with_script(2274, script(CHANGE_SCRIPT)).into())
+}
+
+#[test]
+fn groestlcoin_address() {
+    let pubkey = PublicKey::new(keypair().public_key());
+
+    let address = GroestlcoinAddress::p2pkh(&pubkey, Network::Bitcoin);
+    assert_eq!(address.to_string(), ADDRESS);
+    assert_eq!(address.script_pubkey(), script(MY_SCRIPT));
+    assert_eq!(GroestlcoinAddress::from_str(ADDRESS).unwrap(), address);
+
+    let p2sh = GroestlcoinAddress::from_str("31inaRqambLsd9D7Ke4USZmGEVd3PHkh7P").unwrap();
+    assert_eq!(p2sh.script_pubkey(), script(CHANGE_SCRIPT));
+    assert_eq!(p2sh.to_string(), "31inaRqambLsd9D7Ke4USZmGEVd3PHkh7P");
+
+    let segwit = "grs1qw4teyraux2s77nhjdwh9ar8rl9dt7zww8r6lne";
+    let p2wpkh = GroestlcoinAddress::from_str(segwit).unwrap();
+    assert_eq!(p2wpkh.script_pubkey(), script(TO_SCRIPT));
+    assert_eq!(
+        GroestlcoinAddress::from_script(&script(TO_SCRIPT), Network::Bitcoin)
+            .unwrap()
+            .to_string(),
+        segwit
+    );
+
+    let testnet = GroestlcoinAddress::p2wpkh(&pubkey, Network::Testnet).unwrap();
+    assert!(testnet.to_string().starts_with("tgrs1q"));
+    assert_eq!(
+        GroestlcoinAddress::from_str(&testnet.to_string()).unwrap(),
+        testnet
+    );
+
+    // Truncated addresses, and addresses with Bitcoin's checksum.
+    assert!(GroestlcoinAddress::from_str("Fj62rBJi8LvbmWu2jzkaUX1NFXLEqDLo").is_err());
+    assert!(GroestlcoinAddress::from_str("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2").is_err());
+    assert!(GroestlcoinAddress::from_str("bc1qunq74p3h8425hr6wllevlvqqr6sezfxj262rff").is_err());
+}
+
+#[test]
+fn groestlcoin_hashes() {
+    let data = b"groestlcoin";
+
+    assert_eq!(
+        hex::encode(&Hasher::Sha256.hash(b""), false),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    assert_ne!(Hasher::Sha256.hash(data), Hasher::Sha256d.hash(data));
+
+    let encoded = ChainHashes::GROESTLCOIN.base58check_encode(data);
+    assert_eq!(
+        ChainHashes::GROESTLCOIN
+            .base58check_decode(&encoded)
+            .unwrap(),
+        data
+    );
+    assert!(ChainHashes::BITCOIN.base58check_decode(&encoded).is_err());
+}
+
+#[test]
+fn groestlcoin_sign_p2pkh() {
+    let signed = builder()
+        .chain(Chain::Groestlcoin)
+        .sign_inputs(keypair())
+        .unwrap();
+
+    assert_eq!(hex::encode(&signed.serialize().unwrap(), false), SIGNED_TX);
+    assert_eq!(signed.txid().to_string(), SIGNED_TXID);
+    assert_ne!(signed.txid(), signed.inner.txid());
+
+    // The chain is a preset of the hash functions.
+    let hashed = builder()
+        .hashes(ChainHashes::GROESTLCOIN)
+        .sign_inputs(keypair())
+        .unwrap();
+    assert_eq!(hashed.inner, signed.inner);
+
+    // Only P2PKH inputs are supported.
+    let alice = keypair();
+    let p2wpkh = crate::TxInputP2WPKH::new(
+        Txid::from_slice(&hex::decode(ORIGIN_TXID).unwrap()).unwrap(),
+        1,
+        alice.try_into().unwrap(),
+        4774,
+    );
+    let result = builder()
+        .chain(Chain::Groestlcoin)
+        .add_input(p2wpkh.into())
+        .sign_inputs(alice);
+    assert!(result.is_err());
+}
@@ -0,0 +1,361 @@
+use crate::claim::{AuxRand, KeyPairSigner};
+use crate::htlc::{Htlc, HtlcTimelock};
+use crate::script::to_asm;
+use crate::tests::brc20_transfer::{ALICE_WIF, BOB_WIF};
+use crate::{keypair_from_wif, Recipient, TransactionBuilder, TxInput, TxOutputP2WPKH};
+use bitcoin::blockdata::locktime::absolute::LockTime;
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::key::KeyPair;
+use bitcoin::secp256k1::{self, schnorr, Secp256k1, XOnlyPublicKey};
+use bitcoin::sighash::{EcdsaSighashType, Prevouts, SighashCache, TapSighashType};
+use bitcoin::taproot::{ControlBlock, LeafVersion, TapLeafHash};
+use bitcoin::{Network, PublicKey, ScriptBuf, Sequence, Transaction, TxOut, Txid};
+use std::str::FromStr;
+use tw_encoding::hex;
+
+const PREIMAGE: [u8; 32] = [0x42; 32];
+const FUNDING_TXID: &str = "1e1cdc48aa990d7e154a161d5b5f1cad737742e97d2712ab188027bb42e6e47b";
+const HTLC_SATOSHIS: u64 = 50_000;
+const SEND_SATOSHIS: u64 = 49_000;
+
+fn keys() -> (KeyPair, KeyPair) {
+    (
+        keypair_from_wif(ALICE_WIF).unwrap(),
+        keypair_from_wif(BOB_WIF).unwrap(),
+    )
+}
+
+/// Alice can claim with the preimage, Bob can refund.
+fn htlc(timelock: HtlcTimelock) -> Htlc {
+    let (alice, bob) = keys();
+    Htlc::new(
+        sha256::Hash::hash(&PREIMAGE),
+        Recipient::<PublicKey>::from(alice),
+        Recipient::<PublicKey>::from(bob),
+        timelock,
+    )
+    .unwrap()
+}
+
+fn absolute() -> HtlcTimelock {
+    HtlcTimelock::Absolute(LockTime::from_height(800_000).unwrap())
+}
+
+fn txid() -> Txid {
+    Txid::from_str(FUNDING_TXID).unwrap()
+}
+
+/// Sends the HTLC funds to a P2WPKH output of `keypair`.
+fn spend(input: TxInput, lock_time: LockTime, keypair: KeyPair) -> Transaction {
+    let recipient = Recipient::<PublicKey>::from(keypair);
+
+    TransactionBuilder::new()
+        .lock_time(lock_time)
+        .add_input(input)
+        .add_output(TxOutputP2WPKH::new(SEND_SATOSHIS, recipient.try_into().unwrap()).into())
+        .sign_inputs(KeyPairSigner::new(keypair).aux_rand(AuxRand::Deterministic))
+        .unwrap()
+        .inner
+}
+
+fn verify_p2wsh_signature(tx: &Transaction, witness_script: &ScriptBuf, pubkey: &PublicKey) {
+    let sighash = SighashCache::new(tx)
+        .segwit_signature_hash(0, witness_script, HTLC_SATOSHIS, EcdsaSighashType::All)
+        .unwrap();
+    let message = secp256k1::Message::from_slice(sighash.as_ref()).unwrap();
+
+    let sig = bitcoin::ecdsa::Signature::from_slice(&tx.input[0].witness[0]).unwrap();
+    Secp256k1::new()
+        .verify_ecdsa(&message, &sig.sig, &pubkey.inner)
+        .unwrap();
+}
+
+fn verify_p2tr_spend(tx: &Transaction, htlc: &Htlc, leaf: &ScriptBuf, signer: &KeyPair) {
+    let secp = Secp256k1::new();
+    let witness = &tx.input[0].witness;
+    let script_pubkey = htlc.p2tr_output(HTLC_SATOSHIS).script_pubkey().to_owned();
+
+    // The control block commits to the leaf being executed.
+    let control_block = ControlBlock::decode(witness.last().unwrap()).unwrap();
+    let output_key = XOnlyPublicKey::from_slice(&script_pubkey.as_bytes()[2..]).unwrap();
+    assert!(control_block.verify_taproot_commitment(&secp, output_key, leaf));
+    assert_eq!(witness.second_to_last().unwrap(), leaf.as_bytes());
+
+    // The signature is valid for the leaf.
+    let prevouts = [TxOut {
+        value: HTLC_SATOSHIS,
+        script_pubkey,
+    }];
+    let sighash = SighashCache::new(tx)
+        .taproot_script_spend_signature_hash(
+            0,
+            &Prevouts::All(&prevouts),
+            TapLeafHash::from_script(leaf, LeafVersion::TapScript),
+            TapSighashType::Default,
+        )
+        .unwrap();
+    let message = secp256k1::Message::from_slice(sighash.as_ref()).unwrap();
+    let sig = schnorr::Signature::from_slice(&witness[0]).unwrap();
+    secp.verify_schnorr(&sig, &message, &signer.x_only_public_key().0)
+        .unwrap();
+}
+
+#[test]
+fn htlc_scripts() {
+    let (alice, bob) = keys();
+    let htlc = htlc(absolute());
+
+    let hash = hex::encode(&sha256::Hash::hash(&PREIMAGE).to_byte_array(), false);
+    let alice_key = hex::encode(&alice.public_key().serialize(), false);
+    let bob_key = hex::encode(&bob.public_key().serialize(), false);
+
+    assert_eq!(
+        to_asm(&htlc.witness_script()),
+        format!(
+            "OP_IF OP_SIZE 32 OP_EQUALVERIFY OP_SHA256 {hash} OP_EQUALVERIFY {alice_key} \
+             OP_ELSE 800000 OP_CHECKLOCKTIMEVERIFY OP_DROP {bob_key} OP_ENDIF OP_CHECKSIG"
+        )
+    );
+    assert_eq!(
+        to_asm(&htlc.claim_leaf()),
+        format!(
+            "OP_SIZE 32 OP_EQUALVERIFY OP_SHA256 {hash} OP_EQUALVERIFY {} OP_CHECKSIG",
+            &alice_key[2..]
+        )
+    );
+    assert_eq!(
+        to_asm(&htlc.refund_leaf()),
+        format!(
+            "800000 OP_CHECKLOCKTIMEVERIFY OP_DROP {} OP_CHECKSIG",
+            &bob_key[2..]
+        )
+    );
+
+    let htlc = self::htlc(HtlcTimelock::Relative(Sequence::from_height(144)));
+    assert_eq!(
+        to_asm(&htlc.refund_leaf()),
+        format!(
+            "144 OP_CHECKSEQUENCEVERIFY OP_DROP {} OP_CHECKSIG",
+            &bob_key[2..]
+        )
+    );
+
+    // The addresses match the outputs.
+    let address = htlc.p2wsh_address(Network::Bitcoin);
+    assert!(address.to_string().starts_with("bc1q"));
+    assert_eq!(
+        address.script_pubkey(),
+        htlc.p2wsh_output(HTLC_SATOSHIS).script_pubkey().to_owned()
+    );
+
+    let address = htlc.p2tr_address(Network::Bitcoin);
+    assert!(address.to_string().starts_with("bc1p"));
+    assert_eq!(
+        address.script_pubkey(),
+        htlc.p2tr_output(HTLC_SATOSHIS).script_pubkey().to_owned()
+    );
+
+    // Relative timelocks must be valid sequences.
+    assert!(Htlc::new(
+        sha256::Hash::hash(&PREIMAGE),
+        Recipient::<PublicKey>::from(alice),
+        Recipient::<PublicKey>::from(bob),
+        HtlcTimelock::Relative(Sequence::MAX),
+    )
+    .is_err());
+}
+
+#[test]
+fn htlc_p2wsh_claim() {
+    let (alice, bob) = keys();
+    let htlc = htlc(absolute());
+
+    // A wrong preimage is refused.
+    assert!(htlc
+        .claim_p2wsh(txid(), 0, HTLC_SATOSHIS, &[0x43; 32])
+        .is_err());
+
+    let input = htlc
+        .claim_p2wsh(txid(), 0, HTLC_SATOSHIS, &PREIMAGE)
+        .unwrap();
+
+    // Only Alice can claim.
+    assert!(TransactionBuilder::new()
+        .add_input(input.clone())
+        .add_output(
+            TxOutputP2WPKH::new(
+                SEND_SATOSHIS,
+                Recipient::<PublicKey>::from(bob).try_into().unwrap()
+            )
+            .into()
+        )
+        .sign_inputs(bob)
+        .is_err());
+
+    let tx = spend(input, LockTime::ZERO, alice);
+
+    let witness = &tx.input[0].witness;
+    assert_eq!(witness.len(), 4);
+    assert_eq!(&witness[1], PREIMAGE.as_slice());
+    assert_eq!(&witness[2], &[1]);
+    assert_eq!(&witness[3], htlc.witness_script().as_bytes());
+    verify_p2wsh_signature(
+        &tx,
+        &htlc.witness_script(),
+        &PublicKey::new(alice.public_key()),
+    );
+
+    assert_eq!(tx.lock_time, LockTime::ZERO);
+    assert_eq!(tx.input[0].sequence, Sequence::MAX);
+    // Deterministic signatures result in a fixed wtxid.
+    assert_eq!(
+        tx.wtxid().to_string(),
+        "3279da6876807ccb67f25af74deb7244eb607acc572ebccd85b7c676863d3b51"
+    );
+}
+
+#[test]
+fn htlc_refund_requires_lock_time() {
+    let (_, bob) = keys();
+    let htlc = htlc(absolute());
+    let recipient = Recipient::<PublicKey>::from(bob);
+
+    let inputs = [
+        htlc.refund_p2wsh(txid(), 0, HTLC_SATOSHIS),
+        htlc.refund_p2tr(txid(), 0, HTLC_SATOSHIS),
+    ];
+
+    for input in inputs {
+        let builder = TransactionBuilder::new().add_input(input).add_output(
+            TxOutputP2WPKH::new(SEND_SATOSHIS, recipient.clone().try_into().unwrap()).into(),
+        );
+
+        // The lock time is missing, too early or of the wrong unit.
+        for lock_time in [
+            LockTime::ZERO,
+            LockTime::from_height(799_999).unwrap(),
+            LockTime::from_time(1_700_000_000).unwrap(),
+        ] {
+            assert!(builder
+                .clone()
+                .lock_time(lock_time)
+                .sign_inputs(bob)
+                .is_err());
+        }
+
+        assert!(builder
+            .lock_time(LockTime::from_height(800_001).unwrap())
+            .sign_inputs(KeyPairSigner::new(bob).aux_rand(AuxRand::Deterministic))
+            .is_ok());
+    }
+}
+
+#[test]
+fn htlc_p2wsh_refund() {
+    let (alice, bob) = keys();
+    let htlc = htlc(absolute());
+
+    let input = htlc.refund_p2wsh(txid(), 0, HTLC_SATOSHIS);
+
+    // Only Bob can refund.
+    assert!(TransactionBuilder::new()
+        .lock_time(htlc.refund_lock_time())
+        .add_input(input.clone())
+        .add_output(
+            TxOutputP2WPKH::new(
+                SEND_SATOSHIS,
+                Recipient::<PublicKey>::from(alice).try_into().unwrap()
+            )
+            .into()
+        )
+        .sign_inputs(alice)
+        .is_err());
+
+    let tx = spend(input, htlc.refund_lock_time(), bob);
+
+    // The lock time is enforced by the transaction.
+    assert_eq!(tx.lock_time, LockTime::from_height(800_000).unwrap());
+    assert_eq!(tx.input[0].sequence, Sequence::ENABLE_LOCKTIME_NO_RBF);
+
+    let witness = &tx.input[0].witness;
+    assert_eq!(witness.len(), 3);
+    assert!(witness[1].is_empty());
+    assert_eq!(&witness[2], htlc.witness_script().as_bytes());
+    verify_p2wsh_signature(
+        &tx,
+        &htlc.witness_script(),
+        &PublicKey::new(bob.public_key()),
+    );
+
+    // Deterministic signatures result in a fixed wtxid.
+    assert_eq!(
+        tx.wtxid().to_string(),
+        "9f44f5fad03c0bc6fcf18325a228ea7e2d963095a3adcb89334da7e4c39d61d7"
+    );
+}
+
+#[test]
+fn htlc_p2tr_claim() {
+    let (alice, bob) = keys();
+    let htlc = htlc(absolute());
+
+    assert!(htlc
+        .claim_p2tr(txid(), 0, HTLC_SATOSHIS, &PREIMAGE[..31])
+        .is_err());
+
+    let input = htlc
+        .claim_p2tr(txid(), 0, HTLC_SATOSHIS, &PREIMAGE)
+        .unwrap();
+
+    // Bob is neither the signer of the leaf nor owns the internal key.
+    assert!(TransactionBuilder::new()
+        .add_input(input.clone())
+        .add_output(
+            TxOutputP2WPKH::new(
+                SEND_SATOSHIS,
+                Recipient::<PublicKey>::from(bob).try_into().unwrap()
+            )
+            .into()
+        )
+        .sign_inputs(bob)
+        .is_err());
+
+    let tx = spend(input, LockTime::ZERO, alice);
+
+    let witness = &tx.input[0].witness;
+    assert_eq!(witness.len(), 4);
+    assert_eq!(&witness[1], PREIMAGE.as_slice());
+    verify_p2tr_spend(&tx, &htlc, &htlc.claim_leaf(), &alice);
+
+    // Deterministic signatures result in a fixed wtxid.
+    assert_eq!(
+        tx.wtxid().to_string(),
+        "56ee90ed72416e77038040901cddfc8ad18491803928855fbcf0b4d05bcd57c2"
+    );
+}
+
+#[test]
+fn htlc_p2tr_refund_relative() {
+    let (_, bob) = keys();
+    let htlc = htlc(HtlcTimelock::Relative(Sequence::from_height(144)));
+
+    let tx = spend(
+        htlc.refund_p2tr(txid(), 0, HTLC_SATOSHIS),
+        htlc.refund_lock_time(),
+        bob,
+    );
+
+    // The relative lock time is enforced by the sequence.
+    assert_eq!(tx.version, 2);
+    assert_eq!(tx.lock_time, LockTime::ZERO);
+    assert_eq!(tx.input[0].sequence, Sequence::from_height(144));
+
+    assert_eq!(tx.input[0].witness.len(), 3);
+    verify_p2tr_spend(&tx, &htlc, &htlc.refund_leaf(), &bob);
+
+    // Deterministic signatures result in a fixed wtxid.
+    assert_eq!(
+        tx.wtxid().to_string(),
+        "7788049345d47666148afb3740f1997232b4021e000569a59866005377397020"
+    );
+}
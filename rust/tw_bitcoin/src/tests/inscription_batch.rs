@@ -0,0 +1,189 @@
+use crate::claim::{AuxRand, KeyPairSigner};
+use crate::coin_selection::Utxo;
+use crate::inscription_batch::{BatchMode, InscriptionBatch, DEFAULT_POSTAGE};
+use crate::ordering::TxOrdering;
+use crate::ordinals::{InscriptionContent, OrdinalsInscription};
+use crate::sat_point::SatDestination;
+use crate::tests::p2pkh::{ALICE_WIF, BOB_WIF};
+use crate::{keypair_from_wif, Recipient, TxInputP2WPKH, TxOutput, TxOutputP2WPKH};
+use bitcoin::hashes::Hash;
+use bitcoin::opcodes::all::OP_CHECKSIG;
+use bitcoin::secp256k1::XOnlyPublicKey;
+use bitcoin::{PublicKey, ScriptBuf, Txid};
+use tw_encoding::hex;
+
+const FULL_SATOSHIS: u64 = 100_000;
+const SAT_VB: u64 = 2;
+
+fn alice() -> Recipient<PublicKey> {
+    Recipient::<PublicKey>::from(keypair_from_wif(ALICE_WIF).unwrap())
+}
+
+fn script(recipient: Recipient<PublicKey>) -> ScriptBuf {
+    let output: TxOutput = TxOutputP2WPKH::new(0, recipient.try_into().unwrap()).into();
+    output.script_pubkey().to_owned()
+}
+
+fn bob() -> Recipient<PublicKey> {
+    Recipient::<PublicKey>::from(keypair_from_wif(BOB_WIF).unwrap())
+}
+
+fn batch(mode: BatchMode) -> InscriptionBatch {
+    InscriptionBatch::new(alice())
+        .mode(mode)
+        .sat_vb(SAT_VB)
+        .add_inscription(b"text/plain", b"first", script(bob()))
+        .add_inscription(b"text/plain", b"second", script(alice()))
+        .add_inscription(b"text/plain", b"third", script(bob()))
+}
+
+fn utxos() -> Vec<Utxo> {
+    let input = TxInputP2WPKH::new(
+        Txid::all_zeros(),
+        0,
+        alice().try_into().unwrap(),
+        FULL_SATOSHIS,
+    );
+
+    vec![Utxo::new(input.into())]
+}
+
+#[test]
+fn envelope_pointer() {
+    // A single content without pointer results in the same envelope.
+    let single = OrdinalsInscription::new(b"text/plain", b"hello", alice()).unwrap();
+    let batch = OrdinalsInscription::new_batch(
+        &[InscriptionContent::new(b"text/plain", b"hello").pointer(0)],
+        alice(),
+    )
+    .unwrap();
+    assert_eq!(single.taproot_program(), batch.taproot_program());
+
+    // Two envelopes, where the second has the pointer tag (2) set to 10000.
+    let batch = OrdinalsInscription::new_batch(
+        &[
+            InscriptionContent::new(b"text/plain", b"a"),
+            InscriptionContent::new(b"text/plain", b"b").pointer(10_000),
+        ],
+        alice(),
+    )
+    .unwrap();
+
+    let expected = "0063036f726401010a746578742f706c61696e00016168\
+                    0063036f726401010a746578742f706c61696e010202102700016268";
+    assert_eq!(
+        hex::encode(batch.taproot_program().as_bytes(), false),
+        expected
+    );
+}
+
+#[test]
+fn batch_shared_envelope() {
+    let plan = batch(BatchMode::SharedEnvelope)
+        .plan(&utxos(), script(alice()))
+        .unwrap();
+    assert_eq!(plan.envelopes().len(), 1);
+
+    // Only Alice can reveal the envelope.
+    let guard = ScriptBuf::builder()
+        .push_x_only_key(&XOnlyPublicKey::from(alice().public_key().inner))
+        .push_opcode(OP_CHECKSIG)
+        .into_script();
+    assert!(plan.envelopes()[0]
+        .taproot_program()
+        .as_bytes()
+        .starts_with(guard.as_bytes()));
+
+    // The commit output pays for the postage and the reveal fee.
+    let signer =
+        KeyPairSigner::new(keypair_from_wif(ALICE_WIF).unwrap()).aux_rand(AuxRand::Deterministic);
+    let commit = plan.commit().clone().sign_inputs(signer).unwrap().inner;
+    assert_eq!(commit.output.len(), 2);
+    assert_eq!(
+        commit.output[0].value,
+        3 * DEFAULT_POSTAGE + plan.reveal_fee()
+    );
+    assert_eq!(
+        FULL_SATOSHIS - commit.output.iter().map(|output| output.value).sum::<u64>(),
+        plan.commit_fee()
+    );
+
+    // The first inscription is revealed on the first satoshi of the input,
+    // the others are routed by their pointer. All of them are protected.
+    let reveal = plan.reveal(commit.txid());
+    let destinations: Vec<SatDestination> = reveal
+        .track_inscriptions()
+        .unwrap()
+        .into_iter()
+        .map(|(_, destination)| destination)
+        .collect();
+    assert_eq!(
+        destinations,
+        (0..3)
+            .map(|vout| SatDestination::Output { vout, offset: 0 })
+            .collect::<Vec<_>>()
+    );
+
+    // Reordering keeps each inscription on the output of its destination.
+    let ordered = reveal
+        .clone()
+        .ordering(TxOrdering::Bip69)
+        .sign_inputs(signer)
+        .unwrap()
+        .inner;
+    let scripts: Vec<ScriptBuf> = ordered
+        .output
+        .iter()
+        .map(|output| output.script_pubkey.clone())
+        .collect();
+    assert_eq!(scripts, vec![script(bob()), script(alice()), script(bob())]);
+
+    let reveal = reveal.sign_inputs(signer).unwrap().inner;
+    assert_eq!(reveal.input.len(), 1);
+    assert_eq!(reveal.input[0].previous_output.txid, commit.txid());
+    assert!(reveal
+        .output
+        .iter()
+        .all(|output| output.value == DEFAULT_POSTAGE));
+    assert!(reveal.vsize() as u64 * SAT_VB <= plan.reveal_fee());
+}
+
+#[test]
+fn batch_separate_inputs() {
+    let plan = batch(BatchMode::SeparateInputs)
+        .postage(546)
+        .plan(&utxos(), script(alice()))
+        .unwrap();
+    assert_eq!(plan.envelopes().len(), 3);
+
+    let signer = KeyPairSigner::new(keypair_from_wif(ALICE_WIF).unwrap());
+    let commit = plan.commit().clone().sign_inputs(signer).unwrap().inner;
+    let values: Vec<u64> = commit.output.iter().map(|output| output.value).collect();
+    assert_eq!(&values[..3], &[546, 546, 546 + plan.reveal_fee()]);
+
+    // Each inscription lands on its own output.
+    let reveal = plan.reveal(commit.txid());
+    let destinations: Vec<SatDestination> = reveal
+        .track_inscriptions()
+        .unwrap()
+        .into_iter()
+        .map(|(_, destination)| destination)
+        .collect();
+    assert_eq!(
+        destinations,
+        (0..3)
+            .map(|vout| SatDestination::Output { vout, offset: 0 })
+            .collect::<Vec<_>>()
+    );
+
+    let reveal = reveal.sign_inputs(signer).unwrap().inner;
+    assert_eq!(reveal.input.len(), 3);
+    assert_eq!(reveal.output.len(), 3);
+    assert!(reveal.vsize() as u64 * SAT_VB <= plan.reveal_fee());
+}
+
+#[test]
+fn batch_without_inscriptions() {
+    let plan = InscriptionBatch::new(alice()).plan(&utxos(), script(alice()));
+    assert!(plan.is_err());
+}
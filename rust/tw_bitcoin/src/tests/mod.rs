@@ -1,11 +1,31 @@
 mod address;
+mod bitcoin_cash;
 mod brc20_transfer;
+mod coin_selection;
 mod data;
+mod decred;
 mod fee;
+mod fee_bump;
 mod ffi;
+mod groestlcoin;
+mod htlc;
+mod inscription_batch;
+mod multisig;
+mod musig2;
 mod nft;
+mod ordering;
+mod ordinals;
 mod p2pkh;
 mod p2tr_key_path;
 mod p2wpkh;
+mod pos;
+mod remote_signer;
+mod runes;
+mod sat_point;
+mod script;
+mod silent_payments;
+mod summary;
+mod validation;
+mod zcash;
 
 pub const ONE_BTC: u64 = 100_000_000;
@@ -0,0 +1,277 @@
+use crate::claim::{AuxRand, KeyPairSigner};
+use crate::multisig::TapscriptMultisig;
+use crate::script::to_asm;
+use crate::tests::brc20_transfer::{ALICE_WIF, BOB_WIF};
+use crate::{
+    estimate_weight, keypair_from_wif, Recipient, TransactionBuilder, TxInput,
+    TxInputP2TRScriptPath, TxOutput, TxOutputP2WPKH,
+};
+use bitcoin::key::KeyPair;
+use bitcoin::secp256k1::{self, Secp256k1, SecretKey, XOnlyPublicKey};
+use bitcoin::sighash::{Prevouts, SighashCache, TapSighashType};
+use bitcoin::taproot::{ControlBlock, LeafVersion, TapLeafHash};
+use bitcoin::{Network, PublicKey, ScriptBuf, Transaction, TxOut, Txid};
+use std::str::FromStr;
+use tw_encoding::hex;
+
+const CAROL_WIF: &str = "cQUNzeMnF9xPPLqZhH7hMVYGwSuu3b78zznuc5UrxgXnYQBq6Bx1";
+const TXID: &str = "1e1cdc48aa990d7e154a161d5b5f1cad737742e97d2712ab188027bb42e6e47b";
+const MULTISIG_SATOSHIS: u64 = 100_000;
+const SEND_SATOSHIS: u64 = 99_000;
+
+fn keypairs() -> [KeyPair; 3] {
+    [ALICE_WIF, BOB_WIF, CAROL_WIF].map(|wif| keypair_from_wif(wif).unwrap())
+}
+
+fn xonly(keypair: &KeyPair) -> XOnlyPublicKey {
+    keypair.x_only_public_key().0
+}
+
+/// Generates `count` distinct keys.
+fn generated_keys(count: u8) -> Vec<XOnlyPublicKey> {
+    let secp = Secp256k1::new();
+    (1..=count)
+        .map(|byte| {
+            let secret = SecretKey::from_slice(&[byte; 32]).unwrap();
+            xonly(&KeyPair::from_secret_key(&secp, &secret))
+        })
+        .collect()
+}
+
+fn two_of_three() -> TapscriptMultisig {
+    TapscriptMultisig::new(2, keypairs().iter().map(xonly).collect()).unwrap()
+}
+
+fn output() -> TxOutput {
+    let recipient = Recipient::<PublicKey>::from(keypairs()[0]);
+    TxOutputP2WPKH::new(SEND_SATOSHIS, recipient.try_into().unwrap()).into()
+}
+
+fn sign(input: TxInputP2TRScriptPath, keypair: KeyPair) -> Transaction {
+    TransactionBuilder::new()
+        .add_input(input.into())
+        .add_output(output())
+        .sign_inputs(KeyPairSigner::new(keypair).aux_rand(AuxRand::Deterministic))
+        .unwrap()
+        .inner
+}
+
+fn verify_signature(
+    tx: &Transaction,
+    multisig: &TapscriptMultisig,
+    index: usize,
+    key: &XOnlyPublicKey,
+) {
+    let leaf = multisig.leaf_script();
+    let prevouts = [TxOut {
+        value: MULTISIG_SATOSHIS,
+        script_pubkey: multisig
+            .p2tr_output(MULTISIG_SATOSHIS)
+            .script_pubkey()
+            .to_owned(),
+    }];
+
+    let sighash = SighashCache::new(tx)
+        .taproot_script_spend_signature_hash(
+            0,
+            &Prevouts::All(&prevouts),
+            TapLeafHash::from_script(&leaf, LeafVersion::TapScript),
+            TapSighashType::Default,
+        )
+        .unwrap();
+    let message = secp256k1::Message::from_slice(sighash.as_ref()).unwrap();
+
+    let sig = secp256k1::schnorr::Signature::from_slice(&tx.input[0].witness[index]).unwrap();
+    Secp256k1::new()
+        .verify_schnorr(&sig, &message, key)
+        .unwrap();
+}
+
+#[test]
+fn multisig_leaf_script() {
+    let multisig = two_of_three();
+    let [alice, bob, carol] = keypairs();
+
+    let key = |keypair: &KeyPair| hex::encode(&xonly(keypair).serialize(), false);
+    assert_eq!(
+        to_asm(&multisig.leaf_script()),
+        format!(
+            "{} OP_CHECKSIG {} OP_CHECKSIGADD {} OP_CHECKSIGADD 2 OP_NUMEQUAL",
+            key(&alice),
+            key(&bob),
+            key(&carol)
+        )
+    );
+
+    // The leaf script can be parsed back.
+    assert_eq!(
+        TapscriptMultisig::from_leaf(&multisig.leaf_script()),
+        Some(multisig.clone())
+    );
+
+    // Thresholds above 16 are pushed as data.
+    let large = TapscriptMultisig::new(17, generated_keys(20)).unwrap();
+    assert!(to_asm(&large.leaf_script()).ends_with("OP_CHECKSIGADD 17 OP_NUMEQUAL"));
+    assert_eq!(
+        TapscriptMultisig::from_leaf(&large.leaf_script()),
+        Some(large)
+    );
+
+    // Other scripts are not recognized.
+    let single_sig = ScriptBuf::builder()
+        .push_x_only_key(&xonly(&alice))
+        .push_opcode(bitcoin::opcodes::all::OP_CHECKSIG)
+        .into_script();
+    assert!(TapscriptMultisig::from_leaf(&single_sig).is_none());
+
+    // The address matches the output.
+    let address = multisig.p2tr_address(Network::Bitcoin);
+    assert!(address.to_string().starts_with("bc1p"));
+    assert_eq!(
+        address.script_pubkey(),
+        multisig
+            .p2tr_output(MULTISIG_SATOSHIS)
+            .script_pubkey()
+            .to_owned()
+    );
+}
+
+#[test]
+fn multisig_invalid_threshold() {
+    let keys = generated_keys(3);
+
+    assert!(TapscriptMultisig::new(0, keys.clone()).is_err());
+    assert!(TapscriptMultisig::new(4, keys.clone()).is_err());
+    assert!(TapscriptMultisig::new(3, keys.clone()).is_ok());
+
+    // Duplicate keys are refused.
+    assert!(TapscriptMultisig::new(2, vec![keys[0], keys[1], keys[0]]).is_err());
+}
+
+#[test]
+fn multisig_sign_incrementally() {
+    let multisig = two_of_three();
+    let [alice, _, carol] = keypairs();
+    let txid = Txid::from_str(TXID).unwrap();
+
+    // Carol signs first, Alice and Bob are absent.
+    let tx = sign(multisig.input(txid, 0, MULTISIG_SATOSHIS), carol);
+
+    let witness = &tx.input[0].witness;
+    assert_eq!(witness.len(), 5);
+    assert_eq!(witness[0].len(), 64);
+    assert!(witness[1].is_empty());
+    assert!(witness[2].is_empty());
+    assert!(!multisig.is_complete(witness));
+    verify_signature(&tx, &multisig, 0, &xonly(&carol));
+
+    let signatures = multisig.signatures(witness).unwrap();
+    assert_eq!(signatures.len(), 1);
+    assert_eq!(signatures[0].0, xonly(&carol));
+
+    // Alice adds her signature, completing the Witness.
+    let mut input = multisig.input(txid, 0, MULTISIG_SATOSHIS);
+    for (key, sig) in signatures {
+        input = input.add_signature(key, sig);
+    }
+
+    let tx = sign(input.clone(), alice);
+
+    // <sig carol> <> <sig alice> <script> <control block>
+    let witness = &tx.input[0].witness;
+    assert_eq!(witness.len(), 5);
+    assert!(witness[1].is_empty());
+    assert!(multisig.is_complete(witness));
+    verify_signature(&tx, &multisig, 0, &xonly(&carol));
+    verify_signature(&tx, &multisig, 2, &xonly(&alice));
+
+    assert_eq!(&witness[3], multisig.leaf_script().as_bytes());
+    let control_block = ControlBlock::decode(&witness[4]).unwrap();
+    let script_pubkey = multisig
+        .p2tr_output(MULTISIG_SATOSHIS)
+        .script_pubkey()
+        .to_owned();
+    let output_key = XOnlyPublicKey::from_slice(&script_pubkey.as_bytes()[2..]).unwrap();
+    assert!(control_block.verify_taproot_commitment(
+        &Secp256k1::new(),
+        output_key,
+        &multisig.leaf_script()
+    ));
+
+    // The weight estimate accounts for the absent signer.
+    let estimated = estimate_weight(&[TxInput::from(input)], &[output()]);
+    assert_eq!(estimated, tx.weight());
+}
+
+#[test]
+fn multisig_refuses_unknown_signer() {
+    let multisig = two_of_three();
+    let txid = Txid::from_str(TXID).unwrap();
+
+    let secret = SecretKey::from_slice(&[1; 32]).unwrap();
+    let stranger = KeyPair::from_secret_key(&Secp256k1::new(), &secret);
+
+    let result = TransactionBuilder::new()
+        .add_input(multisig.input(txid, 0, MULTISIG_SATOSHIS).into())
+        .add_output(output())
+        .sign_inputs(stranger);
+    assert!(result.is_err());
+}
+
+/// Evaluates the leaf script as `OP_CHECKSIG` and `OP_CHECKSIGADD` would:
+/// each non-empty signature must be valid and their count must equal the
+/// threshold of `OP_NUMEQUAL`.
+fn satisfies_script(tx: &Transaction, multisig: &TapscriptMultisig) -> bool {
+    let witness = &tx.input[0].witness;
+    let keys = multisig.keys();
+
+    let mut count = 0;
+    for (index, key) in keys.iter().rev().enumerate() {
+        if !witness[index].is_empty() {
+            verify_signature(tx, multisig, index, key);
+            count += 1;
+        }
+    }
+
+    count == multisig.required()
+}
+
+#[test]
+fn multisig_sign_with_all_keys() {
+    let multisig = two_of_three();
+    let [alice, bob, carol] = keypairs();
+    let txid = Txid::from_str(TXID).unwrap();
+
+    // Every signer adds their signature to those of the previous signers.
+    let mut signatures = vec![];
+    let mut tx = None;
+    for keypair in [alice, bob, carol] {
+        let mut input = multisig.input(txid, 0, MULTISIG_SATOSHIS);
+        for (key, sig) in &signatures {
+            input = input.add_signature(*key, *sig);
+        }
+
+        let signed = sign(input, keypair);
+        signatures = multisig.signatures(&signed.input[0].witness).unwrap();
+        tx = Some(signed);
+    }
+
+    // Only two signatures are used, including Carol's as the last signer.
+    // <sig carol> <> <sig alice> <script> <control block>
+    let tx = tx.unwrap();
+    let witness = &tx.input[0].witness;
+    assert_eq!(witness.len(), 5);
+    assert!(witness[1].is_empty());
+    assert!(multisig.is_complete(witness));
+    assert!(satisfies_script(&tx, &multisig));
+
+    // A Witness with all three signatures fails the script.
+    let mut overfull = tx.clone();
+    let bob_only = sign(multisig.input(txid, 0, MULTISIG_SATOSHIS), bob);
+    let mut items = overfull.input[0].witness.to_vec();
+    items[1] = bob_only.input[0].witness[1].to_vec();
+    overfull.input[0].witness = bitcoin::Witness::from_slice(&items);
+
+    assert!(!multisig.is_complete(&overfull.input[0].witness));
+    assert!(!satisfies_script(&overfull, &multisig));
+}
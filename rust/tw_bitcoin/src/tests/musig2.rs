@@ -0,0 +1,370 @@
+use crate::claim::ClaimLocation;
+use crate::musig2::{
+    key_path_witness, nonce_gen, KeyAggContext, PartialSignature, PublicNonce, SecretNonce, Session,
+};
+use crate::tests::brc20_transfer::{ALICE_WIF, BOB_WIF};
+use crate::utils::tagged_hash;
+use crate::{
+    keypair_from_wif, Recipient, TransactionBuilder, TxInputP2TRKeyPath, TxOutput, TxOutputP2WPKH,
+};
+use bitcoin::key::KeyPair;
+use bitcoin::secp256k1::{self, schnorr, Message, PublicKey, Secp256k1, SecretKey};
+use bitcoin::sighash::{Prevouts, SighashCache};
+use bitcoin::{Transaction, TxOut, Txid};
+use std::str::FromStr;
+use tw_encoding::hex;
+
+const TXID: &str = "1e1cdc48aa990d7e154a161d5b5f1cad737742e97d2712ab188027bb42e6e47b";
+const FULL_SATOSHIS: u64 = 100_000;
+const SEND_SATOSHIS: u64 = 99_000;
+
+fn pubkey(hex_str: &str) -> PublicKey {
+    PublicKey::from_slice(&hex::decode(hex_str).unwrap()).unwrap()
+}
+
+fn keypairs() -> [KeyPair; 2] {
+    [ALICE_WIF, BOB_WIF].map(|wif| keypair_from_wif(wif).unwrap())
+}
+
+fn key_agg() -> KeyAggContext {
+    KeyAggContext::new(keypairs().iter().map(KeyPair::public_key).collect()).unwrap()
+}
+
+/// Runs both rounds of the protocol between Alice and Bob.
+fn sign(key_agg: &KeyAggContext, message: &Message) -> schnorr::Signature {
+    let [alice, bob] = keypairs();
+
+    // First round: exchange the public nonces.
+    let (alice_secret, alice_public) = key_agg.generate_nonce(&alice, message).unwrap();
+    let (bob_secret, bob_public) = key_agg.generate_nonce(&bob, message).unwrap();
+
+    // Second round: exchange the partial signatures.
+    let session = Session::new(key_agg, &[alice_public, bob_public], message).unwrap();
+    let alice_partial = session.partial_sign(alice_secret, &alice).unwrap();
+    let bob_partial = session.partial_sign(bob_secret, &bob).unwrap();
+
+    assert!(session.verify_partial_signature(&alice_partial, &alice_public, &alice.public_key()));
+    assert!(session.verify_partial_signature(&bob_partial, &bob_public, &bob.public_key()));
+
+    session.aggregate(&[alice_partial, bob_partial]).unwrap()
+}
+
+fn builder(key_agg: &KeyAggContext) -> TransactionBuilder {
+    let txid = Txid::from_str(TXID).unwrap();
+    let input = TxInputP2TRKeyPath::new(txid, 0, key_agg.recipient(), FULL_SATOSHIS);
+
+    let recipient = Recipient::<bitcoin::PublicKey>::from(keypairs()[1]);
+    let output: TxOutput = TxOutputP2WPKH::new(SEND_SATOSHIS, recipient.try_into().unwrap()).into();
+
+    TransactionBuilder::new()
+        .add_input(input.into())
+        .add_output(output)
+}
+
+#[test]
+fn musig2_key_agg_vectors() {
+    // Test vectors of BIP-327.
+    let x0 = pubkey("02F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9");
+    let x1 = pubkey("03DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659");
+    let x2 = pubkey("023590A94E768F8E1815C2F24B4D80A8E3149316C3518CE7B7AD338368D038CA66");
+
+    let vectors = [
+        (
+            vec![x0, x1, x2],
+            "90539eede565f5d054f32cc0c220126889ed1e5d193baf15aef344fe59d4610c",
+        ),
+        (
+            vec![x2, x1, x0],
+            "6204de8b083426dc6eaf9502d27024d53fc826bf7d2012148a0575435df54b2b",
+        ),
+        (
+            vec![x0, x0, x0],
+            "b436e3bad62b8cd409969a224731c193d051162d8c5ae8b109306127da3aa935",
+        ),
+        (
+            vec![x0, x0, x1, x1],
+            "69bc22bfa5d106306e48a20679de1d7389386124d07571d0d872686028c26a3e",
+        ),
+    ];
+
+    for (keys, expected) in vectors {
+        let key_agg = KeyAggContext::new(keys).unwrap();
+        assert_eq!(
+            hex::encode(&key_agg.internal_key().serialize(), false),
+            expected
+        );
+    }
+
+    assert!(KeyAggContext::new(vec![]).is_err());
+}
+
+#[test]
+fn musig2_nonce_gen() {
+    let secret_key = SecretKey::from_slice(&[2; 32]).unwrap();
+    let pubkey = secret_key.public_key(&Secp256k1::new());
+    let rand = [0; 32];
+
+    let nonce = |rand: [u8; 32], secret_key: Option<&SecretKey>| {
+        nonce_gen(
+            rand,
+            secret_key,
+            &pubkey,
+            Some(&[7; 32]),
+            Some(&[1; 32]),
+            &[8; 32],
+        )
+        .unwrap()
+    };
+
+    // The secret key is masked with the hash of the randomness, which is
+    // then used in place of the randomness.
+    let aux = tagged_hash("MuSig/aux", &[&rand]);
+    let mut masked = secret_key.secret_bytes();
+    for (byte, aux) in masked.iter_mut().zip(aux) {
+        *byte ^= aux;
+    }
+
+    let (secret, public) = nonce(rand, Some(&secret_key));
+    let (expected_secret, expected_public) = nonce(masked, None);
+    assert_eq!(
+        secret.dangerous_into_bytes(),
+        expected_secret.dangerous_into_bytes()
+    );
+    assert_eq!(public, expected_public);
+
+    // Every input is committed to.
+    let (_, other) = nonce_gen(
+        rand,
+        Some(&secret_key),
+        &pubkey,
+        None,
+        Some(&[1; 32]),
+        &[8; 32],
+    )
+    .unwrap();
+    assert_ne!(other, public);
+    let (_, other) = nonce_gen(
+        rand,
+        Some(&secret_key),
+        &pubkey,
+        Some(&[7; 32]),
+        None,
+        &[8; 32],
+    )
+    .unwrap();
+    assert_ne!(other, public);
+    let (_, other) = nonce_gen(
+        rand,
+        Some(&secret_key),
+        &pubkey,
+        Some(&[7; 32]),
+        Some(&[1; 32]),
+        &[],
+    )
+    .unwrap();
+    assert_ne!(other, public);
+}
+
+#[test]
+fn musig2_sign_vectors() {
+    // Test vectors of BIP-327.
+    let keypair = KeyPair::from_seckey_slice(
+        &Secp256k1::new(),
+        &hex::decode("7FB9E0E687ADA1EEBF7ECFE2F21E73EBDB51A7D450948DFE8D76D7F2D1007671").unwrap(),
+    )
+    .unwrap();
+    let pubkeys = [
+        pubkey("03935F972DA013F80AE011890FA89B67A27B7BE6CCB24D3274D18B2D4067F261A9"),
+        pubkey("02F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9"),
+        pubkey("02DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA661"),
+    ];
+    assert_eq!(keypair.public_key(), pubkeys[0]);
+
+    let secnonce = hex::decode("508B81A611F100A6B2B6B29656590898AF488BCF2E1F55CF22E5CFB84421FE61FA27FD49B1D50085B481285E1CA205D55C82CC1B31FF5CD54A489829355901F703935F972DA013F80AE011890FA89B67A27B7BE6CCB24D3274D18B2D4067F261A9").unwrap();
+    let pnonces = [
+        "0337C87821AFD50A8644D820A8F3E02E499C931865C2360FB43D0A0D20DAFE07EA0287BF891D2A6DEAEBADC909352AA9405D1428C15F4B75F04DAE642A95C2548480",
+        "0279BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F817980279BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798",
+        "032DE2662628C90B03F5E720284EB52FF7D71F4284F627B68A853D78C78E1FFE9303E4C5524E83FFE1493B9077CF1CA6BEB2090C93D930321071AD40B2F44E599046",
+    ]
+    .map(|pnonce| PublicNonce::from_slice(&hex::decode(pnonce).unwrap()).unwrap());
+    let message = Message::from_slice(
+        &hex::decode("F95466D086770E689964664219266FE5ED215C92AE20BAB5C9D79ADDDDF3C0CF").unwrap(),
+    )
+    .unwrap();
+
+    let vectors = [
+        (
+            [0, 1, 2],
+            "012abbcb52b3016ac03ad82395a1a415c48b93def78718e62a7a90052fe224fb",
+        ),
+        (
+            [1, 0, 2],
+            "9ff2f7aaa856150cc8819254218d3adeeb0535269051897724f9db3789513a52",
+        ),
+        (
+            [1, 2, 0],
+            "fa23c359f6fac4e7796bb93bc9f0532a95468c539ba20ff86d7c76ed92227900",
+        ),
+    ];
+
+    for (indices, expected) in vectors {
+        let keys = indices.iter().map(|index| pubkeys[*index]).collect();
+        let nonces: Vec<PublicNonce> = indices.iter().map(|index| pnonces[*index]).collect();
+
+        let key_agg = KeyAggContext::new_untweaked(keys).unwrap();
+        let session = Session::new(&key_agg, &nonces, &message).unwrap();
+
+        let partial = session
+            .partial_sign(
+                SecretNonce::dangerous_from_bytes(&secnonce).unwrap(),
+                &keypair,
+            )
+            .unwrap();
+        assert_eq!(hex::encode(&partial.serialize(), false), expected);
+        assert!(session.verify_partial_signature(&partial, &pnonces[0], &pubkeys[0]));
+    }
+}
+
+#[test]
+fn musig2_key_agg_taproot_tweak() {
+    let key_agg = key_agg();
+    let secp = Secp256k1::new();
+
+    // The output key is the BIP-86 tweak of the aggregated key.
+    let expected = bitcoin::Address::p2tr(
+        &secp,
+        key_agg.internal_key(),
+        None,
+        bitcoin::Network::Bitcoin,
+    );
+    let actual = bitcoin::Address::p2tr_tweaked(
+        key_agg.recipient().tweaked_pubkey(),
+        bitcoin::Network::Bitcoin,
+    );
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn musig2_sign_key_path() {
+    let key_agg = key_agg();
+
+    let sighashes = builder(&key_agg).signature_hashes().unwrap();
+    assert_eq!(sighashes.len(), 1);
+    let sig = sign(&key_agg, &sighashes[0]);
+
+    let tx: Transaction = builder(&key_agg)
+        .sign_inputs_fn(|_, sighash| {
+            assert_eq!(sighash, sighashes[0]);
+            Ok(ClaimLocation::Witness(key_path_witness(sig)))
+        })
+        .unwrap()
+        .inner;
+
+    // The Witness is a single signature, like any other key-path spend.
+    let witness = &tx.input[0].witness;
+    assert_eq!(witness.len(), 1);
+    assert_eq!(witness[0].len(), 64);
+
+    let prevouts = [TxOut {
+        value: FULL_SATOSHIS,
+        script_pubkey: bitcoin::ScriptBuf::new_v1_p2tr_tweaked(
+            key_agg.recipient().tweaked_pubkey(),
+        ),
+    }];
+    let sighash = SighashCache::new(&tx)
+        .taproot_key_spend_signature_hash(
+            0,
+            &Prevouts::All(&prevouts),
+            bitcoin::sighash::TapSighashType::Default,
+        )
+        .unwrap();
+    let message = secp256k1::Message::from_slice(sighash.as_ref()).unwrap();
+
+    let sig = schnorr::Signature::from_slice(&witness[0]).unwrap();
+    let output_key = key_agg.recipient().tweaked_pubkey().to_inner();
+    Secp256k1::new()
+        .verify_schnorr(&sig, &message, &output_key)
+        .unwrap();
+}
+
+#[test]
+fn musig2_nonce_serialization() {
+    let key_agg = key_agg();
+    let [alice, _] = keypairs();
+    let message = Message::from_slice(&[7; 32]).unwrap();
+
+    let (secret, public) = key_agg
+        .generate_nonce_with_rand(&alice, &message, [1; 32])
+        .unwrap();
+
+    let serialized = secret.dangerous_into_bytes();
+    assert_eq!(
+        SecretNonce::dangerous_from_bytes(&serialized)
+            .unwrap()
+            .dangerous_into_bytes(),
+        serialized
+    );
+    assert_eq!(
+        PublicNonce::from_slice(&public.serialize()).unwrap(),
+        public
+    );
+
+    // The same randomness produces the same nonce, a different one doesn't.
+    let (_, same) = key_agg
+        .generate_nonce_with_rand(&alice, &message, [1; 32])
+        .unwrap();
+    let (_, other) = key_agg
+        .generate_nonce_with_rand(&alice, &message, [2; 32])
+        .unwrap();
+    assert_eq!(same, public);
+    assert_ne!(other, public);
+
+    assert!(SecretNonce::dangerous_from_bytes(&serialized[1..]).is_err());
+    assert!(PublicNonce::from_slice(&[0; PublicNonce::SIZE]).is_err());
+}
+
+#[test]
+fn musig2_invalid_signing() {
+    let key_agg = key_agg();
+    let [alice, bob] = keypairs();
+    let message = Message::from_slice(&[7; 32]).unwrap();
+
+    let stranger = KeyPair::from_seckey_slice(&Secp256k1::new(), &[1; 32]).unwrap();
+    assert!(key_agg.generate_nonce(&stranger, &message).is_err());
+
+    let (alice_secret, alice_public) = key_agg.generate_nonce(&alice, &message).unwrap();
+    let (bob_secret, bob_public) = key_agg.generate_nonce(&bob, &message).unwrap();
+
+    // Exactly one public nonce per signer is required.
+    assert!(Session::new(&key_agg, &[], &message).is_err());
+    assert!(Session::new(&key_agg, &[alice_public], &message).is_err());
+    let nonces = [alice_public, bob_public, alice_public];
+    assert!(Session::new(&key_agg, &nonces, &message).is_err());
+
+    let session = Session::new(&key_agg, &[alice_public, bob_public], &message).unwrap();
+
+    // The nonce belongs to another signer, no signature is created.
+    let bob_bytes = bob_secret.dangerous_into_bytes();
+    let bob_secret = SecretNonce::dangerous_from_bytes(&bob_bytes).unwrap();
+    assert!(session.partial_sign(bob_secret, &alice).is_err());
+
+    // The nonce is not part of the session.
+    let (other_secret, _) = key_agg.generate_nonce(&alice, &message).unwrap();
+    assert!(session.partial_sign(other_secret, &alice).is_err());
+
+    let alice_partial = session.partial_sign(alice_secret, &alice).unwrap();
+    let bob_partial = session
+        .partial_sign(SecretNonce::dangerous_from_bytes(&bob_bytes).unwrap(), &bob)
+        .unwrap();
+
+    // A partial signature doesn't verify for another signer.
+    assert!(!session.verify_partial_signature(&alice_partial, &bob_public, &bob.public_key()));
+
+    // All partial signatures are required, and must be valid.
+    assert!(session.aggregate(&[alice_partial]).is_err());
+    let mut tampered = alice_partial.serialize();
+    tampered[31] ^= 1;
+    let tampered = PartialSignature::from_slice(&tampered).unwrap();
+    assert!(session.aggregate(&[tampered, bob_partial]).is_err());
+    assert!(session.aggregate(&[alice_partial, bob_partial]).is_ok());
+}
@@ -1,3 +1,4 @@
+use crate::claim::{AuxRand, KeyPairSigner};
 use crate::nft::OrdinalNftInscription;
 use crate::{
     keypair_from_wif, TXOutputP2TRScriptPath, TransactionBuilder, TxInputP2TRScriptPath,
@@ -76,9 +77,12 @@ fn inscribe_nft() {
         .build()
         .unwrap();
 
-    let transaction = TransactionBuilder::new()
+    let builder = TransactionBuilder::new()
         .add_input(input.into())
-        .add_output(output.into())
+        .add_output(output.into());
+
+    let transaction = builder
+        .clone()
         .sign_inputs(alice)
         .unwrap()
         .serialize()
@@ -92,4 +96,16 @@ fn inscribe_nft() {
     assert_eq!(hex[292..], REVEAL_RAW_TX[292..]);
 
     // Successfully broadcasted: https://www.blockchain.com/explorer/transactions/btc/173f8350b722243d44cc8db5584de76b432eb6d0888d9e66e662db51584f44ac
+
+    // Without auxiliary randomness, the signature is reproducible.
+    let sign_deterministic = || {
+        let signer = KeyPairSigner::new(alice).aux_rand(AuxRand::Deterministic);
+        builder
+            .clone()
+            .sign_inputs(signer)
+            .unwrap()
+            .serialize()
+            .unwrap()
+    };
+    assert_eq!(sign_deterministic(), sign_deterministic());
 }
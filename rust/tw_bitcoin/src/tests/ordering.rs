@@ -0,0 +1,178 @@
+use crate::claim::ClaimLocation;
+use crate::ordering::TxOrdering;
+use crate::runes::{Artifact, RuneId, Runestone};
+use crate::tests::p2pkh::{ALICE_WIF, BOB_WIF};
+use crate::{
+    keypair_from_wif, Recipient, TransactionBuilder, TxInput, TxInputP2WPKH, TxOutput,
+    TxOutputP2PKH, TxOutputP2WPKH,
+};
+use bitcoin::{PublicKey, Transaction, Txid, Witness};
+use std::str::FromStr;
+
+// Those txids are sorted in reverse order when compared by their internal
+// byte order.
+const TXID_LOW: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const TXID_HIGH: &str = "0100000000000000000000000000000000000000000000000000000000000000";
+
+fn input(txid: &str, vout: u32, satoshis: u64) -> TxInput {
+    let alice = Recipient::<PublicKey>::from(keypair_from_wif(ALICE_WIF).unwrap());
+    TxInputP2WPKH::new(
+        Txid::from_str(txid).unwrap(),
+        vout,
+        alice.try_into().unwrap(),
+        satoshis,
+    )
+    .into()
+}
+
+fn bob() -> Recipient<PublicKey> {
+    Recipient::<PublicKey>::from(keypair_from_wif(BOB_WIF).unwrap())
+}
+
+fn p2wpkh(satoshis: u64) -> TxOutput {
+    TxOutputP2WPKH::new(satoshis, bob().try_into().unwrap()).into()
+}
+
+fn p2pkh(satoshis: u64) -> TxOutput {
+    TxOutputP2PKH::new(satoshis, bob()).into()
+}
+
+fn builder() -> TransactionBuilder {
+    TransactionBuilder::new()
+        .add_input(input(TXID_HIGH, 0, 10_000))
+        .add_input(input(TXID_LOW, 1, 10_000))
+        .add_input(input(TXID_LOW, 0, 10_000))
+        .add_output(p2wpkh(2_000))
+        .add_output(p2pkh(1_000))
+        .add_output(p2wpkh(1_000))
+        .add_output(p2wpkh(20_000))
+}
+
+/// Builds the (unsigned) transaction in its final order.
+fn build(builder: TransactionBuilder) -> Transaction {
+    builder
+        .sign_inputs_fn(|_, _| Ok(ClaimLocation::Witness(Witness::new())))
+        .unwrap()
+        .inner
+}
+
+fn outpoints(tx: &Transaction) -> Vec<(String, u32)> {
+    tx.input
+        .iter()
+        .map(|txin| {
+            let point = txin.previous_output;
+            (point.txid.to_string(), point.vout)
+        })
+        .collect()
+}
+
+fn values(tx: &Transaction) -> Vec<u64> {
+    tx.output.iter().map(|output| output.value).collect()
+}
+
+#[test]
+fn ordering_insertion() {
+    let tx = build(builder());
+
+    assert_eq!(
+        outpoints(&tx),
+        vec![
+            (TXID_HIGH.to_string(), 0),
+            (TXID_LOW.to_string(), 1),
+            (TXID_LOW.to_string(), 0)
+        ]
+    );
+    assert_eq!(values(&tx), vec![2_000, 1_000, 1_000, 20_000]);
+}
+
+#[test]
+fn ordering_bip69() {
+    let tx = build(builder().ordering(TxOrdering::Bip69));
+
+    assert_eq!(
+        outpoints(&tx),
+        vec![
+            (TXID_LOW.to_string(), 0),
+            (TXID_LOW.to_string(), 1),
+            (TXID_HIGH.to_string(), 0)
+        ]
+    );
+
+    // Outputs of equal value are sorted by their scriptPubKey. The P2WPKH
+    // script (starting with `OP_0`) comes before the P2PKH script (starting
+    // with `OP_DUP`).
+    assert_eq!(values(&tx), vec![1_000, 1_000, 2_000, 20_000]);
+    assert!(tx.output[0].script_pubkey.is_v0_p2wpkh());
+    assert!(tx.output[1].script_pubkey.is_p2pkh());
+}
+
+#[test]
+fn ordering_shuffle() {
+    let first = build(builder().ordering(TxOrdering::Shuffle(1)));
+    let second = build(builder().ordering(TxOrdering::Shuffle(1)));
+
+    // The same seed results in the same order.
+    assert_eq!(first, second);
+
+    // Any seed results in a permutation.
+    for seed in 0..10 {
+        let tx = build(builder().ordering(TxOrdering::Shuffle(seed)));
+
+        let mut values = values(&tx);
+        values.sort();
+        assert_eq!(values, vec![1_000, 1_000, 2_000, 20_000]);
+        assert_eq!(tx.input.len(), 3);
+    }
+
+    // Different seeds result in different orders.
+    let orders: Vec<_> = (0..10)
+        .map(|seed| build(builder().ordering(TxOrdering::Shuffle(seed))))
+        .collect();
+    assert!(orders.iter().any(|tx| tx != &first));
+}
+
+#[test]
+fn ordering_pin_first_input_output() {
+    let tx = build(
+        builder()
+            .ordering(TxOrdering::Bip69)
+            .pin_first_input_output(true),
+    );
+
+    // The first input and output keep their position.
+    assert_eq!(
+        outpoints(&tx),
+        vec![
+            (TXID_HIGH.to_string(), 0),
+            (TXID_LOW.to_string(), 0),
+            (TXID_LOW.to_string(), 1)
+        ]
+    );
+    assert_eq!(values(&tx), vec![2_000, 1_000, 1_000, 20_000]);
+    assert!(tx.output[1].script_pubkey.is_v0_p2wpkh());
+}
+
+#[test]
+fn ordering_keeps_runestone_outputs() {
+    let id = RuneId::new(840_000, 1).unwrap();
+
+    for ordering in [
+        TxOrdering::Bip69,
+        TxOrdering::Shuffle(1),
+        TxOrdering::Shuffle(2),
+    ] {
+        let tx = build(
+            builder()
+                .transfer_runes(vec![(id, 1_000, p2pkh(546))], Some(p2wpkh(547)))
+                .ordering(ordering),
+        );
+
+        // The outputs keep the indices referenced by the runestone.
+        let Some(Artifact::Runestone(runestone)) = Runestone::decipher(&tx) else {
+            panic!("missing runestone");
+        };
+        assert_eq!(tx.output[runestone.edicts[0].output as usize].value, 546);
+        assert_eq!(tx.output[runestone.pointer.unwrap() as usize].value, 547);
+        assert_eq!(values(&tx), vec![2_000, 1_000, 1_000, 20_000, 546, 547, 0]);
+    }
+}
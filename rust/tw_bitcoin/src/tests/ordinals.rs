@@ -0,0 +1,102 @@
+use crate::claim::{AuxRand, KeyPairSigner};
+use crate::ordinals::{
+    unspendable_internal_key, InscriptionContent, OrdinalsInscription, NUMS_POINT,
+};
+use crate::tests::p2pkh::{ALICE_WIF, BOB_WIF};
+use crate::{keypair_from_wif, Recipient, TransactionBuilder, TxInputP2TRScriptPath};
+use bitcoin::secp256k1::XOnlyPublicKey;
+use bitcoin::{PublicKey, Txid};
+use std::str::FromStr;
+
+const COMMIT_TXID: &str = "579590c3227253ad423b1e7e3c5b073b8a280d307c68aecd779df2600daa2f99";
+const COMMIT_SATOSHIS: u64 = 10_000;
+const POSTAGE: u64 = 546;
+
+fn content() -> InscriptionContent {
+    InscriptionContent::new(b"text/plain", b"inscribed on behalf of Alice")
+}
+
+/// Builds and signs the reveal transaction of the `inscription`.
+fn reveal(inscription: &OrdinalsInscription, signer: KeyPairSigner) -> crate::Result<()> {
+    let input = TxInputP2TRScriptPath::new(
+        Txid::from_str(COMMIT_TXID).unwrap(),
+        0,
+        inscription.recipient().clone(),
+        COMMIT_SATOSHIS,
+        inscription.taproot_program().to_owned(),
+        inscription.spend_info().clone(),
+    );
+
+    TransactionBuilder::new()
+        .add_input(input.into())
+        .add_output(inscription.reveal_output(POSTAGE)?)
+        .sign_inputs(signer)
+        .map(|_| ())
+}
+
+#[test]
+fn inscription_requires_reveal_key() {
+    let builder = OrdinalsInscription::builder().content(content());
+    assert!(builder.build().is_err());
+}
+
+#[test]
+fn inscription_on_behalf_of_user() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let service = keypair_from_wif(BOB_WIF).unwrap();
+    let alice_recipient = Recipient::<PublicKey>::from(alice);
+
+    // The service reveals, while Alice receives the inscription. The commit
+    // output cannot be spent via key-path.
+    let inscription = OrdinalsInscription::builder()
+        .content(content())
+        .reveal_key(Recipient::<PublicKey>::from(service).public_key())
+        .destination(alice_recipient.clone())
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        inscription.recipient().untweaked_pubkey(),
+        XOnlyPublicKey::from_slice(&NUMS_POINT).unwrap()
+    );
+    assert_eq!(
+        inscription.recipient().untweaked_pubkey(),
+        XOnlyPublicKey::from(unspendable_internal_key().inner)
+    );
+    assert_eq!(inscription.destination(), Some(&alice_recipient));
+
+    // The envelope starts with `<service> OP_CHECKSIG`.
+    let script = inscription.taproot_program().as_bytes();
+    assert_eq!(script[0], 32);
+    assert_eq!(&script[1..33], &service.x_only_public_key().0.serialize());
+    assert_eq!(script[33], bitcoin::opcodes::all::OP_CHECKSIG.to_u8());
+
+    // Only the service can reveal.
+    let signer = KeyPairSigner::new(service).aux_rand(AuxRand::Deterministic);
+    reveal(&inscription, signer).unwrap();
+    assert!(reveal(&inscription, KeyPairSigner::new(alice)).is_err());
+}
+
+#[test]
+fn inscription_with_internal_key() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let service = keypair_from_wif(BOB_WIF).unwrap();
+    let alice_recipient = Recipient::<PublicKey>::from(alice);
+
+    // Alice owns the commit output, but only the service can reveal it.
+    let inscription = OrdinalsInscription::builder()
+        .content(content())
+        .reveal_key(Recipient::<PublicKey>::from(service).public_key())
+        .internal_key(alice_recipient.public_key())
+        .destination(alice_recipient)
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        inscription.recipient().untweaked_pubkey(),
+        alice.x_only_public_key().0
+    );
+
+    reveal(&inscription, KeyPairSigner::new(service)).unwrap();
+    assert!(reveal(&inscription, KeyPairSigner::new(alice)).is_err());
+}
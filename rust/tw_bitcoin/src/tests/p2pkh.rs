@@ -52,3 +52,91 @@ fn sign_input_p2pkh_output_p2pkh() {
     let hex = hex::encode(&signed_transaction, false);
     assert_eq!(&hex, TX_RAW);
 }
+
+#[test]
+fn sign_input_p2pkh_uncompressed() {
+    use crate::{Recipient, TxInput};
+    use bitcoin::script::Instruction;
+    use bitcoin::sighash::{EcdsaSighashType, SighashCache};
+    use bitcoin::{PubkeyHash, PublicKey, Weight};
+    use secp256k1::{ecdsa, Message, Secp256k1};
+
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+
+    // The address of Alice's uncompressed public key.
+    let uncompressed = Recipient::<PublicKey>::from_keypair_uncompressed(&alice);
+    assert_ne!(
+        uncompressed.pubkey_hash(),
+        Recipient::<PublicKey>::from_keypair(&alice).pubkey_hash()
+    );
+
+    let input = TxInputP2PKH::builder()
+        .txid(Txid::from_str(TXID).unwrap())
+        .vout(0)
+        .recipient(Recipient::<PubkeyHash>::from(uncompressed.clone()))
+        .satoshis(FULL_SATOSHIS)
+        .uncompressed(true)
+        .build()
+        .unwrap();
+
+    let output = TxOutputP2PKH::builder()
+        .satoshis(SEND_SATOSHIS)
+        .recipient(bob)
+        .build()
+        .unwrap();
+
+    let builder = TransactionBuilder::new()
+        .miner_fee(MINER_FEE)
+        .add_output(output.into());
+
+    // The compressed key can't claim the input.
+    let compressed = TxInput::from(input.clone().uncompressed(false));
+
+    // The estimated size accounts for the larger public key.
+    assert_eq!(
+        TxInput::from(input.clone()).estimated_weight(),
+        compressed.estimated_weight() + Weight::from_non_witness_data_size(32)
+    );
+    assert!(builder
+        .clone()
+        .add_input(compressed)
+        .sign_inputs(alice)
+        .is_err());
+
+    let signed = builder.add_input(input.into()).sign_inputs(alice).unwrap();
+    let tx = &signed.inner;
+
+    // The scriptSig reveals the uncompressed public key.
+    let pushes: Vec<&[u8]> = tx.input[0]
+        .script_sig
+        .instructions()
+        .map(|instruction| match instruction.unwrap() {
+            Instruction::PushBytes(bytes) => bytes.as_bytes(),
+            Instruction::Op(_) => panic!("unexpected opcode"),
+        })
+        .collect();
+
+    assert_eq!(pushes.len(), 2);
+    assert_eq!(pushes[1], uncompressed.public_key().to_bytes().as_slice());
+
+    // The signature commits to the uncompressed P2PKH script.
+    let sighash = SighashCache::new(tx)
+        .legacy_signature_hash(
+            0,
+            &bitcoin::ScriptBuf::new_p2pkh(&uncompressed.pubkey_hash()),
+            EcdsaSighashType::All.to_u32(),
+        )
+        .unwrap();
+    let message = Message::from_slice(sighash.as_ref()).unwrap();
+    let (sig, sighash_type) = pushes[0].split_at(pushes[0].len() - 1);
+
+    assert_eq!(sighash_type, [EcdsaSighashType::All as u8]);
+    Secp256k1::new()
+        .verify_ecdsa(
+            &message,
+            &ecdsa::Signature::from_der(sig).unwrap(),
+            &alice.public_key(),
+        )
+        .unwrap();
+}
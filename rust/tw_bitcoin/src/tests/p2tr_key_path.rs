@@ -1,4 +1,5 @@
 use super::ONE_BTC;
+use crate::claim::{AuxRand, KeyPairSigner};
 use crate::{
     keypair_from_wif, TransactionBuilder, TxInputP2PKH, TxInputP2TRKeyPath, TxOutputP2TRKeyPath,
 };
@@ -84,7 +85,7 @@ fn sign_input_p2pkh_output_p2tr_key_path() {
         .miner_fee(MINER_FEE)
         .add_input(input.into())
         .add_output(output.into())
-        .sign_inputs(bob)
+        .sign_inputs(KeyPairSigner::new(bob).aux_rand(AuxRand::Deterministic))
         .unwrap()
         .serialize()
         .unwrap();
@@ -92,3 +93,48 @@ fn sign_input_p2pkh_output_p2tr_key_path() {
     let hex = hex::encode(&signed_transaction, false);
     assert_eq!(hex, SECOND_TX_RAW);
 }
+
+#[test]
+fn sign_input_p2tr_key_path_aux_rand() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+
+    let input = TxInputP2TRKeyPath::builder()
+        .txid(Txid::from_str(SECOND_TXID).unwrap())
+        .vout(0)
+        .recipient(bob)
+        .satoshis(SEND_SATOSHIS_TO_BOB)
+        .build()
+        .unwrap();
+
+    let output = TxOutputP2TRKeyPath::builder()
+        .recipient(alice)
+        .satoshis(SEND_SATOSHIS_TO_ALICE)
+        .build()
+        .unwrap();
+
+    let builder = TransactionBuilder::new()
+        .miner_fee(MINER_FEE)
+        .add_input(input.into())
+        .add_output(output.into());
+
+    let sign = |aux_rand: AuxRand| {
+        let signed = builder
+            .clone()
+            .sign_inputs(KeyPairSigner::new(bob).aux_rand(aux_rand))
+            .unwrap()
+            .serialize()
+            .unwrap();
+
+        hex::encode(&signed, false)
+    };
+
+    // Explicit auxiliary randomness creates reproducible signatures.
+    let explicit = sign(AuxRand::Explicit([1; 32]));
+    assert_eq!(explicit, sign(AuxRand::Explicit([1; 32])));
+    assert_ne!(explicit, sign(AuxRand::Explicit([2; 32])));
+    assert_ne!(explicit, SECOND_TX_RAW);
+
+    // Fresh randomness is used by default.
+    assert_ne!(sign(AuxRand::Random), sign(AuxRand::Random));
+}
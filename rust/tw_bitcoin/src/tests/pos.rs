@@ -0,0 +1,79 @@
+use crate::{pos, TransactionBuilder, TxInput, TxInputP2PKH, TxOutputP2PKH};
+use bitcoin::key::KeyPair;
+use bitcoin::secp256k1::Secp256k1;
+use bitcoin::{ScriptBuf, Sequence, Txid};
+use std::str::FromStr;
+use tw_encoding::hex;
+
+// Signed with Trust Wallet Core, on Neblio.
+pub const PRIVATE_KEY: &str = "4222aae79af41eade7b07ce6fd44d926ea8e3f95e51a06e85f8bdec89680cbd9";
+pub const TXID: &str = "a5a6e147da0f1b3f6dfd1081f91b0c6e31f030ae66c4be4cf4b0db0ac8b2407d";
+pub const TIME: u32 = 1_656_474_580;
+pub const MY_SCRIPT: &str = "76a914ae40b2142aba5ddd10f74d9440bfda8a36cbad5b88ac";
+pub const TO_SCRIPT: &str = "76a914412033ed457c72ca70bab5fbfdc03256bd2ce07d88ac";
+const SIGNED_TX: &str = "01000000d4cbbb62017d40b2c80adbb0f44cbec466ae30f0316e0c1bf98110fd6d3f1b0fda47e1a6a5000000006a47304402207f77f7ed50ec56447fd108b2a9a693b2ac9f62e99b59dfa914f242510943187602204618fd9195050c763eb93644e51344f6c00e4dd93aa41bb42bce42c9e4cc53b6012103787a4c5ff72dce6d97f9b6360dc302b2d8a833e8c570dcc124a96e5f564bb524feffffff02002f6859000000001976a914412033ed457c72ca70bab5fbfdc03256bd2ce07d88ac009d693a000000001976a914ae40b2142aba5ddd10f74d9440bfda8a36cbad5b88ac00000000";
+
+pub fn keypair() -> KeyPair {
+    KeyPair::from_seckey_slice(&Secp256k1::new(), &hex::decode(PRIVATE_KEY).unwrap()).unwrap()
+}
+
+pub fn script(hex: &str) -> ScriptBuf {
+    ScriptBuf::from_bytes(hex::decode(hex).unwrap())
+}
+
+fn builder() -> TransactionBuilder {
+    let alice = keypair();
+
+    let mut input = TxInput::from(TxInputP2PKH::new_with_script(
+        Txid::from_str(TXID).unwrap(),
+        0,
+        alice.into(),
+        2_500_000_000,
+        script(MY_SCRIPT),
+    ));
+    input.set_sequence(Sequence::ENABLE_LOCKTIME_NO_RBF);
+
+    TransactionBuilder::new()
+        .version(pos::TX_VERSION)
+        .max_fee_rate(u64::MAX)
+        .add_input(input)
+        .add_output(TxOutputP2PKH::new_with_script(1_500_000_000, script(TO_SCRIPT)).into())
+        .add_output(TxOutputP2PKH::new_with_script(980_000_000, script(MY_SCRIPT)).into())
+}
+
+#[test]
+fn pos_sign_p2pkh() {
+    let signed = builder().timestamp(TIME).sign_inputs(keypair()).unwrap();
+
+    let encoded = signed.serialize().unwrap();
+    assert_eq!(hex::encode(&encoded, false), SIGNED_TX);
+
+    // The time follows the version.
+    assert_eq!(encoded[4..8], TIME.to_le_bytes());
+    assert_eq!(signed.txid(), pos::txid(&signed.inner, TIME));
+    assert_ne!(signed.txid(), signed.inner.txid());
+}
+
+#[test]
+fn pos_time_is_signed() {
+    let hashes = builder().timestamp(TIME).signature_hashes().unwrap();
+    let later = builder().timestamp(TIME + 1).signature_hashes().unwrap();
+    let bitcoin = builder().signature_hashes().unwrap();
+
+    assert_ne!(hashes, later);
+    assert_ne!(hashes, bitcoin);
+
+    // Only P2PKH inputs are supported.
+    let alice = keypair();
+    let p2wpkh = crate::TxInputP2WPKH::new(
+        Txid::from_str(TXID).unwrap(),
+        1,
+        alice.try_into().unwrap(),
+        2_500_000_000,
+    );
+    let result = builder()
+        .timestamp(TIME)
+        .add_input(p2wpkh.into())
+        .sign_inputs(alice);
+    assert!(result.is_err());
+}
@@ -0,0 +1,155 @@
+use crate::claim::{AuxRand, KeyPairSigner, SigningKey};
+use crate::remote_signer::{RemoteSigner, SignRequest, SignatureKind};
+use crate::tests::p2tr_key_path::*;
+use crate::{
+    keypair_from_wif, Error, Result, TransactionBuilder, TxInputP2PKH, TxInputP2TRKeyPath,
+    TxOutputP2TRKeyPath,
+};
+use bitcoin::secp256k1::{KeyPair, Message};
+use bitcoin::{PublicKey, Txid};
+use std::cell::RefCell;
+use std::str::FromStr;
+use tw_encoding::hex;
+
+const KEY_ID: &[u8] = b"secure-enclave/bob";
+
+/// Signs like the host would, with a key held outside of the signer.
+fn host_sign(keypair: KeyPair, request: &SignRequest) -> Result<Vec<u8>> {
+    let host = KeyPairSigner::new(keypair).aux_rand(AuxRand::Deterministic);
+    let sighash = Message::from_slice(&request.sighash).unwrap();
+
+    let signature = match request.kind {
+        SignatureKind::Ecdsa => host.sign_ecdsa(&sighash)?.serialize_der().to_vec(),
+        SignatureKind::SchnorrKeyPath => host.sign_schnorr(&sighash, true)?.as_ref().to_vec(),
+        SignatureKind::Schnorr => host.sign_schnorr(&sighash, false)?.as_ref().to_vec(),
+    };
+
+    Ok(signature)
+}
+
+fn p2pkh_builder() -> TransactionBuilder {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+
+    let input = TxInputP2PKH::builder()
+        .txid(Txid::from_str(FIRST_TXID).unwrap())
+        .vout(0)
+        .recipient(alice)
+        .satoshis(FULL_SATOSHIS)
+        .build()
+        .unwrap();
+
+    let output = TxOutputP2TRKeyPath::builder()
+        .recipient(bob)
+        .satoshis(SEND_SATOSHIS_TO_BOB)
+        .build()
+        .unwrap();
+
+    TransactionBuilder::new()
+        .miner_fee(MINER_FEE)
+        .add_input(input.into())
+        .add_output(output.into())
+}
+
+fn p2tr_builder() -> TransactionBuilder {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+
+    let input = TxInputP2TRKeyPath::builder()
+        .txid(Txid::from_str(SECOND_TXID).unwrap())
+        .vout(0)
+        .recipient(bob)
+        .satoshis(SEND_SATOSHIS_TO_BOB)
+        .build()
+        .unwrap();
+
+    let output = TxOutputP2TRKeyPath::builder()
+        .recipient(alice)
+        .satoshis(SEND_SATOSHIS_TO_ALICE)
+        .build()
+        .unwrap();
+
+    TransactionBuilder::new()
+        .miner_fee(MINER_FEE)
+        .add_input(input.into())
+        .add_output(output.into())
+}
+
+#[test]
+fn remote_signer_ecdsa() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let requests = RefCell::new(vec![]);
+
+    let signer = RemoteSigner::new(
+        PublicKey::new(alice.public_key()),
+        KEY_ID.to_vec(),
+        |request: &SignRequest| {
+            requests.borrow_mut().push((
+                request.input_index,
+                request.key_id.to_vec(),
+                request.kind,
+            ));
+            host_sign(alice, request)
+        },
+    );
+
+    let signed = p2pkh_builder().sign_inputs(signer).unwrap();
+    assert_eq!(
+        hex::encode(&signed.serialize().unwrap(), false),
+        FIRST_TX_RAW
+    );
+    assert_eq!(
+        requests.into_inner(),
+        vec![(0, KEY_ID.to_vec(), SignatureKind::Ecdsa)]
+    );
+}
+
+#[test]
+fn remote_signer_schnorr_key_path() {
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+
+    let signer = RemoteSigner::new(
+        PublicKey::new(bob.public_key()),
+        KEY_ID.to_vec(),
+        |request: &SignRequest| {
+            assert_eq!(request.kind, SignatureKind::SchnorrKeyPath);
+            host_sign(bob, request)
+        },
+    );
+
+    let signed = p2tr_builder().sign_inputs(signer).unwrap();
+    assert_eq!(
+        hex::encode(&signed.serialize().unwrap(), false),
+        SECOND_TX_RAW
+    );
+}
+
+#[test]
+fn remote_signer_errors() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+
+    // The host declined to sign.
+    let signer = RemoteSigner::new(
+        PublicKey::new(bob.public_key()),
+        KEY_ID.to_vec(),
+        |_: &SignRequest| Err(Error::Todo),
+    );
+    assert!(p2tr_builder().sign_inputs(signer).is_err());
+
+    // The signature was created with another key.
+    let signer = RemoteSigner::new(
+        PublicKey::new(bob.public_key()),
+        KEY_ID.to_vec(),
+        |request: &SignRequest| host_sign(alice, request),
+    );
+    assert!(p2tr_builder().sign_inputs(signer).is_err());
+
+    // The signature is malformed.
+    let signer = RemoteSigner::new(
+        PublicKey::new(alice.public_key()),
+        KEY_ID.to_vec(),
+        |_: &SignRequest| Ok(vec![0; 72]),
+    );
+    assert!(p2pkh_builder().sign_inputs(signer).is_err());
+}
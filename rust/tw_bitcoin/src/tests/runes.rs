@@ -0,0 +1,330 @@
+use crate::runes::{
+    Artifact, Cenotaph, Edict, Etching, Flaw, Rune, RuneId, Runestone, SpacedRune, Terms,
+};
+use crate::tests::p2pkh::{ALICE_WIF, BOB_WIF};
+use crate::{keypair_from_wif, Recipient, TransactionBuilder, TxInputP2WPKH, TxOutputP2WPKH};
+use bitcoin::blockdata::opcodes::all::{OP_PUSHNUM_13, OP_RETURN, OP_VERIFY};
+use bitcoin::script::{Builder, PushBytesBuf};
+use bitcoin::{absolute::LockTime, PublicKey, ScriptBuf, Transaction, TxOut, Txid};
+use secp256k1::hashes::Hash;
+
+/// Runestone exercising every field, taken from the reference implementation.
+fn full_runestone() -> Runestone {
+    Runestone {
+        edicts: vec![
+            Edict {
+                id: RuneId::new(2, 3).unwrap(),
+                amount: 1,
+                output: 0,
+            },
+            Edict {
+                id: RuneId::new(5, 6).unwrap(),
+                amount: 4,
+                output: 1,
+            },
+        ],
+        etching: Some(Etching {
+            divisibility: Some(7),
+            premine: Some(8),
+            rune: Some(Rune(9)),
+            spacers: Some(10),
+            symbol: Some('@'),
+            terms: Some(Terms {
+                amount: Some(14),
+                cap: Some(11),
+                height: (Some(12), Some(13)),
+                offset: (Some(15), Some(16)),
+            }),
+            turbo: true,
+        }),
+        mint: Some(RuneId::new(17, 18).unwrap()),
+        pointer: Some(0),
+    }
+}
+
+#[rustfmt::skip]
+const FULL_PAYLOAD: &[u8] = &[
+    // Flags: etching, terms and turbo.
+    2, 0b111,
+    // Rune, divisibility, spacers, symbol and premine.
+    4, 9, 1, 7, 3, 10, 5, b'@', 6, 8,
+    // Terms: amount, cap, heights and offsets.
+    10, 14, 8, 11, 12, 12, 14, 13, 16, 15, 18, 16,
+    // Mint and pointer.
+    20, 17, 20, 18, 22, 0,
+    // Body with the delta encoded edicts.
+    0, 2, 3, 1, 0, 3, 6, 4, 1,
+];
+
+/// Transaction with the given `OP_RETURN` script and an additional output.
+fn transaction(script: ScriptBuf) -> Transaction {
+    Transaction {
+        version: 2,
+        lock_time: LockTime::ZERO,
+        input: vec![],
+        output: vec![
+            TxOut {
+                value: 0,
+                script_pubkey: script,
+            },
+            TxOut {
+                value: 546,
+                script_pubkey: ScriptBuf::new(),
+            },
+        ],
+    }
+}
+
+fn runestone_script(payload: &[u8]) -> ScriptBuf {
+    Builder::new()
+        .push_opcode(OP_RETURN)
+        .push_opcode(OP_PUSHNUM_13)
+        .push_slice(PushBytesBuf::try_from(payload.to_vec()).unwrap())
+        .into_script()
+}
+
+fn decipher_payload(payload: &[u8]) -> Option<Artifact> {
+    Runestone::decipher(&transaction(runestone_script(payload)))
+}
+
+fn cenotaph_flaw(payload: &[u8]) -> Option<Flaw> {
+    match decipher_payload(payload) {
+        Some(Artifact::Cenotaph(cenotaph)) => cenotaph.flaw,
+        _ => None,
+    }
+}
+
+#[test]
+fn runes_rune_names() {
+    let names = [
+        (0, "A"),
+        (1, "B"),
+        (25, "Z"),
+        (26, "AA"),
+        (27, "AB"),
+        (51, "AZ"),
+        (52, "BA"),
+        (u128::MAX - 1, "BCGDENLQRQWDSLRUGSNLBTMFIJAU"),
+        (u128::MAX, "BCGDENLQRQWDSLRUGSNLBTMFIJAV"),
+    ];
+
+    for (value, name) in names {
+        assert_eq!(Rune(value).to_string(), name);
+        assert_eq!(name.parse::<Rune>().unwrap(), Rune(value));
+    }
+
+    assert!("".parse::<Rune>().is_err());
+    assert!("a".parse::<Rune>().is_err());
+    assert!("BCGDENLQRQWDSLRUGSNLBTMFIJAW".parse::<Rune>().is_err());
+
+    let spaced: SpacedRune = "UNCOMMON•GOODS".parse().unwrap();
+    assert_eq!(spaced.rune, Rune(2055900680524219742));
+    assert_eq!(spaced.spacers, 0b1000_0000);
+    assert_eq!(spaced.to_string(), "UNCOMMON•GOODS");
+    assert_eq!("UNCOMMON.GOODS".parse::<SpacedRune>().unwrap(), spaced);
+
+    // Leading, trailing and double spacers are rejected.
+    assert!(".A".parse::<SpacedRune>().is_err());
+    assert!("A.".parse::<SpacedRune>().is_err());
+    assert!("A..B".parse::<SpacedRune>().is_err());
+}
+
+#[test]
+fn runes_encipher() {
+    let script = full_runestone().encipher();
+    assert_eq!(script, runestone_script(FULL_PAYLOAD));
+
+    // Edicts are sorted by their rune ID before the delta encoding.
+    let mut runestone = full_runestone();
+    runestone.edicts.reverse();
+    assert_eq!(runestone.encipher(), script);
+
+    // Large payloads are split into pushes of at most 520 bytes.
+    let mut runestone = Runestone::new();
+    for i in 0..100 {
+        runestone = runestone.edict(Edict {
+            id: RuneId::new(840_000 + i, 1).unwrap(),
+            amount: u128::MAX,
+            output: 0,
+        });
+    }
+
+    let pushes = runestone
+        .encipher()
+        .instructions()
+        .filter(|instruction| instruction.as_ref().unwrap().push_bytes().is_some())
+        .count();
+    assert!(pushes > 1);
+
+    let tx = transaction(runestone.encipher());
+    assert_eq!(
+        Runestone::decipher(&tx),
+        Some(Artifact::Runestone(Box::new(runestone)))
+    );
+}
+
+#[test]
+fn runes_decipher() {
+    let tx = transaction(full_runestone().encipher());
+    assert_eq!(
+        Runestone::decipher(&tx),
+        Some(Artifact::Runestone(Box::new(full_runestone())))
+    );
+
+    // Unknown odd tags are ignored.
+    let artifact = decipher_payload(&[20, 1, 20, 2, 23, 5]);
+    assert_eq!(
+        artifact,
+        Some(Artifact::Runestone(Box::new(
+            Runestone::new().mint(RuneId::new(1, 2).unwrap())
+        )))
+    );
+
+    // Plain `OP_RETURN` outputs are no runestones.
+    let script = ScriptBuf::new_op_return(&PushBytesBuf::try_from(vec![0, 1]).unwrap());
+    assert_eq!(Runestone::decipher(&transaction(script)), None);
+}
+
+#[test]
+fn runes_decipher_cenotaph() {
+    // Unrecognized even tag, keeping the etched rune and the mint.
+    let artifact = decipher_payload(&[2, 1, 4, 9, 20, 1, 20, 2, 24, 0]);
+    assert_eq!(
+        artifact,
+        Some(Artifact::Cenotaph(Cenotaph {
+            etching: Some(Rune(9)),
+            flaw: Some(Flaw::UnrecognizedEvenTag),
+            mint: Some(RuneId::new(1, 2).unwrap()),
+        }))
+    );
+
+    assert_eq!(cenotaph_flaw(&[2, 0b1000]), Some(Flaw::UnrecognizedFlag));
+    assert_eq!(cenotaph_flaw(&[20]), Some(Flaw::TruncatedField));
+    assert_eq!(cenotaph_flaw(&[0x80]), Some(Flaw::Varint));
+    assert_eq!(cenotaph_flaw(&[0, 1, 1, 1]), Some(Flaw::TrailingIntegers));
+    assert_eq!(cenotaph_flaw(&[0, 0, 1, 1, 0]), Some(Flaw::EdictRuneId));
+    assert_eq!(cenotaph_flaw(&[0, 1, 1, 1, 3]), Some(Flaw::EdictOutput));
+
+    // The pointer must reference an existing output.
+    assert_eq!(cenotaph_flaw(&[22, 2]), Some(Flaw::UnrecognizedEvenTag));
+
+    // Premine of u128::MAX plus one mint.
+    let mut payload = vec![2, 0b11, 8, 1, 10, 1, 6];
+    payload.extend_from_slice(&[0xff; 18]);
+    payload.push(0x03);
+    assert_eq!(cenotaph_flaw(&payload), Some(Flaw::SupplyOverflow));
+
+    // Non-push opcodes are not allowed.
+    let script = Builder::new()
+        .push_opcode(OP_RETURN)
+        .push_opcode(OP_PUSHNUM_13)
+        .push_opcode(OP_VERIFY)
+        .into_script();
+    let artifact = Runestone::decipher(&transaction(script));
+    assert!(matches!(
+        artifact,
+        Some(Artifact::Cenotaph(Cenotaph {
+            flaw: Some(Flaw::Opcode),
+            ..
+        }))
+    ));
+}
+
+#[test]
+fn runes_transfer() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let alice_recipient = Recipient::<PublicKey>::from(alice);
+    let bob = Recipient::<PublicKey>::from(keypair_from_wif(BOB_WIF).unwrap());
+
+    // The input holds the runes to be transferred.
+    let input = TxInputP2WPKH::new(
+        Txid::all_zeros(),
+        0,
+        alice_recipient.clone().try_into().unwrap(),
+        10_000,
+    );
+
+    // Bob receives 1_000 runes in the second output, the remaining runes go
+    // back to Alice via the pointer.
+    let runestone = Runestone::new()
+        .edict(Edict {
+            id: RuneId::new(840_000, 1).unwrap(),
+            amount: 1_000,
+            output: 1,
+        })
+        .pointer(2);
+
+    let tx = TransactionBuilder::new()
+        .add_input(input.into())
+        .add_output(runestone.to_output())
+        .add_output(TxOutputP2WPKH::new(546, bob.try_into().unwrap()).into())
+        .add_output(TxOutputP2WPKH::new(8_000, alice_recipient.try_into().unwrap()).into())
+        .sign_inputs(alice)
+        .unwrap()
+        .inner;
+
+    assert_eq!(tx.output[0].value, 0);
+    assert_eq!(
+        Runestone::decipher(&tx),
+        Some(Artifact::Runestone(Box::new(runestone)))
+    );
+}
+
+#[test]
+fn runes_transfer_builder() {
+    let keypair = keypair_from_wif(ALICE_WIF).unwrap();
+    let alice = Recipient::<PublicKey>::from(keypair);
+    let bob = Recipient::<PublicKey>::from(keypair_from_wif(BOB_WIF).unwrap());
+    let id = RuneId::new(840_000, 1).unwrap();
+
+    let input = TxInputP2WPKH::new(
+        Txid::all_zeros(),
+        0,
+        alice.clone().try_into().unwrap(),
+        10_000,
+    );
+
+    let tx = TransactionBuilder::new()
+        .add_input(input.into())
+        .add_output(TxOutputP2WPKH::new(5_000, bob.clone().try_into().unwrap()).into())
+        .transfer_runes(
+            vec![
+                (
+                    id,
+                    1_000,
+                    TxOutputP2WPKH::new(546, bob.try_into().unwrap()).into(),
+                ),
+                (
+                    id,
+                    0,
+                    TxOutputP2WPKH::new(546, alice.clone().try_into().unwrap()).into(),
+                ),
+            ],
+            Some(TxOutputP2WPKH::new(546, alice.try_into().unwrap()).into()),
+        )
+        .sign_inputs(keypair)
+        .unwrap()
+        .inner;
+
+    // The edicts and the pointer refer to the outputs following the existing
+    // one, the runestone is added last.
+    let expected = Runestone::new()
+        .edict(Edict {
+            id,
+            amount: 1_000,
+            output: 1,
+        })
+        .edict(Edict {
+            id,
+            amount: 0,
+            output: 2,
+        })
+        .pointer(3);
+
+    assert_eq!(tx.output.len(), 5);
+    assert_eq!(tx.output[4].script_pubkey, expected.encipher());
+    assert_eq!(
+        Runestone::decipher(&tx),
+        Some(Artifact::Runestone(Box::new(expected)))
+    );
+}
@@ -0,0 +1,164 @@
+use crate::claim::ClaimLocation;
+use crate::ordering::TxOrdering;
+use crate::sat_point::{track_sat_point, SatDestination, SatPoint};
+use crate::tests::p2pkh::{ALICE_WIF, BOB_WIF};
+use crate::{
+    keypair_from_wif, Recipient, TransactionBuilder, TxInput, TxInputP2WPKH, TxOutput,
+    TxOutputOpReturn, TxOutputP2WPKH,
+};
+use bitcoin::{OutPoint, PublicKey, Txid, Witness};
+use std::str::FromStr;
+
+const TXID: &str = "1e1cdc48aa990d7e154a161d5b5f1cad737742e97d2712ab188027bb42e6e47b";
+
+fn outpoint(vout: u32) -> OutPoint {
+    OutPoint::new(Txid::from_str(TXID).unwrap(), vout)
+}
+
+fn input(vout: u32, satoshis: u64) -> TxInput {
+    let alice = Recipient::<PublicKey>::from(keypair_from_wif(ALICE_WIF).unwrap());
+    TxInputP2WPKH::new(
+        outpoint(vout).txid,
+        vout,
+        alice.try_into().unwrap(),
+        satoshis,
+    )
+    .into()
+}
+
+fn output(satoshis: u64) -> TxOutput {
+    let bob = Recipient::<PublicKey>::from(keypair_from_wif(BOB_WIF).unwrap());
+    TxOutputP2WPKH::new(satoshis, bob.try_into().unwrap()).into()
+}
+
+#[test]
+fn sat_flow_first_in_first_out() {
+    let inputs = [input(0, 1_000), input(1, 5_000)];
+    let outputs = [output(600), output(4_000)];
+
+    let track = |vout, offset| {
+        track_sat_point(&inputs, &outputs, &SatPoint::new(outpoint(vout), offset)).unwrap()
+    };
+
+    assert_eq!(track(0, 0), SatDestination::Output { vout: 0, offset: 0 });
+    assert_eq!(
+        track(0, 599),
+        SatDestination::Output {
+            vout: 0,
+            offset: 599
+        }
+    );
+    assert_eq!(track(0, 600), SatDestination::Output { vout: 1, offset: 0 });
+    assert_eq!(
+        track(1, 0),
+        SatDestination::Output {
+            vout: 1,
+            offset: 400
+        }
+    );
+    assert_eq!(
+        track(1, 3_599),
+        SatDestination::Output {
+            vout: 1,
+            offset: 3_999
+        }
+    );
+    // The remaining 1_400 sats are paid to the miner.
+    assert_eq!(track(1, 3_600), SatDestination::Fee { offset: 0 });
+    assert_eq!(track(1, 4_999), SatDestination::Fee { offset: 1_399 });
+}
+
+#[test]
+fn sat_flow_op_return_burns() {
+    let inputs = [input(0, 1_000)];
+    let outputs = [TxOutputOpReturn::new(b"hello").unwrap().into(), output(900)];
+
+    // The `OP_RETURN` output does not carry any sats.
+    let destination = track_sat_point(&inputs, &outputs, &SatPoint::new(outpoint(0), 0)).unwrap();
+    assert_eq!(destination, SatDestination::Output { vout: 1, offset: 0 });
+    assert!(!destination.is_lost());
+
+    let outputs = [
+        TxOutputOpReturn::new_with_script(500, bitcoin::ScriptBuf::new_op_return(&[1, 2, 3]))
+            .into(),
+        output(400),
+    ];
+
+    let destination = track_sat_point(&inputs, &outputs, &SatPoint::new(outpoint(0), 0)).unwrap();
+    assert_eq!(destination, SatDestination::Burned { vout: 0, offset: 0 });
+    assert!(destination.is_lost());
+}
+
+#[test]
+fn sat_flow_unknown_sat_point() {
+    let inputs = [input(0, 1_000)];
+    let outputs = [output(900)];
+
+    // Not part of the inputs.
+    assert!(track_sat_point(&inputs, &outputs, &SatPoint::new(outpoint(1), 0)).is_err());
+    // Beyond the value of the input.
+    assert!(track_sat_point(&inputs, &outputs, &SatPoint::new(outpoint(0), 1_000)).is_err());
+}
+
+#[test]
+fn builder_refuses_lost_inscription() {
+    let builder = TransactionBuilder::new()
+        .add_input(input(0, 10_000))
+        .add_input(input(1, 1_000))
+        .add_output(output(10_000))
+        .protect_inscription(SatPoint::new(outpoint(0), 0));
+
+    // The inscription lands on the first output.
+    assert_eq!(
+        builder.track_inscriptions().unwrap(),
+        vec![(
+            SatPoint::new(outpoint(0), 0),
+            SatDestination::Output { vout: 0, offset: 0 }
+        )]
+    );
+    builder.validate().unwrap();
+
+    // The inscription on the second input is paid as fee.
+    let builder = builder.protect_inscription(SatPoint::new(outpoint(1), 500));
+    assert!(builder.validate().is_err());
+
+    // Unknown sat points are refused as well.
+    let builder = TransactionBuilder::new()
+        .add_input(input(0, 10_000))
+        .add_output(output(9_000))
+        .protect_inscription(SatPoint::new(outpoint(1), 0));
+    assert!(builder.validate().is_err());
+}
+
+#[test]
+fn builder_ordering_keeps_inscription_output() {
+    let inscription = SatPoint::new(outpoint(1), 0);
+
+    let builder = TransactionBuilder::new()
+        .add_input(input(0, 5_000))
+        .add_input(input(1, 546))
+        .add_input(input(2, 20_000))
+        .add_output(output(1_000))
+        .add_output(output(4_000))
+        .add_output(output(546))
+        .add_output(output(15_000))
+        .add_output(output(3_000))
+        .ordering(TxOrdering::Bip69)
+        .protect_inscription(inscription);
+
+    let tx = builder
+        .sign_inputs_fn(|_, _| Ok(ClaimLocation::Witness(Witness::new())))
+        .unwrap()
+        .inner;
+
+    // The inputs and outputs up to the inscription keep their position.
+    let inputs: Vec<u32> = tx
+        .input
+        .iter()
+        .map(|txin| txin.previous_output.vout)
+        .collect();
+    let values: Vec<u64> = tx.output.iter().map(|txout| txout.value).collect();
+    assert_eq!(&inputs[..2], &[0, 1]);
+    assert_eq!(&values[..3], &[1_000, 4_000, 546]);
+    assert_eq!(&values[3..], &[3_000, 15_000]);
+}
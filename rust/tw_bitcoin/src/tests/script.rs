@@ -0,0 +1,177 @@
+use crate::script::{from_asm, to_asm, ScriptTemplate};
+use crate::tests::p2pkh::ALICE_WIF;
+use crate::{keypair_from_wif, Recipient, TxOutputOpReturn, TxOutputP2PKH, TxOutputP2WPKH};
+use bitcoin::{PublicKey, ScriptBuf};
+use tw_encoding::hex;
+
+fn from_hex(hex_str: &str) -> ScriptBuf {
+    ScriptBuf::from_bytes(hex::decode(hex_str).unwrap())
+}
+
+fn round_trip(script: &ScriptBuf) {
+    assert_eq!(&from_asm(&to_asm(script)).unwrap(), script);
+}
+
+#[test]
+fn asm_standard_outputs() {
+    let keypair: secp256k1::KeyPair = keypair_from_wif(ALICE_WIF).unwrap();
+    let recipient = Recipient::<PublicKey>::from(keypair);
+
+    let p2pkh = TxOutputP2PKH::new(1_000, recipient.clone()).script_pubkey;
+    let hash = hex::encode(&p2pkh.as_bytes()[3..23], false);
+    assert_eq!(
+        to_asm(&p2pkh),
+        format!("OP_DUP OP_HASH160 {hash} OP_EQUALVERIFY OP_CHECKSIG")
+    );
+    assert_eq!(ScriptTemplate::classify(&p2pkh), ScriptTemplate::PubKeyHash);
+    round_trip(&p2pkh);
+
+    let p2wpkh = TxOutputP2WPKH::new(1_000, recipient.try_into().unwrap()).script_pubkey;
+    assert_eq!(to_asm(&p2wpkh), format!("0 {hash}"));
+    assert_eq!(
+        ScriptTemplate::classify(&p2wpkh),
+        ScriptTemplate::WitnessV0KeyHash
+    );
+    assert_eq!(
+        ScriptTemplate::classify(&p2wpkh).to_string(),
+        "witness_v0_keyhash"
+    );
+    round_trip(&p2wpkh);
+
+    let op_return = TxOutputOpReturn::new(b"hello world").unwrap().script_pubkey;
+    assert_eq!(to_asm(&op_return), "OP_RETURN 68656c6c6f20776f726c64");
+    assert_eq!(
+        ScriptTemplate::classify(&op_return),
+        ScriptTemplate::NullData
+    );
+    round_trip(&op_return);
+}
+
+#[test]
+fn asm_numbers() {
+    // `OP_1NEGATE`, `OP_1`, `OP_16`, followed by pushes of up to four bytes.
+    let script = from_hex("4f51600111028000038fffff04ffffffff");
+    assert_eq!(to_asm(&script), "-1 1 16 17 128 -8388495 -2147483647");
+    round_trip(&script);
+
+    // Numbers are encoded minimally, up to the 32-bit limit.
+    assert_eq!(
+        from_asm("0 -1 17").unwrap().as_bytes(),
+        &[0x00, 0x4f, 0x01, 0x11]
+    );
+    assert_eq!(
+        from_asm("4294967295").unwrap().as_bytes(),
+        &[0x05, 0xff, 0xff, 0xff, 0xff, 0x00]
+    );
+    assert!(from_asm("4294967296").is_err());
+
+    // A non-minimal push of a number is kept as raw bytes.
+    let script = from_hex("0101");
+    assert_eq!(to_asm(&script), "0x0101");
+    round_trip(&script);
+}
+
+#[test]
+fn asm_push_data() {
+    // `OP_PUSHDATA1` with 80 bytes, followed by `OP_PUSHDATA2` with 300 bytes.
+    let mut bytes = vec![0x4c, 80];
+    bytes.extend([0xab; 80]);
+    bytes.extend([0x4d, 0x2c, 0x01]);
+    bytes.extend([0xcd; 300]);
+    let script = ScriptBuf::from_bytes(bytes);
+
+    let asm = to_asm(&script);
+    assert_eq!(asm, format!("{} {}", "ab".repeat(80), "cd".repeat(300)));
+    round_trip(&script);
+
+    // A non-minimal `OP_PUSHDATA1` is kept as raw bytes.
+    let script = from_hex("4c03aabbcc");
+    assert_eq!(to_asm(&script), "0x4c03aabbcc");
+    round_trip(&script);
+
+    // Text and raw bytes.
+    assert_eq!(
+        from_asm("'ord' 0x0101 0").unwrap().as_bytes(),
+        &[0x03, b'o', b'r', b'd', 0x01, 0x01, 0x00]
+    );
+
+    // A truncated push ends the disassembly.
+    assert_eq!(to_asm(&from_hex("76a914aabb")), "OP_DUP OP_HASH160 [error]");
+    assert_eq!(to_asm(&from_hex("4d01")), "[error]");
+}
+
+#[test]
+fn asm_tapscript_opcodes() {
+    let script =
+        from_hex("20a2cd2c6c1d3c0d3b33f7ed5a4f3c5fe3e5bd1cd9f5a2e5f98d2d0f5c1e5e4a8dba529cbb");
+    assert_eq!(
+        to_asm(&script),
+        "a2cd2c6c1d3c0d3b33f7ed5a4f3c5fe3e5bd1cd9f5a2e5f98d2d0f5c1e5e4a8d OP_CHECKSIGADD 2 OP_NUMEQUAL OP_SUCCESS187"
+    );
+    round_trip(&script);
+
+    // Opcode names are accepted without prefix as well.
+    assert_eq!(
+        from_asm("CHECKSIGADD OP_CHECKLOCKTIMEVERIFY NOP4")
+            .unwrap()
+            .as_bytes(),
+        &[0xba, 0xb1, 0xb3]
+    );
+    assert_eq!(to_asm(&from_hex("ff")), "OP_INVALIDOPCODE");
+
+    assert!(from_asm("OP_UNKNOWN").is_err());
+    assert!(from_asm("abc").is_err());
+}
+
+#[test]
+fn asm_inscription_envelope() {
+    // `OP_FALSE OP_IF "ord" 1 "text/plain" OP_0 "hi" OP_ENDIF`, where the content
+    // type tag `1` is pushed as data.
+    let script =
+        from_hex("0063036f7264010118746578742f706c61696e3b636861727365743d7574662d380002686968");
+    assert_eq!(
+        to_asm(&script),
+        "0 OP_IF 6582895 0x0101 746578742f706c61696e3b636861727365743d7574662d38 0 26984 OP_ENDIF"
+    );
+    round_trip(&script);
+}
+
+#[test]
+fn classify_templates() {
+    let classify = |hex_str: &str| ScriptTemplate::classify(&from_hex(hex_str));
+
+    let key = "02a2cd2c6c1d3c0d3b33f7ed5a4f3c5fe3e5bd1cd9f5a2e5f98d2d0f5c1e5e4a8d";
+    assert_eq!(classify(&format!("21{key}ac")), ScriptTemplate::PubKey);
+    assert_eq!(
+        classify(&format!("5121{key}21{key}52ae")),
+        ScriptTemplate::Multisig {
+            required: 1,
+            total: 2
+        }
+    );
+    // The number of keys does not match.
+    assert_eq!(
+        classify(&format!("5121{key}53ae")),
+        ScriptTemplate::NonStandard
+    );
+    assert_eq!(
+        classify("a914000102030405060708090a0b0c0d0e0f1011121387"),
+        ScriptTemplate::ScriptHash
+    );
+    assert_eq!(
+        classify("5120a2cd2c6c1d3c0d3b33f7ed5a4f3c5fe3e5bd1cd9f5a2e5f98d2d0f5c1e5e4a8d"),
+        ScriptTemplate::WitnessV1Taproot
+    );
+    assert_eq!(classify("52020001"), ScriptTemplate::WitnessUnknown);
+    assert_eq!(classify("6a"), ScriptTemplate::NullData);
+    assert_eq!(classify("6a0051"), ScriptTemplate::NullData);
+    assert_eq!(classify("6a76"), ScriptTemplate::NonStandard);
+    assert_eq!(classify("76").to_string(), "nonstandard");
+}
+
+#[test]
+fn debug_shows_asm() {
+    let output = TxOutputOpReturn::new(b"hi").unwrap();
+    let debug = format!("{output:?}");
+    assert!(debug.contains("Script(OP_RETURN 26984)"), "{debug}");
+}
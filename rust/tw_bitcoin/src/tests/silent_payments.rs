@@ -0,0 +1,486 @@
+use crate::claim::{AuxRand, ClaimLocation, KeyPairSigner};
+use crate::silent_payments::{
+    output_keys, tweak_data, SilentPaymentAddress, SilentPaymentReceiver, CHANGE_LABEL,
+};
+use crate::tests::brc20_transfer::{ALICE_WIF, BOB_WIF};
+use crate::{
+    keypair_from_wif, Recipient, TransactionBuilder, TxInput, TxInputP2PKH, TxInputP2TRKeyPath,
+    TxInputP2WPKH, TxInputP2WSH, TxOutput, TxOutputP2WPKH,
+};
+use bitcoin::absolute::LockTime;
+use bitcoin::bech32::{self, ToBase32, Variant};
+use bitcoin::key::{KeyPair, TweakedPublicKey};
+use bitcoin::secp256k1::{Parity, PublicKey, Secp256k1, SecretKey};
+use bitcoin::{Network, OutPoint, ScriptBuf, Transaction, TxOut, Txid, Witness};
+use std::str::FromStr;
+use tw_encoding::hex;
+
+const FIRST_TXID: &str = "1e1cdc48aa990d7e154a161d5b5f1cad737742e97d2712ab188027bb42e6e47b";
+const SECOND_TXID: &str = "797d17d47ae66e598341f9dfdea020b04d4017dcf9cc33f0e51f7a6082171fb1";
+const FULL_SATOSHIS: u64 = 100_000;
+const SEND_SATOSHIS: u64 = 30_000;
+
+fn secret(byte: u8) -> SecretKey {
+    SecretKey::from_slice(&[byte; 32]).unwrap()
+}
+
+fn public(secret: &SecretKey) -> PublicKey {
+    secret.public_key(&Secp256k1::new())
+}
+
+/// The receiver with the scan key `[3; 32]` and spend key `[4; 32]`.
+fn receiver() -> SilentPaymentReceiver {
+    SilentPaymentReceiver::new(secret(3), public(&secret(4)))
+}
+
+/// Alice spends a P2WPKH and a P2TR key-path output.
+fn inputs(alice: &KeyPair) -> Vec<TxInput> {
+    let first = TxInputP2WPKH::new(
+        Txid::from_str(FIRST_TXID).unwrap(),
+        1,
+        alice.try_into().unwrap(),
+        FULL_SATOSHIS,
+    );
+    let second = TxInputP2TRKeyPath::new(
+        Txid::from_str(SECOND_TXID).unwrap(),
+        0,
+        alice.into(),
+        FULL_SATOSHIS,
+    );
+
+    vec![first.into(), second.into()]
+}
+
+fn builder(inputs: &[TxInput]) -> TransactionBuilder {
+    inputs
+        .iter()
+        .fold(TransactionBuilder::new(), |builder, input| {
+            builder.add_input(input.clone())
+        })
+}
+
+/// The tweak data, as computed by an indexer from the public keys of the
+/// inputs.
+fn indexer_tweak_data(alice: &KeyPair, inputs: &[TxInput]) -> PublicKey {
+    let taproot_key = Recipient::<TweakedPublicKey>::from(alice)
+        .tweaked_pubkey()
+        .to_inner()
+        .public_key(Parity::Even);
+    let outpoints: Vec<OutPoint> = inputs
+        .iter()
+        .map(|input| input.ctx().previous_output)
+        .collect();
+
+    tweak_data(&outpoints, &[alice.public_key(), taproot_key]).unwrap()
+}
+
+#[test]
+fn silent_payment_address() {
+    let address = receiver().address(Network::Bitcoin);
+    let encoded = address.to_string();
+    assert!(encoded.starts_with("sp1q"));
+    assert_eq!(encoded.len(), 116);
+    assert_eq!(SilentPaymentAddress::from_str(&encoded).unwrap(), address);
+
+    let testnet = receiver().address(Network::Regtest).to_string();
+    assert!(testnet.starts_with("tsp1q"));
+    assert_eq!(
+        SilentPaymentAddress::from_str(&testnet).unwrap().network(),
+        Network::Testnet
+    );
+
+    // Labeled addresses share the scan key.
+    let labeled = receiver().labeled_address(1, Network::Bitcoin).unwrap();
+    assert_eq!(labeled.scan_key(), address.scan_key());
+    assert_ne!(labeled.spend_key(), address.spend_key());
+
+    let mut payload = address.scan_key().serialize().to_vec();
+    payload.extend(address.spend_key().serialize());
+    let encode = |version: u8, payload: &[u8], variant: Variant| {
+        let mut data = vec![bech32::u5::try_from_u8(version).unwrap()];
+        data.extend(payload.to_base32());
+        bech32::encode("sp", data, variant).unwrap()
+    };
+
+    // Future versions may append data.
+    let mut extended = payload.clone();
+    extended.extend([0; 10]);
+    let future = SilentPaymentAddress::from_str(&encode(1, &extended, Variant::Bech32m)).unwrap();
+    assert_eq!(future.spend_key(), address.spend_key());
+
+    assert!(SilentPaymentAddress::from_str(&encode(0, &extended, Variant::Bech32m)).is_err());
+    assert!(SilentPaymentAddress::from_str(&encode(31, &payload, Variant::Bech32m)).is_err());
+    assert!(SilentPaymentAddress::from_str(&encode(0, &payload, Variant::Bech32)).is_err());
+    assert!(SilentPaymentAddress::from_str(&encode(0, &payload[..65], Variant::Bech32m)).is_err());
+}
+
+#[test]
+fn silent_payment_send_and_scan() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let inputs = inputs(&alice);
+
+    let labeled_receiver = receiver().add_label(1);
+    let address = labeled_receiver.address(Network::Bitcoin);
+    let labeled = labeled_receiver
+        .labeled_address(1, Network::Bitcoin)
+        .unwrap();
+
+    // Two payments to the same address get distinct outputs.
+    let tx: Transaction = builder(&inputs)
+        .add_silent_payments(
+            &[alice],
+            &[
+                (address, SEND_SATOSHIS),
+                (labeled, SEND_SATOSHIS),
+                (address, SEND_SATOSHIS),
+            ],
+        )
+        .unwrap()
+        .sign_inputs(KeyPairSigner::new(alice).aux_rand(AuxRand::Deterministic))
+        .unwrap()
+        .inner;
+
+    assert_eq!(tx.output.len(), 3);
+    assert!(tx
+        .output
+        .iter()
+        .all(|output| output.script_pubkey.is_v1_p2tr()));
+    assert_ne!(tx.output[0].script_pubkey, tx.output[2].script_pubkey);
+
+    let tweak_data = indexer_tweak_data(&alice, &inputs);
+    let found = labeled_receiver.scan(&tweak_data, &tx).unwrap();
+    assert_eq!(found.len(), 3);
+
+    let mut vouts: Vec<(u32, Option<u32>)> = found
+        .iter()
+        .map(|output| (output.vout(), output.label()))
+        .collect();
+    vouts.sort();
+    assert_eq!(vouts, [(0, None), (1, Some(1)), (2, None)]);
+
+    // Without the label, the labeled output is not found. As the outputs
+    // are numbered across labels, scanning stops there.
+    let unlabeled = receiver().scan(&tweak_data, &tx).unwrap();
+    assert_eq!(unlabeled.len(), 1);
+    assert_eq!(unlabeled[0].vout(), 0);
+
+    // Another receiver finds nothing.
+    let other = SilentPaymentReceiver::new(secret(5), public(&secret(4)));
+    assert!(other.scan(&tweak_data, &tx).unwrap().is_empty());
+
+    // The found outputs can be spent via the key path.
+    for output in found {
+        assert_eq!(output.txid(), tx.txid());
+        assert_eq!(output.satoshis(), SEND_SATOSHIS);
+
+        let keypair = output.spending_key(&secret(4)).unwrap();
+        assert!(output.spending_key(&secret(5)).is_err());
+
+        let recipient = Recipient::<bitcoin::PublicKey>::from(keypair);
+        let spend = TransactionBuilder::new()
+            .add_input(output.input())
+            .add_output(TxOutput::from(TxOutputP2WPKH::new(
+                SEND_SATOSHIS - 1_000,
+                recipient.try_into().unwrap(),
+            )))
+            .sign_inputs(keypair)
+            .unwrap()
+            .inner;
+
+        assert_eq!(spend.input[0].witness.len(), 1);
+        assert_eq!(spend.input[0].witness[0].len(), 64);
+    }
+}
+
+#[test]
+fn silent_payment_requires_input_keys() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+    let address = receiver().address(Network::Bitcoin);
+    let inputs = inputs(&alice);
+
+    // The keys of all eligible inputs are required.
+    let result = builder(&inputs).add_silent_payments(&[bob], &[(address, SEND_SATOSHIS)]);
+    assert!(result.is_err());
+
+    // Script inputs are not eligible, but their outpoint counts.
+    let script = ScriptBuf::from_bytes(vec![0x51]);
+    let p2wsh = TxInputP2WSH::new(
+        Txid::from_str(FIRST_TXID).unwrap(),
+        0,
+        Recipient::<bitcoin::PublicKey>::from(bob),
+        FULL_SATOSHIS,
+        script,
+    );
+
+    let mut with_script = inputs.clone();
+    with_script.push(p2wsh.into());
+
+    let tx = builder(&with_script)
+        .add_silent_payments(&[alice], &[(address, SEND_SATOSHIS)])
+        .unwrap()
+        .signature_hashes();
+    assert!(tx.is_ok());
+
+    let outpoints: Vec<OutPoint> = with_script
+        .iter()
+        .map(|input| input.ctx().previous_output)
+        .collect();
+    let taproot_key = Recipient::<TweakedPublicKey>::from(alice)
+        .tweaked_pubkey()
+        .to_inner()
+        .public_key(Parity::Even);
+    let tweak_data = tweak_data(&outpoints, &[alice.public_key(), taproot_key]).unwrap();
+
+    // The smallest outpoint changed, so the output key differs from the one
+    // without the script input.
+    assert_ne!(tweak_data, indexer_tweak_data(&alice, &inputs));
+
+    let output_keys = output_keys(&with_script, &[alice], &[address]);
+    let tx = Transaction {
+        version: 2,
+        lock_time: LockTime::ZERO,
+        input: vec![],
+        output: vec![TxOut {
+            value: SEND_SATOSHIS,
+            script_pubkey: ScriptBuf::new_v1_p2tr_tweaked(output_keys.unwrap()[0]),
+        }],
+    };
+    assert_eq!(receiver().scan(&tweak_data, &tx).unwrap().len(), 1);
+}
+
+// BIP-352 test vectors (`send_and_receive_test_vectors.json`). All of them
+// pay to the same receiver and spend the outpoints with the keys below. Each
+// case lists the inputs, the private key of each input and the output key.
+const BIP352_SCAN_KEY: &str = "0f694e068028a717f8af6b9411f9a133dd3565258714cc226594b34db90c1f2c";
+const BIP352_SPEND_KEY: &str = "9d6ad855ce3417ef84e836892e5a56392bfba05fa5d97ccea30e266f540e08b3";
+const BIP352_ADDRESS: &str = "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqjuexzk6murw56suy3e0rd2cgqvycxttddwsvgxe2usfpxumr70xc9pkqwv";
+const BIP352_TXID_1: &str = "f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16";
+const BIP352_TXID_2: &str = "a1075db55d416d3ca199f55b6084e2115b9345e16c5cf302fc80e9d5fbf5d48d";
+const BIP352_KEY_1: &str = "eadc78165ff1f8ea94ad7cfdc54990738a4c53f6e0507b42154201b8e5dff3b1";
+const BIP352_KEY_2: &str = "93f5ed907ad5b2bdbbdcb5d9116ebc0a4e1f92f910d5260237fa45a9408aad16";
+const BIP352_KEY_EVEN_Y: &str = "fc8716a97a48ba9a05a98ae47b5cd201a25a7fd5d8b73c203c5f7b6b6b3b6ad7";
+const BIP352_KEY_ODD_Y: &str = "1d37787c2b7116ee983e9f9c13269df29091b391c04db94239e0d2bc2182c3bf";
+
+fn bip352_keypair(secret: &str) -> KeyPair {
+    KeyPair::from_seckey_slice(&Secp256k1::new(), &hex::decode(secret).unwrap()).unwrap()
+}
+
+fn bip352_receiver() -> SilentPaymentReceiver {
+    let scan_key = SecretKey::from_slice(&hex::decode(BIP352_SCAN_KEY).unwrap()).unwrap();
+    let spend_key = SecretKey::from_slice(&hex::decode(BIP352_SPEND_KEY).unwrap()).unwrap();
+    SilentPaymentReceiver::new(scan_key, public(&spend_key))
+}
+
+fn bip352_p2wpkh(secret: &str, txid: &str, vout: u32) -> TxInput {
+    let keypair = bip352_keypair(secret);
+    TxInputP2WPKH::new(
+        Txid::from_str(txid).unwrap(),
+        vout,
+        keypair.try_into().unwrap(),
+        FULL_SATOSHIS,
+    )
+    .into()
+}
+
+/// The vectors use the untweaked key of the P2TR output.
+fn bip352_p2tr(secret: &str, txid: &str, vout: u32) -> TxInput {
+    let output_key = bip352_keypair(secret).x_only_public_key().0;
+    TxInputP2TRKeyPath::new(
+        Txid::from_str(txid).unwrap(),
+        vout,
+        Recipient::from(TweakedPublicKey::dangerous_assume_tweaked(output_key)),
+        FULL_SATOSHIS,
+    )
+    .into()
+}
+
+#[test]
+fn silent_payment_bip352_vectors() {
+    let receiver = bip352_receiver();
+    let address = receiver.address(Network::Bitcoin);
+    assert_eq!(address.to_string(), BIP352_ADDRESS);
+
+    let vectors = [
+        (
+            "Simple send: two inputs",
+            vec![
+                bip352_p2wpkh(BIP352_KEY_1, BIP352_TXID_1, 0),
+                bip352_p2wpkh(BIP352_KEY_2, BIP352_TXID_2, 0),
+            ],
+            vec![BIP352_KEY_1, BIP352_KEY_2],
+            "3e9fce73d4e77a4809908e3c3a2e54ee147b9312dc5044a193d1fc85de46e3c1",
+        ),
+        (
+            "Simple send: two inputs, order reversed",
+            vec![
+                bip352_p2wpkh(BIP352_KEY_2, BIP352_TXID_2, 0),
+                bip352_p2wpkh(BIP352_KEY_1, BIP352_TXID_1, 0),
+            ],
+            vec![BIP352_KEY_1, BIP352_KEY_2],
+            "3e9fce73d4e77a4809908e3c3a2e54ee147b9312dc5044a193d1fc85de46e3c1",
+        ),
+        (
+            "Outpoint ordering byte-lexicographically vs. vout integer",
+            vec![
+                bip352_p2wpkh(BIP352_KEY_1, BIP352_TXID_1, 1),
+                bip352_p2wpkh(BIP352_KEY_2, BIP352_TXID_1, 256),
+            ],
+            vec![BIP352_KEY_1, BIP352_KEY_2],
+            "a85ef8701394b517a4b35217c4bd37ac01ebeed4b008f8d0879f9e09ba95319c",
+        ),
+        (
+            "Single recipient: multiple UTXOs from the same public key",
+            vec![
+                bip352_p2wpkh(BIP352_KEY_1, BIP352_TXID_1, 0),
+                bip352_p2wpkh(BIP352_KEY_1, BIP352_TXID_2, 0),
+            ],
+            vec![BIP352_KEY_1, BIP352_KEY_1],
+            "548ae55c8eec1e736e8d3e520f011f1f42a56d166116ad210b3937599f87f566",
+        ),
+        (
+            "Single recipient: taproot only inputs with even y-values",
+            vec![
+                bip352_p2tr(BIP352_KEY_1, BIP352_TXID_1, 0),
+                bip352_p2tr(BIP352_KEY_EVEN_Y, BIP352_TXID_2, 0),
+            ],
+            vec![BIP352_KEY_1, BIP352_KEY_EVEN_Y],
+            "de88bea8e7ffc9ce1af30d1132f910323c505185aec8eae361670421e749a1fb",
+        ),
+        (
+            "Single recipient: taproot only with mixed even/odd y-values",
+            vec![
+                bip352_p2tr(BIP352_KEY_1, BIP352_TXID_1, 0),
+                bip352_p2tr(BIP352_KEY_ODD_Y, BIP352_TXID_2, 0),
+            ],
+            vec![BIP352_KEY_1, BIP352_KEY_ODD_Y],
+            "77cab7dd12b10259ee82c6ea4b509774e33e7078e7138f568092241bf26b99f1",
+        ),
+    ];
+
+    for (comment, inputs, secrets, expected) in vectors {
+        let keys: Vec<KeyPair> = secrets
+            .iter()
+            .map(|secret| bip352_keypair(secret))
+            .collect();
+
+        // Sending.
+        let output_keys = output_keys(&inputs, &keys, &[address]).unwrap();
+        assert_eq!(output_keys.len(), 1, "{}", comment);
+        assert_eq!(output_keys[0].to_string(), expected, "{}", comment);
+
+        // Receiving, with the public keys of the inputs (the even Y output
+        // key of P2TR inputs).
+        let outpoints: Vec<OutPoint> = inputs
+            .iter()
+            .map(|input| input.ctx().previous_output)
+            .collect();
+        let input_keys: Vec<PublicKey> = inputs
+            .iter()
+            .zip(&secrets)
+            .map(|(input, secret)| {
+                let keypair = bip352_keypair(secret);
+                match input {
+                    TxInput::P2TRKeyPath(_) => {
+                        keypair.x_only_public_key().0.public_key(Parity::Even)
+                    },
+                    _ => keypair.public_key(),
+                }
+            })
+            .collect();
+        let tweak_data = tweak_data(&outpoints, &input_keys).unwrap();
+
+        let tx = Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![],
+            output: vec![TxOut {
+                value: SEND_SATOSHIS,
+                script_pubkey: ScriptBuf::new_v1_p2tr_tweaked(output_keys[0]),
+            }],
+        };
+        let found = receiver.scan(&tweak_data, &tx).unwrap();
+        assert_eq!(found.len(), 1, "{}", comment);
+        assert_eq!(found[0].output_key(), output_keys[0], "{}", comment);
+        assert_eq!(found[0].label(), None, "{}", comment);
+
+        let spend_key = SecretKey::from_slice(&hex::decode(BIP352_SPEND_KEY).unwrap()).unwrap();
+        assert!(found[0].spending_key(&spend_key).is_ok(), "{}", comment);
+    }
+}
+
+#[test]
+fn silent_payment_change_label() {
+    let receiver = bip352_receiver();
+    let inputs = vec![
+        bip352_p2wpkh(BIP352_KEY_1, BIP352_TXID_1, 0),
+        bip352_p2wpkh(BIP352_KEY_2, BIP352_TXID_2, 0),
+    ];
+    let keys = [bip352_keypair(BIP352_KEY_1), bip352_keypair(BIP352_KEY_2)];
+
+    // The sender pays the change to its own change address.
+    let change = receiver
+        .labeled_address(CHANGE_LABEL, Network::Bitcoin)
+        .unwrap();
+    let tx: Transaction = builder(&inputs)
+        .add_silent_payments(&keys, &[(change, SEND_SATOSHIS)])
+        .unwrap()
+        .sign_inputs_fn(|_, _| Ok(ClaimLocation::Witness(Witness::new())))
+        .unwrap()
+        .inner;
+
+    let outpoints: Vec<OutPoint> = inputs
+        .iter()
+        .map(|input| input.ctx().previous_output)
+        .collect();
+    let input_keys: Vec<PublicKey> = keys.iter().map(KeyPair::public_key).collect();
+    let tweak_data = tweak_data(&outpoints, &input_keys).unwrap();
+
+    // The change is found without adding the label.
+    let found = receiver.scan(&tweak_data, &tx).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].label(), Some(CHANGE_LABEL));
+}
+
+#[test]
+fn silent_payment_skips_uncompressed_p2pkh() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+    let address = receiver().address(Network::Bitcoin);
+
+    // Bob's uncompressed P2PKH input is not eligible, so his key is not
+    // required, but its outpoint counts.
+    let uncompressed = TxInputP2PKH::new(
+        Txid::from_str(SECOND_TXID).unwrap(),
+        1,
+        Recipient::<bitcoin::PublicKey>::from_keypair_uncompressed(&bob).into(),
+        FULL_SATOSHIS,
+    )
+    .uncompressed(true);
+
+    let mut inputs = inputs(&alice);
+    inputs.push(uncompressed.into());
+
+    let output_keys = output_keys(&inputs, &[alice], &[address]).unwrap();
+
+    let outpoints: Vec<OutPoint> = inputs
+        .iter()
+        .map(|input| input.ctx().previous_output)
+        .collect();
+    let taproot_key = Recipient::<TweakedPublicKey>::from(alice)
+        .tweaked_pubkey()
+        .to_inner()
+        .public_key(Parity::Even);
+    let tweak_data = tweak_data(&outpoints, &[alice.public_key(), taproot_key]).unwrap();
+
+    let tx = Transaction {
+        version: 2,
+        lock_time: LockTime::ZERO,
+        input: vec![],
+        output: vec![TxOut {
+            value: SEND_SATOSHIS,
+            script_pubkey: ScriptBuf::new_v1_p2tr_tweaked(output_keys[0]),
+        }],
+    };
+    assert_eq!(receiver().scan(&tweak_data, &tx).unwrap().len(), 1);
+}
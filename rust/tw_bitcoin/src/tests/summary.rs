@@ -0,0 +1,244 @@
+use crate::brc20::{BRC20Operation, BRC20TransferInscription, Ticker};
+use crate::multisig::TapscriptMultisig;
+use crate::ordinals::{InscriptionContent, OrdinalsInscription};
+use crate::sat_point::SatDestination;
+use crate::summary::{Destination, InputType};
+use crate::tests::brc20_transfer::*;
+use crate::{
+    keypair_from_wif, TXOutputP2TRScriptPath, TransactionBuilder, TxInput, TxInputP2TRScriptPath,
+    TxInputP2WPKH, TxOutputP2WPKH,
+};
+use bitcoin::{Network, PublicKey, Sequence, Txid};
+use std::str::FromStr;
+
+#[test]
+fn summary_brc20_commit_and_reveal() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+    let my_keys = [PublicKey::new(alice.public_key())];
+
+    let ticker = Ticker::new(BRC20_TICKER.to_string()).unwrap();
+    let transfer = BRC20TransferInscription::new(alice.into(), ticker, BRC20_AMOUNT).unwrap();
+
+    // The commit transaction.
+    let input = TxInputP2WPKH::builder()
+        .txid(Txid::from_str(COMMIT_TXID).unwrap())
+        .vout(1)
+        .recipient(alice.try_into().unwrap())
+        .satoshis(FULL_SATOSHIS)
+        .build()
+        .unwrap();
+
+    let output = TXOutputP2TRScriptPath::builder()
+        .recipient(transfer.inscription().recipient().clone())
+        .satoshis(BRC20_INSCRIBE_SATOSHIS)
+        .inscription_commit(true)
+        .build()
+        .unwrap();
+
+    let output_change = TxOutputP2WPKH::builder()
+        .recipient(alice.try_into().unwrap())
+        .satoshis(FOR_FEE_SATOSHIS)
+        .build()
+        .unwrap();
+
+    let summary = TransactionBuilder::new()
+        .add_input(input.into())
+        .add_output(output.into())
+        .add_output(output_change.into())
+        .summary(&my_keys, Network::Bitcoin)
+        .unwrap();
+
+    assert_eq!(summary.inputs.len(), 1);
+    assert_eq!(summary.inputs[0].input_type, InputType::P2WPKH);
+    assert_eq!(summary.inputs[0].satoshis, FULL_SATOSHIS);
+
+    assert!(matches!(
+        summary.outputs[0].destination,
+        Destination::Address(_)
+    ));
+    assert!(summary.outputs[0].inscription_commit);
+    assert!(!summary.outputs[0].is_mine);
+    assert_eq!(
+        summary.outputs[1].destination.to_string(),
+        "bc1quvgm34kal7zke68f5nsrh3k5leg9p2pa2nlgsp"
+    );
+    assert!(summary.outputs[1].is_mine);
+    assert!(!summary.outputs[1].inscription_commit);
+
+    assert_eq!(summary.fee, MINER_FEE);
+    assert_eq!(summary.fee_rate(), MINER_FEE as f64 / summary.vsize as f64);
+    assert!(!summary.replaceable);
+    assert!(!summary.lock_time_enabled);
+    assert!(summary.inscriptions.is_empty());
+
+    // The reveal transaction, sending the inscription to Bob.
+    let input = TxInputP2TRScriptPath::builder()
+        .txid(Txid::from_str(REVEAL_TXID).unwrap())
+        .vout(0)
+        .recipient(transfer.inscription().recipient().clone())
+        .satoshis(BRC20_INSCRIBE_SATOSHIS)
+        .script(transfer.inscription().taproot_program().to_owned())
+        .spend_info(transfer.inscription().spend_info().clone())
+        .build()
+        .unwrap();
+
+    let output = TxOutputP2WPKH::builder()
+        .recipient(bob.try_into().unwrap())
+        .satoshis(BRC20_DUST_SATOSHIS)
+        .build()
+        .unwrap();
+
+    let summary = TransactionBuilder::new()
+        .add_input(input.into())
+        .add_output(output.into())
+        .summary(&my_keys, Network::Bitcoin)
+        .unwrap();
+
+    assert_eq!(summary.inputs[0].input_type, InputType::P2TRScriptPath);
+    assert!(!summary.outputs[0].is_mine);
+    assert_eq!(summary.fee, BRC20_INSCRIBE_SATOSHIS - BRC20_DUST_SATOSHIS);
+
+    assert_eq!(summary.inscriptions.len(), 1);
+    let inscription = &summary.inscriptions[0];
+    assert_eq!(inscription.input, 0);
+    assert_eq!(inscription.content.mime(), b"text/plain;charset=utf-8");
+    assert_eq!(
+        inscription.destination,
+        SatDestination::Output { vout: 0, offset: 0 }
+    );
+    assert_eq!(
+        inscription.brc20,
+        Some(BRC20Operation {
+            operation: "transfer".to_string(),
+            ticker: BRC20_TICKER.to_string(),
+            amount: BRC20_AMOUNT.to_string(),
+        })
+    );
+}
+
+#[test]
+fn summary_script_path_address() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+    let bob = keypair_from_wif(BOB_WIF).unwrap();
+    let multisig = TapscriptMultisig::new(
+        2,
+        vec![alice.x_only_public_key().0, bob.x_only_public_key().0],
+    )
+    .unwrap();
+
+    let input = TxInputP2WPKH::builder()
+        .txid(Txid::from_str(COMMIT_TXID).unwrap())
+        .vout(1)
+        .recipient(alice.try_into().unwrap())
+        .satoshis(FULL_SATOSHIS)
+        .build()
+        .unwrap();
+
+    let summary = TransactionBuilder::new()
+        .add_input(input.into())
+        .add_output(multisig.p2tr_output(FULL_SATOSHIS - MINER_FEE))
+        .summary(&[], Network::Bitcoin)
+        .unwrap();
+
+    // Script-path outputs other than inscription commits are described by
+    // their address.
+    assert_eq!(
+        summary.outputs[0].destination,
+        Destination::Address(multisig.p2tr_address(Network::Bitcoin))
+    );
+    assert!(!summary.outputs[0].inscription_commit);
+}
+
+#[test]
+fn summary_flags() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+
+    let mut input: TxInput = TxInputP2WPKH::builder()
+        .txid(Txid::from_str(COMMIT_TXID).unwrap())
+        .vout(1)
+        .recipient(alice.try_into().unwrap())
+        .satoshis(FULL_SATOSHIS)
+        .build()
+        .unwrap()
+        .into();
+
+    let output = TxOutputP2WPKH::builder()
+        .recipient(alice.try_into().unwrap())
+        .satoshis(FULL_SATOSHIS - MINER_FEE)
+        .build()
+        .unwrap();
+
+    let builder = TransactionBuilder::new()
+        .lock_time_height(800_000)
+        .unwrap()
+        .add_output(output.into());
+
+    // Final sequences disable the lock time.
+    let summary = builder
+        .clone()
+        .add_input(input.clone())
+        .summary(&[], Network::Testnet)
+        .unwrap();
+    assert_eq!(summary.lock_time.to_consensus_u32(), 800_000);
+    assert!(!summary.lock_time_enabled);
+    assert!(!summary.replaceable);
+    assert!(!summary.outputs[0].is_mine);
+    assert!(summary.outputs[0]
+        .destination
+        .to_string()
+        .starts_with("tb1q"));
+
+    input.set_sequence(Sequence::ENABLE_RBF_NO_LOCKTIME);
+    let summary = builder
+        .clone()
+        .add_input(input.clone())
+        .summary(&[], Network::Testnet)
+        .unwrap();
+    assert!(summary.lock_time_enabled);
+    assert!(summary.replaceable);
+
+    // The outputs exceed the inputs.
+    let output = TxOutputP2WPKH::builder()
+        .recipient(alice.try_into().unwrap())
+        .satoshis(MINER_FEE + 1)
+        .build()
+        .unwrap();
+    let result = builder
+        .clone()
+        .add_input(input.clone())
+        .add_output(output.into())
+        .summary(&[], Network::Testnet);
+    assert!(result.is_err());
+
+    // The outputs must not overflow to less than the inputs.
+    let output = TxOutputP2WPKH::builder()
+        .recipient(alice.try_into().unwrap())
+        .satoshis(u64::MAX)
+        .build()
+        .unwrap();
+    let result = builder
+        .add_input(input)
+        .add_output(output.into())
+        .summary(&[], Network::Testnet);
+    assert!(result.is_err());
+}
+
+#[test]
+fn parse_inscription_envelopes() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+
+    let contents = [
+        InscriptionContent::new(b"text/plain", b"first"),
+        InscriptionContent::new(b"image/png", &[1; 1_000]).pointer(546),
+        InscriptionContent::new(b"text/plain", b"third").pointer(0x0102_0304_0506),
+    ];
+    let inscription = OrdinalsInscription::new_batch(&contents, alice.into()).unwrap();
+
+    let parsed = InscriptionContent::parse_envelopes(inscription.taproot_program());
+    assert_eq!(parsed, contents);
+    assert_eq!(parsed[1].pointer_offset(), Some(546));
+
+    // Not a BRC-20 operation.
+    assert!(BRC20Operation::from_content(&parsed[0]).is_none());
+}
@@ -0,0 +1,93 @@
+use crate::tests::p2pkh::{ALICE_WIF, BOB_WIF};
+use crate::{
+    keypair_from_wif, Recipient, TransactionBuilder, TxInputP2WPKH, TxOutputP2PKH, TxOutputP2WPKH,
+};
+use bitcoin::{PublicKey, Txid};
+use secp256k1::hashes::Hash;
+
+/// Alice spends a single P2WPKH UTXO worth `satoshis`.
+fn spend(satoshis: u64) -> TransactionBuilder {
+    let alice = Recipient::<PublicKey>::from(keypair_from_wif(ALICE_WIF).unwrap());
+    let input = TxInputP2WPKH::new(Txid::all_zeros(), 0, alice.try_into().unwrap(), satoshis);
+
+    TransactionBuilder::new().add_input(input.into())
+}
+
+fn bob() -> Recipient<PublicKey> {
+    Recipient::<PublicKey>::from(keypair_from_wif(BOB_WIF).unwrap())
+}
+
+fn pay_bob_p2wpkh(satoshis: u64) -> TxOutputP2WPKH {
+    TxOutputP2WPKH::new(satoshis, bob().try_into().unwrap())
+}
+
+#[test]
+fn validate_balance() {
+    let alice = keypair_from_wif(ALICE_WIF).unwrap();
+
+    // Outputs exceed the inputs.
+    let res = spend(10_000)
+        .add_output(pay_bob_p2wpkh(10_001).into())
+        .sign_inputs(alice);
+    assert!(res.is_err());
+
+    // Inputs don't cover the miner fee.
+    let res = spend(10_000)
+        .miner_fee(1_000)
+        .add_output(pay_bob_p2wpkh(9_500).into())
+        .sign_inputs(alice);
+    assert!(res.is_err());
+
+    let res = spend(10_000)
+        .miner_fee(1_000)
+        .add_output(pay_bob_p2wpkh(9_000).into())
+        .sign_inputs(alice);
+    assert!(res.is_ok());
+
+    // The outputs must not overflow to less than the inputs.
+    let res = spend(10_000)
+        .add_output(pay_bob_p2wpkh(u64::MAX).into())
+        .add_output(pay_bob_p2wpkh(1_000).into())
+        .validate();
+    assert!(res.is_err());
+
+    // Totals above the maximum amount of bitcoin are rejected.
+    let res = spend(21_000_000 * 100_000_000 + 1)
+        .add_output(pay_bob_p2wpkh(10_000).into())
+        .validate();
+    assert!(res.is_err());
+}
+
+#[test]
+fn validate_dust() {
+    // Dust thresholds: 294 sats for P2WPKH, 546 sats for P2PKH.
+    let builder = spend(10_000).add_output(pay_bob_p2wpkh(294).into());
+    assert!(builder.validate().is_ok());
+
+    let builder = spend(10_000).add_output(pay_bob_p2wpkh(293).into());
+    assert!(builder.validate().is_err());
+
+    let builder = spend(10_000).add_output(TxOutputP2PKH::new(500, bob()).into());
+    assert!(builder.validate().is_err());
+
+    // The dust filter can be disabled.
+    let builder = builder.disable_dust_filter(true);
+    assert!(builder.validate().is_ok());
+}
+
+#[test]
+fn validate_max_fee_rate() {
+    // Paying 1 BTC in fees for a single input transaction.
+    let builder = spend(100_050_000).add_output(pay_bob_p2wpkh(50_000).into());
+    assert!(builder.validate().is_err());
+
+    // The limit is configurable.
+    let builder = builder.max_fee_rate(1_000_000);
+    assert!(builder.validate().is_ok());
+
+    // Fee of 1_000 sats for ~110 vbytes.
+    let builder = spend(51_000)
+        .add_output(pay_bob_p2wpkh(50_000).into())
+        .max_fee_rate(5);
+    assert!(builder.validate().is_err());
+}
@@ -0,0 +1,213 @@
+use crate::zcash::*;
+use crate::{TransactionBuilder, TxInput, TxInputP2PKH, TxOutputP2PKH};
+use bitcoin::absolute::LockTime;
+use bitcoin::hashes::Hash;
+use bitcoin::key::KeyPair;
+use bitcoin::secp256k1::{Message, Secp256k1};
+use bitcoin::sighash::EcdsaSighashType;
+use bitcoin::{OutPoint, PublicKey, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, Witness};
+use std::str::FromStr;
+use tw_encoding::hex;
+
+// The ZIP-243 test vector, as used by the C++ implementation.
+const ZIP243_TXID: &str = "a8c685478265f4c14dada651969c45a65e1aeb8cd6791f2f5bb6a1d9952104d9";
+const ZIP243_SCRIPT_SIG: &str = "483045022100a61e5d557568c2ddc1d9b03a7173c6ce7c996c4daecab007ac8f34bee01e6b9702204d38fdc0bcf2728a69fde78462a10fb45a9baa27873e6a5fc45fb5c76764202a01210365ffea3efa3908918a8b8627724af852fc9b86d7375b103ab0543cf418bcaa7f";
+const ZIP243_SCRIPT_CODE: &str = "76a914507173527b4c3318a2aecd793bf1cfed705950cf88ac";
+const ZIP243_TX: &str = "0400008085202f8901a8c685478265f4c14dada651969c45a65e1aeb8cd6791f2f5bb6a1d9952104d9010000006b483045022100a61e5d557568c2ddc1d9b03a7173c6ce7c996c4daecab007ac8f34bee01e6b9702204d38fdc0bcf2728a69fde78462a10fb45a9baa27873e6a5fc45fb5c76764202a01210365ffea3efa3908918a8b8627724af852fc9b86d7375b103ab0543cf418bcaa7ffeffffff02005a6202000000001976a9148132712c3ff19f3a151234616777420a6d7ef22688ac8b959800000000001976a9145453e4698f02a38abdaa521cd1ff2dee6fac187188ac29b0040048b004000000000000000000000000";
+
+// Signed with Trust Wallet Core, Sapling and Blossom.
+const SAPLING_PRIVKEY: &str = "a9684f5bebd0e1208aae2e02bc9e9163bd1965ad23d8538644e1df8b99b99559";
+const SAPLING_TXID: &str = "53685b8809efc50dd7d5cb0906b307a1b8aa5157baa5fc1bd6fe2d0344dd193a";
+const SAPLING_OUTPUT: &str = "76a91449964a736f3713d64283fd0018626ba50091c7e988ac";
+const SAPLING_TX: &str = "0400008085202f890153685b8809efc50dd7d5cb0906b307a1b8aa5157baa5fc1bd6fe2d0344dd193a000000006b483045022100ca0be9f37a4975432a52bb65b25e483f6f93d577955290bb7fb0060a93bfc92002203e0627dff004d3c72a957dc9f8e4e0e696e69d125e4d8e275d119001924d3b48012103b243171fae5516d1dc15f9178cfcc5fdc67b0a883055c117b01ba8af29b953f6ffffffff0140720700000000001976a91449964a736f3713d64283fd0018626ba50091c7e988ac00000000000000000000000000000000000000";
+const SAPLING_SIGNED_TXID: &str =
+    "ec9033381c1cc53ada837ef9981c03ead1c7c41700ff3a954389cfaddc949256";
+
+pub const BLOSSOM_TXID: &str = "2381825cd9069a200944996257e25b9403ba3e296bbc1dd98b01019cc7028cde";
+pub const BLOSSOM_OUTPUT: &str = "76a914c3bacb129d85288a3deb5890ca9b711f7f71392688ac";
+pub const BLOSSOM_TX: &str = "0400008085202f8901de8c02c79c01018bd91dbc6b293eba03945be25762994409209a06d95c828123000000006b483045022100e6e5071811c08d0c2e81cb8682ee36a8c6b645f5c08747acd3e828de2a4d8a9602200b13b36a838c7e8af81f2d6e7e694ede28833a480cfbaaa68a47187655298a7f0121024bc2a31265153f07e70e0bab08724e6b85e217f8cd628ceb62974247bb493382ffffffff01cf440000000000001976a914c3bacb129d85288a3deb5890ca9b711f7f71392688ac00000000000000000000000000000000000000";
+
+fn script(hex: &str) -> ScriptBuf {
+    ScriptBuf::from_bytes(hex::decode(hex).unwrap())
+}
+
+pub fn keypair(hex: &str) -> KeyPair {
+    KeyPair::from_seckey_slice(&Secp256k1::new(), &hex::decode(hex).unwrap()).unwrap()
+}
+
+fn zip243_tx() -> Transaction {
+    let txid = Txid::from_slice(&hex::decode(ZIP243_TXID).unwrap()).unwrap();
+
+    Transaction {
+        version: 4,
+        lock_time: LockTime::from_consensus(0x0004_b029),
+        input: vec![TxIn {
+            previous_output: OutPoint::new(txid, 1),
+            script_sig: script(ZIP243_SCRIPT_SIG),
+            sequence: Sequence(0xffff_fffe),
+            witness: Witness::new(),
+        }],
+        output: vec![
+            TxOut {
+                value: 0x0262_5a00,
+                script_pubkey: script("76a9148132712c3ff19f3a151234616777420a6d7ef22688ac"),
+            },
+            TxOut {
+                value: 0x0098_958b,
+                script_pubkey: script("76a9145453e4698f02a38abdaa521cd1ff2dee6fac187188ac"),
+            },
+        ],
+    }
+}
+
+fn zip243_params() -> ZcashParams {
+    ZcashParams::new(ZcashVersion::Sapling, SAPLING_BRANCH_ID).expiry_height(0x0004_b048)
+}
+
+#[test]
+fn zcash_v4_serialize() {
+    let encoded = zip243_params().serialize(&zip243_tx());
+    assert_eq!(hex::encode(&encoded, false), ZIP243_TX);
+}
+
+#[test]
+fn zcash_v4_signature_hash() {
+    let prevouts = [TxOut {
+        value: 0x02fa_f080,
+        script_pubkey: script(ZIP243_SCRIPT_CODE),
+    }];
+
+    let params = zip243_params();
+    let tx = zip243_tx();
+
+    let hash = params
+        .signature_hash(&tx, &prevouts, 0, EcdsaSighashType::All)
+        .unwrap();
+    assert_eq!(
+        hex::encode(&hash, false),
+        "f3148f80dfab5e573d5edfe7a850f5fd39234f80b5429d3a57edcc11e34c585b"
+    );
+
+    let hash = params
+        .signature_hash(&tx, &prevouts, 0, EcdsaSighashType::NonePlusAnyoneCanPay)
+        .unwrap();
+    assert_eq!(
+        hex::encode(&hash, false),
+        "5ccea8d6d22d2b81d16e05e8f04b226bf60326c025e9f8b4e32ce8736cb5a5ad"
+    );
+
+    // The index must exist, and all prevouts are required.
+    assert!(params
+        .signature_hash(&tx, &prevouts, 1, EcdsaSighashType::All)
+        .is_err());
+    assert!(params
+        .signature_hash(&tx, &[], 0, EcdsaSighashType::All)
+        .is_err());
+}
+
+#[test]
+fn zcash_sapling_sign() {
+    let alice = keypair(SAPLING_PRIVKEY);
+    let txid = Txid::from_slice(&hex::decode(SAPLING_TXID).unwrap()).unwrap();
+
+    let input = TxInputP2PKH::new(txid, 0, alice.into(), 494_000);
+    let output = TxOutputP2PKH::new_with_script(488_000, script(SAPLING_OUTPUT));
+
+    let signed = TransactionBuilder::new()
+        .zcash(ZcashParams::from_branch_id(SAPLING_BRANCH_ID).unwrap())
+        .add_input(input.into())
+        .add_output(output.into())
+        .sign_inputs(alice)
+        .unwrap();
+
+    assert_eq!(hex::encode(&signed.serialize().unwrap(), false), SAPLING_TX);
+    assert_eq!(signed.txid().to_string(), SAPLING_SIGNED_TXID);
+}
+
+#[test]
+fn zcash_v5_sign() {
+    let alice = keypair(SAPLING_PRIVKEY);
+    let txid = Txid::from_slice(&hex::decode(SAPLING_TXID).unwrap()).unwrap();
+
+    let input = TxInputP2PKH::new(txid, 0, alice.into(), 494_000);
+    let output = TxOutputP2PKH::new_with_script(488_000, script(SAPLING_OUTPUT));
+
+    let params = ZcashParams::from_branch_id(NU5_BRANCH_ID)
+        .unwrap()
+        .expiry_height(2_000_000);
+    assert_eq!(params.version(), ZcashVersion::Nu5);
+
+    let builder = TransactionBuilder::new()
+        .zcash(params)
+        .add_input(input.into())
+        .add_output(output.into());
+
+    let sighash = builder.clone().signature_hashes().unwrap()[0];
+    let signed = builder.sign_inputs(alice).unwrap();
+    let encoded = signed.serialize().unwrap();
+
+    // Header, version group ID, branch ID, lock time and expiry height.
+    assert_eq!(
+        hex::encode(&encoded[..20], false),
+        "050000800a27a726b4d0d6c20000000080841e00"
+    );
+    // No Sapling spends and outputs, no Orchard actions.
+    assert!(encoded.ends_with(&[0, 0, 0]));
+
+    // The signature commits to the ZIP-244 digest.
+    let script_sig = &signed.inner.input[0].script_sig;
+    let push = script_sig.instructions().next().unwrap().unwrap();
+    let der = push.push_bytes().unwrap().as_bytes();
+    let sig = bitcoin::ecdsa::Signature::from_slice(der).unwrap();
+    Secp256k1::new()
+        .verify_ecdsa(&sighash, &sig.sig, &alice.public_key())
+        .unwrap();
+
+    // The v5 transaction ID differs from the sighash and the legacy ID.
+    let v5_txid = signed.txid();
+    assert_ne!(v5_txid, signed.inner.txid());
+    assert_ne!(v5_txid.to_byte_array(), *sighash.as_ref());
+
+    // Mined after NU6, the signature hash is different.
+    let nu6 = ZcashParams::from_branch_id(NU6_BRANCH_ID)
+        .unwrap()
+        .expiry_height(2_000_000);
+    let prevouts = [TxOut {
+        value: 494_000,
+        script_pubkey: ScriptBuf::new_p2pkh(&PublicKey::new(alice.public_key()).pubkey_hash()),
+    }];
+    let nu6_hash = nu6
+        .signature_hash(&signed.inner, &prevouts, 0, EcdsaSighashType::All)
+        .unwrap();
+    assert_ne!(Message::from_slice(&nu6_hash).unwrap(), sighash);
+}
+
+#[test]
+fn zcash_branch_ids() {
+    let version = |branch_id| ZcashParams::from_branch_id(branch_id).unwrap().version();
+    assert_eq!(version(SAPLING_BRANCH_ID), ZcashVersion::Sapling);
+    assert_eq!(version(CANOPY_BRANCH_ID), ZcashVersion::Sapling);
+    assert_eq!(version(NU5_BRANCH_ID), ZcashVersion::Nu5);
+    assert_eq!(version(NU6_BRANCH_ID), ZcashVersion::Nu5);
+
+    // Overwinter (version 3) and unknown upgrades are not supported.
+    assert!(ZcashParams::from_branch_id(OVERWINTER_BRANCH_ID).is_err());
+    assert!(ZcashParams::from_branch_id(0).is_err());
+    assert!(ZcashParams::from_branch_id(0x1234_5678).is_err());
+}
+
+#[test]
+fn zcash_p2pkh_only() {
+    let alice = keypair(SAPLING_PRIVKEY);
+    let txid = Txid::from_str(SAPLING_TXID).unwrap();
+
+    let input = crate::TxInputP2WPKH::new(txid, 0, alice.try_into().unwrap(), 494_000);
+    let output = TxOutputP2PKH::new_with_script(488_000, script(SAPLING_OUTPUT));
+
+    let result = TransactionBuilder::new()
+        .zcash(ZcashParams::from_branch_id(SAPLING_BRANCH_ID).unwrap())
+        .add_input(TxInput::from(input))
+        .add_output(output.into())
+        .sign_inputs(alice);
+    assert!(result.is_err());
+}
@@ -1,13 +1,34 @@
+use crate::bitcoin_cash;
 use crate::claim::{ClaimLocation, TransactionSigner};
+use crate::decred::DecredParams;
+use crate::hasher::ChainHashes;
 use crate::input::*;
+use crate::ordering::TxOrdering;
 use crate::output::*;
+use crate::pos;
+use crate::runes::{self, Edict, RuneId, Runestone};
+use crate::sat_point::{track_sat_point, SatDestination, SatPoint};
+use crate::silent_payments::{self, SilentPaymentAddress};
+use crate::summary::TransactionSummary;
+use crate::utils::checked_sum;
+use crate::zcash::ZcashParams;
 use crate::{Error, Result};
 use bitcoin::blockdata::locktime::absolute::{Height, LockTime};
 use bitcoin::consensus::Encodable;
+use bitcoin::hashes::Hash;
+use bitcoin::key::KeyPair;
 use bitcoin::sighash::{EcdsaSighashType, SighashCache, TapSighashType};
 use bitcoin::taproot::{LeafVersion, TapLeafHash};
-use bitcoin::{secp256k1, Address, TxIn, TxOut};
+use bitcoin::{
+    secp256k1, Address, Network, OutPoint, PublicKey, TxIn, TxOut, Txid, VarInt, Witness, Wtxid,
+};
 use bitcoin::{Transaction, Weight};
+use std::cell::RefCell;
+
+/// The maximum fee rate (in satoshis per vbyte) accepted by default, which
+/// matches the `maxfeerate` of Bitcoin Core's `sendrawtransaction` (0.1
+/// BTC/kvB).
+pub const DEFAULT_MAX_FEE_RATE_SAT_VB: u64 = 10_000;
 
 /// Determines the weight of the transaction and calculates the fee with the
 /// given satoshis per vbyte.
@@ -16,6 +37,40 @@ pub fn calculate_fee(tx: &Transaction, sat_vb: u64) -> (Weight, u64) {
     (weight, weight.to_vbytes_ceil() * sat_vb)
 }
 
+/// Estimates the weight of the signed transaction spending `inputs` to
+/// `outputs`, before any signature has been created. See
+/// [`TxInput::estimated_weight`] for the assumptions being made.
+pub fn estimate_weight<'a, I>(inputs: I, outputs: &[TxOutput]) -> Weight
+where
+    I: IntoIterator<Item = &'a TxInput>,
+{
+    let inputs: Vec<&TxInput> = inputs.into_iter().collect();
+
+    // Version, lock time and the input/output counts.
+    let mut weight = Weight::from_non_witness_data_size(
+        4 + 4
+            + VarInt(inputs.len() as u64).len() as u64
+            + VarInt(outputs.len() as u64).len() as u64,
+    );
+
+    if inputs.iter().any(|input| input.is_segwit()) {
+        // SegWit marker and flag.
+        weight += Weight::from_witness_data_size(2);
+
+        // Non-SegWit inputs still require an empty Witness.
+        let legacy = inputs.iter().filter(|input| !input.is_segwit()).count() as u64;
+        weight += Weight::from_witness_data_size(legacy);
+    }
+
+    weight += inputs
+        .iter()
+        .map(|input| input.estimated_weight())
+        .sum::<Weight>();
+    weight += outputs.iter().map(|output| output.weight()).sum::<Weight>();
+
+    weight
+}
+
 #[derive(Debug, Clone)]
 pub struct TransactionBuilder {
     pub version: i32,
@@ -25,6 +80,12 @@ pub struct TransactionBuilder {
     miner_fee: Option<u64>,
     return_address: Option<Address>,
     contains_taproot: bool,
+    disable_dust_filter: bool,
+    max_fee_rate: u64,
+    ordering: TxOrdering,
+    pin_first: bool,
+    protected: Vec<SatPoint>,
+    format: TxFormat,
 }
 
 impl Default for TransactionBuilder {
@@ -38,6 +99,12 @@ impl Default for TransactionBuilder {
             miner_fee: None,
             return_address: None,
             contains_taproot: false,
+            disable_dust_filter: false,
+            max_fee_rate: DEFAULT_MAX_FEE_RATE_SAT_VB,
+            ordering: TxOrdering::default(),
+            pin_first: false,
+            protected: vec![],
+            format: TxFormat::Bitcoin,
         }
     }
 }
@@ -50,6 +117,10 @@ impl TransactionBuilder {
         self.version = version;
         self
     }
+    pub fn lock_time(mut self, lock_time: LockTime) -> Self {
+        self.lock_time = lock_time;
+        self
+    }
     pub fn lock_time_height(mut self, height: u32) -> Result<Self> {
         self.lock_time = LockTime::Blocks(Height::from_consensus(height).map_err(|_| Error::Todo)?);
         Ok(self)
@@ -62,6 +133,73 @@ impl TransactionBuilder {
         self.miner_fee = Some(satoshis);
         self
     }
+    /// Allows outputs below the dust threshold of their script type.
+    pub fn disable_dust_filter(mut self, disable: bool) -> Self {
+        self.disable_dust_filter = disable;
+        self
+    }
+    /// Sets the maximum fee rate (in satoshis per vbyte) the transaction may
+    /// pay, see [`DEFAULT_MAX_FEE_RATE_SAT_VB`].
+    pub fn max_fee_rate(mut self, sat_vb: u64) -> Self {
+        self.max_fee_rate = sat_vb;
+        self
+    }
+    /// Sets the order of the inputs and outputs in the final transaction.
+    pub fn ordering(mut self, ordering: TxOrdering) -> Self {
+        self.ordering = ordering;
+        self
+    }
+    /// Keeps the first input and output in place when reordering, for example
+    /// when the first input carries an inscription that must be transferred
+    /// to the first output.
+    pub fn pin_first_input_output(mut self, pin: bool) -> Self {
+        self.pin_first = pin;
+        self
+    }
+    /// Protects the inscription at the given sat point, which must be part of
+    /// one of the inputs. The transaction is refused if the inscription would
+    /// be paid as fee or burned, and reordering never changes the output it
+    /// lands on.
+    pub fn protect_inscription(mut self, sat_point: SatPoint) -> Self {
+        self.protected.push(sat_point);
+        self
+    }
+    /// Builds a Zcash transparent transaction instead, which is serialized
+    /// and signed as specified by the given parameters. Only P2PKH inputs are
+    /// supported, and the version of the builder is ignored.
+    pub fn zcash(mut self, params: ZcashParams) -> Self {
+        self.format = TxFormat::Zcash(params);
+        self
+    }
+    /// Builds a Decred transaction instead, see [`crate::decred`]. Only P2PKH
+    /// inputs are supported, and the version of the builder is ignored.
+    pub fn decred(mut self, params: DecredParams) -> Self {
+        self.format = TxFormat::Decred(params);
+        self
+    }
+    /// Builds a transaction of a Proof-of-Stake fork instead, which carries
+    /// the given time after the version, see [`crate::pos`]. Only P2PKH
+    /// inputs are supported. Those forks usually expect [`pos::TX_VERSION`].
+    pub fn timestamp(mut self, time: u32) -> Self {
+        self.format = TxFormat::Timestamped(time);
+        self
+    }
+    /// Selects the chain of the transaction, see [`Chain`].
+    pub fn chain(mut self, chain: Chain) -> Self {
+        self.format = match chain {
+            Chain::Bitcoin => TxFormat::Bitcoin,
+            Chain::BitcoinCash => TxFormat::BitcoinCash,
+            Chain::Groestlcoin => TxFormat::Hashed(ChainHashes::GROESTLCOIN),
+        };
+        self
+    }
+    /// Replaces the hash functions of the transaction ID and the signature
+    /// hash. Only P2PKH inputs are supported, since the SegWit and Taproot
+    /// signature hashes are tied to SHA-256.
+    pub fn hashes(mut self, hashes: ChainHashes) -> Self {
+        self.format = TxFormat::Hashed(hashes);
+        self
+    }
     pub fn add_input(mut self, input: TxInput) -> Self {
         match input {
             TxInput::P2TRKeyPath(_) | TxInput::P2TRScriptPath(_) => self.contains_taproot = true,
@@ -75,6 +213,48 @@ impl TransactionBuilder {
         self.outputs.push(output);
         self
     }
+    /// Adds an output for each rune transfer, followed by the runestone
+    /// carrying the matching edicts. Runes not transferred are assigned to
+    /// `change` if given, otherwise to the first non-`OP_RETURN` output.
+    pub fn transfer_runes(
+        mut self,
+        transfers: Vec<(RuneId, u128, TxOutput)>,
+        change: Option<TxOutput>,
+    ) -> Self {
+        let mut runestone = Runestone::new();
+
+        for (id, amount, output) in transfers {
+            runestone = runestone.edict(Edict {
+                id,
+                amount,
+                output: self.outputs.len() as u32,
+            });
+            self.outputs.push(output);
+        }
+
+        if let Some(change) = change {
+            runestone = runestone.pointer(self.outputs.len() as u32);
+            self.outputs.push(change);
+        }
+
+        self.outputs.push(runestone.to_output());
+        self
+    }
+    /// Adds the outputs paying `satoshis` to the silent payment addresses. The
+    /// output keys depend on the inputs, so this must be called once all
+    /// inputs have been added. `keys` are the private keys of the inputs, see
+    /// [`crate::silent_payments::output_keys`].
+    pub fn add_silent_payments(
+        mut self,
+        keys: &[KeyPair],
+        payments: &[(SilentPaymentAddress, u64)],
+    ) -> Result<Self> {
+        let outputs = silent_payments::outputs(&self.inputs, keys, payments)?;
+        self.outputs
+            .extend(outputs.into_iter().map(TxOutput::P2TRKeyPath));
+
+        Ok(self)
+    }
     pub fn sign_inputs<S>(self, signer: S) -> Result<TransactionSigned>
     where
         S: TransactionSigner,
@@ -86,6 +266,9 @@ impl TransactionBuilder {
             TxInput::P2WPKH(p) => signer
                 .claim_p2wpkh(p, sighash, EcdsaSighashType::All)
                 .map(|claim| ClaimLocation::Witness(claim.0)),
+            TxInput::P2WSH(p) => signer
+                .claim_p2wsh(p, sighash, EcdsaSighashType::All)
+                .map(|claim| ClaimLocation::Witness(claim.0)),
             TxInput::P2TRKeyPath(p) => signer
                 .claim_p2tr_key_path(p, sighash, TapSighashType::Default)
                 .map(|claim| ClaimLocation::Witness(claim.0)),
@@ -94,10 +277,174 @@ impl TransactionBuilder {
                 .map(|claim| ClaimLocation::Witness(claim.0)),
         })
     }
-    pub fn sign_inputs_fn<F>(self, signer: F) -> Result<TransactionSigned>
+    /// Computes the sighash of each input, in the order of the signed
+    /// transaction, without signing it. This is used when the signatures are
+    /// created elsewhere, such as with [`crate::musig2`], and then passed on
+    /// via [`TransactionBuilder::sign_inputs_fn`].
+    pub fn signature_hashes(self) -> Result<Vec<secp256k1::Message>> {
+        let hashes = RefCell::new(vec![]);
+        self.sign_inputs_fn(|_, sighash| {
+            hashes.borrow_mut().push(sighash);
+            Ok(ClaimLocation::Witness(Witness::new()))
+        })?;
+
+        Ok(hashes.into_inner())
+    }
+    /// Reports where each of the protected inscriptions ends up, in the current
+    /// order of the inputs and outputs.
+    pub fn track_inscriptions(&self) -> Result<Vec<(SatPoint, SatDestination)>> {
+        self.protected
+            .iter()
+            .map(|sat_point| {
+                track_sat_point(&self.inputs, &self.outputs, sat_point)
+                    .map(|destination| (*sat_point, destination))
+            })
+            .collect()
+    }
+    /// Describes the transaction for confirmation by the user, with the
+    /// inputs and outputs in the order they will be signed. See
+    /// [`TransactionSummary::new`] for `my_keys` and `network`. Only the
+    /// chains of [`Chain`] are supported, since the addresses and the size of
+    /// the other formats are not described correctly.
+    pub fn summary(&self, my_keys: &[PublicKey], network: Network) -> Result<TransactionSummary> {
+        let chain = match &self.format {
+            TxFormat::Bitcoin => Chain::Bitcoin,
+            TxFormat::BitcoinCash => Chain::BitcoinCash,
+            TxFormat::Hashed(hashes) if *hashes == ChainHashes::GROESTLCOIN => Chain::Groestlcoin,
+            _ => return Err(Error::Todo),
+        };
+
+        let mut ordered = self.clone();
+        ordered.apply_ordering();
+
+        TransactionSummary::new(
+            &ordered.inputs,
+            &ordered.outputs,
+            self.lock_time,
+            my_keys,
+            network,
+            chain,
+        )
+    }
+    /// Validates the transaction before it gets signed. The inputs must cover
+    /// the outputs plus the miner fee (if set), the lock time must satisfy
+    /// the inputs requiring one, no protected inscription may be lost, the
+    /// outputs must not be dust (unless the dust filter is disabled) and the
+    /// fee rate must not exceed the maximum fee rate.
+    pub fn validate(&self) -> Result<()> {
+        let total_in = checked_sum(self.inputs.iter().map(|input| input.satoshis()))?;
+        let total_out = checked_sum(self.outputs.iter().map(|output| output.satoshis()))?;
+
+        // Satoshi output check.
+        let fee = total_in.checked_sub(total_out).ok_or(Error::Todo)?;
+        if fee < self.miner_fee.unwrap_or_default() {
+            return Err(Error::Todo);
+        }
+
+        // Inputs such as HTLC refunds cannot be spent before their timeout.
+        let locked = self
+            .inputs
+            .iter()
+            .filter_map(|input| input.ctx().min_lock_time)
+            .any(|min_lock_time| !min_lock_time.is_implied_by(self.lock_time));
+
+        if locked {
+            return Err(Error::Todo);
+        }
+
+        // Protected inscriptions must not be paid as fee or burned.
+        let lost = self
+            .track_inscriptions()?
+            .iter()
+            .any(|(_, destination)| destination.is_lost());
+
+        if lost {
+            return Err(Error::Todo);
+        }
+
+        // Dust check, the threshold depends on the script type.
+        if !self.disable_dust_filter {
+            let has_dust = self
+                .outputs
+                .iter()
+                .any(|output| output.satoshis() < output.script_pubkey().dust_value().to_sat());
+
+            if has_dust {
+                return Err(Error::Todo);
+            }
+        }
+
+        // Fee rate sanity check, protecting against swapped amounts.
+        let vsize = estimate_weight(&self.inputs, &self.outputs).to_vbytes_ceil();
+        if fee > self.max_fee_rate.saturating_mul(vsize) {
+            return Err(Error::Todo);
+        }
+
+        Ok(())
+    }
+    /// Applies the configured ordering to the inputs and outputs.
+    ///
+    /// Inscriptions are revealed on the first satoshi of the Taproot
+    /// script-path input and transferred to the first output. Hence, all
+    /// inputs up to the last script-path input and the first output keep
+    /// their position. The same applies to the inputs and outputs carrying
+    /// protected inscriptions.
+    fn apply_ordering(&mut self) {
+        let mut pinned_inputs = self
+            .inputs
+            .iter()
+            .rposition(|input| matches!(input, TxInput::P2TRScriptPath(_)))
+            .map(|index| index + 1)
+            .unwrap_or_default();
+
+        if self.pin_first {
+            pinned_inputs = pinned_inputs.max(1);
+        }
+
+        let mut pinned_outputs = pinned_inputs.min(1);
+
+        // Only the sats of the inputs and outputs before the inscription
+        // determine where it lands, so everything after can be reordered.
+        for sat_point in &self.protected {
+            if let Some(index) = self
+                .inputs
+                .iter()
+                .position(|input| input.ctx().previous_output == sat_point.outpoint)
+            {
+                pinned_inputs = pinned_inputs.max(index + 1);
+            }
+
+            match track_sat_point(&self.inputs, &self.outputs, sat_point) {
+                Ok(SatDestination::Output { vout, .. } | SatDestination::Burned { vout, .. }) => {
+                    pinned_outputs = pinned_outputs.max(vout as usize + 1);
+                },
+                _ => pinned_outputs = self.outputs.len(),
+            }
+        }
+
+        // Edicts and the pointer of a runestone refer to the outputs by index.
+        if self
+            .outputs
+            .iter()
+            .any(|output| runes::is_runestone(output.script_pubkey()))
+        {
+            pinned_outputs = self.outputs.len();
+        }
+
+        self.ordering.apply(
+            &mut self.inputs,
+            &mut self.outputs,
+            pinned_inputs,
+            pinned_outputs,
+        );
+    }
+    pub fn sign_inputs_fn<F>(mut self, signer: F) -> Result<TransactionSigned>
     where
         F: Fn(&TxInput, secp256k1::Message) -> Result<ClaimLocation>,
     {
+        self.validate()?;
+        self.apply_ordering();
+
         // Prepare boilerplate transaction for `bitcoin` crate.
         let mut tx = Transaction {
             version: self.version,
@@ -118,27 +465,21 @@ impl TransactionBuilder {
             tx.output.push(btc_txout);
         }
 
-        // Satoshi output check
-        /*
-        // TODO: This should be enabled, eventually.
-        let miner_fee = self.miner_fee.ok_or(Error::Todo)?;
-        if total_satoshis_outputs + miner_fee > total_satoshi_inputs {
-            return Err(Error::Todo);
-        }
-        */
-
-        // If Taproot is enabled, we prepare the full `TxOuts` (value and
-        // scriptPubKey) for hashing, which will then be signed. What
-        // distinguishes this from legacy signing is that the output value in
-        // satoshis is actually part of the signature.
-        let mut prevouts = vec![];
-        if self.contains_taproot {
-            for input in &self.inputs {
-                prevouts.push(TxOut {
-                    value: input.ctx().value,
-                    script_pubkey: input.ctx().script_pubkey.clone(),
-                });
-            }
+        // We prepare the full `TxOuts` (value and scriptPubKey) for hashing,
+        // which will then be signed. What distinguishes Taproot from legacy
+        // signing is that the output value in satoshis is actually part of
+        // the signature.
+        let prevouts: Vec<TxOut> = self
+            .inputs
+            .iter()
+            .map(|input| TxOut {
+                value: input.ctx().value,
+                script_pubkey: input.ctx().script_pubkey.clone(),
+            })
+            .collect();
+
+        if !matches!(self.format, TxFormat::Bitcoin) {
+            return self.sign_p2pkh_inputs(tx, prevouts, signer);
         }
 
         let mut cache = SighashCache::new(tx);
@@ -184,6 +525,22 @@ impl TransactionBuilder {
 
                     claims.push((index, updated));
                 },
+                TxInput::P2WSH(p2wsh) => {
+                    let hash = cache
+                        .segwit_signature_hash(
+                            index,
+                            p2wsh.witness_script(),
+                            p2wsh.ctx().value,
+                            EcdsaSighashType::All,
+                        )
+                        .map_err(|_| Error::Todo)?;
+
+                    let message = secp256k1::Message::from_slice(hash.as_ref())
+                        .expect("Sighash must always convert to secp256k1::Message");
+                    let updated = signer(input, message)?;
+
+                    claims.push((index, updated));
+                },
                 TxInput::P2TRKeyPath(_) => {
                     let hash = cache
                         .taproot_key_spend_signature_hash(
@@ -222,29 +579,179 @@ impl TransactionBuilder {
         }
 
         let mut tx = cache.into_transaction();
+        apply_claims(&mut tx, claims);
 
-        // Update the transaction with the updated scriptSig/Witness.
-        for (index, claim_loc) in claims {
-            match claim_loc {
-                ClaimLocation::Script(script) => {
-                    tx.input[index].script_sig = script;
-                },
-                ClaimLocation::Witness(witness) => {
-                    tx.input[index].witness = witness;
-                },
+        Ok(TransactionSigned {
+            inner: tx,
+            format: TxFormat::Bitcoin,
+            prevouts,
+        })
+    }
+    /// Signs the transaction of another chain, where only P2PKH inputs are
+    /// supported and the signature hash depends on the format.
+    fn sign_p2pkh_inputs<F>(
+        &self,
+        mut tx: Transaction,
+        prevouts: Vec<TxOut>,
+        signer: F,
+    ) -> Result<TransactionSigned>
+    where
+        F: Fn(&TxInput, secp256k1::Message) -> Result<ClaimLocation>,
+    {
+        if let Some(version) = self.format.version() {
+            tx.version = version;
+        }
+
+        // Witness outputs would be unspendable on chains without SegWit.
+        if !self.format.supports_segwit()
+            && tx
+                .output
+                .iter()
+                .any(|output| output.script_pubkey.is_witness_program())
+        {
+            return Err(Error::Todo);
+        }
+
+        let mut claims = vec![];
+        for (index, input) in self.inputs.iter().enumerate() {
+            if !matches!(input, TxInput::P2PKH(_)) {
+                return Err(Error::Todo);
             }
+
+            let hash = self.format.p2pkh_signature_hash(&tx, &prevouts, index)?;
+            let message = secp256k1::Message::from_slice(&hash)
+                .expect("Sighash must always convert to secp256k1::Message");
+            let updated = self.format.finalize_claim(signer(input, message)?)?;
+
+            claims.push((index, updated));
         }
 
-        Ok(TransactionSigned { inner: tx })
+        apply_claims(&mut tx, claims);
+
+        Ok(TransactionSigned {
+            inner: tx,
+            format: self.format.clone(),
+            prevouts,
+        })
+    }
+}
+
+/// Updates the transaction with the updated scriptSig/Witness.
+fn apply_claims(tx: &mut Transaction, claims: Vec<(usize, ClaimLocation)>) {
+    for (index, claim_loc) in claims {
+        match claim_loc {
+            ClaimLocation::Script(script) => {
+                tx.input[index].script_sig = script;
+            },
+            ClaimLocation::Witness(witness) => {
+                tx.input[index].witness = witness;
+            },
+        }
+    }
+}
+
+/// The chains sharing Bitcoin's transaction format, but not necessarily its
+/// signature hash.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Chain {
+    #[default]
+    Bitcoin,
+    /// Signs with `SIGHASH_FORKID`, see [`crate::bitcoin_cash`]. Only P2PKH
+    /// inputs are supported.
+    BitcoinCash,
+    /// Uses the hash functions of [`ChainHashes::GROESTLCOIN`]. Only P2PKH
+    /// inputs are supported.
+    Groestlcoin,
+}
+
+/// The serialization and signature hash format of the transaction.
+#[derive(Debug, Clone, Default)]
+enum TxFormat {
+    #[default]
+    Bitcoin,
+    BitcoinCash,
+    Hashed(ChainHashes),
+    Zcash(ZcashParams),
+    Decred(DecredParams),
+    Timestamped(u32),
+}
+
+impl TxFormat {
+    /// The version of the transaction, if determined by the format.
+    fn version(&self) -> Option<i32> {
+        match self {
+            TxFormat::Bitcoin
+            | TxFormat::BitcoinCash
+            | TxFormat::Hashed(_)
+            | TxFormat::Timestamped(_) => None,
+            TxFormat::Zcash(zcash) => Some(zcash.version_number()),
+            TxFormat::Decred(decred) => Some(decred.version_number()),
+        }
+    }
+    /// Whether the chain accepts outputs to witness programs.
+    fn supports_segwit(&self) -> bool {
+        matches!(self, TxFormat::Bitcoin | TxFormat::Hashed(_))
+    }
+    /// Computes the signature hash of the P2PKH input at `index`.
+    fn p2pkh_signature_hash(
+        &self,
+        tx: &Transaction,
+        prevouts: &[TxOut],
+        index: usize,
+    ) -> Result<[u8; 32]> {
+        let script_pubkey = &prevouts.get(index).ok_or(Error::Todo)?.script_pubkey;
+
+        match self {
+            TxFormat::Bitcoin => SighashCache::new(tx)
+                .legacy_signature_hash(index, script_pubkey, EcdsaSighashType::All.to_u32())
+                .map(|hash| hash.to_byte_array())
+                .map_err(|_| Error::Todo),
+            TxFormat::BitcoinCash => {
+                bitcoin_cash::signature_hash(tx, prevouts, index, EcdsaSighashType::All)
+            },
+            TxFormat::Hashed(hashes) => {
+                hashes.signature_hash(tx, index, script_pubkey, EcdsaSighashType::All)
+            },
+            TxFormat::Zcash(zcash) => {
+                zcash.signature_hash(tx, prevouts, index, EcdsaSighashType::All)
+            },
+            TxFormat::Decred(decred) => {
+                decred.signature_hash(tx, index, script_pubkey, EcdsaSighashType::All)
+            },
+            TxFormat::Timestamped(time) => {
+                pos::signature_hash(tx, *time, index, script_pubkey, EcdsaSighashType::All)
+            },
+        }
+    }
+    /// Adjusts the claim of a P2PKH input to the format.
+    fn finalize_claim(&self, claim: ClaimLocation) -> Result<ClaimLocation> {
+        match (self, claim) {
+            (TxFormat::BitcoinCash, ClaimLocation::Script(script)) => {
+                Ok(ClaimLocation::Script(bitcoin_cash::with_fork_id(&script)?))
+            },
+            (_, claim) => Ok(claim),
+        }
     }
 }
 
 pub struct TransactionSigned {
     pub inner: Transaction,
+    format: TxFormat,
+    prevouts: Vec<TxOut>,
 }
 
 impl TransactionSigned {
     pub fn serialize(&self) -> Result<Vec<u8>> {
+        match &self.format {
+            TxFormat::Bitcoin | TxFormat::BitcoinCash | TxFormat::Hashed(_) => {},
+            TxFormat::Zcash(zcash) => return Ok(zcash.serialize(&self.inner)),
+            TxFormat::Decred(decred) => {
+                let amounts: Vec<u64> = self.prevouts.iter().map(|prevout| prevout.value).collect();
+                return decred.serialize(&self.inner, &amounts);
+            },
+            TxFormat::Timestamped(time) => return Ok(pos::serialize(&self.inner, *time)),
+        }
+
         let mut buffer = vec![];
         self.inner
             .consensus_encode(&mut buffer)
@@ -252,4 +759,65 @@ impl TransactionSigned {
 
         Ok(buffer)
     }
+    /// The transaction ID, which depends on the serialization format.
+    pub fn txid(&self) -> Txid {
+        match &self.format {
+            TxFormat::Bitcoin | TxFormat::BitcoinCash => self.inner.txid(),
+            TxFormat::Hashed(hashes) => hashes.txid(&self.inner),
+            TxFormat::Zcash(zcash) => zcash.txid(&self.inner),
+            TxFormat::Decred(decred) => decred.txid(&self.inner),
+            TxFormat::Timestamped(time) => pos::txid(&self.inner, *time),
+        }
+    }
+    /// The witness transaction ID as specified in BIP-141, which equals the
+    /// transaction ID if no input has a witness.
+    pub fn wtxid(&self) -> Wtxid {
+        match &self.format {
+            TxFormat::Bitcoin | TxFormat::BitcoinCash => self.inner.wtxid(),
+            // Only the P2PKH inputs of the other chains can be signed.
+            _ => Wtxid::from_raw_hash(self.txid().to_raw_hash()),
+        }
+    }
+    /// The serialization without witnesses, from which the transaction ID is
+    /// computed. The same as [`TransactionSigned::serialize`] if no input has
+    /// a witness.
+    pub fn serialize_stripped(&self) -> Result<Vec<u8>> {
+        if !matches!(self.format, TxFormat::Bitcoin) {
+            return self.serialize();
+        }
+
+        let mut stripped = self.inner.clone();
+        for input in &mut stripped.input {
+            input.witness.clear();
+        }
+
+        let mut buffer = vec![];
+        stripped
+            .consensus_encode(&mut buffer)
+            .map_err(|_| Error::Todo)?;
+
+        Ok(buffer)
+    }
+    /// The weight of the serialized transaction, where witness data counts
+    /// one weight unit per byte and all other data four.
+    pub fn weight(&self) -> Result<Weight> {
+        let size = self.serialize()?.len() as u64;
+        let stripped_size = self.serialize_stripped()?.len() as u64;
+
+        Ok(Weight::from_non_witness_data_size(stripped_size)
+            + Weight::from_witness_data_size(size - stripped_size))
+    }
+    /// The virtual size of the serialized transaction, which the fee rate is
+    /// based on.
+    pub fn vsize(&self) -> Result<u64> {
+        Ok(self.weight()?.to_vbytes_ceil())
+    }
+    /// The Decred tree of the spent output, [`crate::decred::TREE_REGULAR`]
+    /// for all other chains.
+    pub fn tree(&self, outpoint: &OutPoint) -> i8 {
+        match &self.format {
+            TxFormat::Decred(decred) => decred.tree_of(outpoint),
+            _ => crate::decred::TREE_REGULAR,
+        }
+    }
 }
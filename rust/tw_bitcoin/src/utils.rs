@@ -1,6 +1,15 @@
+use crate::bitcoin_cash::BITCOIN_CASH_COIN_TYPE;
+use crate::decred::DECRED_COIN_TYPE;
+use crate::ffi::BITCOIN_COIN_TYPE;
+use crate::groestlcoin::GROESTLCOIN_COIN_TYPE;
+use crate::hasher::Hasher;
 use crate::{Error, Result};
+use bitcoin::bip32::{DerivationPath, ExtendedPrivKey};
+use bitcoin::hashes::{sha256, Hash, HashEngine};
 use bitcoin::key::{KeyPair, PrivateKey, PublicKey, TapTweak, TweakedPublicKey};
 use bitcoin::secp256k1::{self, XOnlyPublicKey};
+use bitcoin::{Amount, Network};
+use std::str::FromStr;
 
 pub fn keypair_from_wif(string: &str) -> Result<KeyPair> {
     let pk = PrivateKey::from_wif(string).map_err(|_| Error::Todo)?;
@@ -8,8 +17,129 @@ pub fn keypair_from_wif(string: &str) -> Result<KeyPair> {
     Ok(keypair)
 }
 
+/// The SLIP-44 coin type of Litecoin.
+pub const LITECOIN_COIN_TYPE: u32 = 2;
+/// The SLIP-44 coin type of Dogecoin.
+pub const DOGECOIN_COIN_TYPE: u32 = 3;
+
+const WIF_COMPRESSED: u8 = 0x01;
+
+/// How the private keys of a coin are WIF encoded.
+struct WifFormat {
+    /// The version prefix on mainnet and on the test networks.
+    mainnet: &'static [u8],
+    testnet: &'static [u8],
+    /// The hash of the base58check checksum.
+    checksum: Hasher,
+    /// Whether a trailing byte marks keys with a compressed public key.
+    /// Otherwise, the public key is always compressed.
+    compression_flag: bool,
+}
+
+impl WifFormat {
+    fn of(coin_type: u32) -> Result<Self> {
+        let (mainnet, testnet): (&[u8], &[u8]) = match coin_type {
+            BITCOIN_COIN_TYPE | BITCOIN_CASH_COIN_TYPE | GROESTLCOIN_COIN_TYPE => {
+                (&[0x80], &[0xef])
+            },
+            LITECOIN_COIN_TYPE => (&[0xb0], &[0xef]),
+            DOGECOIN_COIN_TYPE => (&[0x9e], &[0xf1]),
+            // The network ID, followed by the signature type of secp256k1.
+            DECRED_COIN_TYPE => (&[0x22, 0xde, 0x00], &[0x23, 0x0e, 0x00]),
+            _ => return Err(Error::Todo),
+        };
+
+        let checksum = match coin_type {
+            GROESTLCOIN_COIN_TYPE => Hasher::Groestl512d,
+            DECRED_COIN_TYPE => Hasher::Blake256,
+            _ => Hasher::Sha256d,
+        };
+
+        Ok(WifFormat {
+            mainnet,
+            testnet,
+            checksum,
+            compression_flag: coin_type != DECRED_COIN_TYPE,
+        })
+    }
+}
+
+/// Decodes the WIF encoded private key of the coin `coin_type`, which must be
+/// encoded for `network`. The version prefix and the checksum are those of
+/// the coin. The key remembers whether its public key is compressed.
+pub fn private_key_from_wif(string: &str, coin_type: u32, network: Network) -> Result<PrivateKey> {
+    let format = WifFormat::of(coin_type)?;
+    let data = format.checksum.base58check_decode(string)?;
+
+    let prefix = if network == Network::Bitcoin {
+        format.mainnet
+    } else {
+        format.testnet
+    };
+    let key = data.strip_prefix(prefix).ok_or(Error::Todo)?;
+
+    let (key, compressed) = match key {
+        [key @ .., WIF_COMPRESSED] if format.compression_flag && key.len() == 32 => (key, true),
+        key if format.compression_flag => (key, false),
+        key => (key, true),
+    };
+
+    let mut private_key = PrivateKey::from_slice(key, network).map_err(|_| Error::Todo)?;
+    private_key.compressed = compressed;
+
+    Ok(private_key)
+}
+
+/// Derives the private key at `path` from the extended private key, which
+/// must be encoded for `network`. Derived keys are always compressed.
+pub fn private_key_from_xprv(xprv: &str, path: &str, network: Network) -> Result<PrivateKey> {
+    let xprv = ExtendedPrivKey::from_str(xprv).map_err(|_| Error::Todo)?;
+    if !same_network(xprv.network, network) {
+        return Err(Error::Todo);
+    }
+
+    let path = DerivationPath::from_str(path).map_err(|_| Error::Todo)?;
+    let derived = xprv
+        .derive_priv(&secp256k1::Secp256k1::new(), &path)
+        .map_err(|_| Error::Todo)?;
+
+    Ok(derived.to_priv())
+}
+
+/// WIF and extended keys only distinguish mainnet from the test networks.
+pub(crate) fn same_network(encoded: Network, network: Network) -> bool {
+    (encoded == Network::Bitcoin) == (network == Network::Bitcoin)
+}
+
+/// Sums the amounts in satoshis, rejecting totals above the maximum amount of
+/// bitcoin that can ever exist instead of overflowing.
+pub(crate) fn checked_sum<I>(amounts: I) -> Result<u64>
+where
+    I: IntoIterator<Item = u64>,
+{
+    amounts
+        .into_iter()
+        .try_fold(0_u64, |sum, satoshis| sum.checked_add(satoshis))
+        .filter(|sum| *sum <= Amount::MAX_MONEY.to_sat())
+        .ok_or(Error::Todo)
+}
+
 pub(crate) fn tweak_pubkey(pubkey: PublicKey) -> TweakedPublicKey {
     let xonly = XOnlyPublicKey::from(pubkey.inner);
     let (tweaked, _) = xonly.tap_tweak(&secp256k1::Secp256k1::new(), None);
     tweaked
 }
+
+/// Computes the tagged hash as specified in BIP-340.
+pub(crate) fn tagged_hash(tag: &str, data: &[&[u8]]) -> [u8; 32] {
+    let tag_hash = sha256::Hash::hash(tag.as_bytes());
+
+    let mut engine = sha256::Hash::engine();
+    engine.input(tag_hash.as_ref());
+    engine.input(tag_hash.as_ref());
+    for chunk in data {
+        engine.input(chunk);
+    }
+
+    sha256::Hash::from_engine(engine).to_byte_array()
+}
@@ -0,0 +1,387 @@
+//! Zcash transparent transactions, in the Sapling (v4) and NU5 (v5) format.
+//! Shielded spends and outputs are not supported, only transparent P2PKH
+//! inputs can be signed.
+//!
+//! The signature hashes are specified in ZIP-243 (v4) and ZIP-244 (v5), both
+//! based on BLAKE2b with personalization. The consensus branch ID of the
+//! network upgrade is committed to in the signature, so it must match the
+//! upgrade active at the time the transaction is mined.
+
+use crate::{Error, Result};
+use bitcoin::consensus::Encodable;
+use bitcoin::hashes::{sha256d, Hash};
+use bitcoin::sighash::EcdsaSighashType;
+use bitcoin::{OutPoint, Transaction, TxOut, Txid};
+use tw_hash::blake2::blake2_b_personal;
+
+/// See ZIP-201. Its version 3 format is not supported.
+pub const OVERWINTER_BRANCH_ID: u32 = 0x5ba8_1b19;
+/// See ZIP-205.
+pub const SAPLING_BRANCH_ID: u32 = 0x76b8_09bb;
+/// See ZIP-206.
+pub const BLOSSOM_BRANCH_ID: u32 = 0x2bb4_0e60;
+/// See ZIP-250.
+pub const HEARTWOOD_BRANCH_ID: u32 = 0xf5b9_230b;
+/// See ZIP-251.
+pub const CANOPY_BRANCH_ID: u32 = 0xe9ff_75a6;
+/// See ZIP-252.
+pub const NU5_BRANCH_ID: u32 = 0xc2d6_d0b4;
+/// See ZIP-253.
+pub const NU6_BRANCH_ID: u32 = 0xc8e7_1055;
+
+/// Set on the version to mark the transaction as overwintered (ZIP-202).
+const OVERWINTERED: u32 = 1 << 31;
+
+const SAPLING_VERSION_GROUP_ID: u32 = 0x892f_2085;
+const NU5_VERSION_GROUP_ID: u32 = 0x26a7_270a;
+
+const HASH_SIZE: usize = 32;
+
+/// The transaction format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZcashVersion {
+    /// Version 4, introduced with Sapling.
+    Sapling,
+    /// Version 5, introduced with NU5.
+    Nu5,
+}
+
+/// The Zcash specifics of a transaction, see
+/// [`crate::TransactionBuilder::zcash`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZcashParams {
+    version: ZcashVersion,
+    branch_id: u32,
+    expiry_height: u32,
+}
+
+impl ZcashParams {
+    pub fn new(version: ZcashVersion, branch_id: u32) -> Self {
+        ZcashParams {
+            version,
+            branch_id,
+            expiry_height: 0,
+        }
+    }
+    /// Uses the version 5 format from NU5 on, and version 4 before. Fails
+    /// for Overwinter and unknown network upgrades.
+    pub fn from_branch_id(branch_id: u32) -> Result<Self> {
+        let version = match branch_id {
+            SAPLING_BRANCH_ID | BLOSSOM_BRANCH_ID | HEARTWOOD_BRANCH_ID | CANOPY_BRANCH_ID => {
+                ZcashVersion::Sapling
+            },
+            NU5_BRANCH_ID | NU6_BRANCH_ID => ZcashVersion::Nu5,
+            _ => return Err(Error::Todo),
+        };
+
+        Ok(Self::new(version, branch_id))
+    }
+    /// Sets the block height after which the transaction can no longer be
+    /// mined, `0` (the default) disables the expiry.
+    pub fn expiry_height(mut self, expiry_height: u32) -> Self {
+        self.expiry_height = expiry_height;
+        self
+    }
+    pub fn version(&self) -> ZcashVersion {
+        self.version
+    }
+    pub fn branch_id(&self) -> u32 {
+        self.branch_id
+    }
+    /// The version field of the transaction, without the overwintered flag.
+    pub fn version_number(&self) -> i32 {
+        match self.version {
+            ZcashVersion::Sapling => 4,
+            ZcashVersion::Nu5 => 5,
+        }
+    }
+    fn header(&self) -> u32 {
+        self.version_number() as u32 | OVERWINTERED
+    }
+    fn version_group_id(&self) -> u32 {
+        match self.version {
+            ZcashVersion::Sapling => SAPLING_VERSION_GROUP_ID,
+            ZcashVersion::Nu5 => NU5_VERSION_GROUP_ID,
+        }
+    }
+    /// Serializes the transaction, ignoring its version (which is replaced
+    /// by the Zcash version) and any Witness.
+    pub fn serialize(&self, tx: &Transaction) -> Vec<u8> {
+        let mut buffer = vec![];
+        buffer.extend(self.header().to_le_bytes());
+        buffer.extend(self.version_group_id().to_le_bytes());
+
+        match self.version {
+            ZcashVersion::Sapling => {
+                encode(&tx.input, &mut buffer);
+                encode(&tx.output, &mut buffer);
+                buffer.extend(tx.lock_time.to_consensus_u32().to_le_bytes());
+                buffer.extend(self.expiry_height.to_le_bytes());
+
+                // valueBalance, vShieldedSpend, vShieldedOutput and vJoinSplit.
+                buffer.extend(0u64.to_le_bytes());
+                buffer.extend([0, 0, 0]);
+            },
+            ZcashVersion::Nu5 => {
+                buffer.extend(self.branch_id.to_le_bytes());
+                buffer.extend(tx.lock_time.to_consensus_u32().to_le_bytes());
+                buffer.extend(self.expiry_height.to_le_bytes());
+                encode(&tx.input, &mut buffer);
+                encode(&tx.output, &mut buffer);
+
+                // nSpendsSapling, nOutputsSapling and nActionsOrchard.
+                buffer.extend([0, 0, 0]);
+            },
+        }
+
+        buffer
+    }
+    /// The transaction ID, which is the double SHA-256 of the transaction for
+    /// version 4 and the ZIP-244 digest for version 5.
+    pub fn txid(&self, tx: &Transaction) -> Txid {
+        match self.version {
+            ZcashVersion::Sapling => {
+                let hash = sha256d::Hash::hash(&self.serialize(tx));
+                Txid::from_raw_hash(hash)
+            },
+            ZcashVersion::Nu5 => {
+                let digest = self.v5_digest(tx, &self.v5_transparent_digest(tx));
+                Txid::from_byte_array(digest)
+            },
+        }
+    }
+    /// Computes the signature hash of the transparent input at `index`, where
+    /// `prevouts` are the outputs spent by all inputs of the transaction.
+    pub fn signature_hash(
+        &self,
+        tx: &Transaction,
+        prevouts: &[TxOut],
+        index: usize,
+        sighash_type: EcdsaSighashType,
+    ) -> Result<[u8; HASH_SIZE]> {
+        if index >= tx.input.len() || prevouts.len() != tx.input.len() {
+            return Err(Error::Todo);
+        }
+
+        match self.version {
+            ZcashVersion::Sapling => Ok(self.v4_signature_hash(tx, prevouts, index, sighash_type)),
+            ZcashVersion::Nu5 => Ok(self.v5_signature_hash(tx, prevouts, index, sighash_type)),
+        }
+    }
+    /// The signature hash of ZIP-243. The scriptCode of the P2PKH input is
+    /// its scriptPubKey.
+    fn v4_signature_hash(
+        &self,
+        tx: &Transaction,
+        prevouts: &[TxOut],
+        index: usize,
+        sighash_type: EcdsaSighashType,
+    ) -> [u8; HASH_SIZE] {
+        let (anyone_can_pay, base) = split_sighash_type(sighash_type);
+        let zeros = [0; HASH_SIZE];
+
+        let mut data = vec![];
+        data.extend(self.header().to_le_bytes());
+        data.extend(self.version_group_id().to_le_bytes());
+
+        if anyone_can_pay {
+            data.extend(zeros);
+        } else {
+            data.extend(prevouts_hash(tx));
+        }
+
+        if anyone_can_pay || base != EcdsaSighashType::All {
+            data.extend(zeros);
+        } else {
+            data.extend(sequence_hash(tx));
+        }
+
+        match base {
+            EcdsaSighashType::Single if index < tx.output.len() => {
+                data.extend(outputs_hash(&tx.output[index..=index]));
+            },
+            EcdsaSighashType::All => data.extend(outputs_hash(&tx.output)),
+            _ => data.extend(zeros),
+        }
+
+        // hashJoinSplits, hashShieldedSpends and hashShieldedOutputs.
+        data.extend([0; 3 * HASH_SIZE]);
+
+        data.extend(tx.lock_time.to_consensus_u32().to_le_bytes());
+        data.extend(self.expiry_height.to_le_bytes());
+        // valueBalance
+        data.extend(0u64.to_le_bytes());
+        data.extend(sighash_type.to_u32().to_le_bytes());
+
+        let input = &tx.input[index];
+        encode(&input.previous_output, &mut data);
+        encode(&prevouts[index].script_pubkey, &mut data);
+        data.extend(prevouts[index].value.to_le_bytes());
+        data.extend(input.sequence.to_consensus_u32().to_le_bytes());
+
+        self.branch_hash(b"ZcashSigHash", &data)
+    }
+    /// `BLAKE2b-256(personalization || branch ID, data)`, used for the final
+    /// signature hash and transaction ID.
+    fn branch_hash(&self, personalization: &[u8; 12], data: &[u8]) -> [u8; HASH_SIZE] {
+        let mut personal = personalization.to_vec();
+        personal.extend(self.branch_id.to_le_bytes());
+
+        blake2b(&personal, data)
+    }
+    /// The header digest of ZIP-244 (T.1).
+    fn v5_header_digest(&self, tx: &Transaction) -> [u8; HASH_SIZE] {
+        let mut data = vec![];
+        data.extend(self.header().to_le_bytes());
+        data.extend(self.version_group_id().to_le_bytes());
+        data.extend(self.branch_id.to_le_bytes());
+        data.extend(tx.lock_time.to_consensus_u32().to_le_bytes());
+        data.extend(self.expiry_height.to_le_bytes());
+
+        blake2b(b"ZTxIdHeadersHash", &data)
+    }
+    /// The transparent digest of ZIP-244 (T.2) for the transaction ID.
+    fn v5_transparent_digest(&self, tx: &Transaction) -> [u8; HASH_SIZE] {
+        if tx.input.is_empty() && tx.output.is_empty() {
+            return blake2b(b"ZTxIdTranspaHash", &[]);
+        }
+
+        let mut data = vec![];
+        data.extend(v5_prevouts_digest(tx));
+        data.extend(v5_sequence_digest(tx));
+        data.extend(v5_outputs_digest(&tx.output));
+
+        blake2b(b"ZTxIdTranspaHash", &data)
+    }
+    /// Combines the digests of all bundles, where the Sapling and Orchard
+    /// bundles are always empty.
+    fn v5_digest(&self, tx: &Transaction, transparent_digest: &[u8]) -> [u8; HASH_SIZE] {
+        let mut data = vec![];
+        data.extend(self.v5_header_digest(tx));
+        data.extend(transparent_digest);
+        data.extend(blake2b(b"ZTxIdSaplingHash", &[]));
+        data.extend(blake2b(b"ZTxIdOrchardHash", &[]));
+
+        self.branch_hash(b"ZcashTxHash_", &data)
+    }
+    /// The signature hash of ZIP-244 (S.2) for a transparent input.
+    fn v5_signature_hash(
+        &self,
+        tx: &Transaction,
+        prevouts: &[TxOut],
+        index: usize,
+        sighash_type: EcdsaSighashType,
+    ) -> [u8; HASH_SIZE] {
+        let (anyone_can_pay, base) = split_sighash_type(sighash_type);
+
+        let mut data = vec![sighash_type.to_u32() as u8];
+        if anyone_can_pay {
+            data.extend(blake2b(b"ZTxIdPrevoutHash", &[]));
+            data.extend(blake2b(b"ZTxTrAmountsHash", &[]));
+            data.extend(blake2b(b"ZTxTrScriptsHash", &[]));
+            data.extend(blake2b(b"ZTxIdSequencHash", &[]));
+        } else {
+            let mut amounts = vec![];
+            let mut scripts = vec![];
+            for prevout in prevouts {
+                amounts.extend(prevout.value.to_le_bytes());
+                encode(&prevout.script_pubkey, &mut scripts);
+            }
+
+            data.extend(v5_prevouts_digest(tx));
+            data.extend(blake2b(b"ZTxTrAmountsHash", &amounts));
+            data.extend(blake2b(b"ZTxTrScriptsHash", &scripts));
+            data.extend(v5_sequence_digest(tx));
+        }
+
+        match base {
+            EcdsaSighashType::Single if index < tx.output.len() => {
+                data.extend(v5_outputs_digest(&tx.output[index..=index]));
+            },
+            EcdsaSighashType::All => data.extend(v5_outputs_digest(&tx.output)),
+            _ => data.extend(v5_outputs_digest(&[])),
+        }
+
+        let input = &tx.input[index];
+        let mut txin = vec![];
+        encode(&input.previous_output, &mut txin);
+        txin.extend(prevouts[index].value.to_le_bytes());
+        encode(&prevouts[index].script_pubkey, &mut txin);
+        txin.extend(input.sequence.to_consensus_u32().to_le_bytes());
+        data.extend(blake2b(b"Zcash___TxInHash", &txin));
+
+        let transparent_digest = blake2b(b"ZTxIdTranspaHash", &data);
+        self.v5_digest(tx, &transparent_digest)
+    }
+}
+
+fn blake2b(personal: &[u8], data: &[u8]) -> [u8; HASH_SIZE] {
+    blake2_b_personal(data, HASH_SIZE, personal)
+        .try_into()
+        .expect("BLAKE2b returns the requested hash size")
+}
+
+fn encode<T: Encodable + ?Sized>(value: &T, buffer: &mut Vec<u8>) {
+    value
+        .consensus_encode(buffer)
+        .expect("encoding to a vector never fails");
+}
+
+/// Splits the sighash type into the `ANYONECANPAY` flag and the base type.
+fn split_sighash_type(sighash_type: EcdsaSighashType) -> (bool, EcdsaSighashType) {
+    use EcdsaSighashType::*;
+
+    match sighash_type {
+        AllPlusAnyoneCanPay => (true, All),
+        NonePlusAnyoneCanPay => (true, None),
+        SinglePlusAnyoneCanPay => (true, Single),
+        base => (false, base),
+    }
+}
+
+fn outpoints(tx: &Transaction) -> Vec<u8> {
+    let mut data = vec![];
+    for input in &tx.input {
+        encode::<OutPoint>(&input.previous_output, &mut data);
+    }
+    data
+}
+
+fn sequences(tx: &Transaction) -> Vec<u8> {
+    tx.input
+        .iter()
+        .flat_map(|input| input.sequence.to_consensus_u32().to_le_bytes())
+        .collect()
+}
+
+fn outputs(outputs: &[TxOut]) -> Vec<u8> {
+    let mut data = vec![];
+    for output in outputs {
+        encode(output, &mut data);
+    }
+    data
+}
+
+fn prevouts_hash(tx: &Transaction) -> [u8; HASH_SIZE] {
+    blake2b(b"ZcashPrevoutHash", &outpoints(tx))
+}
+
+fn sequence_hash(tx: &Transaction) -> [u8; HASH_SIZE] {
+    blake2b(b"ZcashSequencHash", &sequences(tx))
+}
+
+fn outputs_hash(tx_outputs: &[TxOut]) -> [u8; HASH_SIZE] {
+    blake2b(b"ZcashOutputsHash", &outputs(tx_outputs))
+}
+
+fn v5_prevouts_digest(tx: &Transaction) -> [u8; HASH_SIZE] {
+    blake2b(b"ZTxIdPrevoutHash", &outpoints(tx))
+}
+
+fn v5_sequence_digest(tx: &Transaction) -> [u8; HASH_SIZE] {
+    blake2b(b"ZTxIdSequencHash", &sequences(tx))
+}
+
+fn v5_outputs_digest(tx_outputs: &[TxOut]) -> [u8; HASH_SIZE] {
+    blake2b(b"ZTxIdOutputsHash", &outputs(tx_outputs))
+}